@@ -0,0 +1,38 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    // Resolve the version of the bundled RocksDB library from the lockfile (the 'librocksdb-sys' \
+    //   crate version carries the RocksDB version as build metadata, eg. '0.16.0+8.10.0')
+    let lock_path = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("Cargo.lock");
+
+    let rocksdb_version = fs::read_to_string(&lock_path)
+        .ok()
+        .and_then(|lock| {
+            let mut lines = lock.lines();
+
+            while let Some(line) = lines.next() {
+                if line == "name = \"librocksdb-sys\"" {
+                    return lines.next().and_then(|version_line| {
+                        version_line
+                            .split('+')
+                            .nth(1)
+                            .map(|version| version.trim_end_matches('"').to_string())
+                    });
+                }
+            }
+
+            None
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=SONIC_ROCKSDB_VERSION={}", rocksdb_version);
+    println!("cargo:rerun-if-changed=Cargo.lock");
+}
@@ -0,0 +1,337 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::{App, Arg};
+
+struct LoadArgs {
+    address: String,
+    password: String,
+    mode: LoadMode,
+    collection: String,
+    bucket: String,
+    concurrency: usize,
+    operations: usize,
+}
+
+#[derive(Clone, Copy)]
+enum LoadMode {
+    Push,
+    Query,
+}
+
+struct LoadClient {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+struct LoadReport {
+    latencies: Vec<Duration>,
+    errors: usize,
+}
+
+const CORPUS_WORDS: &[&str] = &[
+    "lighthouse",
+    "harbor",
+    "keeper",
+    "night",
+    "storm",
+    "ship",
+    "anchor",
+    "coast",
+    "signal",
+    "wave",
+    "tide",
+    "beacon",
+    "sailor",
+    "fog",
+    "rock",
+    "island",
+];
+
+impl LoadMode {
+    fn from_str(value: &str) -> Option<LoadMode> {
+        match value {
+            "push" => Some(LoadMode::Push),
+            "query" => Some(LoadMode::Query),
+            _ => None,
+        }
+    }
+
+    fn channel_mode(self) -> &'static str {
+        match self {
+            LoadMode::Push => "ingest",
+            LoadMode::Query => "search",
+        }
+    }
+}
+
+impl LoadClient {
+    fn connect(args: &LoadArgs) -> Result<LoadClient, io::Error> {
+        let writer = TcpStream::connect(&args.address)?;
+
+        writer.set_nodelay(true)?;
+
+        let mut client = LoadClient {
+            reader: BufReader::new(writer.try_clone()?),
+            writer,
+        };
+
+        // Expect 'CONNECTED', then start the channel in the mode of the load
+        client.expect("CONNECTED")?;
+        client.send(&format!(
+            "START {} {}",
+            args.mode.channel_mode(),
+            args.password
+        ))?;
+        client.expect("STARTED")?;
+
+        Ok(client)
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), io::Error> {
+        write!(self.writer, "{}\r\n", command)
+    }
+
+    fn read(&mut self) -> Result<String, io::Error> {
+        let mut line = String::new();
+
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "connection closed",
+            ));
+        }
+
+        Ok(line.trim_end().to_string())
+    }
+
+    fn expect(&mut self, prefix: &str) -> Result<String, io::Error> {
+        let line = self.read()?;
+
+        if line.starts_with(prefix) {
+            Ok(line)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("expected: {}, got: {}", prefix, line),
+            ))
+        }
+    }
+
+    fn run(&mut self, args: &LoadArgs, index: usize) -> Result<(), io::Error> {
+        match args.mode {
+            LoadMode::Push => {
+                self.send(&format!(
+                    "PUSH {} {} o:{} \"{}\"",
+                    args.collection,
+                    args.bucket,
+                    index,
+                    corpus_text(index, 12)
+                ))?;
+                self.expect("OK")?;
+            }
+            LoadMode::Query => {
+                // Queries are answered asynchronously: 'PENDING' first, then an 'EVENT'
+                self.send(&format!(
+                    "QUERY {} {} \"{}\" LIMIT(10)",
+                    args.collection,
+                    args.bucket,
+                    corpus_text(index, 2)
+                ))?;
+                self.expect("PENDING")?;
+                self.expect("EVENT")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl LoadReport {
+    fn merge(reports: Vec<LoadReport>) -> LoadReport {
+        let mut merged = LoadReport {
+            latencies: Vec::new(),
+            errors: 0,
+        };
+
+        for report in reports {
+            merged.latencies.extend(report.latencies);
+            merged.errors += report.errors;
+        }
+
+        merged.latencies.sort_unstable();
+
+        merged
+    }
+
+    fn percentile(&self, percentile: usize) -> Duration {
+        if self.latencies.is_empty() {
+            Duration::default()
+        } else {
+            self.latencies[(self.latencies.len() - 1) * percentile / 100]
+        }
+    }
+}
+
+fn corpus_text(index: usize, words: usize) -> String {
+    // Generate a synthetic text, made of words picked from the corpus
+    (0..words)
+        .map(|word| CORPUS_WORDS[(index * 7 + word * 3) % CORPUS_WORDS.len()])
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn make_args() -> LoadArgs {
+    let matches = App::new("loadgen")
+        .about("Drive Sonic Channel with concurrent PUSH or QUERY commands")
+        .arg(
+            Arg::new("address")
+                .long("address")
+                .help("Address of the Sonic Channel listener")
+                .default_value("[::1]:1491")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("password")
+                .long("password")
+                .help("Sonic Channel password")
+                .default_value("SecretPassword")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("mode")
+                .long("mode")
+                .help("Commands to issue (either: push or query)")
+                .default_value("query")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("collection")
+                .long("collection")
+                .help("Collection to issue commands against")
+                .default_value("loadgen")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("bucket")
+                .long("bucket")
+                .help("Bucket to issue commands against")
+                .default_value("default")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("concurrency")
+                .short('c')
+                .long("concurrency")
+                .help("Number of concurrent connections")
+                .default_value("4")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("operations")
+                .short('n')
+                .long("operations")
+                .help("Number of commands to issue per connection")
+                .default_value("1000")
+                .takes_value(true),
+        )
+        .get_matches();
+
+    let value = |name| matches.value_of(name).expect("invalid argument value");
+
+    LoadArgs {
+        address: value("address").to_string(),
+        password: value("password").to_string(),
+        mode: LoadMode::from_str(value("mode")).expect("invalid mode value"),
+        collection: value("collection").to_string(),
+        bucket: value("bucket").to_string(),
+        concurrency: value("concurrency")
+            .parse()
+            .expect("invalid concurrency value"),
+        operations: value("operations")
+            .parse()
+            .expect("invalid operations value"),
+    }
+}
+
+fn main() {
+    let args = make_args();
+
+    let started = Instant::now();
+
+    // Run each connection in its own thread (objects are spread across connections, so that \
+    //   pushed objects do not overlap)
+    let reports: Vec<LoadReport> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..args.concurrency)
+            .map(|worker| {
+                let args = &args;
+
+                scope.spawn(move || {
+                    let mut report = LoadReport {
+                        latencies: Vec::with_capacity(args.operations),
+                        errors: 0,
+                    };
+
+                    let mut client = match LoadClient::connect(args) {
+                        Ok(client) => client,
+                        Err(err) => {
+                            eprintln!("worker {} could not connect: {}", worker, err);
+
+                            report.errors += args.operations;
+
+                            return report;
+                        }
+                    };
+
+                    for operation in 0..args.operations {
+                        let operation_started = Instant::now();
+
+                        match client.run(args, worker * args.operations + operation) {
+                            Ok(_) => report.latencies.push(operation_started.elapsed()),
+                            Err(err) => {
+                                eprintln!("worker {} command failed: {}", worker, err);
+
+                                report.errors += 1;
+                            }
+                        }
+                    }
+
+                    client.send("QUIT").ok();
+
+                    report
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("worker crashed"))
+            .collect()
+    });
+
+    let elapsed = started.elapsed();
+    let report = LoadReport::merge(reports);
+
+    println!(
+        "commands: {} ok, {} failed in {:.2?} ({:.0} commands/s)",
+        report.latencies.len(),
+        report.errors,
+        elapsed,
+        report.latencies.len() as f64 / elapsed.as_secs_f64()
+    );
+    println!(
+        "latency: p50 {:.2?}, p95 {:.2?}, p99 {:.2?}, max {:.2?}",
+        report.percentile(50),
+        report.percentile(95),
+        report.percentile(99),
+        report.percentile(100)
+    );
+}
@@ -4,23 +4,36 @@
 // Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
 use std::fmt;
+use std::fs;
+use std::io;
 use std::path::Path;
 use std::str::{self, SplitWhitespace};
 use std::vec::Vec;
+use unicode_segmentation::UnicodeSegmentation;
 
+use super::errors::ChannelRecentErrors;
 use super::format::unescape;
+use super::handle::PROTOCOL_REVISION;
+use super::idempotency::{ChannelIdempotency, IDEMPOTENCY_KEY_LENGTH_MAXIMUM};
+use super::record::ChannelRecorder;
 use super::statistics::ChannelStatistics;
+use crate::lexer::token::{TokenLexerBuilder, TokenLexerMode};
 use crate::query::builder::{QueryBuilder, QueryBuilderResult};
+use crate::query::expression::QueryExpressionBuilder;
 use crate::query::types::{
-    ListMetaData, QueryGenericLang, QueryMetaData, QuerySearchLimit, QuerySearchOffset,
+    ListMetaData, PushMetaData, QueryGenericLang, QueryMetaData, QueryPushBoost, QueryPushSuggest,
+    QueryPushWeight, QuerySearchCompletion, QuerySearchLimit, QuerySearchOffset, QuerySearchSyntax,
+    QuerySearchTimeout, QuerySearchTypos, RetainMetaData, QUERY_SEARCH_TYPOS_MAXIMUM,
 };
 use crate::store::fst::StoreFSTPool;
-use crate::store::kv::StoreKVPool;
-use crate::store::operation::StoreOperationDispatch;
+use crate::store::item::StoreItemPart;
+use crate::store::kv::{StoreKVBuilder, StoreKVPool};
+use crate::store::operation::{StoreOperationDispatch, StoreOperationError};
+use crate::store::STORE_FORMAT_REVISION;
 use crate::APP_CONF;
 
 #[derive(PartialEq)]
@@ -28,12 +41,20 @@ pub enum ChannelCommandError {
     UnknownCommand,
     NotFound,
     QueryError,
+    QueryTimeout,
+    BucketFull,
+    BucketImmutable,
+    BucketCollision,
+    StoreBusy,
+    StoreCorrupted,
     InternalError,
+    InvalidEncoding,
     ShuttingDown,
     PolicyReject(&'static str),
     InvalidFormat(&'static str),
     InvalidMetaKey((String, String)),
     InvalidMetaValue((String, String)),
+    InvalidExpression((&'static str, usize)),
 }
 
 #[derive(PartialEq)]
@@ -43,6 +64,7 @@ pub enum ChannelCommandResponse {
     Pong,
     Pending(String),
     Result(String),
+    Line(String),
     Event(&'static str, String, String),
     Ended(&'static str),
     Err(ChannelCommandError),
@@ -55,11 +77,30 @@ pub struct ChannelCommandControl;
 
 pub type ChannelCommandResponseArgs = (&'static str, Option<Vec<String>>);
 
+struct ChannelCommandPush<'a> {
+    collection: &'a str,
+    bucket: &'a str,
+    object: &'a str,
+    text: String,
+    snippet: Option<String>,
+    lang: Option<QueryGenericLang>,
+    suggest: QueryPushSuggest,
+    boost: Option<QueryPushBoost>,
+    key: Option<&'a str>,
+    weights: Vec<(&'a str, QueryPushWeight)>,
+    verbose: bool,
+}
+
 type ChannelResult = Result<Vec<ChannelCommandResponse>, ChannelCommandError>;
 type MetaPartsResult<'a> = Result<(&'a str, &'a str), (&'a str, &'a str)>;
 
 pub const EVENT_ID_SIZE: usize = 8;
 
+const PUSH_WEIGHTS_MAXIMUM: usize = 32;
+const STOPWORDS_MAXIMUM: usize = 1000;
+const STOPWORDS_WORD_LENGTH_MAXIMUM: usize = 64;
+const COUNT_OBJECTS_TARGET: &str = "OBJECTS";
+
 const TEXT_PART_BOUNDARY: char = '"';
 const TEXT_PART_ESCAPE: char = '\\';
 const META_PART_GROUP_OPEN: char = '(';
@@ -67,16 +108,56 @@ const META_PART_GROUP_CLOSE: char = ')';
 
 static BACKUP_KV_PATH: &str = "kv";
 static BACKUP_FST_PATH: &str = "fst";
+static BACKUP_MANIFEST_PATH: &str = "manifest";
 
 lazy_static! {
     pub static ref COMMANDS_MODE_SEARCH: Vec<&'static str> =
-        vec!["QUERY", "SUGGEST", "LIST", "PING", "HELP", "QUIT"];
-    pub static ref COMMANDS_MODE_INGEST: Vec<&'static str> =
-        vec!["PUSH", "POP", "COUNT", "FLUSHC", "FLUSHB", "FLUSHO", "PING", "HELP", "QUIT"];
-    pub static ref COMMANDS_MODE_CONTROL: Vec<&'static str> =
-        vec!["TRIGGER", "INFO", "PING", "HELP", "QUIT"];
-    pub static ref CONTROL_TRIGGER_ACTIONS: Vec<&'static str> =
-        vec!["consolidate", "backup", "restore"];
+        vec!["QUERY", "SUGGEST", "COOCCUR", "LIST", "VALIDATE", "PING", "VERSION", "HELP", "QUIT"];
+    pub static ref COMMANDS_MODE_INGEST: Vec<&'static str> = vec![
+        "PUSH",
+        "POP",
+        "LISTO",
+        "COUNT",
+        "TERMS",
+        "IDF",
+        "FLUSHC",
+        "FLUSHB",
+        "FLUSHO",
+        "MOVEO",
+        "SWAP",
+        "RETAIN",
+        "RETENTION",
+        "FREEZE",
+        "SNAPSHOT",
+        "DIFF",
+        "PING",
+        "VERSION",
+        "HELP",
+        "QUIT",
+    ];
+    pub static ref COMMANDS_MODE_CONTROL: Vec<&'static str> = vec![
+        "TRIGGER",
+        "INFO",
+        "METRICS",
+        "ERRORS",
+        "LIST",
+        "STOPWORDS",
+        "CACHE",
+        "UNFREEZE",
+        "PING",
+        "VERSION",
+        "HELP",
+        "QUIT"
+    ];
+    pub static ref CONTROL_TRIGGER_ACTIONS: Vec<&'static str> = vec![
+        "consolidate",
+        "backup",
+        "restore",
+        "unsuggest",
+        "sync",
+        "recompress",
+        "replay"
+    ];
     static ref MANUAL_MODE_SEARCH: HashMap<&'static str, &'static Vec<&'static str>> =
         [("commands", &*COMMANDS_MODE_SEARCH)]
             .iter()
@@ -104,6 +185,7 @@ impl ChannelCommandResponse {
             ChannelCommandResponse::Pong => ("PONG", None),
             ChannelCommandResponse::Pending(ref id) => ("PENDING", Some(vec![id.to_owned()])),
             ChannelCommandResponse::Result(ref id) => ("RESULT", Some(vec![id.to_owned()])),
+            ChannelCommandResponse::Line(ref line) => ("", Some(vec![line.to_owned()])),
             ChannelCommandResponse::Event(ref query, ref id, ref payload) => (
                 "EVENT",
                 Some(vec![query.to_string(), id.to_owned(), payload.to_owned()]),
@@ -122,6 +204,36 @@ impl ChannelCommandBase {
         }
     }
 
+    pub fn dispatch_version(mut parts: SplitWhitespace) -> ChannelResult {
+        match parts.next() {
+            None => {
+                // Notice: this only exposes build information, and is therefore safe to be \
+                //   returned to any client.
+                let features: Vec<&str> = [
+                    ("allocator-jemalloc", cfg!(feature = "allocator-jemalloc")),
+                    ("channel-tls", cfg!(feature = "channel-tls")),
+                    ("tokenizer-chinese", cfg!(feature = "tokenizer-chinese")),
+                    ("tokenizer-japanese", cfg!(feature = "tokenizer-japanese")),
+                ]
+                .iter()
+                .filter(|feature| feature.1)
+                .map(|feature| feature.0)
+                .collect();
+
+                Ok(vec![ChannelCommandResponse::Result(format!(
+                    "version({}) format({}) protocol({}) features({}) backends(kv:rocksdb,fst) \
+                     rocksdb({})",
+                    env!("CARGO_PKG_VERSION"),
+                    STORE_FORMAT_REVISION,
+                    PROTOCOL_REVISION,
+                    features.join(","),
+                    env!("SONIC_ROCKSDB_VERSION")
+                ))])
+            }
+            _ => Err(ChannelCommandError::InvalidFormat("VERSION")),
+        }
+    }
+
     pub fn dispatch_quit(mut parts: SplitWhitespace) -> ChannelResult {
         match parts.next() {
             None => Ok(vec![ChannelCommandResponse::Ended("quit")]),
@@ -160,6 +272,14 @@ impl ChannelCommandBase {
         }
     }
 
+    pub fn has_next_text_parts(parts: &SplitWhitespace) -> bool {
+        // Peek at the next part, without consuming it
+        parts
+            .clone()
+            .next()
+            .is_some_and(|part| part.starts_with(TEXT_PART_BOUNDARY))
+    }
+
     pub fn parse_text_parts(parts: &mut SplitWhitespace) -> Option<String> {
         // Parse text parts and nest them together
         let mut text_raw = String::new();
@@ -244,7 +364,7 @@ impl ChannelCommandBase {
     }
 
     pub fn parse_next_meta_parts<'a>(
-        parts: &'a mut SplitWhitespace,
+        parts: &mut SplitWhitespace<'a>,
     ) -> Option<MetaPartsResult<'a>> {
         if let Some(part) = parts.next() {
             // Parse meta (with format: 'KEY(VALUE)'; no '(' or ')' is allowed in KEY and VALUE)
@@ -296,17 +416,45 @@ impl ChannelCommandBase {
         ChannelCommandError::InvalidMetaValue((meta_key.to_owned(), meta_value.to_owned()))
     }
 
+    pub fn make_error_operation(operation_error: StoreOperationError) -> ChannelCommandError {
+        match operation_error {
+            StoreOperationError::Failed => ChannelCommandError::QueryError,
+            StoreOperationError::BucketFull => ChannelCommandError::BucketFull,
+            StoreOperationError::BucketImmutable => ChannelCommandError::BucketImmutable,
+            StoreOperationError::BucketCollision => ChannelCommandError::BucketCollision,
+            StoreOperationError::StoreBusy => ChannelCommandError::StoreBusy,
+            StoreOperationError::StoreCorrupted => ChannelCommandError::StoreCorrupted,
+            StoreOperationError::Timeout => ChannelCommandError::QueryTimeout,
+        }
+    }
+
     pub fn commit_ok_operation(query_builder: QueryBuilderResult) -> ChannelResult {
-        query_builder
-            .and_then(StoreOperationDispatch::dispatch)
-            .map(|_| vec![ChannelCommandResponse::Ok])
-            .or(Err(ChannelCommandError::QueryError))
+        Self::respond_ok_operation(
+            query_builder
+                .or(Err(StoreOperationError::Failed))
+                .and_then(StoreOperationDispatch::dispatch),
+        )
     }
 
     pub fn commit_result_operation(query_builder: QueryBuilderResult) -> ChannelResult {
-        query_builder
-            .and_then(StoreOperationDispatch::dispatch)
-            .or(Err(ChannelCommandError::QueryError))
+        Self::respond_result_operation(
+            query_builder
+                .or(Err(StoreOperationError::Failed))
+                .and_then(StoreOperationDispatch::dispatch),
+        )
+    }
+
+    fn respond_ok_operation(result: Result<Option<String>, StoreOperationError>) -> ChannelResult {
+        result
+            .map(|_| vec![ChannelCommandResponse::Ok])
+            .map_err(Self::make_error_operation)
+    }
+
+    fn respond_result_operation(
+        result: Result<Option<String>, StoreOperationError>,
+    ) -> ChannelResult {
+        result
+            .map_err(Self::make_error_operation)
             .and_then(|result| {
                 if let Some(result_inner) = result {
                     Ok(vec![ChannelCommandResponse::Result(result_inner)])
@@ -331,6 +479,7 @@ impl ChannelCommandBase {
         //   consumer via a worker thread pool.
 
         query_builder
+            .or(Err(StoreOperationError::Failed))
             .and_then(StoreOperationDispatch::dispatch)
             .map(|results| {
                 vec![
@@ -342,7 +491,7 @@ impl ChannelCommandBase {
                     ),
                 ]
             })
-            .or(Err(ChannelCommandError::QueryError))
+            .map_err(Self::make_error_operation)
     }
 
     pub fn generate_event_id() -> String {
@@ -371,8 +520,29 @@ impl ChannelCommandSearch {
                 );
 
                 // Define query parameters
-                let (mut query_limit, mut query_offset, mut query_lang) =
-                    (APP_CONF.channel.search.query_limit_default, 0, None);
+                let (
+                    mut query_limit,
+                    mut query_offset,
+                    mut query_lang,
+                    mut query_syntax,
+                    mut query_federate,
+                    mut query_timeout,
+                    mut query_typos,
+                    mut query_completion,
+                    mut query_snippets,
+                    mut query_terms,
+                ) = (
+                    APP_CONF.channel.search.query_limit_default,
+                    0,
+                    None,
+                    QuerySearchSyntax::Plain,
+                    None,
+                    QuerySearchTimeout::Partial,
+                    APP_CONF.channel.search.query_typo_tolerance,
+                    QuerySearchCompletion::AllTerms,
+                    false,
+                    false,
+                );
 
                 // Parse meta parts (meta comes after text; extract meta parts second)
                 let mut last_meta_err = None;
@@ -380,17 +550,37 @@ impl ChannelCommandSearch {
                 while let Some(meta_result) = ChannelCommandBase::parse_next_meta_parts(&mut parts)
                 {
                     match Self::handle_query_meta(meta_result) {
-                        Ok((Some(query_limit_parsed), None, None)) => {
+                        Ok(QueryMetaData::Limit(query_limit_parsed)) => {
                             query_limit = query_limit_parsed
                         }
-                        Ok((None, Some(query_offset_parsed), None)) => {
+                        Ok(QueryMetaData::Offset(query_offset_parsed)) => {
                             query_offset = query_offset_parsed
                         }
-                        Ok((None, None, Some(query_lang_parsed))) => {
+                        Ok(QueryMetaData::Lang(query_lang_parsed)) => {
                             query_lang = Some(query_lang_parsed)
                         }
+                        Ok(QueryMetaData::Syntax(query_syntax_parsed)) => {
+                            query_syntax = query_syntax_parsed
+                        }
+                        Ok(QueryMetaData::Federate(query_federate_parsed)) => {
+                            query_federate = Some(query_federate_parsed)
+                        }
+                        Ok(QueryMetaData::Timeout(query_timeout_parsed)) => {
+                            query_timeout = query_timeout_parsed
+                        }
+                        Ok(QueryMetaData::Typos(query_typos_parsed)) => {
+                            query_typos = query_typos_parsed
+                        }
+                        Ok(QueryMetaData::Partial(query_completion_parsed)) => {
+                            query_completion = query_completion_parsed
+                        }
+                        Ok(QueryMetaData::Snippets(query_snippets_parsed)) => {
+                            query_snippets = query_snippets_parsed
+                        }
+                        Ok(QueryMetaData::Terms(query_terms_parsed)) => {
+                            query_terms = query_terms_parsed
+                        }
                         Err(parse_err) => last_meta_err = Some(parse_err),
-                        _ => {}
                     }
                 }
 
@@ -402,31 +592,137 @@ impl ChannelCommandSearch {
                     Err(ChannelCommandError::PolicyReject(
                         "LIMIT out of minimum/maximum bounds",
                     ))
+                } else if query_snippets && query_federate.is_some() {
+                    Err(ChannelCommandError::PolicyReject(
+                        "SNIPPETS cannot be used with FEDERATE",
+                    ))
+                } else if query_terms && query_federate.is_some() {
+                    Err(ChannelCommandError::PolicyReject(
+                        "TERMS cannot be used with FEDERATE",
+                    ))
+                } else if let Some(query_federate) = query_federate {
+                    // Federate query on main collection + listed collections (each listed \
+                    //   collection is only queried once)
+                    let mut query_collections = vec![collection];
+
+                    for federate_collection in query_federate {
+                        if !query_collections.contains(&federate_collection) {
+                            query_collections.push(federate_collection);
+                        }
+                    }
+
+                    if query_collections.len()
+                        > APP_CONF.channel.search.query_federate_maximum as usize
+                    {
+                        Err(ChannelCommandError::PolicyReject(
+                            "FEDERATE above maximum collections",
+                        ))
+                    } else {
+                        debug!(
+                            "will search federated for #{} with text: {}, limit: {}, offset: {}, \
+                             locale: <{:?}>, syntax: {:?}, collections: {:?}",
+                            event_id,
+                            text,
+                            query_limit,
+                            query_offset,
+                            query_lang,
+                            query_syntax,
+                            query_collections
+                        );
+
+                        // Commit 'search' query (federated)
+                        ChannelCommandBase::commit_pending_operation(
+                            "QUERY",
+                            &event_id,
+                            QueryBuilder::search_federated(
+                                &event_id,
+                                &query_collections,
+                                bucket,
+                                &text,
+                                query_limit,
+                                query_offset,
+                                query_lang,
+                                query_syntax,
+                                query_timeout,
+                                query_typos,
+                                query_completion,
+                            ),
+                        )
+                    }
                 } else {
                     debug!(
-                        "will search for #{} with text: {}, limit: {}, offset: {}, locale: <{:?}>",
-                        event_id, text, query_limit, query_offset, query_lang
+                        "will search for #{} with text: {}, limit: {}, offset: {}, locale: <{:?}>, \
+                         syntax: {:?}, snippets: {}, terms: {}",
+                        event_id,
+                        text,
+                        query_limit,
+                        query_offset,
+                        query_lang,
+                        query_syntax,
+                        query_snippets,
+                        query_terms
                     );
 
                     // Commit 'search' query
                     ChannelCommandBase::commit_pending_operation(
                         "QUERY",
                         &event_id,
-                        QueryBuilder::search(
-                            &event_id,
-                            collection,
-                            bucket,
-                            &text,
-                            query_limit,
-                            query_offset,
-                            query_lang,
-                        ),
+                        match query_syntax {
+                            QuerySearchSyntax::Plain => QueryBuilder::search(
+                                &event_id,
+                                collection,
+                                bucket,
+                                &text,
+                                query_limit,
+                                query_offset,
+                                query_lang,
+                                query_timeout,
+                                query_typos,
+                                query_completion,
+                                query_snippets,
+                                query_terms,
+                            ),
+                            QuerySearchSyntax::Boolean => QueryBuilder::search_boolean(
+                                &event_id,
+                                collection,
+                                bucket,
+                                &text,
+                                query_limit,
+                                query_offset,
+                                query_snippets,
+                                query_terms,
+                            ),
+                            QuerySearchSyntax::Raw => QueryBuilder::search_raw(
+                                &event_id,
+                                collection,
+                                bucket,
+                                &text,
+                                query_limit,
+                                query_offset,
+                                query_timeout,
+                                query_snippets,
+                                query_terms,
+                            ),
+                            QuerySearchSyntax::Phrase => QueryBuilder::search_phrase(
+                                &event_id,
+                                collection,
+                                bucket,
+                                &text,
+                                query_limit,
+                                query_offset,
+                                query_lang,
+                                query_snippets,
+                                query_terms,
+                            ),
+                        },
                     )
                 }
             }
             _ => Err(ChannelCommandError::InvalidFormat(
                 "QUERY <collection> <bucket> \"<terms>\" [LIMIT(<count>)]? [OFFSET(<count>)]? \
-                 [LANG(<locale>)]?",
+                 [LANG(<locale>)]? [SYNTAX(<syntax>)]? [FEDERATE(<collections>)]? \
+                 [TIMEOUT(<outcome>)]? [FUZZY(<typos>)]? [PARTIAL(<partial>)]? \
+                 [SNIPPETS(<snippets>)]? [TERMS(<terms>)]?",
             )),
         }
     }
@@ -489,6 +785,88 @@ impl ChannelCommandSearch {
         }
     }
 
+    pub fn dispatch_cooccur(mut parts: SplitWhitespace) -> ChannelResult {
+        match (
+            parts.next(),
+            parts.next(),
+            ChannelCommandBase::parse_text_parts(&mut parts),
+        ) {
+            (Some(collection), Some(bucket), Some(text)) => {
+                // Generate command identifier
+                let event_id = ChannelCommandBase::generate_event_id();
+
+                debug!(
+                    "dispatching search cooccur #{} on collection: {} and bucket: {}",
+                    event_id, collection, bucket
+                );
+
+                // Define cooccur parameters (shares limits with suggest)
+                let mut cooccur_limit = APP_CONF.channel.search.suggest_limit_default;
+
+                // Parse meta parts (meta comes after text; extract meta parts second)
+                let mut last_meta_err = None;
+
+                while let Some(meta_result) = ChannelCommandBase::parse_next_meta_parts(&mut parts)
+                {
+                    match Self::handle_suggest_meta(meta_result) {
+                        Ok(Some(cooccur_limit_parsed)) => cooccur_limit = cooccur_limit_parsed,
+                        Err(parse_err) => last_meta_err = Some(parse_err),
+                        _ => {}
+                    }
+                }
+
+                if let Some(err) = last_meta_err {
+                    Err(err)
+                } else if cooccur_limit < 1
+                    || cooccur_limit > APP_CONF.channel.search.suggest_limit_maximum
+                {
+                    Err(ChannelCommandError::PolicyReject(
+                        "LIMIT out of minimum/maximum bounds",
+                    ))
+                } else {
+                    debug!(
+                        "will cooccur for #{} with text: {}, limit: {}",
+                        event_id, text, cooccur_limit
+                    );
+
+                    // Commit 'cooccur' query
+                    ChannelCommandBase::commit_pending_operation(
+                        "COOCCUR",
+                        &event_id,
+                        QueryBuilder::cooccur(&event_id, collection, bucket, &text, cooccur_limit),
+                    )
+                }
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "COOCCUR <collection> <bucket> \"<word>\" [LIMIT(<count>)]?",
+            )),
+        }
+    }
+
+    pub fn dispatch_validate(mut parts: SplitWhitespace) -> ChannelResult {
+        match (
+            ChannelCommandBase::parse_text_parts(&mut parts),
+            parts.next(),
+        ) {
+            (Some(text), None) => {
+                debug!("dispatching search validate for text: {}", text);
+
+                // Parse boolean expression only (this does not touch the store, and therefore \
+                //   does not need to go through the query executors)
+                match QueryExpressionBuilder::parse(&text) {
+                    Ok(expression) => {
+                        Ok(vec![ChannelCommandResponse::Result(expression.to_string())])
+                    }
+                    Err(parse_err) => Err(ChannelCommandError::InvalidExpression((
+                        parse_err.error.as_str(),
+                        parse_err.position,
+                    ))),
+                }
+            }
+            _ => Err(ChannelCommandError::InvalidFormat("VALIDATE \"<terms>\"")),
+        }
+    }
+
     pub fn dispatch_list(mut parts: SplitWhitespace) -> ChannelResult {
         match (parts.next(), parts.next()) {
             (Some(collection), Some(bucket)) => {
@@ -556,7 +934,7 @@ impl ChannelCommandSearch {
                     "LIMIT" => {
                         // 'LIMIT(<count>)' where 0 <= <count> < 2^16
                         if let Ok(query_limit_parsed) = meta_value.parse::<QuerySearchLimit>() {
-                            Ok((Some(query_limit_parsed), None, None))
+                            Ok(QueryMetaData::Limit(query_limit_parsed))
                         } else {
                             Err(ChannelCommandBase::make_error_invalid_meta_value(
                                 meta_key, meta_value,
@@ -566,7 +944,7 @@ impl ChannelCommandSearch {
                     "OFFSET" => {
                         // 'OFFSET(<count>)' where 0 <= <count> < 2^32
                         if let Ok(query_offset_parsed) = meta_value.parse::<QuerySearchOffset>() {
-                            Ok((None, Some(query_offset_parsed), None))
+                            Ok(QueryMetaData::Offset(query_offset_parsed))
                         } else {
                             Err(ChannelCommandBase::make_error_invalid_meta_value(
                                 meta_key, meta_value,
@@ -576,7 +954,91 @@ impl ChannelCommandSearch {
                     "LANG" => {
                         // 'LANG(<locale>)' where <locale> ∈ ISO 639-3
                         if let Some(query_lang_parsed) = QueryGenericLang::from_value(meta_value) {
-                            Ok((None, None, Some(query_lang_parsed)))
+                            Ok(QueryMetaData::Lang(query_lang_parsed))
+                        } else {
+                            Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            ))
+                        }
+                    }
+                    "SYNTAX" => {
+                        // 'SYNTAX(<syntax>)' where <syntax> ∈ {plain, boolean, raw}
+                        if let Some(query_syntax_parsed) = QuerySearchSyntax::from_value(meta_value)
+                        {
+                            Ok(QueryMetaData::Syntax(query_syntax_parsed))
+                        } else {
+                            Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            ))
+                        }
+                    }
+                    "FEDERATE" => {
+                        // 'FEDERATE(<collections>)' where <collections> is a comma-separated \
+                        //   list of collection names
+                        let query_federate_parsed: Vec<&str> = meta_value.split(',').collect();
+
+                        if query_federate_parsed
+                            .iter()
+                            .all(|collection| !collection.is_empty())
+                        {
+                            Ok(QueryMetaData::Federate(query_federate_parsed))
+                        } else {
+                            Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            ))
+                        }
+                    }
+                    "TIMEOUT" => {
+                        // 'TIMEOUT(<outcome>)' where <outcome> ∈ {partial, error}
+                        if let Some(query_timeout_parsed) =
+                            QuerySearchTimeout::from_value(meta_value)
+                        {
+                            Ok(QueryMetaData::Timeout(query_timeout_parsed))
+                        } else {
+                            Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            ))
+                        }
+                    }
+                    "FUZZY" => {
+                        // 'FUZZY(<typos>)' where 0 <= <typos> <= 2
+                        match meta_value.parse::<QuerySearchTypos>() {
+                            Ok(query_typos_parsed)
+                                if query_typos_parsed <= QUERY_SEARCH_TYPOS_MAXIMUM =>
+                            {
+                                Ok(QueryMetaData::Typos(query_typos_parsed))
+                            }
+                            _ => Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            )),
+                        }
+                    }
+                    "PARTIAL" => {
+                        // 'PARTIAL(<partial>)' where <partial> ∈ {true, false}
+                        if let Some(query_completion_parsed) =
+                            QuerySearchCompletion::from_value(meta_value)
+                        {
+                            Ok(QueryMetaData::Partial(query_completion_parsed))
+                        } else {
+                            Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            ))
+                        }
+                    }
+                    "SNIPPETS" => {
+                        // 'SNIPPETS(<snippets>)' where <snippets> ∈ {true, false}
+                        if let Ok(query_snippets_parsed) = meta_value.parse::<bool>() {
+                            Ok(QueryMetaData::Snippets(query_snippets_parsed))
+                        } else {
+                            Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            ))
+                        }
+                    }
+                    "TERMS" => {
+                        // 'TERMS(<terms>)' where <terms> ∈ {true, false}
+                        if let Ok(query_terms_parsed) = meta_value.parse::<bool>() {
+                            Ok(QueryMetaData::Terms(query_terms_parsed))
                         } else {
                             Err(ChannelCommandBase::make_error_invalid_meta_value(
                                 meta_key, meta_value,
@@ -662,7 +1124,89 @@ impl ChannelCommandSearch {
 }
 
 impl ChannelCommandIngest {
-    pub fn dispatch_push(mut parts: SplitWhitespace) -> ChannelResult {
+    pub fn dispatch_push(parts: SplitWhitespace) -> ChannelResult {
+        Self::dispatch_pushes(vec![parts])
+            .pop()
+            .unwrap_or(Err(ChannelCommandError::InternalError))
+    }
+
+    pub fn dispatch_pushes(parts_groups: Vec<SplitWhitespace>) -> Vec<ChannelResult> {
+        // Parse all pushes first, then commit all pushes that can be committed at once (pushes \
+        //   must all target the same collection; each push gets its own response)
+        let pushes: Vec<Result<Option<ChannelCommandPush>, ChannelCommandError>> =
+            parts_groups.into_iter().map(Self::parse_push).collect();
+
+        let mut queries = Vec::with_capacity(pushes.len());
+
+        let pushes_built: Vec<bool> = pushes
+            .iter()
+            .map(|push| match push {
+                Ok(Some(push)) => {
+                    let query_builder = QueryBuilder::push(
+                        push.collection,
+                        push.bucket,
+                        push.object,
+                        &push.text,
+                        push.lang.clone(),
+                        push.suggest,
+                        push.boost,
+                        &push.weights,
+                        push.snippet.as_deref(),
+                        push.verbose,
+                    );
+
+                    query_builder.map(|query| queries.push(query)).is_ok()
+                }
+                _ => false,
+            })
+            .collect();
+
+        let queries_count = queries.len();
+
+        // Commit 'push' queries (along with push stats, if verbose)
+        let mut results = StoreOperationDispatch::dispatch_pushes(queries)
+            .unwrap_or_else(|err| vec![Err(err); queries_count])
+            .into_iter();
+
+        pushes
+            .into_iter()
+            .zip(pushes_built)
+            .map(|(push, is_built)| match push {
+                Ok(Some(push)) => {
+                    let result = if is_built {
+                        results.next().unwrap_or(Err(StoreOperationError::Failed))
+                    } else {
+                        Err(StoreOperationError::Failed)
+                    };
+
+                    let result = if push.verbose {
+                        ChannelCommandBase::respond_result_operation(result)
+                    } else {
+                        ChannelCommandBase::respond_ok_operation(result)
+                    };
+
+                    // Record idempotency key once the push succeeded (a failed push can be \
+                    //   retried with the same key)
+                    if let (Some(push_key), Ok(_)) = (push.key, &result) {
+                        ChannelIdempotency::record(
+                            push.collection,
+                            push.bucket,
+                            push.object,
+                            push_key,
+                        );
+                    }
+
+                    result
+                }
+                Ok(None) => Ok(vec![ChannelCommandResponse::Ok]),
+                Err(err) => Err(err),
+            })
+            .collect()
+    }
+
+    fn parse_push(
+        mut parts: SplitWhitespace,
+    ) -> Result<Option<ChannelCommandPush>, ChannelCommandError> {
         match (
             parts.next(),
             parts.next(),
@@ -676,8 +1220,36 @@ impl ChannelCommandIngest {
                 );
                 debug!("ingest push has text: {}", text);
 
+                // Parse snippet parts? (an optional snippet text may follow the pushed text)
+                let push_snippet = if ChannelCommandBase::has_next_text_parts(&parts) {
+                    match ChannelCommandBase::parse_text_parts(&mut parts) {
+                        Some(snippet) => Some(snippet),
+                        None => {
+                            return Err(ChannelCommandError::InvalidFormat(
+                                "PUSH <collection> <bucket> <object> \"<text>\" [\"<snippet>\"]?",
+                            ))
+                        }
+                    }
+                } else {
+                    None
+                };
+
                 // Define push parameters
-                let mut push_lang = None;
+                let (
+                    mut push_lang,
+                    mut push_suggest,
+                    mut push_boost,
+                    mut push_key,
+                    mut push_weights,
+                    mut push_verbose,
+                ) = (
+                    None,
+                    QueryPushSuggest::Incremental,
+                    None,
+                    None,
+                    Vec::new(),
+                    false,
+                );
 
                 // Parse meta parts (meta comes after text; extract meta parts second)
                 let mut last_meta_err = None;
@@ -685,28 +1257,78 @@ impl ChannelCommandIngest {
                 while let Some(meta_result) = ChannelCommandBase::parse_next_meta_parts(&mut parts)
                 {
                     match Self::handle_push_meta(meta_result) {
-                        Ok(Some(push_lang_parsed)) => push_lang = Some(push_lang_parsed),
+                        Ok(PushMetaData::Lang(push_lang_parsed)) => {
+                            push_lang = Some(push_lang_parsed)
+                        }
+                        Ok(PushMetaData::Suggest(push_suggest_parsed)) => {
+                            push_suggest = push_suggest_parsed
+                        }
+                        Ok(PushMetaData::Boost(push_boost_parsed)) => {
+                            push_boost = Some(push_boost_parsed)
+                        }
+                        Ok(PushMetaData::Key(push_key_parsed)) => {
+                            push_key = Some(push_key_parsed)
+                        }
+                        Ok(PushMetaData::Weights(push_weights_parsed)) => {
+                            push_weights = push_weights_parsed
+                        }
+                        Ok(PushMetaData::Verbose(push_verbose_parsed)) => {
+                            push_verbose = push_verbose_parsed
+                        }
                         Err(parse_err) => last_meta_err = Some(parse_err),
-                        _ => {}
                     }
                 }
 
                 if let Some(err) = last_meta_err {
                     Err(err)
+                } else if push_key
+                    .is_some_and(|push_key| {
+                        ChannelIdempotency::has(collection, bucket, object, push_key)
+                    })
+                {
+                    // This push was already processed (eg. retried by the client after a \
+                    //   timeout), thus acknowledge it again without indexing it twice
+                    // Notice: a replayed push responds 'OK' even if 'VERBOSE(true)' is set, as \
+                    //   there are no push stats to return since nothing got indexed.
+                    info!(
+                        "skipped push with already-processed idempotency key: {:?}",
+                        push_key
+                    );
+
+                    Ok(None)
                 } else {
                     debug!(
-                        "will push for text: {} with hinted locale: <{:?}>",
-                        text, push_lang
+                        "will push for text: {} with hinted locale: <{:?}>, suggest: {:?}, \
+                         boost: {:?}, key: {:?}, weights: {:?}, snippet: {:?}, verbose: {}",
+                        text,
+                        push_lang,
+                        push_suggest,
+                        push_boost,
+                        push_key,
+                        push_weights,
+                        push_snippet,
+                        push_verbose
                     );
 
-                    // Commit 'push' query
-                    ChannelCommandBase::commit_ok_operation(QueryBuilder::push(
-                        collection, bucket, object, &text, push_lang,
-                    ))
+                    Ok(Some(ChannelCommandPush {
+                        collection,
+                        bucket,
+                        object,
+                        text,
+                        snippet: push_snippet,
+                        lang: push_lang,
+                        suggest: push_suggest,
+                        boost: push_boost,
+                        key: push_key,
+                        weights: push_weights,
+                        verbose: push_verbose,
+                    }))
                 }
             }
             _ => Err(ChannelCommandError::InvalidFormat(
-                "PUSH <collection> <bucket> <object> \"<text>\" [LANG(<locale>)]?",
+                "PUSH <collection> <bucket> <object> \"<text>\" [\"<snippet>\"]? [LANG(<locale>)]? \
+                 [SUGGEST(<suggest>)]? [BOOST(<boost>)]? [KEY(<key>)]? [WEIGHTS(<weights>)]? \
+                 [VERBOSE(<verbose>)]?",
             )),
         }
     }
@@ -739,6 +1361,17 @@ impl ChannelCommandIngest {
 
     pub fn dispatch_count(mut parts: SplitWhitespace) -> ChannelResult {
         match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(collection), Some(bucket), Some(COUNT_OBJECTS_TARGET), None) => {
+                debug!(
+                    "dispatching ingest count objects in collection: {}, bucket: {}",
+                    collection, bucket
+                );
+
+                // Make 'count objects' query
+                ChannelCommandBase::commit_result_operation(QueryBuilder::count_objects(
+                    collection, bucket,
+                ))
+            }
             (Some(collection), bucket_part, object_part, None) => {
                 debug!("dispatching ingest count in collection: {}", collection);
 
@@ -750,47 +1383,132 @@ impl ChannelCommandIngest {
                 ))
             }
             _ => Err(ChannelCommandError::InvalidFormat(
-                "COUNT <collection> [<bucket> [<object>]?]?",
+                "COUNT <collection> [<bucket> [<object>|OBJECTS]?]?",
             )),
         }
     }
 
-    pub fn dispatch_flushc(mut parts: SplitWhitespace) -> ChannelResult {
-        match (parts.next(), parts.next()) {
-            (Some(collection), None) => {
+    pub fn dispatch_listo(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(collection), Some(bucket), Some(prefix)) => {
                 debug!(
-                    "dispatching ingest flush collection in collection: {}",
-                    collection
+                    "dispatching ingest list objects in collection: {}, bucket: {}, prefix: {}",
+                    collection, bucket, prefix
                 );
 
-                // Make 'flushc' query
-                ChannelCommandBase::commit_result_operation(QueryBuilder::flushc(collection))
+                // Define list parameters
+                let (mut list_limit, mut list_offset) =
+                    (APP_CONF.channel.search.list_limit_default, 0);
+
+                // Parse meta parts (meta comes last; extract meta parts second)
+                let mut last_meta_err = None;
+
+                while let Some(meta_result) = ChannelCommandBase::parse_next_meta_parts(&mut parts)
+                {
+                    match ChannelCommandSearch::handle_list_meta(meta_result) {
+                        Ok(metadata) => match metadata {
+                            (Some(list_limit_parsed), None) => list_limit = list_limit_parsed,
+                            (None, Some(list_offset_parsed)) => list_offset = list_offset_parsed,
+                            _ => {}
+                        },
+                        Err(parse_err) => last_meta_err = Some(parse_err),
+                    }
+                }
+
+                if let Some(err) = last_meta_err {
+                    Err(err)
+                } else if list_limit < 1 || list_limit > APP_CONF.channel.search.list_limit_maximum
+                {
+                    Err(ChannelCommandError::PolicyReject(
+                        "LIMIT out of minimum/maximum bounds",
+                    ))
+                } else {
+                    // Make 'listo' query
+                    ChannelCommandBase::commit_result_operation(QueryBuilder::listo(
+                        collection,
+                        bucket,
+                        prefix,
+                        list_limit,
+                        list_offset,
+                    ))
+                }
             }
-            _ => Err(ChannelCommandError::InvalidFormat("FLUSHC <collection>")),
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "LISTO <collection> <bucket> <prefix> [LIMIT(<count>)]? [OFFSET(<count>)]?",
+            )),
         }
     }
 
-    pub fn dispatch_flushb(mut parts: SplitWhitespace) -> ChannelResult {
+    pub fn dispatch_terms(mut parts: SplitWhitespace) -> ChannelResult {
         match (parts.next(), parts.next(), parts.next()) {
             (Some(collection), Some(bucket), None) => {
                 debug!(
-                    "dispatching ingest flush bucket in collection: {}, bucket: {}",
+                    "dispatching ingest terms in collection: {}, bucket: {}",
                     collection, bucket
                 );
 
-                // Make 'flushb' query
-                ChannelCommandBase::commit_result_operation(QueryBuilder::flushb(
-                    collection, bucket,
-                ))
+                // Make 'terms' query
+                ChannelCommandBase::commit_result_operation(QueryBuilder::terms(collection, bucket))
             }
             _ => Err(ChannelCommandError::InvalidFormat(
-                "FLUSHB <collection> <bucket>",
+                "TERMS <collection> <bucket>",
             )),
         }
     }
 
-    pub fn dispatch_flusho(mut parts: SplitWhitespace) -> ChannelResult {
-        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+    pub fn dispatch_idf(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(collection), Some(bucket), None) => {
+                debug!(
+                    "dispatching ingest idf in collection: {}, bucket: {}",
+                    collection, bucket
+                );
+
+                // Make 'idf' query
+                ChannelCommandBase::commit_result_operation(QueryBuilder::idf(collection, bucket))
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "IDF <collection> <bucket>",
+            )),
+        }
+    }
+
+    pub fn dispatch_flushc(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next()) {
+            (Some(collection), None) => {
+                debug!(
+                    "dispatching ingest flush collection in collection: {}",
+                    collection
+                );
+
+                // Make 'flushc' query
+                ChannelCommandBase::commit_result_operation(QueryBuilder::flushc(collection))
+            }
+            _ => Err(ChannelCommandError::InvalidFormat("FLUSHC <collection>")),
+        }
+    }
+
+    pub fn dispatch_flushb(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(collection), Some(bucket), None) => {
+                debug!(
+                    "dispatching ingest flush bucket in collection: {}, bucket: {}",
+                    collection, bucket
+                );
+
+                // Make 'flushb' query
+                ChannelCommandBase::commit_result_operation(QueryBuilder::flushb(
+                    collection, bucket,
+                ))
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "FLUSHB <collection> <bucket>",
+            )),
+        }
+    }
+
+    pub fn dispatch_flusho(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
             (Some(collection), Some(bucket), Some(object), None) => {
                 debug!(
                     "dispatching ingest flush object in collection: {}, bucket: {}, object: {}",
@@ -808,13 +1526,189 @@ impl ChannelCommandIngest {
         }
     }
 
+    pub fn dispatch_moveo(mut parts: SplitWhitespace) -> ChannelResult {
+        match (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) {
+            (Some(collection), Some(bucket), Some(object), Some(to_bucket), None) => {
+                debug!(
+                    "dispatching ingest move object in collection: {}, bucket: {}, object: {} \
+                     to bucket: {}",
+                    collection, bucket, object, to_bucket
+                );
+
+                // Make 'moveo' query
+                ChannelCommandBase::commit_result_operation(QueryBuilder::moveo(
+                    collection, bucket, object, to_bucket,
+                ))
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "MOVEO <collection> <bucket> <object> <to_bucket>",
+            )),
+        }
+    }
+
+    pub fn dispatch_swap(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(collection), Some(bucket), Some(with_bucket), None) => {
+                debug!(
+                    "dispatching ingest swap bucket in collection: {}, bucket: {} with bucket: {}",
+                    collection, bucket, with_bucket
+                );
+
+                // Make 'swap' query
+                ChannelCommandBase::commit_result_operation(QueryBuilder::swap(
+                    collection,
+                    bucket,
+                    with_bucket,
+                ))
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "SWAP <collection> <bucket> <with_bucket>",
+            )),
+        }
+    }
+
+    pub fn dispatch_retain(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next()) {
+            (Some(collection), Some(bucket)) => {
+                debug!(
+                    "dispatching ingest retain in collection: {}, bucket: {}",
+                    collection, bucket
+                );
+
+                // Define retain parameters (no limit means that the policy gets removed)
+                let (mut retain_age, mut retain_objects) = (None, None);
+
+                // Parse meta parts
+                let mut last_meta_err = None;
+
+                while let Some(meta_result) = ChannelCommandBase::parse_next_meta_parts(&mut parts)
+                {
+                    match Self::handle_retain_meta(meta_result) {
+                        Ok((Some(retain_age_parsed), None)) => retain_age = Some(retain_age_parsed),
+                        Ok((None, Some(retain_objects_parsed))) => {
+                            retain_objects = Some(retain_objects_parsed)
+                        }
+                        Err(parse_err) => last_meta_err = Some(parse_err),
+                        _ => {}
+                    }
+                }
+
+                if let Some(err) = last_meta_err {
+                    Err(err)
+                } else {
+                    debug!(
+                        "will retain with age: {:?}, objects: {:?}",
+                        retain_age, retain_objects
+                    );
+
+                    // Commit 'retain' query
+                    ChannelCommandBase::commit_ok_operation(QueryBuilder::retain(
+                        collection,
+                        bucket,
+                        retain_age,
+                        retain_objects,
+                    ))
+                }
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "RETAIN <collection> <bucket> [AGE(<seconds>)]? [OBJECTS(<count>)]?",
+            )),
+        }
+    }
+
+    pub fn dispatch_retention(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(collection), Some(bucket), None) => {
+                debug!(
+                    "dispatching ingest retention in collection: {}, bucket: {}",
+                    collection, bucket
+                );
+
+                // Make 'retention' query
+                ChannelCommandBase::commit_result_operation(QueryBuilder::retention(
+                    collection, bucket,
+                ))
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "RETENTION <collection> <bucket>",
+            )),
+        }
+    }
+
+    pub fn dispatch_freeze(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(collection), Some(bucket), None) => {
+                debug!(
+                    "dispatching ingest freeze in collection: {}, bucket: {}",
+                    collection, bucket
+                );
+
+                // Make 'freeze' query (immutability can only be lifted from a control channel)
+                ChannelCommandBase::commit_ok_operation(QueryBuilder::freeze(
+                    collection, bucket, true,
+                ))
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "FREEZE <collection> <bucket>",
+            )),
+        }
+    }
+
+    pub fn dispatch_snapshot(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(collection), Some(bucket), Some(name), None) => {
+                debug!(
+                    "dispatching ingest snapshot in collection: {}, bucket: {}, name: {}",
+                    collection, bucket, name
+                );
+
+                // Make 'snapshot' query
+                ChannelCommandBase::commit_result_operation(QueryBuilder::snapshot(
+                    collection, bucket, name,
+                ))
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "SNAPSHOT <collection> <bucket> <name>",
+            )),
+        }
+    }
+
+    pub fn dispatch_diff(mut parts: SplitWhitespace) -> ChannelResult {
+        match (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) {
+            (Some(collection), Some(bucket), Some(from_name), Some(to_name), None) => {
+                debug!(
+                    "dispatching ingest diff in collection: {}, bucket: {}, from: {} to: {}",
+                    collection, bucket, from_name, to_name
+                );
+
+                // Make 'diff' query
+                ChannelCommandBase::commit_result_operation(QueryBuilder::diff(
+                    collection, bucket, from_name, to_name,
+                ))
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "DIFF <collection> <bucket> <from_name> <to_name>",
+            )),
+        }
+    }
+
     pub fn dispatch_help(parts: SplitWhitespace) -> ChannelResult {
         ChannelCommandBase::generic_dispatch_help(parts, &*MANUAL_MODE_INGEST)
     }
 
-    fn handle_push_meta(
-        meta_result: MetaPartsResult,
-    ) -> Result<Option<QueryGenericLang>, ChannelCommandError> {
+    fn handle_push_meta(meta_result: MetaPartsResult) -> Result<PushMetaData, ChannelCommandError> {
         match meta_result {
             Ok((meta_key, meta_value)) => {
                 debug!("handle push meta: {} = {}", meta_key, meta_value);
@@ -823,7 +1717,87 @@ impl ChannelCommandIngest {
                     "LANG" => {
                         // 'LANG(<locale>)' where <locale> ∈ ISO 639-3
                         if let Some(query_lang_parsed) = QueryGenericLang::from_value(meta_value) {
-                            Ok(Some(query_lang_parsed))
+                            Ok(PushMetaData::Lang(query_lang_parsed))
+                        } else {
+                            Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            ))
+                        }
+                    }
+                    "SUGGEST" => {
+                        // 'SUGGEST(<suggest>)' where <suggest> ∈ {incremental, deferred}
+                        if let Some(push_suggest_parsed) = QueryPushSuggest::from_value(meta_value)
+                        {
+                            Ok(PushMetaData::Suggest(push_suggest_parsed))
+                        } else {
+                            Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            ))
+                        }
+                    }
+                    "BOOST" => {
+                        // 'BOOST(<boost>)' where <boost> is a strictly positive factor
+                        match meta_value.parse::<QueryPushBoost>() {
+                            Ok(push_boost_parsed)
+                                if push_boost_parsed.is_finite() && push_boost_parsed > 0.0 =>
+                            {
+                                Ok(PushMetaData::Boost(push_boost_parsed))
+                            }
+                            _ => Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            )),
+                        }
+                    }
+                    "KEY" => {
+                        // 'KEY(<key>)' where <key> is a client-generated idempotency key
+                        if !meta_value.is_empty()
+                            && meta_value.len() <= IDEMPOTENCY_KEY_LENGTH_MAXIMUM
+                        {
+                            Ok(PushMetaData::Key(meta_value))
+                        } else {
+                            Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            ))
+                        }
+                    }
+                    "WEIGHTS" => {
+                        // 'WEIGHTS(<weights>)' where <weights> is a comma-separated list of \
+                        //   '<term>:<weight>' pairs, each weight being a strictly positive factor
+                        let push_weights_parsed: Option<Vec<(&str, QueryPushWeight)>> = meta_value
+                            .split(',')
+                            .map(|meta_weight| {
+                                let separator_index = meta_weight.rfind(':')?;
+
+                                let (term, weight) = (
+                                    &meta_weight[..separator_index],
+                                    meta_weight[(separator_index + 1)..]
+                                        .parse::<QueryPushWeight>()
+                                        .ok()?,
+                                );
+
+                                if !term.is_empty() && weight.is_finite() && weight > 0.0 {
+                                    Some((term, weight))
+                                } else {
+                                    None
+                                }
+                            })
+                            .collect();
+
+                        match push_weights_parsed {
+                            Some(push_weights_parsed)
+                                if push_weights_parsed.len() <= PUSH_WEIGHTS_MAXIMUM =>
+                            {
+                                Ok(PushMetaData::Weights(push_weights_parsed))
+                            }
+                            _ => Err(ChannelCommandBase::make_error_invalid_meta_value(
+                                meta_key, meta_value,
+                            )),
+                        }
+                    }
+                    "VERBOSE" => {
+                        // 'VERBOSE(<verbose>)' where <verbose> ∈ {true, false}
+                        if let Ok(push_verbose_parsed) = meta_value.parse::<bool>() {
+                            Ok(PushMetaData::Verbose(push_verbose_parsed))
                         } else {
                             Err(ChannelCommandBase::make_error_invalid_meta_value(
                                 meta_key, meta_value,
@@ -840,6 +1814,36 @@ impl ChannelCommandIngest {
             )),
         }
     }
+
+    fn handle_retain_meta(
+        meta_result: MetaPartsResult,
+    ) -> Result<RetainMetaData, ChannelCommandError> {
+        match meta_result {
+            Ok((meta_key, meta_value)) => {
+                debug!("handle retain meta: {} = {}", meta_key, meta_value);
+
+                match (meta_key, meta_value.parse::<u32>()) {
+                    // 'AGE(<seconds>)' where 0 < <seconds> < 2^32
+                    ("AGE", Ok(retain_age_parsed)) if retain_age_parsed > 0 => {
+                        Ok((Some(retain_age_parsed), None))
+                    }
+                    // 'OBJECTS(<count>)' where 0 < <count> < 2^32
+                    ("OBJECTS", Ok(retain_objects_parsed)) if retain_objects_parsed > 0 => {
+                        Ok((None, Some(retain_objects_parsed)))
+                    }
+                    ("AGE", _) | ("OBJECTS", _) => Err(
+                        ChannelCommandBase::make_error_invalid_meta_value(meta_key, meta_value),
+                    ),
+                    _ => Err(ChannelCommandBase::make_error_invalid_meta_key(
+                        meta_key, meta_value,
+                    )),
+                }
+            }
+            Err(err) => Err(ChannelCommandBase::make_error_invalid_meta_key(
+                err.0, err.1,
+            )),
+        }
+    }
 }
 
 impl ChannelCommandControl {
@@ -855,8 +1859,9 @@ impl ChannelCommandControl {
                 match action_key_lower.as_str() {
                     "consolidate" => {
                         if data_part.is_none() {
-                            // Force a FST consolidate
+                            // Force a FST consolidate (and refresh stale IDF weights)
                             StoreFSTPool::consolidate(true);
+                            StoreKVPool::consolidate(true);
 
                             Ok(vec![ChannelCommandResponse::Ok])
                         } else {
@@ -869,8 +1874,15 @@ impl ChannelCommandControl {
                                 // Proceed KV + FST backup
                                 let path = Path::new(path);
 
+                                // Remove the manifest of any previous backup at path first \
+                                //   (as it would validate this backup, if interrupted)
+                                if Self::remove_backup_manifest(path).is_err() {
+                                    return Err(ChannelCommandError::InternalError);
+                                }
+
                                 if StoreKVPool::backup(&path.join(BACKUP_KV_PATH)).is_ok()
                                     && StoreFSTPool::backup(&path.join(BACKUP_FST_PATH)).is_ok()
+                                    && Self::write_backup_manifest(path).is_ok()
                                 {
                                     Ok(vec![ChannelCommandResponse::Ok])
                                 } else {
@@ -883,9 +1895,16 @@ impl ChannelCommandControl {
                     "restore" => {
                         match (data_part, last_part) {
                             (Some(path), None) => {
-                                // Proceed KV + FST restore
+                                // Proceed KV + FST restore (if the backup was made with the \
+                                //   current store format)
                                 let path = Path::new(path);
 
+                                if !Self::check_backup_manifest(path) {
+                                    return Err(ChannelCommandError::PolicyReject(
+                                        "TRIGGER restore requires a backup of the same format",
+                                    ));
+                                }
+
                                 if StoreKVPool::restore(&path.join(BACKUP_KV_PATH)).is_ok()
                                     && StoreFSTPool::restore(&path.join(BACKUP_FST_PATH)).is_ok()
                                 {
@@ -897,6 +1916,99 @@ impl ChannelCommandControl {
                             _ => Err(ChannelCommandError::InvalidFormat("TRIGGER restore <path>")),
                         }
                     }
+                    "unsuggest" => {
+                        match (
+                            data_part,
+                            last_part,
+                            ChannelCommandBase::parse_text_parts(&mut parts),
+                        ) {
+                            (Some(collection), Some(bucket), Some(text)) => {
+                                // Lex words the way popped text is lexed, as graph words are \
+                                //   stored normalized
+                                let words: Vec<String> = match TokenLexerBuilder::from(
+                                    TokenLexerMode::NormalizeOnly,
+                                    &text,
+                                ) {
+                                    Ok(lexer) => lexer.map(|(word, _)| word).collect(),
+                                    Err(_) => Vec::new(),
+                                };
+
+                                // Proceed FST words removal (KV is left untouched)
+                                if let Ok(count) =
+                                    StoreFSTPool::unsuggest(collection, bucket, &words)
+                                {
+                                    Ok(vec![ChannelCommandResponse::Result(count.to_string())])
+                                } else {
+                                    Err(ChannelCommandError::InternalError)
+                                }
+                            }
+                            _ => Err(ChannelCommandError::InvalidFormat(
+                                "TRIGGER unsuggest <collection> <bucket> \"<words>\"",
+                            )),
+                        }
+                    }
+                    "sync" => {
+                        if data_part.is_none() {
+                            // Force a KV WAL sync (responds once all writes are durable)
+                            if StoreKVPool::sync().is_ok() {
+                                Ok(vec![ChannelCommandResponse::Ok])
+                            } else {
+                                Err(ChannelCommandError::InternalError)
+                            }
+                        } else {
+                            Err(ChannelCommandError::InvalidFormat("TRIGGER sync"))
+                        }
+                    }
+                    "recompress" => {
+                        match (data_part, last_part) {
+                            (Some(collection), None) => {
+                                // Proceed KV full compaction (rewrites all data with the \
+                                //   configured compression codec)
+                                if let Ok((size_before, size_after)) =
+                                    StoreKVPool::recompress(collection)
+                                {
+                                    Ok(vec![ChannelCommandResponse::Result(format!(
+                                        "before({}) after({})",
+                                        size_before, size_after
+                                    ))])
+                                } else {
+                                    Err(ChannelCommandError::InternalError)
+                                }
+                            }
+                            _ => Err(ChannelCommandError::InvalidFormat(
+                                "TRIGGER recompress <collection>",
+                            )),
+                        }
+                    }
+                    "replay" => {
+                        match (data_part, last_part, parts.next()) {
+                            (Some(name), Some(collection), None) => {
+                                if let Some(ref record_path) = APP_CONF.channel.record_path {
+                                    // Proceed command record replay (against the given \
+                                    //   collection, in place of the recorded collections)
+                                    if let Ok(replay) = ChannelRecorder::replay(
+                                        Path::new(record_path),
+                                        name,
+                                        collection,
+                                    ) {
+                                        Ok(vec![ChannelCommandResponse::Result(format!(
+                                            "commands({}) errors({})",
+                                            replay.commands, replay.errors
+                                        ))])
+                                    } else {
+                                        Err(ChannelCommandError::InternalError)
+                                    }
+                                } else {
+                                    Err(ChannelCommandError::PolicyReject(
+                                        "TRIGGER replay requires a record path",
+                                    ))
+                                }
+                            }
+                            _ => Err(ChannelCommandError::InvalidFormat(
+                                "TRIGGER replay <record> <collection>",
+                            )),
+                        }
+                    }
                     _ => Err(ChannelCommandError::NotFound),
                 }
             }
@@ -911,33 +2023,377 @@ impl ChannelCommandControl {
                 Ok(vec![ChannelCommandResponse::Result(format!(
                     "uptime({}) clients_connected({}) commands_total({}) \
                      command_latency_best({}) command_latency_worst({}) \
-                     kv_open_count({}) fst_open_count({}) fst_consolidate_count({})",
+                     kv_open_count({}) kv_write_stall_count({}) kv_estimated_keys({}) \
+                     kv_pending_compaction_bytes({}) fst_open_count({}) \
+                     fst_consolidate_count({}) fst_consolidate_took({})",
                     statistics.uptime,
                     statistics.clients_connected,
                     statistics.commands_total,
                     statistics.command_latency_best,
                     statistics.command_latency_worst,
                     statistics.kv_open_count,
+                    statistics.kv_write_stall_count,
+                    statistics.kv_estimated_keys,
+                    statistics.kv_pending_compaction_bytes,
                     statistics.fst_open_count,
-                    statistics.fst_consolidate_count
+                    statistics.fst_consolidate_count,
+                    statistics.fst_consolidate_took
                 ))])
             }
             _ => Err(ChannelCommandError::InvalidFormat("INFO")),
         }
     }
 
+    pub fn dispatch_metrics(mut parts: SplitWhitespace) -> ChannelResult {
+        match parts.next() {
+            None => {
+                let metrics = ChannelStatistics::gather().to_prometheus();
+
+                // Respond with the number of lines, followed by the metrics lines as-is (as \
+                //   the Prometheus text format spans over multiple lines)
+                let mut responses = Vec::with_capacity(metrics.len() + 1);
+
+                responses.push(ChannelCommandResponse::Result(format!(
+                    "lines({})",
+                    metrics.len()
+                )));
+                responses.extend(metrics.into_iter().map(ChannelCommandResponse::Line));
+
+                Ok(responses)
+            }
+            _ => Err(ChannelCommandError::InvalidFormat("METRICS")),
+        }
+    }
+
+    pub fn dispatch_errors(mut parts: SplitWhitespace) -> ChannelResult {
+        match parts.next() {
+            None => {
+                let recent_errors: Vec<String> = ChannelRecentErrors::list()
+                    .into_iter()
+                    .map(|recent_error| {
+                        format!(
+                            "error({},{},{},{})",
+                            recent_error.time,
+                            recent_error.command,
+                            recent_error.collection.as_deref().unwrap_or("none"),
+                            recent_error.message
+                        )
+                    })
+                    .collect();
+
+                Ok(vec![ChannelCommandResponse::Result(
+                    recent_errors.join(" "),
+                )])
+            }
+            _ => Err(ChannelCommandError::InvalidFormat("ERRORS")),
+        }
+    }
+
+    pub fn dispatch_stopwords(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next()) {
+            (Some(action_key), Some(collection)) => {
+                let action_key_lower = action_key.to_lowercase();
+
+                debug!(
+                    "dispatching control stopwords operation: {} in collection: {}",
+                    action_key_lower, collection
+                );
+
+                match action_key_lower.as_str() {
+                    "get" if parts.next().is_none() => StoreKVPool::get_stopwords(collection)
+                        .map(|stopwords| vec![ChannelCommandResponse::Result(stopwords.join(" "))])
+                        .or(Err(ChannelCommandError::InternalError)),
+                    "set" => match ChannelCommandBase::parse_text_parts(&mut parts) {
+                        Some(text) if parts.next().is_none() => {
+                            Self::commit_stopwords(collection, Self::parse_stopwords(&text)?)
+                        }
+                        _ => Err(ChannelCommandError::InvalidFormat(
+                            "STOPWORDS set <collection> \"<words>\"",
+                        )),
+                    },
+                    "clear" if parts.next().is_none() => {
+                        Self::commit_stopwords(collection, HashSet::new())
+                    }
+                    _ => Err(ChannelCommandError::InvalidFormat(
+                        "STOPWORDS <operation> <collection> [\"<words>\"]?",
+                    )),
+                }
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "STOPWORDS <operation> <collection> [\"<words>\"]?",
+            )),
+        }
+    }
+
+    pub fn dispatch_cache(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(action_key), argument, collection, None) => {
+                let action_key_lower = action_key.to_lowercase();
+
+                debug!("dispatching control cache operation: {}", action_key_lower);
+
+                match (action_key_lower.as_str(), argument, collection) {
+                    ("get", None, None) => Ok(vec![Self::format_block_cache()]),
+                    ("set", Some(capacity), None) => match capacity.parse::<usize>() {
+                        Ok(capacity) => {
+                            if StoreKVPool::resize_block_cache(capacity).is_ok() {
+                                Ok(vec![ChannelCommandResponse::Ok])
+                            } else {
+                                Err(ChannelCommandError::PolicyReject(
+                                    "CACHE capacity below minimum",
+                                ))
+                            }
+                        }
+                        Err(_) => Err(ChannelCommandError::InvalidFormat("CACHE set <bytes>")),
+                    },
+                    ("stats", Some(cache), collection) => {
+                        match (cache.to_lowercase().as_str(), collection) {
+                            ("block", None) => Ok(vec![Self::format_block_cache()]),
+                            ("idf", collection) if Self::is_cache_collection(collection) => {
+                                let usage = StoreKVPool::idf_cache(collection)
+                                    .or(Err(ChannelCommandError::InternalError))?;
+
+                                Ok(vec![ChannelCommandResponse::Result(format!(
+                                    "entries({}) buckets({}) hits({}) misses({})",
+                                    usage.entries, usage.buckets, usage.hits, usage.misses
+                                ))])
+                            }
+                            _ => Err(ChannelCommandError::InvalidFormat(
+                                "CACHE stats <cache> [<collection>]?",
+                            )),
+                        }
+                    }
+                    ("clear", Some(cache), collection) => {
+                        match (cache.to_lowercase().as_str(), collection) {
+                            ("block", None) => {
+                                StoreKVPool::clear_block_cache();
+
+                                Ok(vec![ChannelCommandResponse::Ok])
+                            }
+                            ("idf", collection) if Self::is_cache_collection(collection) => {
+                                StoreKVPool::clear_idf_cache(collection)
+                                    .map(|count| {
+                                        vec![ChannelCommandResponse::Result(count.to_string())]
+                                    })
+                                    .or(Err(ChannelCommandError::InternalError))
+                            }
+                            _ => Err(ChannelCommandError::InvalidFormat(
+                                "CACHE clear <cache> [<collection>]?",
+                            )),
+                        }
+                    }
+                    _ => Err(ChannelCommandError::InvalidFormat(
+                        "CACHE <operation> [<bytes> | <cache> [<collection>]?]?",
+                    )),
+                }
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "CACHE <operation> [<bytes> | <cache> [<collection>]?]?",
+            )),
+        }
+    }
+
+    pub fn dispatch_list(mut parts: SplitWhitespace) -> ChannelResult {
+        let (target, collection) = (parts.next().map(str::to_lowercase), parts.next());
+
+        let names = match (target.as_deref(), collection, parts.next()) {
+            (Some("collections"), None, None) => {
+                debug!("dispatching control list collections");
+
+                StoreKVPool::list_collections()
+            }
+            (Some("buckets"), Some(collection), None) => {
+                debug!(
+                    "dispatching control list buckets in collection: {}",
+                    collection
+                );
+
+                StoreKVPool::list_buckets(collection)
+            }
+            _ => {
+                return Err(ChannelCommandError::InvalidFormat(
+                    "LIST <target> [<collection>]?",
+                ))
+            }
+        };
+
+        match names {
+            Ok(names) if names.is_empty() => {
+                Ok(vec![ChannelCommandResponse::Result("none".to_string())])
+            }
+            Ok(names) => Ok(vec![ChannelCommandResponse::Result(names.join(" "))]),
+            Err(_) => Err(ChannelCommandError::InternalError),
+        }
+    }
+
+    pub fn dispatch_unfreeze(mut parts: SplitWhitespace) -> ChannelResult {
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(collection), Some(bucket), None) => {
+                debug!(
+                    "dispatching control unfreeze in collection: {}, bucket: {}",
+                    collection, bucket
+                );
+
+                // Make 'freeze' query (lifting immutability)
+                ChannelCommandBase::commit_ok_operation(QueryBuilder::freeze(
+                    collection, bucket, false,
+                ))
+            }
+            _ => Err(ChannelCommandError::InvalidFormat(
+                "UNFREEZE <collection> <bucket>",
+            )),
+        }
+    }
+
+    fn parse_stopwords(text: &str) -> Result<HashSet<String>, ChannelCommandError> {
+        let mut stopwords = HashSet::new();
+
+        for word in text.split_whitespace() {
+            let word = word.to_lowercase();
+
+            // Only accept words that the lexer would yield as-is (otherwise those could never \
+            //   be matched against lexed words)
+            if word.chars().count() > STOPWORDS_WORD_LENGTH_MAXIMUM
+                || word.unicode_words().ne(std::iter::once(word.as_str()))
+            {
+                return Err(ChannelCommandError::PolicyReject(
+                    "STOPWORDS word is not a single word",
+                ));
+            }
+
+            stopwords.insert(word);
+        }
+
+        if stopwords.len() > STOPWORDS_MAXIMUM {
+            Err(ChannelCommandError::PolicyReject(
+                "STOPWORDS above maximum words",
+            ))
+        } else {
+            Ok(stopwords)
+        }
+    }
+
+    fn write_backup_manifest(path: &Path) -> Result<(), io::Error> {
+        // Notice: the manifest gets written last, so that an interrupted backup has no manifest.
+        fs::write(
+            path.join(BACKUP_MANIFEST_PATH),
+            format!(
+                "format({}) keys({}) seed({}) version({})\n",
+                STORE_FORMAT_REVISION,
+                StoreKVBuilder::key_format(),
+                APP_CONF.store.kv.bucket_hash_seed,
+                env!("CARGO_PKG_VERSION")
+            ),
+        )
+    }
+
+    fn remove_backup_manifest(path: &Path) -> Result<(), io::Error> {
+        match fs::remove_file(path.join(BACKUP_MANIFEST_PATH)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_backup_manifest(path: &Path) -> bool {
+        match fs::read_to_string(path.join(BACKUP_MANIFEST_PATH)) {
+            Ok(manifest) => {
+                let value = |name: &str| {
+                    manifest
+                        .split_whitespace()
+                        .find_map(|part| part.strip_prefix(name)?.strip_prefix('('))
+                        .and_then(|part| part.strip_suffix(')'))
+                        .and_then(|part| part.parse::<u32>().ok())
+                };
+
+                // Notice: the key format and bucket hash seed must match as well, as restored \
+                //   collections would refuse to open otherwise.
+                if value("format") == Some(STORE_FORMAT_REVISION as u32)
+                    && value("keys") == Some(StoreKVBuilder::key_format() as u32)
+                    && value("seed") == Some(APP_CONF.store.kv.bucket_hash_seed)
+                {
+                    true
+                } else {
+                    error!(
+                        "refusing to restore backup at path: {:?} with manifest: {}",
+                        path,
+                        manifest.trim()
+                    );
+
+                    false
+                }
+            }
+            Err(err) => {
+                error!(
+                    "refusing to restore backup at path: {:?} without manifest: {}",
+                    path, err
+                );
+
+                false
+            }
+        }
+    }
+
+    fn format_block_cache() -> ChannelCommandResponse {
+        let block_cache = StoreKVPool::block_cache();
+
+        ChannelCommandResponse::Result(format!(
+            "capacity({}) usage({}) pinned_usage({})",
+            block_cache.capacity, block_cache.usage, block_cache.pinned_usage
+        ))
+    }
+
+    fn is_cache_collection(collection: Option<&str>) -> bool {
+        collection.map_or(true, |collection| {
+            StoreItemPart::from_str(collection).is_ok()
+        })
+    }
+
+    fn commit_stopwords(collection: &str, stopwords: HashSet<String>) -> ChannelResult {
+        if StoreItemPart::from_str(collection).is_err() {
+            return Err(ChannelCommandError::InvalidFormat(
+                "STOPWORDS <operation> <collection> [\"<words>\"]?",
+            ));
+        }
+
+        if StoreKVPool::set_stopwords(collection, stopwords).is_ok() {
+            Ok(vec![ChannelCommandResponse::Ok])
+        } else {
+            Err(ChannelCommandError::InternalError)
+        }
+    }
+
     pub fn dispatch_help(parts: SplitWhitespace) -> ChannelResult {
         ChannelCommandBase::generic_dispatch_help(parts, &*MANUAL_MODE_CONTROL)
     }
 }
 
+impl ChannelCommandError {
+    pub fn to_redacted_string(&self) -> String {
+        // Strip values that come from the issued command (those may contain user text)
+        match self {
+            ChannelCommandError::InvalidMetaKey(_) => "invalid_meta_key".to_string(),
+            ChannelCommandError::InvalidMetaValue(ref data) => {
+                format!("invalid_meta_value({})", data.0)
+            }
+            _ => self.to_string(),
+        }
+    }
+}
+
 impl fmt::Display for ChannelCommandError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         match self {
             ChannelCommandError::UnknownCommand => write!(f, "unknown_command"),
             ChannelCommandError::NotFound => write!(f, "not_found"),
             ChannelCommandError::QueryError => write!(f, "query_error"),
+            ChannelCommandError::QueryTimeout => write!(f, "query_timeout"),
+            ChannelCommandError::BucketFull => write!(f, "bucket_full"),
+            ChannelCommandError::BucketImmutable => write!(f, "bucket_immutable"),
+            ChannelCommandError::BucketCollision => write!(f, "bucket_collision"),
+            ChannelCommandError::StoreBusy => write!(f, "store_busy"),
+            ChannelCommandError::StoreCorrupted => write!(f, "store_corrupted"),
             ChannelCommandError::InternalError => write!(f, "internal_error"),
+            ChannelCommandError::InvalidEncoding => write!(f, "invalid_encoding"),
             ChannelCommandError::ShuttingDown => write!(f, "shutting_down"),
             ChannelCommandError::PolicyReject(reason) => write!(f, "policy_reject({})", reason),
             ChannelCommandError::InvalidFormat(format) => write!(f, "invalid_format({})", format),
@@ -947,6 +2403,9 @@ impl fmt::Display for ChannelCommandError {
             ChannelCommandError::InvalidMetaValue(ref data) => {
                 write!(f, "invalid_meta_value({}[{}])", data.0, data.1)
             }
+            ChannelCommandError::InvalidExpression(ref data) => {
+                write!(f, "invalid_expression({}[{}])", data.0, data.1)
+            }
         }
     }
 }
@@ -954,6 +2413,8 @@ impl fmt::Display for ChannelCommandError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::store::identifiers::StoreTermHash;
+    use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder};
 
     #[test]
     fn it_matches_command_response_string() {
@@ -967,4 +2428,521 @@ mod tests {
             "ERR"
         );
     }
+
+    #[test]
+    fn it_dispatches_metrics() {
+        let responses = ChannelCommandControl::dispatch_metrics("".split_whitespace())
+            .ok()
+            .expect("metrics should be returned");
+
+        let lines: Vec<String> = responses
+            .iter()
+            .skip(1)
+            .filter_map(|response| match response {
+                ChannelCommandResponse::Line(line) => Some(line.to_owned()),
+                _ => None,
+            })
+            .collect();
+
+        if let ChannelCommandResponse::Result(ref result) = responses[0] {
+            assert_eq!(result, &format!("lines({})", lines.len()));
+        } else {
+            panic!("metrics should start with their number of lines");
+        }
+        assert!(lines.contains(&"# TYPE sonic_commands_total counter".to_string()));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("sonic_kv_write_stalls_total ")));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("sonic_kv_pending_compaction_bytes ")));
+        assert!(ChannelCommandControl::dispatch_metrics("all".split_whitespace()).is_err());
+    }
+
+    #[test]
+    fn it_dispatches_version() {
+        let response = ChannelCommandBase::dispatch_version("".split_whitespace());
+
+        if let Ok(ref responses) = response {
+            if let [ChannelCommandResponse::Result(ref result)] = responses[..] {
+                assert!(result.contains(&format!("version({})", env!("CARGO_PKG_VERSION"))));
+                assert!(result.contains(&format!("format({})", STORE_FORMAT_REVISION)));
+            } else {
+                panic!("version response should be a single result");
+            }
+        } else {
+            panic!("version command should succeed");
+        }
+
+        assert!(ChannelCommandBase::dispatch_version("extra".split_whitespace()).is_err());
+    }
+
+    #[test]
+    fn it_handles_retain_meta() {
+        assert!(
+            ChannelCommandIngest::handle_retain_meta(Ok(("AGE", "3600"))).ok()
+                == Some((Some(3600), None))
+        );
+        assert!(
+            ChannelCommandIngest::handle_retain_meta(Ok(("OBJECTS", "100"))).ok()
+                == Some((None, Some(100)))
+        );
+        assert!(ChannelCommandIngest::handle_retain_meta(Ok(("AGE", "0"))).is_err());
+        assert!(ChannelCommandIngest::handle_retain_meta(Ok(("OBJECTS", "-1"))).is_err());
+        assert!(ChannelCommandIngest::handle_retain_meta(Ok(("LIMIT", "10"))).is_err());
+    }
+
+    #[test]
+    fn it_handles_query_federate_meta() {
+        assert!(
+            ChannelCommandSearch::handle_query_meta(Ok(("FEDERATE", "articles,pages"))).ok()
+                == Some(QueryMetaData::Federate(vec!["articles", "pages"]))
+        );
+        assert!(ChannelCommandSearch::handle_query_meta(Ok(("FEDERATE", "articles,"))).is_err());
+        assert!(ChannelCommandSearch::handle_query_meta(Ok(("FEDERATE", ""))).is_err());
+    }
+
+    #[test]
+    fn it_handles_query_timeout_meta() {
+        assert!(
+            ChannelCommandSearch::handle_query_meta(Ok(("TIMEOUT", "error"))).ok()
+                == Some(QueryMetaData::Timeout(QuerySearchTimeout::Error))
+        );
+        assert!(ChannelCommandSearch::handle_query_meta(Ok(("TIMEOUT", "1000"))).is_err());
+    }
+
+    #[test]
+    fn it_handles_query_fuzzy_meta() {
+        assert!(
+            ChannelCommandSearch::handle_query_meta(Ok(("FUZZY", "0"))).ok()
+                == Some(QueryMetaData::Typos(0))
+        );
+        assert!(
+            ChannelCommandSearch::handle_query_meta(Ok(("FUZZY", "2"))).ok()
+                == Some(QueryMetaData::Typos(2))
+        );
+        assert!(ChannelCommandSearch::handle_query_meta(Ok(("FUZZY", "3"))).is_err());
+        assert!(ChannelCommandSearch::handle_query_meta(Ok(("FUZZY", "-1"))).is_err());
+    }
+
+    #[test]
+    fn it_handles_query_terms_meta() {
+        assert!(
+            ChannelCommandSearch::handle_query_meta(Ok(("TERMS", "true"))).ok()
+                == Some(QueryMetaData::Terms(true))
+        );
+        assert!(ChannelCommandSearch::handle_query_meta(Ok(("TERMS", "yes"))).is_err());
+    }
+
+    #[test]
+    fn it_handles_query_partial_meta() {
+        assert!(
+            ChannelCommandSearch::handle_query_meta(Ok(("PARTIAL", "true"))).ok()
+                == Some(QueryMetaData::Partial(QuerySearchCompletion::LastTerm))
+        );
+        assert!(
+            ChannelCommandSearch::handle_query_meta(Ok(("PARTIAL", "false"))).ok()
+                == Some(QueryMetaData::Partial(QuerySearchCompletion::Disabled))
+        );
+        assert!(ChannelCommandSearch::handle_query_meta(Ok(("PARTIAL", "yes"))).is_err());
+    }
+
+    #[test]
+    fn it_handles_push_weights_meta() {
+        assert!(
+            ChannelCommandIngest::handle_push_meta(Ok(("WEIGHTS", "sonic:4,backend:0.5"))).ok()
+                == Some(PushMetaData::Weights(vec![
+                    ("sonic", 4.0),
+                    ("backend", 0.5)
+                ]))
+        );
+        assert!(ChannelCommandIngest::handle_push_meta(Ok(("WEIGHTS", "sonic:0"))).is_err());
+        assert!(ChannelCommandIngest::handle_push_meta(Ok(("WEIGHTS", "sonic:inf"))).is_err());
+        assert!(ChannelCommandIngest::handle_push_meta(Ok(("WEIGHTS", ":2"))).is_err());
+        assert!(ChannelCommandIngest::handle_push_meta(Ok(("WEIGHTS", "sonic"))).is_err());
+    }
+
+    #[test]
+    fn it_pushes_once_per_idempotency_key() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:13").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:13").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        let push = |object: &str, text: &str| {
+            ChannelCommandIngest::dispatch_push(
+                format!(
+                    "c:test:13 b:test:13 {} \"{}\" KEY(k:1) VERBOSE(true)",
+                    object, text
+                )
+                .split_whitespace(),
+            )
+            .ok()
+            .and_then(|mut responses| responses.pop())
+        };
+
+        assert!(matches!(
+            push("o:1", "first text"),
+            Some(ChannelCommandResponse::Result(_))
+        ));
+
+        // Replayed pushes are acknowledged with no push stats, as nothing gets indexed
+        assert!(push("o:1", "retried text") == Some(ChannelCommandResponse::Ok));
+
+        assert!(action
+            .get_term_to_iids(StoreTermHash::from("first"))
+            .unwrap()
+            .is_some());
+        assert!(action
+            .get_term_to_iids(StoreTermHash::from("retried"))
+            .unwrap()
+            .is_none());
+
+        // The same key used for another object does not skip its push
+        assert!(matches!(
+            push("o:2", "other text"),
+            Some(ChannelCommandResponse::Result(_))
+        ));
+
+        assert!(action
+            .get_term_to_iids(StoreTermHash::from("other"))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn it_counts_bucket_objects() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:73").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:73").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        for object in &["o:1", "o:2"] {
+            assert!(ChannelCommandIngest::dispatch_push(
+                format!("c:test:73 b:test:73 {} \"lighthouse keeper\"", object).split_whitespace()
+            )
+            .is_ok());
+        }
+
+        let count = |parts: &str| {
+            ChannelCommandIngest::dispatch_count(parts.split_whitespace())
+                .ok()
+                .and_then(|mut responses| responses.pop())
+        };
+
+        assert!(
+            count("c:test:73 b:test:73 OBJECTS")
+                == Some(ChannelCommandResponse::Result("2".to_string()))
+        );
+        assert!(
+            count("c:test:73 b:test:73 o:1")
+                == Some(ChannelCommandResponse::Result("2".to_string()))
+        );
+        assert!(count("c:test:73 b:test:73 OBJECTS extra").is_none());
+    }
+
+    #[test]
+    fn it_dispatches_validate() {
+        assert!(
+            ChannelCommandSearch::dispatch_validate(
+                "\"(Michael OR Mike) AND NOT Dake\"".split_whitespace()
+            )
+            .ok()
+                == Some(vec![ChannelCommandResponse::Result(
+                    "((Michael OR Mike) AND NOT Dake)".to_string()
+                )])
+        );
+
+        let validate_err = |text: &str| {
+            ChannelCommandSearch::dispatch_validate(text.split_whitespace())
+                .err()
+                .map(|err| err.to_string())
+        };
+
+        assert_eq!(
+            validate_err("\"(Michael OR Mike AND Dake\""),
+            Some("invalid_expression(unbalanced_group[0])".to_string())
+        );
+        assert_eq!(
+            validate_err("\"Michael AND\""),
+            Some("invalid_expression(missing_operand[11])".to_string())
+        );
+        assert_eq!(
+            validate_err("\"NOT Dake\""),
+            Some("invalid_expression(unbound_negation[0])".to_string())
+        );
+        assert_eq!(
+            validate_err("\"Michael\" Dake"),
+            Some("invalid_format(VALIDATE \"<terms>\")".to_string())
+        );
+    }
+
+    #[test]
+    fn it_drops_uploaded_stopwords_from_ingests() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:22").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:22").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+        assert!(
+            ChannelCommandControl::dispatch_stopwords("clear c:test:22".split_whitespace()).is_ok()
+        );
+
+        let push = |object: &str| {
+            ChannelCommandIngest::dispatch_push(
+                format!("c:test:22 b:test:22 {} \"acme anvil\" LANG(eng)", object)
+                    .split_whitespace(),
+            )
+            .is_ok()
+        };
+
+        let has_term = |object: &str, term: &str| {
+            let iid = action.get_oid_to_iid(object).unwrap().unwrap();
+
+            action
+                .get_iid_to_terms(iid)
+                .unwrap()
+                .unwrap_or_default()
+                .contains(&StoreTermHash::from(term))
+        };
+
+        assert!(push("o:before"));
+        assert!(ChannelCommandControl::dispatch_stopwords(
+            "set c:test:22 \"Acme\"".split_whitespace()
+        )
+        .is_ok());
+        assert!(push("o:after"));
+
+        // Previously indexed occurrences remain, until the object gets pushed again
+        assert!(has_term("o:before", "acme"));
+        assert!(!has_term("o:after", "acme"));
+        assert!(has_term("o:after", "anvil"));
+
+        assert_eq!(
+            ChannelCommandControl::dispatch_stopwords("get c:test:22".split_whitespace())
+                .ok()
+                .map(|response| response[0].to_args().1),
+            Some(Some(vec!["acme".to_string()]))
+        );
+
+        assert!(ChannelCommandControl::dispatch_stopwords(
+            "set c:test:22 \"acme-corp\"".split_whitespace()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn it_returns_verbose_push_stats() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:25").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:25").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        let push = |text: &str, verbose: &str| {
+            ChannelCommandIngest::dispatch_push(
+                format!(
+                    "c:test:25 b:test:25 o:1 \"{}\" LANG(eng) VERBOSE({})",
+                    text, verbose
+                )
+                .split_whitespace(),
+            )
+            .ok()
+            .map(|response| {
+                let (name, values) = response[0].to_args();
+
+                // Notice: the processing time varies, thus leave it aside
+                let values = values.map(|values| {
+                    values[0]
+                        .split(' ')
+                        .filter(|value| !value.starts_with("took("))
+                        .collect::<Vec<&str>>()
+                        .join(" ")
+                });
+
+                (name, values)
+            })
+        };
+
+        assert_eq!(
+            push("lighthouse keeper", "true"),
+            Some((
+                "RESULT",
+                Some("tokens(2) terms_new(2) terms_existing(0) object(new)".to_string())
+            ))
+        );
+        assert_eq!(
+            push("lighthouse harbor", "true"),
+            Some((
+                "RESULT",
+                Some("tokens(2) terms_new(1) terms_existing(1) object(existing)".to_string())
+            ))
+        );
+        assert_eq!(push("lighthouse harbor", "false"), Some(("OK", None)));
+    }
+
+    #[test]
+    fn it_resizes_block_cache() {
+        let cache_capacity =
+            || match ChannelCommandControl::dispatch_cache("get".split_whitespace()).as_deref() {
+                Ok([ChannelCommandResponse::Result(result)]) => result
+                    .strip_prefix("capacity(")
+                    .and_then(|result| result.split(')').next())
+                    .map(|capacity| capacity.to_string()),
+                _ => None,
+            };
+
+        let capacity_default = APP_CONF.store.kv.database.block_cache * 1024;
+
+        assert_eq!(cache_capacity(), Some(capacity_default.to_string()));
+
+        // Reduce capacity, which should be reflected in the reported cache size
+        assert!(ChannelCommandControl::dispatch_cache("set 2097152".split_whitespace()).is_ok());
+        assert_eq!(cache_capacity(), Some("2097152".to_string()));
+
+        // Reject invalid capacities (the capacity must be left unchanged)
+        assert!(ChannelCommandControl::dispatch_cache("set 1024".split_whitespace()).is_err());
+        assert!(ChannelCommandControl::dispatch_cache("set -1".split_whitespace()).is_err());
+        assert!(ChannelCommandControl::dispatch_cache("set".split_whitespace()).is_err());
+        assert_eq!(cache_capacity(), Some("2097152".to_string()));
+
+        assert!(ChannelCommandControl::dispatch_cache(
+            format!("set {}", capacity_default).split_whitespace()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn it_clears_idf_cache() {
+        let dispatch = |command: &str| {
+            ChannelCommandControl::dispatch_cache(command.split_whitespace())
+                .ok()
+                .and_then(|mut responses| responses.pop())
+                .map(|response| match response {
+                    ChannelCommandResponse::Result(result) => result,
+                    _ => "OK".to_string(),
+                })
+        };
+
+        let entries = || {
+            dispatch("stats idf c:test:42").and_then(|result| {
+                result
+                    .strip_prefix("entries(")
+                    .and_then(|result| result.split(')').next())
+                    .map(|entries| entries.to_string())
+            })
+        };
+
+        assert!(ChannelCommandIngest::dispatch_flushc("c:test:42".split_whitespace()).is_ok());
+
+        for (object, text) in &[("o:1", "cached lighthouse"), ("o:2", "cached harbor")] {
+            assert!(ChannelCommandIngest::dispatch_push(
+                format!("c:test:42 b:test:42 {} \"{}\"", object, text).split_whitespace()
+            )
+            .is_ok());
+        }
+
+        assert_eq!(entries(), Some("0".to_string()));
+
+        // Computing weights populates the cache, while clearing it resets its entry count
+        assert!(
+            ChannelCommandIngest::dispatch_idf("c:test:42 b:test:42".split_whitespace()).is_ok()
+        );
+        assert_eq!(entries(), Some("3".to_string()));
+
+        assert_eq!(dispatch("clear idf c:test:42"), Some("3".to_string()));
+        assert_eq!(entries(), Some("0".to_string()));
+
+        // Cleared weights are computed on the fly again (and counted as misses), until the \
+        //   cache gets populated again
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:42").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:42").unwrap(), store);
+
+        let (hits, misses) = {
+            let usage = StoreKVPool::idf_cache(Some("c:test:42")).unwrap();
+
+            (usage.hits, usage.misses)
+        };
+
+        let terms = [StoreTermHash::from("cached"), StoreTermHash::from("harbor")];
+        let terms_idf = action.get_terms_idf(&terms);
+
+        assert!(StoreKVPool::idf_cache(Some("c:test:42")).unwrap().misses >= misses + 2);
+
+        assert!(
+            ChannelCommandIngest::dispatch_idf("c:test:42 b:test:42".split_whitespace()).is_ok()
+        );
+        assert_eq!(entries(), Some("3".to_string()));
+
+        assert_eq!(action.get_terms_idf(&terms), terms_idf);
+        assert!(StoreKVPool::idf_cache(Some("c:test:42")).unwrap().hits >= hits + 2);
+
+        // Clearing the block cache keeps its capacity, and invalid caches are rejected
+        let capacity_default = APP_CONF.store.kv.database.block_cache * 1024;
+
+        assert_eq!(dispatch("clear block"), Some("OK".to_string()));
+        assert!(dispatch("stats block")
+            .unwrap()
+            .starts_with(&format!("capacity({})", capacity_default)));
+
+        assert!(dispatch("clear block c:test:42").is_none());
+        assert!(dispatch("clear query").is_none());
+        assert!(dispatch("stats").is_none());
+    }
+
+    #[test]
+    fn it_checks_backup_manifests() {
+        let path = std::env::temp_dir().join("sonic-test-backup-manifest");
+
+        fs::create_dir_all(&path).unwrap();
+        fs::remove_file(path.join(BACKUP_MANIFEST_PATH)).ok();
+
+        // Backups without manifest are not restored (eg. interrupted backups)
+        assert!(!ChannelCommandControl::check_backup_manifest(&path));
+
+        assert!(ChannelCommandControl::write_backup_manifest(&path).is_ok());
+        assert!(ChannelCommandControl::check_backup_manifest(&path));
+
+        // Backups of another store format, key format or bucket hash seed are not restored
+        let manifest = fs::read_to_string(path.join(BACKUP_MANIFEST_PATH)).unwrap();
+
+        let seed = APP_CONF.store.kv.bucket_hash_seed;
+
+        for (part, part_other) in &[
+            (
+                format!("format({})", STORE_FORMAT_REVISION),
+                "format(0)".to_string(),
+            ),
+            (
+                format!("keys({})", StoreKVBuilder::key_format()),
+                "keys(0)".to_string(),
+            ),
+            (
+                format!("keys({})", StoreKVBuilder::key_format()),
+                String::new(),
+            ),
+            (
+                format!("seed({})", seed),
+                format!("seed({})", seed.wrapping_add(1)),
+            ),
+        ] {
+            fs::write(
+                path.join(BACKUP_MANIFEST_PATH),
+                manifest.replace(part, part_other),
+            )
+            .unwrap();
+
+            assert!(!ChannelCommandControl::check_backup_manifest(&path));
+        }
+
+        // The manifest of a previous backup gets removed
+        assert!(ChannelCommandControl::remove_backup_manifest(&path).is_ok());
+        assert!(ChannelCommandControl::remove_backup_manifest(&path).is_ok());
+        assert!(!ChannelCommandControl::check_backup_manifest(&path));
+
+        fs::remove_dir_all(&path).ok();
+    }
 }
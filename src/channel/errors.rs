@@ -0,0 +1,153 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use std::collections::VecDeque;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::command::ChannelCommandError;
+use crate::APP_CONF;
+
+pub struct ChannelRecentErrors;
+
+#[derive(Clone)]
+pub struct ChannelRecentError {
+    pub time: u64,
+    pub command: String,
+    pub collection: Option<String>,
+    pub message: String,
+}
+
+// Commands that take a collection as their first argument (the collection is recorded along \
+//   with errors raised by those commands)
+pub static COLLECTION_COMMANDS: &[&str] = &[
+    "QUERY",
+    "SUGGEST",
+    "COOCCUR",
+    "LIST",
+    "LISTO",
+    "PUSH",
+    "POP",
+    "COUNT",
+    "TERMS",
+    "IDF",
+    "FLUSHC",
+    "FLUSHB",
+    "FLUSHO",
+    "MOVEO",
+    "SWAP",
+    "RETAIN",
+    "RETENTION",
+    "FREEZE",
+    "UNFREEZE",
+    "SNAPSHOT",
+    "DIFF",
+];
+
+lazy_static! {
+    static ref RECENT_ERRORS: RwLock<VecDeque<ChannelRecentError>> = RwLock::new(VecDeque::new());
+}
+
+impl ChannelRecentErrors {
+    pub fn record(message: &str, error: &ChannelCommandError) {
+        let maximum = APP_CONF.channel.recent_errors;
+
+        // Recent errors are disabled? (do not record anything)
+        if maximum == 0 {
+            return;
+        }
+
+        // Important: only keep the command name and its collection from the message, as the \
+        //   rest of the message may contain user text (eg. pushed text, or query terms).
+        let mut parts = message.split_whitespace();
+
+        let command = parts.next().unwrap_or("").to_uppercase();
+
+        let collection = if COLLECTION_COMMANDS.contains(&command.as_str()) {
+            parts.next().map(|collection| collection.to_string())
+        } else {
+            None
+        };
+
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        let mut recent_errors = RECENT_ERRORS.write().unwrap();
+
+        // Evict oldest errors first, if the buffer is full
+        while recent_errors.len() >= maximum {
+            recent_errors.pop_front();
+        }
+
+        recent_errors.push_back(ChannelRecentError {
+            time,
+            command,
+            collection,
+            message: error.to_redacted_string(),
+        });
+    }
+
+    pub fn list() -> Vec<ChannelRecentError> {
+        // List most recent errors first
+        RECENT_ERRORS
+            .read()
+            .unwrap()
+            .iter()
+            .rev()
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::message::{ChannelMessage, ChannelMessageModeIngest};
+    use super::*;
+
+    #[test]
+    fn it_records_recent_errors() {
+        assert!(ChannelMessage::dispatch::<ChannelMessageModeIngest>(
+            "PUSH c:test:16 b:test:16 o:1 \"private text\" LANG(private)"
+        )
+        .is_err());
+
+        let failed_error = ChannelRecentErrors::list()
+            .into_iter()
+            .find(|recent_error| recent_error.collection == Some("c:test:16".to_string()))
+            .expect("failed push should be recorded");
+
+        assert_eq!(failed_error.command, "PUSH");
+        assert_eq!(failed_error.message, "invalid_meta_value(LANG)");
+
+        // Fill the buffer above its bound (oldest errors get evicted)
+        let maximum = APP_CONF.channel.recent_errors;
+
+        for index in 0..(maximum + 10) {
+            ChannelRecentErrors::record(
+                &format!("FLUSHC c:test:16:{}", index),
+                &ChannelCommandError::QueryError,
+            );
+        }
+
+        let recent_errors = ChannelRecentErrors::list();
+
+        assert_eq!(recent_errors.len(), maximum);
+
+        // Notice: other tests may record errors at the same time, thus only check that the \
+        //   oldest errors were evicted, and that the newest error is listed (though not \
+        //   necessarily first).
+        assert!(!recent_errors.iter().any(|recent_error| {
+            recent_error.collection == Some("c:test:16".to_string())
+                || recent_error.collection == Some("c:test:16:0".to_string())
+                || recent_error.collection == Some("c:test:16:9".to_string())
+        }));
+        assert!(recent_errors.iter().any(|recent_error| {
+            recent_error.collection == Some(format!("c:test:16:{}", maximum + 9))
+        }));
+    }
+}
@@ -6,7 +6,8 @@
 
 use std::collections::VecDeque;
 use std::io::{ErrorKind, Read, Write};
-use std::net::TcpStream;
+use std::iter::Peekable;
+use std::path::Path;
 use std::result::Result;
 use std::str;
 use std::time::Duration;
@@ -16,17 +17,23 @@ use super::message::{
     ChannelMessageResult,
 };
 use super::mode::ChannelMode;
+use super::pipeline::ChannelPipeline;
+use super::record::ChannelRecorder;
 use super::statistics::CLIENTS_CONNECTED;
+use super::stream::ChannelStream;
+use crate::config::options::ConfigChannelStartOptions;
 use crate::APP_CONF;
 use crate::LINE_FEED;
 
 pub struct ChannelHandle;
 
+#[derive(Debug, PartialEq)]
 enum ChannelHandleError {
     Closed,
     InvalidMode,
     AuthenticationRequired,
     AuthenticationFailed,
+    InvalidOption,
     NotRecognized,
     TimedOut,
     ConnectionAborted,
@@ -34,12 +41,19 @@ enum ChannelHandleError {
     Unknown,
 }
 
+#[derive(Debug, Default, PartialEq)]
+struct ChannelStartOptions {
+    record: Option<String>,
+    pipeline: bool,
+}
+
 const LINE_END_GAP: usize = 1;
 const BUFFER_SIZE: usize = 20000;
 const MAX_LINE_SIZE: usize = BUFFER_SIZE + LINE_END_GAP + 1;
 const TCP_TIMEOUT_NON_ESTABLISHED: u64 = 10;
-const PROTOCOL_REVISION: u8 = 1;
+pub const PROTOCOL_REVISION: u8 = 1;
 const BUFFER_LINE_SEPARATOR: u8 = b'\n';
+const START_OPTION_SEPARATOR: char = '=';
 
 lazy_static! {
     static ref CONNECTED_BANNER: String = format!(
@@ -56,6 +70,7 @@ impl ChannelHandleError {
             ChannelHandleError::InvalidMode => "invalid_mode",
             ChannelHandleError::AuthenticationRequired => "authentication_required",
             ChannelHandleError::AuthenticationFailed => "authentication_failed",
+            ChannelHandleError::InvalidOption => "invalid_option",
             ChannelHandleError::NotRecognized => "not_recognized",
             ChannelHandleError::TimedOut => "timed_out",
             ChannelHandleError::ConnectionAborted => "connection_aborted",
@@ -66,37 +81,53 @@ impl ChannelHandleError {
 }
 
 impl ChannelHandle {
-    pub fn client(mut stream: TcpStream) {
+    pub fn client(stream: ChannelStream) {
         // Configure stream (non-established)
         ChannelHandle::configure_stream(&stream, false);
 
         // Send connected banner
-        write!(stream, "{}{}", *CONNECTED_BANNER, LINE_FEED).expect("write failed");
+        // Notice: over TLS, the handshake happens upon this first write, which thus fails for \
+        //   any client that does not speak TLS (or gives up on the handshake). This is common \
+        //   enough not to be treated as an error.
+        if let Err(err) = write!(&stream, "{}{}", *CONNECTED_BANNER, LINE_FEED) {
+            info!(
+                "closing channel thread, could not send connected banner: {}",
+                err
+            );
+
+            return;
+        }
 
         // Increment connected clients count
         *CLIENTS_CONNECTED.write().unwrap() += 1;
 
         // Ensure channel mode is set
         match Self::ensure_start(&stream) {
-            Ok(mode) => {
+            Ok((mode, recorder, pipeline)) => {
                 // Configure stream (established)
                 ChannelHandle::configure_stream(&stream, true);
 
                 // Send started acknowledgement (with environment variables)
-                write!(
-                    stream,
+                let started = write!(
+                    &stream,
                     "STARTED {} protocol({}) buffer({}){}",
                     mode.to_str(),
                     PROTOCOL_REVISION,
                     BUFFER_SIZE,
                     LINE_FEED
-                )
-                .expect("write failed");
+                );
 
-                Self::handle_stream(mode, stream);
+                match started {
+                    Ok(_) => Self::handle_stream(mode, stream, recorder, pipeline),
+                    Err(err) => {
+                        info!("closing channel thread, could not send started: {}", err);
+                    }
+                }
             }
             Err(err) => {
-                write!(stream, "ENDED {}{}", err.to_str(), LINE_FEED).expect("write failed");
+                if let Err(write_err) = write!(&stream, "ENDED {}{}", err.to_str(), LINE_FEED) {
+                    debug!("could not send ended ({}): {}", err.to_str(), write_err);
+                }
             }
         }
 
@@ -104,24 +135,22 @@ impl ChannelHandle {
         *CLIENTS_CONNECTED.write().unwrap() -= 1;
     }
 
-    fn configure_stream(stream: &TcpStream, is_established: bool) {
+    fn configure_stream(stream: &ChannelStream, is_established: bool) {
         let tcp_timeout = if is_established {
             APP_CONF.channel.tcp_timeout
         } else {
             TCP_TIMEOUT_NON_ESTABLISHED
         };
 
-        assert!(stream.set_nodelay(true).is_ok());
-
-        assert!(stream
-            .set_read_timeout(Some(Duration::new(tcp_timeout, 0)))
-            .is_ok());
-        assert!(stream
-            .set_write_timeout(Some(Duration::new(tcp_timeout, 0)))
-            .is_ok());
+        assert!(stream.configure(Duration::new(tcp_timeout, 0)).is_ok());
     }
 
-    fn handle_stream(mode: ChannelMode, mut stream: TcpStream) {
+    fn handle_stream(
+        mode: ChannelMode,
+        stream: ChannelStream,
+        mut recorder: Option<ChannelRecorder>,
+        mut pipeline: Option<ChannelPipeline>,
+    ) {
         // Initialize packet buffer
         let mut buffer: VecDeque<u8> = VecDeque::with_capacity(MAX_LINE_SIZE);
 
@@ -129,10 +158,24 @@ impl ChannelHandle {
         'handler: loop {
             let mut read = [0; MAX_LINE_SIZE];
 
-            match stream.read(&mut read) {
+            // Wait for incoming messages until pipelined pushes are due for a flush (if any)
+            if let Some(ref pipeline) = pipeline {
+                let read_timeout = pipeline
+                    .read_timeout()
+                    .unwrap_or_else(|| Duration::new(APP_CONF.channel.tcp_timeout, 0));
+
+                assert!(stream.configure_read(read_timeout).is_ok());
+            }
+
+            match (&stream).read(&mut read) {
                 Ok(n) => {
                     // Should close?
                     if n == 0 {
+                        // Commit pipelined pushes (if any), as the client may still be reading
+                        if let Some(ref mut pipeline) = pipeline {
+                            pipeline.flush(&stream);
+                        }
+
                         break;
                     }
 
@@ -160,8 +203,20 @@ impl ChannelHandle {
                         while let Some(byte) = buffer.pop_front() {
                             // Commit line and start a new one?
                             if byte == BUFFER_LINE_SEPARATOR {
-                                if Self::on_message(&mode, &stream, &processed_line)
-                                    == ChannelMessageResult::Close
+                                // Record line? (if connection opted-in for command recording)
+                                if let Some(ref mut recorder) = recorder {
+                                    recorder.record(&String::from_utf8_lossy(&processed_line));
+                                }
+
+                                // Queue pushes? (if connection opted-in for pipelining)
+                                let is_pipelined = pipeline
+                                    .as_mut()
+                                    .map(|pipeline| pipeline.on_message(&stream, &processed_line))
+                                    .unwrap_or(false);
+
+                                if !is_pipelined
+                                    && Self::on_message(&mode, &stream, &processed_line)
+                                        == ChannelMessageResult::Close
                                 {
                                     // Should close?
                                     break 'handler;
@@ -182,6 +237,19 @@ impl ChannelHandle {
                         }
                     }
                 }
+                Err(ref err)
+                    if (err.kind() == ErrorKind::WouldBlock
+                        || err.kind() == ErrorKind::TimedOut)
+                        && pipeline
+                            .as_ref()
+                            .map(|pipeline| !pipeline.is_empty())
+                            .unwrap_or(false) =>
+                {
+                    // No more commands for now, thus commit pipelined pushes
+                    if let Some(ref mut pipeline) = pipeline {
+                        pipeline.flush(&stream);
+                    }
+                }
                 Err(err) => {
                     error!("closing channel thread with traceback: {}", err);
 
@@ -191,7 +259,16 @@ impl ChannelHandle {
         }
     }
 
-    fn ensure_start(mut stream: &TcpStream) -> Result<ChannelMode, ChannelHandleError> {
+    fn ensure_start(
+        mut stream: &ChannelStream,
+    ) -> Result<
+        (
+            ChannelMode,
+            Option<ChannelRecorder>,
+            Option<ChannelPipeline>,
+        ),
+        ChannelHandleError,
+    > {
         #[allow(clippy::never_loop)]
         loop {
             let mut read = [0; MAX_LINE_SIZE];
@@ -202,7 +279,10 @@ impl ChannelHandle {
                         return Err(ChannelHandleError::Closed);
                     }
 
-                    let mut parts = str::from_utf8(&read[0..n]).unwrap_or("").split_whitespace();
+                    let mut parts = str::from_utf8(&read[0..n])
+                        .unwrap_or("")
+                        .split_whitespace()
+                        .peekable();
 
                     if parts.next().unwrap_or("").to_uppercase().as_str() == "START" {
                         if let Some(res_mode) = parts.next() {
@@ -211,23 +291,21 @@ impl ChannelHandle {
                             // Extract mode
                             if let Ok(mode) = ChannelMode::from_str(res_mode) {
                                 // Check if authenticated?
-                                if let Some(ref auth_password) = APP_CONF.channel.auth_password {
-                                    if let Some(provided_auth) = parts.next() {
-                                        // Compare provided password with configured password
-                                        if provided_auth != auth_password {
-                                            info!("password provided, but does not match");
-
-                                            return Err(ChannelHandleError::AuthenticationFailed);
-                                        }
-                                    } else {
-                                        info!("no password provided, but one required");
-
-                                        // No password was provided, but we require one
-                                        return Err(ChannelHandleError::AuthenticationRequired);
-                                    }
-                                }
+                                Self::authenticate(
+                                    &mut parts,
+                                    APP_CONF.channel.auth_password.as_deref(),
+                                )?;
+
+                                // Apply start options (if any)
+                                let options = Self::apply_start_options(
+                                    parts,
+                                    &APP_CONF.channel.start_options,
+                                )?;
+
+                                let pipeline = Self::open_pipeline(&mode, options.pipeline)?;
+                                let recorder = Self::open_recorder(&mode, options.record)?;
 
-                                return Ok(mode);
+                                return Ok((mode, recorder, pipeline));
                             }
                         }
 
@@ -250,9 +328,132 @@ impl ChannelHandle {
         }
     }
 
+    fn authenticate<'a>(
+        parts: &mut Peekable<impl Iterator<Item = &'a str>>,
+        auth_password: Option<&str>,
+    ) -> Result<(), ChannelHandleError> {
+        if let Some(auth_password) = auth_password {
+            if let Some(provided_auth) = parts.next() {
+                // Compare provided password with configured password
+                if provided_auth != auth_password {
+                    info!("password provided, but does not match");
+
+                    return Err(ChannelHandleError::AuthenticationFailed);
+                }
+            } else {
+                info!("no password provided, but one required");
+
+                // No password was provided, but we require one
+                return Err(ChannelHandleError::AuthenticationRequired);
+            }
+        } else if parts
+            .peek()
+            .map(|part| !part.contains(START_OPTION_SEPARATOR))
+            .unwrap_or(false)
+        {
+            // Notice: skip any password provided while none is required, as it cannot be \
+            //   mistaken for an option.
+            parts.next();
+        }
+
+        Ok(())
+    }
+
+    fn apply_start_options<'a>(
+        parts: impl Iterator<Item = &'a str>,
+        policy: &ConfigChannelStartOptions,
+    ) -> Result<ChannelStartOptions, ChannelHandleError> {
+        let mut options = ChannelStartOptions::default();
+
+        for part in parts {
+            let mut option = part.splitn(2, START_OPTION_SEPARATOR);
+
+            match (option.next(), option.next()) {
+                (Some("protocol"), Some(value)) => {
+                    // Only the current protocol revision can be requested
+                    if value.parse::<u8>().ok() != Some(PROTOCOL_REVISION) {
+                        info!("requested unsupported protocol revision: {}", value);
+
+                        return Err(ChannelHandleError::InvalidOption);
+                    }
+                }
+                (Some("record"), Some(value)) => {
+                    // Recording requires a record path, and a name usable as a file name
+                    if APP_CONF.channel.record_path.is_none()
+                        || !ChannelRecorder::is_valid_name(value)
+                    {
+                        info!("requested unavailable command recording: {}", value);
+
+                        return Err(ChannelHandleError::InvalidOption);
+                    }
+
+                    options.record = Some(value.to_string());
+                }
+                (Some("pipeline"), Some(value)) => {
+                    options.pipeline = match value {
+                        "1" => true,
+                        "0" => false,
+                        _ => {
+                            info!("requested invalid pipelining: {}", value);
+
+                            return Err(ChannelHandleError::InvalidOption);
+                        }
+                    };
+                }
+                _ => {
+                    // Notice: unknown options may be passed by clients made for a newer protocol \
+                    //   revision, hence they get ignored unless strict mode is configured.
+                    if policy == &ConfigChannelStartOptions::Strict {
+                        info!("got unknown start option, rejecting: {}", part);
+
+                        return Err(ChannelHandleError::InvalidOption);
+                    }
+
+                    debug!("got unknown start option, ignoring: {}", part);
+                }
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn open_recorder(
+        mode: &ChannelMode,
+        record: Option<String>,
+    ) -> Result<Option<ChannelRecorder>, ChannelHandleError> {
+        match (record, APP_CONF.channel.record_path.as_ref()) {
+            (Some(name), Some(record_path)) => ChannelRecorder::open(
+                Path::new(record_path),
+                &name,
+                mode,
+                APP_CONF.channel.record_size_maximum,
+                APP_CONF.channel.record_redact,
+            )
+            .map(Some)
+            .map_err(|_| ChannelHandleError::InvalidOption),
+            _ => Ok(None),
+        }
+    }
+
+    fn open_pipeline(
+        mode: &ChannelMode,
+        pipeline: bool,
+    ) -> Result<Option<ChannelPipeline>, ChannelHandleError> {
+        // Notice: only pushes get pipelined, thus pipelining is only available to ingest mode.
+        match (pipeline, mode) {
+            (true, ChannelMode::Ingest) => Ok(Some(ChannelPipeline::new())),
+            (true, _) => {
+                info!("requested pipelining outside of ingest mode");
+
+                Err(ChannelHandleError::InvalidOption)
+            }
+            (false, _) => Ok(None),
+        }
+    }
+
     fn on_message(
         mode: &ChannelMode,
-        stream: &TcpStream,
+        stream: &ChannelStream,
         message_slice: &[u8],
     ) -> ChannelMessageResult {
         match mode {
@@ -268,3 +469,240 @@ impl ChannelHandle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_authenticates_start_requests() {
+        fn authenticate<'a>(
+            parts: &'a str,
+            auth_password: Option<&str>,
+        ) -> Result<Vec<&'a str>, ChannelHandleError> {
+            let mut parts = parts.split_whitespace().peekable();
+
+            ChannelHandle::authenticate(&mut parts, auth_password).map(|_| parts.collect())
+        }
+
+        // Password required
+        assert_eq!(
+            authenticate("SecretPassword protocol=1", Some("SecretPassword")),
+            Ok(vec!["protocol=1"])
+        );
+        assert_eq!(
+            authenticate("WrongPassword", Some("SecretPassword")),
+            Err(ChannelHandleError::AuthenticationFailed)
+        );
+        assert_eq!(
+            authenticate("", Some("SecretPassword")),
+            Err(ChannelHandleError::AuthenticationRequired)
+        );
+
+        // No password required (any provided password is skipped)
+        assert_eq!(authenticate("", None), Ok(vec![]));
+        assert_eq!(
+            authenticate("AnyPassword protocol=1", None),
+            Ok(vec!["protocol=1"])
+        );
+        assert_eq!(authenticate("protocol=1", None), Ok(vec!["protocol=1"]));
+    }
+
+    #[test]
+    fn it_applies_known_start_options() {
+        for policy in &[
+            ConfigChannelStartOptions::Lenient,
+            ConfigChannelStartOptions::Strict,
+        ] {
+            assert_eq!(
+                ChannelHandle::apply_start_options(Vec::new().into_iter(), policy),
+                Ok(ChannelStartOptions::default())
+            );
+            assert_eq!(
+                ChannelHandle::apply_start_options(vec!["protocol=1"].into_iter(), policy),
+                Ok(ChannelStartOptions::default())
+            );
+            assert_eq!(
+                ChannelHandle::apply_start_options(vec!["protocol=2"].into_iter(), policy),
+                Err(ChannelHandleError::InvalidOption)
+            );
+            assert_eq!(
+                ChannelHandle::apply_start_options(vec!["protocol"].into_iter(), policy),
+                if policy == &ConfigChannelStartOptions::Strict {
+                    Err(ChannelHandleError::InvalidOption)
+                } else {
+                    Ok(ChannelStartOptions::default())
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn it_handles_unknown_start_options() {
+        assert_eq!(
+            ChannelHandle::apply_start_options(
+                vec!["protocol=1", "compression=zstd"].into_iter(),
+                &ConfigChannelStartOptions::Lenient
+            ),
+            Ok(ChannelStartOptions::default())
+        );
+        assert_eq!(
+            ChannelHandle::apply_start_options(
+                vec!["protocol=1", "compression=zstd"].into_iter(),
+                &ConfigChannelStartOptions::Strict
+            ),
+            Err(ChannelHandleError::InvalidOption)
+        );
+        assert_eq!(
+            ChannelHandle::apply_start_options(
+                vec!["garbage"].into_iter(),
+                &ConfigChannelStartOptions::Lenient
+            ),
+            Ok(ChannelStartOptions::default())
+        );
+    }
+
+    #[test]
+    fn it_rejects_record_option_without_record_path() {
+        assert!(APP_CONF.channel.record_path.is_none());
+
+        assert_eq!(
+            ChannelHandle::apply_start_options(
+                vec!["record=session-1"].into_iter(),
+                &ConfigChannelStartOptions::Lenient
+            ),
+            Err(ChannelHandleError::InvalidOption)
+        );
+    }
+
+    #[test]
+    fn it_applies_pipeline_start_option() {
+        assert_eq!(
+            ChannelHandle::apply_start_options(
+                vec!["pipeline=1"].into_iter(),
+                &ConfigChannelStartOptions::Strict
+            ),
+            Ok(ChannelStartOptions {
+                record: None,
+                pipeline: true,
+            })
+        );
+        assert_eq!(
+            ChannelHandle::apply_start_options(
+                vec!["pipeline=0"].into_iter(),
+                &ConfigChannelStartOptions::Strict
+            ),
+            Ok(ChannelStartOptions::default())
+        );
+        assert_eq!(
+            ChannelHandle::apply_start_options(
+                vec!["pipeline=yes"].into_iter(),
+                &ConfigChannelStartOptions::Lenient
+            ),
+            Err(ChannelHandleError::InvalidOption)
+        );
+
+        // Only ingest connections can pipeline pushes
+        assert!(ChannelHandle::open_pipeline(&ChannelMode::Ingest, true)
+            .unwrap()
+            .is_some());
+        assert!(ChannelHandle::open_pipeline(&ChannelMode::Ingest, false)
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            ChannelHandle::open_pipeline(&ChannelMode::Search, true).err(),
+            Some(ChannelHandleError::InvalidOption)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_handles_pipelined_commands() {
+        use std::os::unix::net::UnixStream;
+
+        let (client, server) = UnixStream::pair().unwrap();
+
+        // Issue all commands at once, before reading any response
+        (&client)
+            .write_all(b"PING\nINVALID\nPING\r\nQUIT\n")
+            .unwrap();
+
+        ChannelHandle::handle_stream(ChannelMode::Search, ChannelStream::Unix(server), None, None);
+
+        let mut responses = String::new();
+
+        (&client).read_to_string(&mut responses).unwrap();
+
+        // Responses come in the order of commands
+        assert_eq!(
+            responses,
+            "PONG\r\nERR unknown_command\r\nPONG\r\nENDED quit\r\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_acknowledges_pipelined_pushes() {
+        use std::os::unix::net::UnixStream;
+
+        let (client, server) = UnixStream::pair().unwrap();
+
+        // Pushes get committed at once when another command is received
+        (&client)
+            .write_all(
+                b"PUSH c:test:71 b:test:71 o:1 \"lighthouse keeper\"\n\
+                  PUSH c:test:71 b:test:71 o:2 \"lighthouse beacon\"\n\
+                  PING\n\
+                  PUSH c:test:71 b:test:71 o:3 \"harbor\"\n\
+                  QUIT\n",
+            )
+            .unwrap();
+
+        ChannelHandle::handle_stream(
+            ChannelMode::Ingest,
+            ChannelStream::Unix(server),
+            None,
+            Some(ChannelPipeline::new()),
+        );
+
+        let mut responses = String::new();
+
+        (&client).read_to_string(&mut responses).unwrap();
+
+        // Acknowledgements carry the sequence of their command on the connection
+        assert_eq!(responses, "OK 1\r\nOK 2\r\nPONG\r\nOK 4\r\nENDED quit\r\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_flushes_pipelined_pushes_after_delay() {
+        use std::os::unix::net::UnixStream;
+        use std::thread;
+
+        let (client, server) = UnixStream::pair().unwrap();
+
+        let handler = thread::spawn(move || {
+            ChannelHandle::handle_stream(
+                ChannelMode::Ingest,
+                ChannelStream::Unix(server),
+                None,
+                Some(ChannelPipeline::new()),
+            )
+        });
+
+        // Pushes get committed once no other command is received for a while
+        (&client)
+            .write_all(b"PUSH c:test:71 b:test:71:delay o:1 \"lighthouse\"\n")
+            .unwrap();
+
+        let mut acknowledgement = [0; 6];
+
+        (&client).read_exact(&mut acknowledgement).unwrap();
+
+        assert_eq!(&acknowledgement, b"OK 1\r\n");
+
+        (&client).write_all(b"QUIT\n").unwrap();
+
+        assert!(handler.join().is_ok());
+    }
+}
@@ -0,0 +1,145 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use hashbrown::HashMap;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::APP_CONF;
+
+type ChannelIdempotencyKey = (String, String, String, String);
+
+struct ChannelIdempotencyKeys {
+    recorded: HashMap<ChannelIdempotencyKey, Instant>,
+    order: VecDeque<(ChannelIdempotencyKey, Instant)>,
+}
+
+pub struct ChannelIdempotency;
+
+pub const IDEMPOTENCY_KEY_LENGTH_MAXIMUM: usize = 128;
+
+lazy_static! {
+    static ref IDEMPOTENCY_KEYS: Mutex<ChannelIdempotencyKeys> =
+        Mutex::new(ChannelIdempotencyKeys {
+            recorded: HashMap::new(),
+            order: VecDeque::new(),
+        });
+}
+
+impl ChannelIdempotency {
+    pub fn has(collection: &str, bucket: &str, object: &str, key: &str) -> bool {
+        let mut keys = IDEMPOTENCY_KEYS.lock().unwrap();
+
+        keys.expire(Instant::now());
+
+        keys.recorded
+            .contains_key(&Self::make_key(collection, bucket, object, key))
+    }
+
+    pub fn record(collection: &str, bucket: &str, object: &str, key: &str) {
+        let maximum = APP_CONF.channel.push_idempotency_keys;
+
+        // Idempotency keys are disabled? (do not record anything)
+        if maximum == 0 {
+            return;
+        }
+
+        let (mut keys, now) = (IDEMPOTENCY_KEYS.lock().unwrap(), Instant::now());
+        let idempotency_key = Self::make_key(collection, bucket, object, key);
+
+        keys.expire(now);
+
+        if !keys.recorded.contains_key(&idempotency_key) {
+            // Bound the number of recorded keys (evict the oldest keys first)
+            while keys.order.len() >= maximum {
+                keys.evict_oldest();
+            }
+
+            keys.recorded.insert(idempotency_key.clone(), now);
+            keys.order.push_back((idempotency_key, now));
+
+            debug!("recorded push idempotency key: {}", key);
+        }
+    }
+
+    fn make_key(collection: &str, bucket: &str, object: &str, key: &str) -> ChannelIdempotencyKey {
+        // Notice: the object is part of the key, as a key reused for another object (eg. by a \
+        //   client generating keys from a counter) must not skip the push of that object.
+        (
+            collection.to_string(),
+            bucket.to_string(),
+            object.to_string(),
+            key.to_string(),
+        )
+    }
+}
+
+impl ChannelIdempotencyKeys {
+    fn expire(&mut self, now: Instant) {
+        let ttl = Duration::from_secs(APP_CONF.channel.push_idempotency_ttl);
+
+        // Notice: keys are ordered by record time, so expired keys all come first
+        while let Some((_, recorded_at)) = self.order.front() {
+            if now.duration_since(*recorded_at) >= ttl {
+                self.evict_oldest();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some((idempotency_key, recorded_at)) = self.order.pop_front() {
+            // Only remove the recorded key if it was not recorded again since
+            if self.recorded.get(&idempotency_key) == Some(&recorded_at) {
+                self.recorded.remove(&idempotency_key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_records_idempotency_keys() {
+        assert!(!ChannelIdempotency::has(
+            "c:test:12",
+            "b:test:12",
+            "o:1",
+            "k:1"
+        ));
+
+        ChannelIdempotency::record("c:test:12", "b:test:12", "o:1", "k:1");
+
+        assert!(ChannelIdempotency::has(
+            "c:test:12",
+            "b:test:12",
+            "o:1",
+            "k:1"
+        ));
+        assert!(!ChannelIdempotency::has(
+            "c:test:12",
+            "b:test:12",
+            "o:1",
+            "k:2"
+        ));
+        assert!(!ChannelIdempotency::has(
+            "c:test:12",
+            "b:test:12",
+            "o:2",
+            "k:1"
+        ));
+        assert!(!ChannelIdempotency::has(
+            "c:test:12",
+            "b:other:12",
+            "o:1",
+            "k:1"
+        ));
+    }
+}
@@ -4,13 +4,28 @@
 // Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
-use std::net::TcpListener;
+#[cfg(feature = "channel-tls")]
+use rustls::ServerConfig;
+use std::io;
+use std::net::{TcpListener, TcpStream};
 use std::process;
-use std::sync::RwLock;
+use std::sync::{Once, RwLock};
 use std::thread;
 
+#[cfg(unix)]
+use std::fs::{self, Permissions};
+#[cfg(unix)]
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+#[cfg(feature = "channel-tls")]
+use std::sync::Arc;
+
 use super::handle::ChannelHandle;
-use crate::{APP_CONF, THREAD_NAME_CHANNEL_CLIENT};
+use super::stream::ChannelStream;
+#[cfg(feature = "channel-tls")]
+use super::tls::ChannelTLS;
+use crate::{APP_CONF, THREAD_NAME_CHANNEL_CLIENT, THREAD_NAME_CHANNEL_MASTER};
 
 pub struct ChannelListenBuilder;
 pub struct ChannelListen;
@@ -19,6 +34,13 @@ lazy_static! {
     pub static ref CHANNEL_AVAILABLE: RwLock<bool> = RwLock::new(true);
 }
 
+#[cfg(feature = "channel-tls")]
+type ChannelListenTLS = Option<Arc<ServerConfig>>;
+#[cfg(not(feature = "channel-tls"))]
+type ChannelListenTLS = Option<()>;
+
+static CHANNEL_UNIX_LISTEN: Once = Once::new();
+
 impl ChannelListenBuilder {
     pub fn build() -> ChannelListen {
         ChannelListen {}
@@ -27,30 +49,34 @@ impl ChannelListenBuilder {
 
 impl ChannelListen {
     pub fn run(&self) {
+        match (APP_CONF.channel.tcp, &APP_CONF.channel.unix_socket_path) {
+            (true, Some(socket_path)) => {
+                // Listen on the UNIX socket in its own thread, alongside TCP
+                // Notice: this is done once, as this method is called again if the TCP listener \
+                //   crashes.
+                CHANNEL_UNIX_LISTEN.call_once(|| Self::spawn_unix(socket_path));
+
+                Self::listen_tcp();
+            }
+            (false, Some(socket_path)) => Self::listen_unix(socket_path),
+
+            // Notice: TCP cannot be disabled if no UNIX socket is set (see config validation).
+            (_, None) => Self::listen_tcp(),
+        }
+    }
+
+    fn listen_tcp() {
+        let tls = Self::make_tls();
+
         match TcpListener::bind(APP_CONF.channel.inet) {
             Ok(listener) => {
                 info!("listening on tcp://{}", APP_CONF.channel.inet);
 
-                for stream in listener.incoming() {
-                    match stream {
-                        Ok(stream) => {
-                            thread::Builder::new()
-                                .name(THREAD_NAME_CHANNEL_CLIENT.to_string())
-                                .spawn(move || {
-                                    if let Ok(peer_addr) = stream.peer_addr() {
-                                        debug!("channel client connecting: {}", peer_addr);
-                                    }
-
-                                    // Create client
-                                    ChannelHandle::client(stream);
-                                })
-                                .ok();
-                        }
-                        Err(err) => {
-                            warn!("error handling stream: {}", err);
-                        }
-                    }
-                }
+                Self::accept(
+                    listener
+                        .incoming()
+                        .map(|stream| stream.and_then(|stream| Self::wrap_tcp(stream, &tls))),
+                );
             }
             Err(err) => {
                 error!("error binding channel listener: {}", err);
@@ -61,8 +87,141 @@ impl ChannelListen {
         }
     }
 
+    #[cfg(feature = "channel-tls")]
+    fn make_tls() -> ChannelListenTLS {
+        APP_CONF
+            .channel
+            .tls
+            .as_ref()
+            .map(|config| match ChannelTLS::load(config) {
+                Ok(config) => {
+                    info!("channel tcp connections will use tls");
+
+                    config
+                }
+                Err(err) => {
+                    error!("error loading channel tls certificate: {}", err);
+
+                    // Exit Sonic
+                    process::exit(1);
+                }
+            })
+    }
+
+    #[cfg(not(feature = "channel-tls"))]
+    fn make_tls() -> ChannelListenTLS {
+        if APP_CONF.channel.tls.is_some() {
+            error!("channel tls is configured, but sonic was built without tls support");
+
+            // Exit Sonic
+            process::exit(1);
+        }
+
+        None
+    }
+
+    #[cfg(feature = "channel-tls")]
+    fn wrap_tcp(stream: TcpStream, tls: &ChannelListenTLS) -> Result<ChannelStream, io::Error> {
+        match tls {
+            Some(config) => ChannelStream::tls(stream, config.clone()),
+            None => Ok(ChannelStream::Tcp(stream)),
+        }
+    }
+
+    #[cfg(not(feature = "channel-tls"))]
+    fn wrap_tcp(stream: TcpStream, _tls: &ChannelListenTLS) -> Result<ChannelStream, io::Error> {
+        Ok(ChannelStream::Tcp(stream))
+    }
+
+    fn spawn_unix(socket_path: &'static str) {
+        thread::Builder::new()
+            .name(THREAD_NAME_CHANNEL_MASTER.to_string())
+            .spawn(move || Self::listen_unix(socket_path))
+            .expect("could not spawn unix channel listener");
+    }
+
+    #[cfg(unix)]
+    fn listen_unix(socket_path: &str) {
+        // Remove any socket left over by a previous run (binding would fail otherwise)
+        Self::remove_unix(socket_path);
+
+        let listener = UnixListener::bind(socket_path).and_then(|listener| {
+            // Restrict the local users that can connect to the socket
+            fs::set_permissions(
+                socket_path,
+                Permissions::from_mode(APP_CONF.channel.unix_socket_mode),
+            )
+            .map(|_| listener)
+        });
+
+        match listener {
+            Ok(listener) => {
+                info!("listening on unix://{}", socket_path);
+
+                Self::accept(
+                    listener
+                        .incoming()
+                        .map(|stream| stream.map(ChannelStream::Unix)),
+                );
+            }
+            Err(err) => {
+                error!("error binding unix channel listener: {}", err);
+
+                // Exit Sonic
+                process::exit(1);
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn listen_unix(_socket_path: &str) {
+        error!("unix channel listener is only supported on unix systems");
+
+        // Exit Sonic
+        process::exit(1);
+    }
+
+    #[cfg(unix)]
+    fn remove_unix(socket_path: &str) {
+        // Notice: only sockets get removed, as to never remove a file misconfigured as socket.
+        if let Ok(metadata) = fs::symlink_metadata(socket_path) {
+            if metadata.file_type().is_socket() {
+                fs::remove_file(socket_path).ok();
+            }
+        }
+    }
+
+    fn accept(incoming: impl Iterator<Item = Result<ChannelStream, io::Error>>) {
+        for stream in incoming {
+            match stream {
+                Ok(stream) => {
+                    thread::Builder::new()
+                        .name(THREAD_NAME_CHANNEL_CLIENT.to_string())
+                        .spawn(move || {
+                            if let Some(peer) = stream.peer() {
+                                debug!("channel client connecting: {}", peer);
+                            }
+
+                            // Create client
+                            ChannelHandle::client(stream);
+                        })
+                        .ok();
+                }
+                Err(err) => {
+                    warn!("error handling stream: {}", err);
+                }
+            }
+        }
+    }
+
     pub fn teardown() {
         // Channel cannot be used anymore
         *CHANNEL_AVAILABLE.write().unwrap() = false;
+
+        // Remove the UNIX socket (so that clients do not connect to a stopped Sonic)
+        #[cfg(unix)]
+        if let Some(ref socket_path) = APP_CONF.channel.unix_socket_path {
+            Self::remove_unix(socket_path);
+        }
     }
 }
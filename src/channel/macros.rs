@@ -16,6 +16,7 @@ macro_rules! gen_channel_message_mode_handle {
                     $external => $internal(parts),
                 )+
                 "PING" => ChannelCommandBase::dispatch_ping(parts),
+                "VERSION" => ChannelCommandBase::dispatch_version(parts),
                 "QUIT" => ChannelCommandBase::dispatch_quit(parts),
                 _ => Ok(vec![ChannelCommandResponse::Err(
                     ChannelCommandError::InternalError,
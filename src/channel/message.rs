@@ -4,8 +4,8 @@
 // Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
+use std::borrow::Cow;
 use std::io::Write;
-use std::net::TcpStream;
 use std::str::{self, SplitWhitespace};
 use std::time::Instant;
 
@@ -14,8 +14,12 @@ use super::command::{
     ChannelCommandResponse, ChannelCommandResponseArgs, ChannelCommandSearch,
     COMMANDS_MODE_CONTROL, COMMANDS_MODE_INGEST, COMMANDS_MODE_SEARCH,
 };
+use super::errors::ChannelRecentErrors;
 use super::listen::CHANNEL_AVAILABLE;
 use super::statistics::{COMMANDS_TOTAL, COMMAND_LATENCY_BEST, COMMAND_LATENCY_WORST};
+use super::stream::ChannelStream;
+use crate::config::options::ConfigChannelInputEncoding;
+use crate::APP_CONF;
 use crate::LINE_FEED;
 
 pub struct ChannelMessage;
@@ -37,12 +41,14 @@ pub trait ChannelMessageMode {
 
 impl ChannelMessage {
     pub fn on<M: ChannelMessageMode>(
-        mut stream: &TcpStream,
+        stream: &ChannelStream,
         message_slice: &[u8],
     ) -> ChannelMessageResult {
-        let message = str::from_utf8(message_slice).unwrap_or("");
+        let message = Self::decode(message_slice, &APP_CONF.channel.input_encoding);
 
-        debug!("got channel message: {}", message);
+        if let Ok(ref message) = message {
+            debug!("got channel message: {}", message);
+        }
 
         let command_start = Instant::now();
 
@@ -56,8 +62,8 @@ impl ChannelMessage {
             response_args_groups =
                 vec![ChannelCommandResponse::Err(ChannelCommandError::ShuttingDown).to_args()];
         } else {
-            // Handle response arguments to issued command
-            response_args_groups = match M::handle(message) {
+            // Handle response arguments to issued command (or reject an undecodable command)
+            response_args_groups = match message.and_then(|message| Self::dispatch::<M>(&message)) {
                 Ok(resp_groups) => resp_groups
                     .iter()
                     .map(|resp| match resp {
@@ -65,6 +71,7 @@ impl ChannelMessage {
                         | ChannelCommandResponse::Pong
                         | ChannelCommandResponse::Pending(_)
                         | ChannelCommandResponse::Result(_)
+                        | ChannelCommandResponse::Line(_)
                         | ChannelCommandResponse::Event(_, _, _)
                         | ChannelCommandResponse::Void
                         | ChannelCommandResponse::Err(_) => resp.to_args(),
@@ -79,6 +86,132 @@ impl ChannelMessage {
         }
 
         // Serve response messages on socket
+        Self::respond(stream, response_args_groups);
+
+        // Measure and account time it took to execute command
+        Self::account(command_start, 1);
+
+        result
+    }
+
+    pub fn on_pushes(stream: &ChannelStream, messages: &[(u64, Vec<u8>)]) {
+        let command_start = Instant::now();
+
+        // Process responses for issued pipelined pushes (each push gets its own response, though \
+        //   all pushes get committed at once)
+        let mut response_args_groups: Vec<ChannelCommandResponseArgs> =
+            Vec::with_capacity(messages.len());
+
+        if !(*CHANNEL_AVAILABLE.read().unwrap()) {
+            // Server going down, reject commands
+            for _ in messages {
+                response_args_groups
+                    .push(ChannelCommandResponse::Err(ChannelCommandError::ShuttingDown).to_args());
+            }
+        } else {
+            let decoded: Vec<Result<Cow<str>, ChannelCommandError>> = messages
+                .iter()
+                .map(|(_, message_slice)| {
+                    Self::decode(message_slice, &APP_CONF.channel.input_encoding)
+                })
+                .collect();
+
+            let mut results = ChannelCommandIngest::dispatch_pushes(
+                decoded
+                    .iter()
+                    .filter_map(|message| message.as_ref().ok())
+                    .map(|message| Self::extract(message).1)
+                    .collect(),
+            )
+            .into_iter();
+
+            for ((sequence, _), message) in messages.iter().zip(decoded) {
+                let result = match message {
+                    Ok(message) => {
+                        let result = results
+                            .next()
+                            .unwrap_or(Err(ChannelCommandError::InternalError));
+
+                        Self::record_errors(&message, &result);
+
+                        result
+                    }
+                    Err(reason) => Err(reason),
+                };
+
+                match result {
+                    Ok(resp_groups) => {
+                        for resp in resp_groups {
+                            // Acknowledge pipelined pushes along with their sequence number
+                            if resp == ChannelCommandResponse::Ok {
+                                response_args_groups.push(("OK", Some(vec![sequence.to_string()])));
+                            } else {
+                                response_args_groups.push(resp.to_args());
+                            }
+                        }
+                    }
+                    Err(reason) => {
+                        response_args_groups.push(ChannelCommandResponse::Err(reason).to_args())
+                    }
+                }
+            }
+        }
+
+        // Serve response messages on socket
+        Self::respond(stream, response_args_groups);
+
+        // Measure and account time it took to execute commands
+        Self::account(command_start, messages.len() as u32);
+    }
+
+    pub fn dispatch<M: ChannelMessageMode>(
+        message: &str,
+    ) -> Result<Vec<ChannelCommandResponse>, ChannelCommandError> {
+        let result = M::handle(message);
+
+        Self::record_errors(message, &result);
+
+        result
+    }
+
+    fn record_errors(
+        message: &str,
+        result: &Result<Vec<ChannelCommandResponse>, ChannelCommandError>,
+    ) {
+        // Record errors returned to issued command (for diagnostics)
+        match result {
+            Ok(ref resp_groups) => {
+                for resp in resp_groups {
+                    if let ChannelCommandResponse::Err(reason) = resp {
+                        ChannelRecentErrors::record(message, reason);
+                    }
+                }
+            }
+            Err(ref reason) => ChannelRecentErrors::record(message, reason),
+        }
+    }
+
+    pub fn decode<'a>(
+        message_slice: &'a [u8],
+        policy: &ConfigChannelInputEncoding,
+    ) -> Result<Cow<'a, str>, ChannelCommandError> {
+        match str::from_utf8(message_slice) {
+            Ok(message) => Ok(Cow::Borrowed(message)),
+            Err(err) => {
+                info!("got channel message with invalid utf-8: {}", err);
+
+                // Notice: lossy-decoded messages hold the replacement character in place of \
+                //   invalid bytes, which the keyer and the lexer handle as any other character.
+                if policy == &ConfigChannelInputEncoding::Lenient {
+                    Ok(String::from_utf8_lossy(message_slice))
+                } else {
+                    Err(ChannelCommandError::InvalidEncoding)
+                }
+            }
+        }
+    }
+
+    fn respond(mut stream: &ChannelStream, response_args_groups: Vec<ChannelCommandResponseArgs>) {
         for response_args in response_args_groups {
             if !response_args.0.is_empty() {
                 if let Some(ref values) = response_args.1 {
@@ -96,9 +229,18 @@ impl ChannelMessage {
 
                     debug!("wrote response with no values: {}", response_args.0);
                 }
+            } else if let Some(ref values) = response_args.1 {
+                // Write raw response line (this continues a previous response)
+                let values_string = values.join(" ");
+
+                write!(stream, "{}{}", values_string, LINE_FEED).expect("write failed");
+
+                debug!("wrote raw response line: {}", values_string);
             }
         }
+    }
 
+    fn account(command_start: Instant, commands_count: u32) {
         // Measure and log time it took to execute command
         // Notice: this is critical as to raise developer awareness on the performance bits when \
         //   altering commands-related code, or when making changes to underlying store executors.
@@ -144,10 +286,8 @@ impl ChannelMessage {
             }
 
             // Increment total commands
-            *COMMANDS_TOTAL.write().unwrap() += 1;
+            *COMMANDS_TOTAL.write().unwrap() += commands_count as u64;
         }
-
-        result
     }
 
     fn extract(message: &str) -> (String, SplitWhitespace) {
@@ -166,7 +306,9 @@ impl ChannelMessageMode for ChannelMessageModeSearch {
         gen_channel_message_mode_handle!(message, COMMANDS_MODE_SEARCH, {
             "QUERY" => ChannelCommandSearch::dispatch_query,
             "SUGGEST" => ChannelCommandSearch::dispatch_suggest,
+            "COOCCUR" => ChannelCommandSearch::dispatch_cooccur,
             "LIST" => ChannelCommandSearch::dispatch_list,
+            "VALIDATE" => ChannelCommandSearch::dispatch_validate,
             "HELP" => ChannelCommandSearch::dispatch_help,
         })
     }
@@ -177,10 +319,20 @@ impl ChannelMessageMode for ChannelMessageModeIngest {
         gen_channel_message_mode_handle!(message, COMMANDS_MODE_INGEST, {
             "PUSH" => ChannelCommandIngest::dispatch_push,
             "POP" => ChannelCommandIngest::dispatch_pop,
+            "LISTO" => ChannelCommandIngest::dispatch_listo,
             "COUNT" => ChannelCommandIngest::dispatch_count,
+            "TERMS" => ChannelCommandIngest::dispatch_terms,
+            "IDF" => ChannelCommandIngest::dispatch_idf,
             "FLUSHC" => ChannelCommandIngest::dispatch_flushc,
             "FLUSHB" => ChannelCommandIngest::dispatch_flushb,
             "FLUSHO" => ChannelCommandIngest::dispatch_flusho,
+            "MOVEO" => ChannelCommandIngest::dispatch_moveo,
+            "SWAP" => ChannelCommandIngest::dispatch_swap,
+            "RETAIN" => ChannelCommandIngest::dispatch_retain,
+            "RETENTION" => ChannelCommandIngest::dispatch_retention,
+            "FREEZE" => ChannelCommandIngest::dispatch_freeze,
+            "SNAPSHOT" => ChannelCommandIngest::dispatch_snapshot,
+            "DIFF" => ChannelCommandIngest::dispatch_diff,
             "HELP" => ChannelCommandIngest::dispatch_help,
         })
     }
@@ -191,7 +343,53 @@ impl ChannelMessageMode for ChannelMessageModeControl {
         gen_channel_message_mode_handle!(message, COMMANDS_MODE_CONTROL, {
             "TRIGGER" => ChannelCommandControl::dispatch_trigger,
             "INFO" => ChannelCommandControl::dispatch_info,
+            "METRICS" => ChannelCommandControl::dispatch_metrics,
+            "ERRORS" => ChannelCommandControl::dispatch_errors,
+            "LIST" => ChannelCommandControl::dispatch_list,
+            "STOPWORDS" => ChannelCommandControl::dispatch_stopwords,
+            "CACHE" => ChannelCommandControl::dispatch_cache,
+            "UNFREEZE" => ChannelCommandControl::dispatch_unfreeze,
             "HELP" => ChannelCommandControl::dispatch_help,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_decodes_invalid_utf8_messages() {
+        let (text_slice, object_slice): (&[u8], &[u8]) = (
+            b"PUSH c:test:24 b:test:24 o:1 \"caf\xc3 lighthouse\" LANG(eng)",
+            b"PUSH c:test:24 b:test:24 o:\xff \"lighthouse\" LANG(eng)",
+        );
+
+        assert!(
+            ChannelMessage::decode(b"PING", &ConfigChannelInputEncoding::Strict).ok()
+                == Some(Cow::Borrowed("PING"))
+        );
+        assert!(
+            ChannelMessage::decode(text_slice, &ConfigChannelInputEncoding::Strict).err()
+                == Some(ChannelCommandError::InvalidEncoding)
+        );
+
+        let (text_message, object_message) = (
+            ChannelMessage::decode(text_slice, &ConfigChannelInputEncoding::Lenient)
+                .ok()
+                .expect("lenient decoding should not fail"),
+            ChannelMessage::decode(object_slice, &ConfigChannelInputEncoding::Lenient)
+                .ok()
+                .expect("lenient decoding should not fail"),
+        );
+
+        assert_eq!(
+            text_message,
+            "PUSH c:test:24 b:test:24 o:1 \"caf\u{fffd} lighthouse\" LANG(eng)"
+        );
+
+        // Lossy-decoded text gets indexed, while non-ASCII identifiers are still rejected
+        assert!(ChannelMessage::dispatch::<ChannelMessageModeIngest>(&text_message).is_ok());
+        assert!(ChannelMessage::dispatch::<ChannelMessageModeIngest>(&object_message).is_err());
+    }
+}
@@ -8,10 +8,18 @@
 mod macros;
 
 mod command;
+mod errors;
 mod format;
 mod handle;
+mod idempotency;
 mod message;
 mod mode;
+mod pipeline;
+mod record;
+mod stream;
+
+#[cfg(feature = "channel-tls")]
+mod tls;
 
 pub mod listen;
 pub mod statistics;
@@ -0,0 +1,102 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use std::str;
+use std::time::{Duration, Instant};
+
+use super::message::ChannelMessage;
+use super::stream::ChannelStream;
+use crate::APP_CONF;
+
+pub struct ChannelPipeline {
+    pushes: Vec<(u64, Vec<u8>)>,
+    collection: Option<String>,
+    sequence: u64,
+    started: Option<Instant>,
+}
+
+const PIPELINE_COMMAND: &str = "PUSH";
+const PIPELINE_READ_TIMEOUT_MINIMUM: Duration = Duration::from_millis(1);
+
+impl ChannelPipeline {
+    pub fn new() -> ChannelPipeline {
+        ChannelPipeline {
+            pushes: Vec::new(),
+            collection: None,
+            sequence: 0,
+            started: None,
+        }
+    }
+
+    pub fn on_message(&mut self, stream: &ChannelStream, message_slice: &[u8]) -> bool {
+        // Count all commands, as sequence numbers are positions of commands on the connection
+        self.sequence += 1;
+
+        if let Some(collection) = Self::pipelined_collection(message_slice) {
+            // Pushes to another collection cannot share the same batch, thus flush first
+            if self.collection.as_deref() != Some(collection) {
+                self.flush(stream);
+
+                self.collection = Some(collection.to_string());
+            }
+
+            if self.started.is_none() {
+                self.started = Some(Instant::now());
+            }
+
+            self.pushes.push((self.sequence, message_slice.to_vec()));
+
+            if self.pushes.len() >= APP_CONF.channel.pipeline_flush_commands {
+                self.flush(stream);
+            }
+
+            true
+        } else {
+            // Any other command flushes queued pushes, as to keep responses ordered
+            self.flush(stream);
+
+            false
+        }
+    }
+
+    pub fn flush(&mut self, stream: &ChannelStream) {
+        if !self.pushes.is_empty() {
+            debug!("flushing {} pipelined pushes", self.pushes.len());
+
+            ChannelMessage::on_pushes(stream, &self.pushes);
+
+            self.pushes.clear();
+        }
+
+        self.collection = None;
+        self.started = None;
+    }
+
+    pub fn read_timeout(&self) -> Option<Duration> {
+        // Wait for more commands until queued pushes are due for a flush (if any)
+        self.started.map(|started| {
+            Duration::from_millis(APP_CONF.channel.pipeline_flush_after)
+                .checked_sub(started.elapsed())
+                .unwrap_or_default()
+                .max(PIPELINE_READ_TIMEOUT_MINIMUM)
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pushes.is_empty()
+    }
+
+    fn pipelined_collection(message_slice: &[u8]) -> Option<&str> {
+        // Notice: undecodable pushes are not pipelined, and get rejected as any other command.
+        let mut parts = str::from_utf8(message_slice).ok()?.split_whitespace();
+
+        if parts.next()?.eq_ignore_ascii_case(PIPELINE_COMMAND) {
+            parts.next()
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,458 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use std::collections::hash_map::RandomState;
+use std::fs::{self, File, OpenOptions};
+use std::hash::BuildHasher;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use super::command::{ChannelCommandError, ChannelCommandResponse};
+use super::errors::COLLECTION_COMMANDS;
+use super::message::{ChannelMessage, ChannelMessageModeIngest, ChannelMessageModeSearch};
+use super::mode::ChannelMode;
+
+pub struct ChannelRecorder {
+    file: File,
+    size: u64,
+    size_maximum: u64,
+    redact: bool,
+    redact_key: RandomState,
+    stopped: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ChannelRecordReplay {
+    pub commands: usize,
+    pub errors: usize,
+}
+
+const RECORD_NAME_LENGTH_MAXIMUM: usize = 64;
+const RECORD_FILE_EXTENSION: &str = "log";
+const RECORD_HEADER_MODE: &str = "# mode ";
+
+const TEXT_PART_BOUNDARY: char = '"';
+const TEXT_PART_ESCAPE: char = '\\';
+const META_PART_GROUP_OPEN: char = '(';
+const META_PART_GROUP_CLOSE: char = ')';
+
+// Notice: the values of these metas only hold settings, thus they are recorded as-is (the \
+//   values of any other meta, eg. idempotency keys or term weights, get redacted).
+static RECORD_META_KEPT: &[&str] = &[
+    "LIMIT", "OFFSET", "LANG", "SYNTAX", "FEDERATE", "TIMEOUT", "FUZZY", "PARTIAL", "SNIPPETS",
+    "TERMS", "BOOST", "SUGGEST", "VERBOSE",
+];
+
+impl ChannelRecorder {
+    pub fn open(
+        directory: &Path,
+        name: &str,
+        mode: &ChannelMode,
+        size_maximum: u64,
+        redact: bool,
+    ) -> Result<ChannelRecorder, ()> {
+        // Notice: control commands act on the whole server, thus they cannot be replayed \
+        //   against a test collection.
+        if let ChannelMode::Control = mode {
+            return Err(());
+        }
+
+        let path = Self::path(directory, name)?;
+
+        fs::create_dir_all(directory).map_err(|err| {
+            error!("could not create command record directory: {}", err);
+        })?;
+
+        // Important: never overwrite an existing record, as it may not have been replayed yet.
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|err| {
+                error!("could not create command record: {:?}: {}", path, err);
+            })?;
+
+        // Write the record header (the mode commands are replayed in)
+        let header = format!("{}{}\n", RECORD_HEADER_MODE, mode.to_str());
+
+        file.write_all(header.as_bytes()).map_err(|err| {
+            error!("could not write command record header: {}", err);
+        })?;
+
+        info!("recording commands to: {:?}", path);
+
+        Ok(ChannelRecorder {
+            file,
+            size: header.len() as u64,
+            size_maximum,
+            redact,
+            redact_key: RandomState::new(),
+            stopped: false,
+        })
+    }
+
+    pub fn record(&mut self, message: &str) {
+        let message = message.trim();
+
+        if self.stopped || message.is_empty() {
+            return;
+        }
+
+        let line = if self.redact {
+            format!("{}\n", self.redact(message))
+        } else {
+            format!("{}\n", message)
+        };
+
+        // Stop recording once the record would grow above its maximum size (any further \
+        //   command is dropped, as replaying a record with holes would be misleading)
+        if self.size + line.len() as u64 > self.size_maximum {
+            info!("command record reached its maximum size, stopping recording");
+
+            self.stopped = true;
+
+            return;
+        }
+
+        if let Err(err) = self.file.write_all(line.as_bytes()) {
+            error!(
+                "could not write to command record, stopping recording: {}",
+                err
+            );
+
+            self.stopped = true;
+        } else {
+            self.size += line.len() as u64;
+        }
+    }
+
+    pub fn replay(
+        directory: &Path,
+        name: &str,
+        collection: &str,
+    ) -> Result<ChannelRecordReplay, ()> {
+        let path = Self::path(directory, name)?;
+
+        let file = File::open(&path).map_err(|err| {
+            error!("could not open command record: {:?}: {}", path, err);
+        })?;
+
+        let mut lines = BufReader::new(file).lines();
+
+        // Read the mode commands were recorded in
+        let mode = match lines.next() {
+            Some(Ok(header)) => header
+                .strip_prefix(RECORD_HEADER_MODE)
+                .and_then(|mode| ChannelMode::from_str(mode).ok())
+                .ok_or(())?,
+            _ => return Err(()),
+        };
+
+        let mut replay = ChannelRecordReplay {
+            commands: 0,
+            errors: 0,
+        };
+
+        for line in lines {
+            let line = line.map_err(|_| ())?;
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let message = Self::rewrite_collection(&line, collection);
+
+            debug!("replaying recorded command: {}", message);
+
+            let result = match mode {
+                ChannelMode::Search => {
+                    ChannelMessage::dispatch::<ChannelMessageModeSearch>(&message)
+                }
+                ChannelMode::Ingest => {
+                    ChannelMessage::dispatch::<ChannelMessageModeIngest>(&message)
+                }
+                ChannelMode::Control => return Err(()),
+            };
+
+            replay.commands += 1;
+
+            if Self::is_error(&result) {
+                replay.errors += 1;
+            }
+        }
+
+        info!(
+            "replayed {} recorded commands from: {:?} with {} errors",
+            replay.commands, path, replay.errors
+        );
+
+        Ok(replay)
+    }
+
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name.len() <= RECORD_NAME_LENGTH_MAXIMUM
+            && name
+                .chars()
+                .all(|character| character.is_ascii_alphanumeric() || "-_".contains(character))
+    }
+
+    fn path(directory: &Path, name: &str) -> Result<PathBuf, ()> {
+        // Important: validate the record name, as it is used to build a file path.
+        if Self::is_valid_name(name) {
+            Ok(directory.join(format!("{}.{}", name, RECORD_FILE_EXTENSION)))
+        } else {
+            Err(())
+        }
+    }
+
+    fn redact(&self, message: &str) -> String {
+        let mut redacted = String::with_capacity(message.len());
+        let (mut word, mut meta_key) = (String::new(), String::new());
+        let (mut in_text, mut in_meta, mut is_escaped) = (false, false, false);
+
+        // Replace each word in text parts and meta values with a stable pseudonym (the same \
+        //   word always gets the same pseudonym within a record, which keeps pushed text and \
+        //   query terms matching on replay)
+        for character in message.chars() {
+            if ((in_text && !is_escaped) || in_meta) && character.is_alphanumeric() {
+                word.push(character);
+
+                continue;
+            }
+
+            if !word.is_empty() {
+                redacted.push_str(&self.pseudonym(&word, in_meta));
+
+                word.clear();
+            }
+
+            if in_text && !is_escaped && character == TEXT_PART_ESCAPE {
+                is_escaped = true;
+            } else if !is_escaped && !in_meta && character == TEXT_PART_BOUNDARY {
+                in_text = !in_text;
+            } else if !in_text && character == META_PART_GROUP_OPEN {
+                in_meta = !RECORD_META_KEPT.contains(&meta_key.to_uppercase().as_str());
+            } else if !in_text && character == META_PART_GROUP_CLOSE {
+                in_meta = false;
+            } else {
+                is_escaped = false;
+            }
+
+            // Track the key of the current meta part (outside of text parts)
+            if !in_text && character.is_whitespace() {
+                meta_key.clear();
+            } else if !in_text && character != META_PART_GROUP_OPEN {
+                meta_key.push(character);
+            }
+
+            redacted.push(character);
+        }
+
+        if !word.is_empty() {
+            redacted.push_str(&self.pseudonym(&word, in_meta));
+        }
+
+        redacted
+    }
+
+    fn pseudonym(&self, word: &str, in_meta: bool) -> String {
+        // Notice: numbers in meta values are kept, as they are settings (eg. term weights).
+        if in_meta && word.chars().all(|character| character.is_ascii_digit()) {
+            return word.to_string();
+        }
+
+        // Important: hash words with a random key, which is generated for each record and \
+        //   never stored. Pseudonyms thus cannot be reversed by hashing a dictionary of words.
+        format!("r{:016x}", self.redact_key.hash_one(word.to_lowercase()))
+    }
+
+    fn rewrite_collection(message: &str, collection: &str) -> String {
+        let mut parts = message.splitn(3, ' ');
+
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(command), Some(_), remainder)
+                if COLLECTION_COMMANDS.contains(&command.to_uppercase().as_str()) =>
+            {
+                if let Some(remainder) = remainder {
+                    format!("{} {} {}", command, collection, remainder)
+                } else {
+                    format!("{} {}", command, collection)
+                }
+            }
+            _ => message.to_string(),
+        }
+    }
+
+    fn is_error(result: &Result<Vec<ChannelCommandResponse>, ChannelCommandError>) -> bool {
+        match result {
+            Ok(resp_groups) => resp_groups
+                .iter()
+                .any(|resp| matches!(resp, ChannelCommandResponse::Err(_))),
+            Err(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::identifiers::StoreTermHashed;
+    use crate::store::item::StoreItemPart;
+    use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+    use std::env;
+
+    fn open_recorder(
+        name: &str,
+        mode: &ChannelMode,
+        size_maximum: u64,
+        redact: bool,
+    ) -> ChannelRecorder {
+        let directory = env::temp_dir().join("sonic-test-record");
+
+        fs::remove_file(ChannelRecorder::path(&directory, name).unwrap()).ok();
+
+        ChannelRecorder::open(&directory, name, mode, size_maximum, redact).unwrap()
+    }
+
+    fn objects_terms(collection: &str, bucket: &str) -> Vec<(String, Vec<StoreTermHashed>)> {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, collection).unwrap();
+        let action = StoreKVActionBuilder::access(StoreItemPart::from_str(bucket).unwrap(), store);
+
+        let mut objects_terms = action.get_objects_terms().unwrap();
+
+        for (_, terms) in objects_terms.iter_mut() {
+            terms.sort_unstable();
+        }
+
+        objects_terms.sort_unstable();
+
+        objects_terms
+    }
+
+    #[test]
+    fn it_redacts_recorded_text() {
+        let recorder = open_recorder("test-redact", &ChannelMode::Ingest, 4096, true);
+
+        let (hello, secret, world) = (
+            recorder.pseudonym("hello", false),
+            recorder.pseudonym("secret1", true),
+            recorder.pseudonym("world", true),
+        );
+
+        assert_eq!(
+            recorder.redact(
+                "PUSH c:test b:test o:1 \"Hello, \\\"hello\\\"\" LANG(eng) KEY(Secret1) WEIGHTS(world:2)"
+            ),
+            format!(
+                "PUSH c:test b:test o:1 \"{}, \\\"{}\\\"\" LANG(eng) KEY({}) WEIGHTS({}:2)",
+                hello, hello, secret, world
+            )
+        );
+        assert_eq!(
+            recorder.redact("FLUSHB c:test b:test"),
+            "FLUSHB c:test b:test"
+        );
+
+        // Pseudonyms are keyed per record, thus the same word gets another pseudonym in \
+        //   another record
+        assert_eq!(hello, recorder.pseudonym("HELLO", false));
+        assert_ne!(
+            hello,
+            open_recorder("test-redact", &ChannelMode::Ingest, 4096, true)
+                .pseudonym("hello", false)
+        );
+    }
+
+    #[test]
+    fn it_validates_record_names() {
+        assert!(ChannelRecorder::is_valid_name("debug_session-1"));
+        assert!(!ChannelRecorder::is_valid_name(""));
+        assert!(!ChannelRecorder::is_valid_name("../escape"));
+        assert!(!ChannelRecorder::is_valid_name(&"a".repeat(65)));
+    }
+
+    #[test]
+    fn it_replays_recorded_commands() {
+        let directory = env::temp_dir().join("sonic-test-record");
+
+        for redact in &[false, true] {
+            let mut recorder = open_recorder("test-27", &ChannelMode::Ingest, 4096, *redact);
+
+            assert!(
+                ChannelMessage::dispatch::<ChannelMessageModeIngest>("FLUSHC c:test:27").is_ok()
+            );
+
+            for message in &[
+                "PUSH c:test:27 b:test:27 o:1 \"hello  world\"",
+                "PUSH c:test:27 b:test:27 o:2 \"hello replayed world\"",
+                "POP c:test:27 b:test:27 o:2 \"world\"",
+                "PUSH c:test:27 b:test:27 o:3 \"another text\"",
+                "FLUSHO c:test:27 b:test:27 o:3",
+            ] {
+                assert!(ChannelMessage::dispatch::<ChannelMessageModeIngest>(message).is_ok());
+
+                recorder.record(message);
+            }
+
+            assert!(
+                ChannelMessage::dispatch::<ChannelMessageModeIngest>("FLUSHC c:test:28").is_ok()
+            );
+
+            assert_eq!(
+                ChannelRecorder::replay(&directory, "test-27", "c:test:28"),
+                Ok(ChannelRecordReplay {
+                    commands: 5,
+                    errors: 0,
+                })
+            );
+
+            let (recorded, replayed) = (
+                objects_terms("c:test:27", "b:test:27"),
+                objects_terms("c:test:28", "b:test:27"),
+            );
+
+            assert_eq!(recorded.len(), 2);
+            assert_eq!(replayed.len(), 2);
+
+            // Notice: redacted text gets indexed as pseudonyms, thus only the same objects are \
+            //   expected.
+            if *redact {
+                for ((recorded_oid, _), (replayed_oid, _)) in recorded.iter().zip(replayed.iter()) {
+                    assert_eq!(recorded_oid, replayed_oid);
+                }
+            } else {
+                assert_eq!(recorded, replayed);
+            }
+        }
+    }
+
+    #[test]
+    fn it_stops_recording_above_maximum_size() {
+        let directory = env::temp_dir().join("sonic-test-record");
+
+        let mut recorder = open_recorder("test-bound", &ChannelMode::Search, 64, false);
+
+        recorder.record("PING");
+        recorder.record(&format!("QUERY c:test:27 b:test:27 \"{}\"", "a".repeat(64)));
+        recorder.record("PING");
+
+        assert_eq!(
+            fs::read_to_string(directory.join("test-bound.log")).unwrap(),
+            "# mode search\nPING\n"
+        );
+
+        // Existing records are never overwritten, and control commands are never recorded
+        assert!(
+            ChannelRecorder::open(&directory, "test-bound", &ChannelMode::Search, 64, false)
+                .is_err()
+        );
+        assert!(
+            ChannelRecorder::open(&directory, "test-bound", &ChannelMode::Control, 64, false)
+                .is_err()
+        );
+    }
+}
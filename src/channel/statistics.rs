@@ -19,6 +19,70 @@ lazy_static! {
     pub static ref COMMAND_LATENCY_WORST: RwLock<u32> = RwLock::new(0);
 }
 
+// Metrics exposed in Prometheus text format (name, type, help)
+static PROMETHEUS_METRICS: &[(&str, &str, &str)] = &[
+    (
+        "sonic_uptime_seconds",
+        "gauge",
+        "Time elapsed since the server started.",
+    ),
+    (
+        "sonic_clients_connected",
+        "gauge",
+        "Number of clients connected to the channel.",
+    ),
+    (
+        "sonic_commands_total",
+        "counter",
+        "Number of commands processed by the channel.",
+    ),
+    (
+        "sonic_command_latency_best_milliseconds",
+        "gauge",
+        "Best latency of a processed command.",
+    ),
+    (
+        "sonic_command_latency_worst_milliseconds",
+        "gauge",
+        "Worst latency of a processed command.",
+    ),
+    (
+        "sonic_kv_open_count",
+        "gauge",
+        "Number of key-value stores that are open.",
+    ),
+    (
+        "sonic_kv_write_stalls_total",
+        "counter",
+        "Number of writes that were stalled by the key-value stores.",
+    ),
+    (
+        "sonic_kv_estimated_keys",
+        "gauge",
+        "Estimated number of keys held by the key-value stores that are open.",
+    ),
+    (
+        "sonic_kv_pending_compaction_bytes",
+        "gauge",
+        "Estimated number of bytes pending compaction in the key-value stores that are open.",
+    ),
+    (
+        "sonic_fst_open_count",
+        "gauge",
+        "Number of suggestion graphs that are open.",
+    ),
+    (
+        "sonic_fst_consolidate_count",
+        "gauge",
+        "Number of suggestion graphs pending consolidation.",
+    ),
+    (
+        "sonic_fst_consolidate_last_milliseconds",
+        "gauge",
+        "Time the last consolidation of suggestion graphs took.",
+    ),
+];
+
 #[derive(Default)]
 pub struct ChannelStatistics {
     pub uptime: u64,
@@ -27,8 +91,12 @@ pub struct ChannelStatistics {
     pub command_latency_best: u32,
     pub command_latency_worst: u32,
     pub kv_open_count: usize,
+    pub kv_write_stall_count: u64,
+    pub kv_estimated_keys: u64,
+    pub kv_pending_compaction_bytes: u64,
     pub fst_open_count: usize,
     pub fst_consolidate_count: usize,
+    pub fst_consolidate_took: u64,
 }
 
 pub fn ensure_states() {
@@ -46,6 +114,14 @@ impl ChannelStatistics {
     pub fn gather() -> ChannelStatistics {
         let (kv_count, fst_count) = (StoreKVPool::count(), StoreFSTPool::count());
 
+        // Sum database properties over open stores (properties that cannot be read count as zero)
+        let (mut kv_estimated_keys, mut kv_pending_compaction_bytes) = (0, 0);
+
+        for (_, stats) in StoreKVPool::stats() {
+            kv_estimated_keys += stats.estimated_keys.unwrap_or(0);
+            kv_pending_compaction_bytes += stats.pending_compaction_bytes.unwrap_or(0);
+        }
+
         ChannelStatistics {
             uptime: START_TIME.elapsed().as_secs(),
             clients_connected: *CLIENTS_CONNECTED.read().unwrap(),
@@ -53,8 +129,69 @@ impl ChannelStatistics {
             command_latency_best: *COMMAND_LATENCY_BEST.read().unwrap(),
             command_latency_worst: *COMMAND_LATENCY_WORST.read().unwrap(),
             kv_open_count: kv_count,
+            kv_write_stall_count: StoreKVPool::count_write_stalls(),
+            kv_estimated_keys,
+            kv_pending_compaction_bytes,
             fst_open_count: fst_count.0,
             fst_consolidate_count: fst_count.1,
+            fst_consolidate_took: StoreFSTPool::consolidate_took(),
+        }
+    }
+
+    pub fn to_prometheus(&self) -> Vec<String> {
+        // Notice: values must be listed in the same order as metrics are defined
+        let values = [
+            self.uptime,
+            self.clients_connected as u64,
+            self.commands_total,
+            self.command_latency_best as u64,
+            self.command_latency_worst as u64,
+            self.kv_open_count as u64,
+            self.kv_write_stall_count,
+            self.kv_estimated_keys,
+            self.kv_pending_compaction_bytes,
+            self.fst_open_count as u64,
+            self.fst_consolidate_count as u64,
+            self.fst_consolidate_took,
+        ];
+
+        let mut lines = Vec::with_capacity(PROMETHEUS_METRICS.len() * 3);
+
+        for ((name, kind, help), value) in PROMETHEUS_METRICS.iter().zip(values.iter()) {
+            lines.push(format!("# HELP {} {}", name, help));
+            lines.push(format!("# TYPE {} {}", name, kind));
+            lines.push(format!("{} {}", name, value));
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_formats_prometheus_metrics() {
+        let lines = ChannelStatistics {
+            commands_total: 42,
+            ..Default::default()
+        }
+        .to_prometheus();
+
+        assert_eq!(lines.len(), PROMETHEUS_METRICS.len() * 3);
+        assert!(lines.contains(&"# TYPE sonic_commands_total counter".to_string()));
+        assert!(lines.contains(&"sonic_commands_total 42".to_string()));
+
+        // Each sample line must hold a metric name that was described, and a numeric value
+        for line in lines.iter().filter(|line| !line.starts_with('#')) {
+            let mut parts = line.split(' ');
+
+            let (name, value) = (parts.next().unwrap(), parts.next().unwrap());
+
+            assert!(parts.next().is_none());
+            assert!(PROMETHEUS_METRICS.iter().any(|metric| metric.0 == name));
+            assert!(value.parse::<u64>().is_ok());
         }
     }
 }
@@ -0,0 +1,140 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+#[cfg(feature = "channel-tls")]
+use rustls::{ServerConfig, ServerConnection, StreamOwned};
+#[cfg(feature = "channel-tls")]
+use std::cell::RefCell;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+#[cfg(feature = "channel-tls")]
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+pub enum ChannelStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+    // Notice: a TLS stream needs to be mutably borrowed to be read from or written to, though \
+    //   the stream is passed around as a shared reference (it is used from a single thread).
+    #[cfg(feature = "channel-tls")]
+    Tls(Box<RefCell<StreamOwned<ServerConnection, TcpStream>>>),
+}
+
+impl ChannelStream {
+    #[cfg(feature = "channel-tls")]
+    pub fn tls(stream: TcpStream, config: Arc<ServerConfig>) -> Result<ChannelStream, io::Error> {
+        // Notice: the handshake happens on the first read or write from the stream.
+        let connection = ServerConnection::new(config)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        Ok(ChannelStream::Tls(Box::new(RefCell::new(
+            StreamOwned::new(connection, stream),
+        ))))
+    }
+
+    pub fn configure(&self, timeout: Duration) -> Result<(), io::Error> {
+        match self {
+            ChannelStream::Tcp(stream) => Self::configure_tcp(stream, timeout),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))
+            }
+            #[cfg(feature = "channel-tls")]
+            ChannelStream::Tls(stream) => Self::configure_tcp(&stream.borrow().sock, timeout),
+        }
+    }
+
+    pub fn configure_read(&self, timeout: Duration) -> Result<(), io::Error> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.set_read_timeout(Some(timeout)),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => stream.set_read_timeout(Some(timeout)),
+            #[cfg(feature = "channel-tls")]
+            ChannelStream::Tls(stream) => stream.borrow().sock.set_read_timeout(Some(timeout)),
+        }
+    }
+
+    fn configure_tcp(stream: &TcpStream, timeout: Duration) -> Result<(), io::Error> {
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))
+    }
+
+    pub fn peer(&self) -> Option<String> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.peer_addr().ok().map(|addr| addr.to_string()),
+            #[cfg(unix)]
+            ChannelStream::Unix(_) => Some("unix".to_string()),
+            #[cfg(feature = "channel-tls")]
+            ChannelStream::Tls(stream) => stream
+                .borrow()
+                .sock
+                .peer_addr()
+                .ok()
+                .map(|addr| format!("{} (tls)", addr)),
+        }
+    }
+}
+
+impl Read for &ChannelStream {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            ChannelStream::Tcp(stream) => (&*stream).read(buffer),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => (&*stream).read(buffer),
+            #[cfg(feature = "channel-tls")]
+            ChannelStream::Tls(stream) => stream.borrow_mut().read(buffer),
+        }
+    }
+}
+
+impl Write for &ChannelStream {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        match *self {
+            ChannelStream::Tcp(stream) => (&*stream).write(buffer),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => (&*stream).write(buffer),
+            #[cfg(feature = "channel-tls")]
+            ChannelStream::Tls(stream) => stream.borrow_mut().write(buffer),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            ChannelStream::Tcp(stream) => (&*stream).flush(),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => (&*stream).flush(),
+            #[cfg(feature = "channel-tls")]
+            ChannelStream::Tls(stream) => stream.borrow_mut().flush(),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_and_writes_unix_streams() {
+        let (left, right) = UnixStream::pair().unwrap();
+        let (left, right) = (ChannelStream::Unix(left), ChannelStream::Unix(right));
+
+        assert!(left.configure(Duration::from_secs(1)).is_ok());
+        assert_eq!(left.peer(), Some("unix".to_string()));
+
+        writeln!(&left, "PING").unwrap();
+
+        let mut read = [0; 8];
+
+        assert_eq!((&right).read(&mut read).unwrap(), 5);
+        assert_eq!(&read[0..5], b"PING\n");
+    }
+}
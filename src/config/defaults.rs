@@ -7,6 +7,12 @@
 use std::net::SocketAddr;
 use std::path::PathBuf;
 
+use super::options::{
+    ConfigChannelInputEncoding, ConfigChannelSearchRanker, ConfigChannelStartOptions, ConfigLexer,
+    ConfigStoreFSTGraphWarmup, ConfigStoreKVBucketHash, ConfigStoreKVDatabaseCompression,
+    ConfigStoreKVDatabaseWriteStall,
+};
+
 pub fn server_log_level() -> String {
     "error".to_string()
 }
@@ -15,10 +21,58 @@ pub fn channel_inet() -> SocketAddr {
     "[::1]:1491".parse().unwrap()
 }
 
+pub fn channel_tcp() -> bool {
+    true
+}
+
 pub fn channel_tcp_timeout() -> u64 {
     300
 }
 
+pub fn channel_unix_socket_mode() -> u32 {
+    0o660
+}
+
+pub fn channel_start_options() -> ConfigChannelStartOptions {
+    ConfigChannelStartOptions::Lenient
+}
+
+pub fn channel_input_encoding() -> ConfigChannelInputEncoding {
+    ConfigChannelInputEncoding::Strict
+}
+
+pub fn channel_push_idempotency_ttl() -> u64 {
+    600
+}
+
+pub fn channel_push_idempotency_keys() -> usize {
+    10000
+}
+
+pub fn channel_pipeline_flush_commands() -> usize {
+    100
+}
+
+pub fn channel_pipeline_flush_after() -> u64 {
+    10
+}
+
+pub fn channel_recent_errors() -> usize {
+    50
+}
+
+pub fn channel_record_size_maximum() -> u64 {
+    1048576
+}
+
+pub fn channel_record_redact() -> bool {
+    true
+}
+
+pub fn channel_response_size_maximum() -> usize {
+    1048576
+}
+
 pub fn channel_search_query_limit_default() -> u16 {
     10
 }
@@ -31,6 +85,50 @@ pub fn channel_search_query_alternates_try() -> usize {
     4
 }
 
+pub fn channel_search_query_typo_tolerance() -> u8 {
+    1
+}
+
+pub fn channel_search_query_prefetch() -> bool {
+    false
+}
+
+pub fn channel_search_query_order_terms() -> bool {
+    false
+}
+
+pub fn channel_search_query_zero_copy() -> bool {
+    true
+}
+
+pub fn channel_search_query_snapshot() -> bool {
+    false
+}
+
+pub fn channel_search_query_federate_maximum() -> u8 {
+    8
+}
+
+pub fn channel_search_query_term_frequency_minimum() -> usize {
+    0
+}
+
+pub fn channel_search_query_term_frequency_strict() -> bool {
+    false
+}
+
+pub fn channel_search_query_time_budget() -> u64 {
+    0
+}
+
+pub fn channel_search_query_ranker() -> ConfigChannelSearchRanker {
+    ConfigChannelSearchRanker::Idf
+}
+
+pub fn channel_search_query_rank_candidates() -> usize {
+    1000
+}
+
 pub fn channel_search_suggest_limit_default() -> u16 {
     5
 }
@@ -47,6 +145,16 @@ pub fn channel_search_list_limit_maximum() -> u16 {
     500
 }
 
+pub fn lexer() -> ConfigLexer {
+    ConfigLexer {
+        cjk_ngram: lexer_cjk_ngram(),
+    }
+}
+
+pub fn lexer_cjk_ngram() -> bool {
+    false
+}
+
 pub fn store_kv_path() -> PathBuf {
     PathBuf::from("./data/store/kv/")
 }
@@ -55,18 +163,62 @@ pub fn store_kv_retain_word_objects() -> usize {
     1000
 }
 
+pub fn store_kv_max_snapshots() -> usize {
+    16
+}
+
+pub fn store_kv_snapshot_expire_after() -> u64 {
+    3600
+}
+
+pub fn store_kv_snippet_length_maximum() -> usize {
+    256
+}
+
+pub fn store_kv_term_positions() -> bool {
+    false
+}
+
+pub fn store_kv_bucket_hash() -> ConfigStoreKVBucketHash {
+    ConfigStoreKVBucketHash::Xxh32
+}
+
+pub fn store_kv_bucket_hash_seed() -> u32 {
+    0
+}
+
+pub fn store_kv_bucket_hash_guard() -> bool {
+    false
+}
+
 pub fn store_kv_pool_inactive_after() -> u64 {
     1800
 }
 
+pub fn store_kv_pool_trim_after() -> u64 {
+    600
+}
+
+pub fn store_kv_pool_compact_after(inactive_after: u64) -> u64 {
+    inactive_after * 2 / 3
+}
+
 pub fn store_kv_database_flush_after() -> u64 {
     900
 }
 
+pub fn store_kv_database_retention_after() -> u64 {
+    300
+}
+
 pub fn store_kv_database_compress() -> bool {
     true
 }
 
+pub fn store_kv_database_compression() -> ConfigStoreKVDatabaseCompression {
+    ConfigStoreKVDatabaseCompression::Zstd
+}
+
 pub fn store_kv_database_parallelism() -> u16 {
     2
 }
@@ -79,14 +231,26 @@ pub fn store_kv_database_max_flushes() -> u16 {
     1
 }
 
+pub fn store_kv_database_max_write_buffers() -> u16 {
+    2
+}
+
 pub fn store_kv_database_write_buffer() -> usize {
     16384
 }
 
+pub fn store_kv_database_block_cache() -> usize {
+    65536
+}
+
 pub fn store_kv_database_write_ahead_log() -> bool {
     true
 }
 
+pub fn store_kv_database_write_stall() -> ConfigStoreKVDatabaseWriteStall {
+    ConfigStoreKVDatabaseWriteStall::Block
+}
+
 pub fn store_fst_path() -> PathBuf {
     PathBuf::from("./data/store/fst/")
 }
@@ -99,6 +263,10 @@ pub fn store_fst_graph_consolidate_after() -> u64 {
     180
 }
 
+pub fn store_fst_graph_hold_after(inactive_after: u64) -> u64 {
+    inactive_after * 4 / 5
+}
+
 pub fn store_fst_graph_max_size() -> usize {
     2048
 }
@@ -106,3 +274,7 @@ pub fn store_fst_graph_max_size() -> usize {
 pub fn store_fst_graph_max_words() -> usize {
     250000
 }
+
+pub fn store_fst_graph_warmup() -> ConfigStoreFSTGraphWarmup {
+    ConfigStoreFSTGraphWarmup::Disabled
+}
@@ -4,7 +4,9 @@
 // Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
 
 use super::defaults;
@@ -14,6 +16,10 @@ use super::env_var;
 pub struct Config {
     pub server: ConfigServer,
     pub channel: ConfigChannel,
+
+    #[serde(default = "defaults::lexer")]
+    pub lexer: ConfigLexer,
+
     pub store: ConfigStore,
 }
 
@@ -34,15 +40,84 @@ pub struct ConfigChannel {
     )]
     pub inet: SocketAddr,
 
+    #[serde(default = "defaults::channel_tcp")]
+    pub tcp: bool,
+
     #[serde(default = "defaults::channel_tcp_timeout")]
     pub tcp_timeout: u64,
 
+    #[serde(default, deserialize_with = "env_var::opt_str")]
+    pub unix_socket_path: Option<String>,
+
+    #[serde(default = "defaults::channel_unix_socket_mode")]
+    pub unix_socket_mode: u32,
+
     #[serde(default, deserialize_with = "env_var::opt_str")]
     pub auth_password: Option<String>,
 
+    #[serde(default = "defaults::channel_start_options")]
+    pub start_options: ConfigChannelStartOptions,
+
+    #[serde(default = "defaults::channel_input_encoding")]
+    pub input_encoding: ConfigChannelInputEncoding,
+
+    #[serde(default = "defaults::channel_push_idempotency_ttl")]
+    pub push_idempotency_ttl: u64,
+
+    #[serde(default = "defaults::channel_push_idempotency_keys")]
+    pub push_idempotency_keys: usize,
+
+    #[serde(default = "defaults::channel_pipeline_flush_commands")]
+    pub pipeline_flush_commands: usize,
+
+    #[serde(default = "defaults::channel_pipeline_flush_after")]
+    pub pipeline_flush_after: u64,
+
+    #[serde(default = "defaults::channel_recent_errors")]
+    pub recent_errors: usize,
+
+    #[serde(default, deserialize_with = "env_var::opt_str")]
+    pub record_path: Option<String>,
+
+    #[serde(default = "defaults::channel_record_size_maximum")]
+    pub record_size_maximum: u64,
+
+    #[serde(default = "defaults::channel_record_redact")]
+    pub record_redact: bool,
+
+    #[serde(default = "defaults::channel_response_size_maximum")]
+    pub response_size_maximum: usize,
+
+    #[serde(default)]
+    pub tls: Option<ConfigChannelTLS>,
+
     pub search: ConfigChannelSearch,
 }
 
+#[derive(Deserialize)]
+#[cfg_attr(not(feature = "channel-tls"), allow(dead_code))]
+pub struct ConfigChannelTLS {
+    #[serde(deserialize_with = "env_var::str")]
+    pub certificate_path: String,
+
+    #[serde(deserialize_with = "env_var::str")]
+    pub key_path: String,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigChannelStartOptions {
+    Lenient,
+    Strict,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigChannelInputEncoding {
+    Lenient,
+    Strict,
+}
+
 #[derive(Deserialize)]
 pub struct ConfigChannelSearch {
     #[serde(default = "defaults::channel_search_query_limit_default")]
@@ -54,6 +129,45 @@ pub struct ConfigChannelSearch {
     #[serde(default = "defaults::channel_search_query_alternates_try")]
     pub query_alternates_try: usize,
 
+    #[serde(default = "defaults::channel_search_query_typo_tolerance")]
+    pub query_typo_tolerance: u8,
+
+    #[serde(default = "defaults::channel_search_query_prefetch")]
+    pub query_prefetch: bool,
+
+    #[serde(default = "defaults::channel_search_query_order_terms")]
+    pub query_order_terms: bool,
+
+    #[serde(default = "defaults::channel_search_query_zero_copy")]
+    pub query_zero_copy: bool,
+
+    #[serde(default = "defaults::channel_search_query_snapshot")]
+    pub query_snapshot: bool,
+
+    #[serde(default = "defaults::channel_search_query_federate_maximum")]
+    pub query_federate_maximum: u8,
+
+    #[serde(default = "defaults::channel_search_query_term_frequency_minimum")]
+    pub query_term_frequency_minimum: usize,
+
+    #[serde(default)]
+    pub query_term_frequency_collections: HashMap<String, usize>,
+
+    #[serde(default = "defaults::channel_search_query_term_frequency_strict")]
+    pub query_term_frequency_strict: bool,
+
+    #[serde(default = "defaults::channel_search_query_time_budget")]
+    pub query_time_budget: u64,
+
+    #[serde(default = "defaults::channel_search_query_ranker")]
+    pub query_ranker: ConfigChannelSearchRanker,
+
+    #[serde(default)]
+    pub query_ranker_collections: HashMap<String, ConfigChannelSearchRanker>,
+
+    #[serde(default = "defaults::channel_search_query_rank_candidates")]
+    pub query_rank_candidates: usize,
+
     #[serde(default = "defaults::channel_search_suggest_limit_default")]
     pub suggest_limit_default: u16,
 
@@ -67,6 +181,20 @@ pub struct ConfigChannelSearch {
     pub list_limit_maximum: u16,
 }
 
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChannelSearchRanker {
+    Idf,
+    Bm25,
+    MatchCount,
+}
+
+#[derive(Deserialize)]
+pub struct ConfigLexer {
+    #[serde(default = "defaults::lexer_cjk_ngram")]
+    pub cjk_ngram: bool,
+}
+
 #[derive(Deserialize)]
 pub struct ConfigStore {
     pub kv: ConfigStoreKV,
@@ -84,14 +212,79 @@ pub struct ConfigStoreKV {
     #[serde(default = "defaults::store_kv_retain_word_objects")]
     pub retain_word_objects: usize,
 
+    pub max_bucket_objects: Option<u32>,
+
+    #[serde(default = "defaults::store_kv_max_snapshots")]
+    pub max_snapshots: usize,
+
+    #[serde(default = "defaults::store_kv_snapshot_expire_after")]
+    pub snapshot_expire_after: u64,
+
+    #[serde(default = "defaults::store_kv_snippet_length_maximum")]
+    pub snippet_length_maximum: usize,
+
+    #[serde(default)]
+    pub oid_separator: Option<char>,
+
+    #[serde(default)]
+    pub oid_normalization: Vec<ConfigStoreKVOIDNormalization>,
+
+    #[serde(default)]
+    pub oid_normalization_collections: HashMap<String, Vec<ConfigStoreKVOIDNormalization>>,
+
+    #[serde(default = "defaults::store_kv_term_positions")]
+    pub term_positions: bool,
+
+    #[serde(default)]
+    pub term_positions_collections: HashMap<String, bool>,
+
+    #[serde(default = "defaults::store_kv_bucket_hash")]
+    pub bucket_hash: ConfigStoreKVBucketHash,
+
+    #[serde(default = "defaults::store_kv_bucket_hash_seed")]
+    pub bucket_hash_seed: u32,
+
+    #[serde(default = "defaults::store_kv_bucket_hash_guard")]
+    pub bucket_hash_guard: bool,
+
     pub pool: ConfigStoreKVPool,
     pub database: ConfigStoreKVDatabase,
 }
 
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigStoreKVOIDNormalization {
+    Trim,
+    Lowercase,
+    Nfc,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigStoreKVBucketHash {
+    Xxh32,
+    Xxh64,
+}
+
 #[derive(Deserialize)]
 pub struct ConfigStoreKVPool {
     #[serde(default = "defaults::store_kv_pool_inactive_after")]
     pub inactive_after: u64,
+
+    #[serde(default = "defaults::store_kv_pool_trim_after")]
+    pub trim_after: u64,
+
+    pub compact_after: Option<u64>,
+
+    pub max_open: Option<usize>,
+}
+
+impl ConfigStoreKVPool {
+    pub fn compact_after(&self) -> u64 {
+        // Default to a fraction of 'inactive_after' (as it must be lower than it)
+        self.compact_after
+            .unwrap_or_else(|| defaults::store_kv_pool_compact_after(self.inactive_after))
+    }
 }
 
 #[derive(Deserialize)]
@@ -99,9 +292,17 @@ pub struct ConfigStoreKVDatabase {
     #[serde(default = "defaults::store_kv_database_flush_after")]
     pub flush_after: u64,
 
+    #[serde(default = "defaults::store_kv_database_retention_after")]
+    pub retention_after: u64,
+
     #[serde(default = "defaults::store_kv_database_compress")]
     pub compress: bool,
 
+    #[serde(default = "defaults::store_kv_database_compression")]
+    pub compression: ConfigStoreKVDatabaseCompression,
+
+    pub compression_level: Option<i32>,
+
     #[serde(default = "defaults::store_kv_database_parallelism")]
     pub parallelism: u16,
 
@@ -116,8 +317,44 @@ pub struct ConfigStoreKVDatabase {
     #[serde(default = "defaults::store_kv_database_write_buffer")]
     pub write_buffer: usize,
 
+    #[serde(default = "defaults::store_kv_database_max_write_buffers")]
+    pub max_write_buffers: u16,
+
+    #[serde(default = "defaults::store_kv_database_block_cache")]
+    pub block_cache: usize,
+
     #[serde(default = "defaults::store_kv_database_write_ahead_log")]
     pub write_ahead_log: bool,
+
+    #[serde(default = "defaults::store_kv_database_write_stall")]
+    pub write_stall: ConfigStoreKVDatabaseWriteStall,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigStoreKVDatabaseWriteStall {
+    Block,
+    Reject,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigStoreKVDatabaseCompression {
+    Zstd,
+    Lz4,
+    Snappy,
+}
+
+impl ConfigStoreKVDatabaseCompression {
+    pub fn levels(&self) -> Option<RangeInclusive<i32>> {
+        // Notice: LZ4 levels are negative, as they set its acceleration (positive levels would \
+        //   be ignored), while Snappy does not have compression levels.
+        match self {
+            ConfigStoreKVDatabaseCompression::Zstd => Some(1..=22),
+            ConfigStoreKVDatabaseCompression::Lz4 => Some(-65537..=-1),
+            ConfigStoreKVDatabaseCompression::Snappy => None,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -132,6 +369,15 @@ pub struct ConfigStoreFST {
     pub graph: ConfigStoreFSTGraph,
 }
 
+impl ConfigStoreFST {
+    pub fn hold_after(&self) -> u64 {
+        // Default to a fraction of the pool 'inactive_after' (as it must be lower than it)
+        self.graph
+            .hold_after
+            .unwrap_or_else(|| defaults::store_fst_graph_hold_after(self.pool.inactive_after))
+    }
+}
+
 #[derive(Deserialize)]
 pub struct ConfigStoreFSTPool {
     #[serde(default = "defaults::store_fst_pool_inactive_after")]
@@ -143,9 +389,22 @@ pub struct ConfigStoreFSTGraph {
     #[serde(default = "defaults::store_fst_graph_consolidate_after")]
     pub consolidate_after: u64,
 
+    pub hold_after: Option<u64>,
+
     #[serde(default = "defaults::store_fst_graph_max_size")]
     pub max_size: usize,
 
     #[serde(default = "defaults::store_fst_graph_max_words")]
     pub max_words: usize,
+
+    #[serde(default = "defaults::store_fst_graph_warmup")]
+    pub warmup: ConfigStoreFSTGraphWarmup,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigStoreFSTGraphWarmup {
+    Disabled,
+    Blocking,
+    Background,
 }
@@ -8,6 +8,7 @@ use std::fs::File;
 use std::io::Read;
 
 use super::options::Config;
+use crate::query::types::QUERY_SEARCH_TYPOS_MAXIMUM;
 use crate::APP_ARGS;
 
 pub struct ConfigReader;
@@ -34,19 +35,74 @@ impl ConfigReader {
     }
 
     fn validate(config: &Config) {
+        // Check 'tcp' for channel (the channel would not listen on anything)
+        if !config.channel.tcp && config.channel.unix_socket_path.is_none() {
+            panic!("tcp for channel must not be disabled if no unix_socket_path is set");
+        }
+
+        // Check 'unix_socket_mode' for channel
+        if config.channel.unix_socket_mode > 0o777 {
+            panic!("unix_socket_mode for channel must be a file mode within 0o777");
+        }
+
+        // Check 'parallelism' for KV
+        if config.store.kv.database.parallelism == 0 {
+            panic!("parallelism for kv must not be zero");
+        }
+
+        // Check 'max_compactions' and 'max_flushes' for KV (background jobs would never run)
+        if config.store.kv.database.max_compactions == 0 {
+            panic!("max_compactions for kv must not be zero");
+        }
+        if config.store.kv.database.max_flushes == 0 {
+            panic!("max_flushes for kv must not be zero");
+        }
+
+        // Check 'query_typo_tolerance' for search channel
+        if config.channel.search.query_typo_tolerance > QUERY_SEARCH_TYPOS_MAXIMUM {
+            panic!("query_typo_tolerance for search must not be greater than 2");
+        }
+
+        // Check 'compression_level' for KV (it must be supported by the compression codec)
+        if let Some(level) = config.store.kv.database.compression_level {
+            match config.store.kv.database.compression.levels() {
+                Some(levels) if levels.contains(&level) => {}
+                Some(levels) => panic!(
+                    "compression_level for kv must be within {:?} for this compression",
+                    levels
+                ),
+                None => panic!("compression_level for kv is not supported by this compression"),
+            }
+        }
+
         // Check 'write_buffer' for KV
         if config.store.kv.database.write_buffer == 0 {
             panic!("write_buffer for kv must not be zero");
         }
 
+        // Check 'max_write_buffers' for KV
+        if config.store.kv.database.max_write_buffers < 2 {
+            panic!("max_write_buffers for kv must be at least 2");
+        }
+
         // Check 'flush_after' for KV
         if config.store.kv.database.flush_after >= config.store.kv.pool.inactive_after {
             panic!("flush_after for kv must be strictly lower than inactive_after");
         }
 
+        // Check 'compact_after' for KV
+        if config.store.kv.pool.compact_after() >= config.store.kv.pool.inactive_after {
+            panic!("compact_after for kv must be strictly lower than inactive_after");
+        }
+
         // Check 'consolidate_after' for FST
         if config.store.fst.graph.consolidate_after >= config.store.fst.pool.inactive_after {
             panic!("consolidate_after for fst must be strictly lower than inactive_after");
         }
+
+        // Check 'hold_after' for FST
+        if config.store.fst.hold_after() >= config.store.fst.pool.inactive_after {
+            panic!("hold_after for fst must be strictly lower than inactive_after");
+        }
     }
 }
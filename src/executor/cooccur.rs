@@ -0,0 +1,158 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::lexer::token::TokenLexer;
+use crate::query::types::{QuerySearchID, QuerySearchLimit};
+use crate::store::fst::{StoreFSTActionBuilder, StoreFSTPool};
+use crate::store::identifiers::StoreTermHashed;
+use crate::store::item::StoreItem;
+use crate::store::kv::{StoreKVAcquireMode, StoreKVAction, StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
+
+pub struct ExecutorCoOccur;
+
+const COOCCUR_OBJECTS_MAXIMUM: usize = 200;
+
+impl ExecutorCoOccur {
+    pub fn execute<'a>(
+        store: StoreItem<'a>,
+        _event_id: QuerySearchID,
+        mut lexer: TokenLexer<'a>,
+        limit: QuerySearchLimit,
+    ) -> Result<Option<Vec<String>>, StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            general_kv_access_lock_read!();
+            general_fst_access_lock_read!();
+
+            let (kv_store, fst_store) = (
+                StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?,
+                StoreFSTPool::acquire(collection, bucket)?,
+            );
+
+            // Important: acquire bucket store read lock
+            executor_kv_lock_read!(kv_store);
+
+            let (kv_action, fst_action) = (
+                StoreKVActionBuilder::access(bucket, kv_store),
+                StoreFSTActionBuilder::access(fst_store),
+            );
+
+            if let (Some((word, word_hashed)), None) = (lexer.next(), lexer.next()) {
+                debug!("running cooccur on word: {}", word);
+
+                let counted_terms = Self::count_terms(&kv_action, word_hashed)?;
+
+                // Map co-occurring term hashes back to their words
+                // Notice: the database only stores term hashes, therefore words are looked \
+                //   up from the suggestion graph. Terms missing from the graph (eg. pending \
+                //   consolidation) are skipped.
+                let terms_hashed: HashSet<StoreTermHashed> = counted_terms
+                    .iter()
+                    .map(|(term_hashed, _)| *term_hashed)
+                    .collect();
+
+                let mut found_words = fst_action.find_hashed_words(&terms_hashed);
+
+                let words: Vec<String> = counted_terms
+                    .into_iter()
+                    .filter_map(|(term_hashed, _)| found_words.remove(&term_hashed))
+                    .take(limit as usize)
+                    .collect();
+
+                return Ok(if words.is_empty() { None } else { Some(words) });
+            }
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+
+    fn count_terms(
+        kv_action: &StoreKVAction,
+        word_hashed: StoreTermHashed,
+    ) -> Result<Vec<(StoreTermHashed, u32)>, StoreOperationError> {
+        let mut term_counts: HashMap<StoreTermHashed, u32> = HashMap::new();
+
+        // Count terms of the most recent objects linked to word (candidates are bounded, as to \
+        //   keep the cost of a single command predictable on very common words)
+        if let Some(word_iids) = kv_action.get_term_to_iids(word_hashed)? {
+            for word_iid in word_iids.into_iter().take(COOCCUR_OBJECTS_MAXIMUM) {
+                if let Some(iid_terms) = kv_action.get_iid_to_terms(word_iid)? {
+                    for iid_term in iid_terms {
+                        if iid_term != word_hashed {
+                            *term_counts.entry(iid_term).or_insert(0) += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Rank terms by descending co-occurrence count (ties are ordered by hash, which keeps \
+        //   results stable across calls)
+        let mut counted_terms: Vec<(StoreTermHashed, u32)> = term_counts.into_iter().collect();
+
+        counted_terms.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        Ok(counted_terms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::identifiers::StoreTermHash;
+    use crate::store::item::StoreItemPart;
+
+    #[test]
+    fn it_counts_cooccurring_terms() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:9").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:9").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        let (coffee, milk, sugar, tea) = (
+            StoreTermHash::from("coffee"),
+            StoreTermHash::from("milk"),
+            StoreTermHash::from("sugar"),
+            StoreTermHash::from("tea"),
+        );
+
+        let objects = vec![
+            (0, vec![coffee, milk]),
+            (1, vec![coffee, milk, sugar]),
+            (2, vec![coffee, sugar, milk]),
+            (3, vec![tea, sugar]),
+        ];
+
+        for (iid, terms) in objects {
+            assert!(action.set_iid_to_terms(iid, &terms).is_ok());
+
+            for term in terms {
+                assert!(action.batch_link_term(term, iid, None).is_ok());
+            }
+        }
+
+        assert_eq!(
+            ExecutorCoOccur::count_terms(&action, coffee),
+            Ok(vec![(milk, 3), (sugar, 2)])
+        );
+
+        let sugar_terms = ExecutorCoOccur::count_terms(&action, sugar).unwrap();
+
+        assert_eq!(sugar_terms.len(), 3);
+        assert_eq!(sugar_terms[0], (coffee.min(milk), 2));
+        assert_eq!(sugar_terms[2], (tea, 1));
+
+        assert_eq!(
+            ExecutorCoOccur::count_terms(&action, StoreTermHash::from("water")),
+            Ok(vec![])
+        );
+    }
+}
@@ -9,11 +9,12 @@ use crate::store::fst::{StoreFSTActionBuilder, StoreFSTMisc};
 use crate::store::item::StoreItem;
 use crate::store::kv::StoreKVActionBuilder;
 use crate::store::kv::{StoreKVAcquireMode, StoreKVPool};
+use crate::store::operation::StoreOperationError;
 
 pub struct ExecutorCount;
 
 impl ExecutorCount {
-    pub fn execute(store: StoreItem) -> Result<u32, ()> {
+    pub fn execute(store: StoreItem) -> Result<u32, StoreOperationError> {
         match store {
             // Count terms in (collection, bucket, object) from KV
             StoreItem(collection, Some(bucket), Some(object)) => {
@@ -21,31 +22,22 @@ impl ExecutorCount {
                 //   prevents the database from being erased while using it in this block.
                 general_kv_access_lock_read!();
 
-                if let Ok(kv_store) = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)
-                {
-                    // Important: acquire bucket store read lock
-                    executor_kv_lock_read!(kv_store);
-
-                    let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
-
-                    // Try to resolve existing OID to IID
-                    let oid = object.as_str();
-
-                    kv_action
-                        .get_oid_to_iid(oid)
-                        .unwrap_or(None)
-                        .map(|iid| {
-                            // List terms for IID
-                            if let Some(terms) = kv_action.get_iid_to_terms(iid).unwrap_or(None) {
-                                terms.len() as u32
-                            } else {
-                                0
-                            }
-                        })
-                        .ok_or(())
-                        .or(Ok(0))
-                } else {
-                    Err(())
+                let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?;
+
+                // Important: acquire bucket store read lock
+                executor_kv_lock_read!(kv_store);
+
+                let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
+
+                // Try to resolve existing OID to IID
+                let oid = object.as_str();
+
+                match kv_action.get_oid_to_iid(oid)? {
+                    // List terms for IID
+                    Some(iid) => Ok(kv_action
+                        .get_iid_to_terms(iid)?
+                        .map_or(0, |terms| terms.len() as u32)),
+                    None => Ok(0),
                 }
             }
             // Count terms in (collection, bucket) from FST
@@ -54,19 +46,39 @@ impl ExecutorCount {
                 //   prevents the graph from being erased while using it in this block.
                 general_fst_access_lock_read!();
 
-                if let Ok(fst_store) = StoreFSTPool::acquire(collection, bucket) {
-                    let fst_action = StoreFSTActionBuilder::access(fst_store);
+                let fst_store = StoreFSTPool::acquire(collection, bucket)?;
 
-                    Ok(fst_action.count_words() as u32)
-                } else {
-                    Err(())
-                }
+                let fst_action = StoreFSTActionBuilder::access(fst_store);
+
+                Ok(fst_action.count_words() as u32)
             }
             // Count buckets in (collection) from FS
             StoreItem(collection, None, None) => {
-                StoreFSTMisc::count_collection_buckets(collection).map(|count| count as u32)
+                Ok(StoreFSTMisc::count_collection_buckets(collection)? as u32)
             }
-            _ => Err(()),
+            _ => Err(StoreOperationError::Failed),
+        }
+    }
+
+    pub fn execute_objects(store: StoreItem) -> Result<u32, StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Count objects in (collection, bucket) from KV
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            general_kv_access_lock_read!();
+
+            let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?;
+
+            // Important: acquire bucket store read lock
+            executor_kv_lock_read!(kv_store);
+
+            let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
+
+            // Notice: the object count is kept up-to-date as objects get pushed and flushed, \
+            //   thus objects do not need to be listed there.
+            kv_action.get_oid_count().map_err(StoreOperationError::from)
+        } else {
+            Err(StoreOperationError::Failed)
         }
     }
 }
@@ -6,12 +6,13 @@
 
 use crate::store::fst::StoreFSTActionBuilder;
 use crate::store::item::StoreItem;
-use crate::store::kv::StoreKVActionBuilder;
+use crate::store::kv::{StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
 
 pub struct ExecutorFlushC;
 
 impl ExecutorFlushC {
-    pub fn execute(store: StoreItem) -> Result<u32, ()> {
+    pub fn execute(store: StoreItem) -> Result<u32, StoreOperationError> {
         // Important: do not acquire the store from there, as otherwise it will remain open \
         //   even if dropped in the inner function, as this caller would still own a reference to \
         //   it.
@@ -21,15 +22,20 @@ impl ExecutorFlushC {
             general_kv_access_lock_write!();
             general_fst_access_lock_write!();
 
+            // Any bucket marked as immutable? (reject erasure of the whole collection)
+            if StoreKVPool::has_immutable_buckets(collection.as_str()) != Ok(false) {
+                return Err(StoreOperationError::BucketImmutable);
+            }
+
             match (
                 StoreKVActionBuilder::erase(collection, None),
                 StoreFSTActionBuilder::erase(collection, None),
             ) {
                 (Ok(erase_count), Ok(_)) => Ok(erase_count),
-                _ => Err(()),
+                _ => Err(StoreOperationError::Failed),
             }
         } else {
-            Err(())
+            Err(StoreOperationError::Failed)
         }
     }
 }
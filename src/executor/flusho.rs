@@ -6,57 +6,68 @@
 
 use crate::store::item::StoreItem;
 use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
 
 pub struct ExecutorFlushO;
 
 impl ExecutorFlushO {
-    pub fn execute(store: StoreItem) -> Result<u32, ()> {
+    pub fn execute(store: StoreItem) -> Result<u32, StoreOperationError> {
         if let StoreItem(collection, Some(bucket), Some(object)) = store {
             // Important: acquire database access read lock, and reference it in context. This \
             //   prevents the database from being erased while using it in this block.
             general_kv_access_lock_read!();
 
-            if let Ok(kv_store) = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection) {
-                // Important: acquire bucket store write lock
-                executor_kv_lock_write!(kv_store);
+            let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?;
 
-                let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
+            // Important: acquire bucket store write lock
+            executor_kv_lock_write!(kv_store);
 
-                // Try to resolve existing OID to IID (if it does not exist, there is nothing to \
-                //   be flushed)
-                let oid = object.as_str();
+            let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
 
-                if let Ok(iid_value) = kv_action.get_oid_to_iid(oid) {
-                    let mut count_flushed = 0;
+            // Bucket marked as immutable? (reject write)
+            kv_action.check_mutable()?;
 
-                    if let Some(iid) = iid_value {
-                        // Resolve terms associated to IID
-                        let iid_terms = {
-                            if let Ok(iid_terms_value) = kv_action.get_iid_to_terms(iid) {
-                                iid_terms_value.unwrap_or_default()
-                            } else {
-                                error!("failed getting flusho executor iid-to-terms");
+            // Bucket hash colliding with another bucket? (reject write)
+            kv_action.check_bucket()?;
 
-                                Vec::new()
-                            }
-                        };
+            // Try to resolve existing OID to IID (if it does not exist, there is nothing to \
+            //   be flushed)
+            let oid = object.as_str();
 
-                        // Flush bucket (batch operation, as it is shared w/ other executors)
-                        if let Ok(batch_count) = kv_action.batch_flush_bucket(iid, oid, &iid_terms)
-                        {
-                            count_flushed += batch_count;
+            if let Ok(iid_value) = kv_action.get_oid_to_iid(oid) {
+                let mut count_flushed = 0;
+
+                if let Some(iid) = iid_value {
+                    // Resolve terms associated to IID
+                    let iid_terms = {
+                        if let Ok(iid_terms_value) = kv_action.get_iid_to_terms(iid) {
+                            iid_terms_value.unwrap_or_default()
                         } else {
+                            error!("failed getting flusho executor iid-to-terms");
+
+                            Vec::new()
+                        }
+                    };
+
+                    // Flush bucket (batch operation, as it is shared w/ other executors)
+                    let mut batch = kv_action.batch();
+
+                    match kv_action.batch_flush_bucket(&mut batch, iid, oid, &iid_terms) {
+                        Ok(batch_count) if batch.commit().is_ok() => {
+                            count_flushed += batch_count;
+                        }
+                        _ => {
                             error!("failed executing batch-flush-bucket in flusho executor");
                         }
                     }
-
-                    return Ok(count_flushed);
-                } else {
-                    error!("failed getting flusho executor oid-to-iid");
                 }
+
+                return Ok(count_flushed);
+            } else {
+                error!("failed getting flusho executor oid-to-iid");
             }
         }
 
-        Err(())
+        Err(StoreOperationError::Failed)
     }
 }
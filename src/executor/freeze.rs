@@ -0,0 +1,202 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use crate::store::item::StoreItem;
+use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
+
+pub struct ExecutorFreeze;
+
+impl ExecutorFreeze {
+    pub fn execute(store: StoreItem, immutable: bool) -> Result<(), StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            general_kv_access_lock_read!();
+
+            // Notice: the collection store gets created if it does not exist yet, as this \
+            //   reserves the bucket (an empty bucket can be marked as immutable).
+            let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::Any, collection)?;
+
+            // Important: acquire bucket store write lock
+            executor_kv_lock_write!(kv_store);
+
+            let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
+
+            // Store bucket immutable state (see write executors, which reject writes to \
+            //   immutable buckets)
+            return kv_action
+                .set_immutable(immutable)
+                .map_err(StoreOperationError::from);
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::flushb::ExecutorFlushB;
+    use crate::executor::flushc::ExecutorFlushC;
+    use crate::executor::flusho::ExecutorFlushO;
+    use crate::executor::moveo::ExecutorMoveO;
+    use crate::executor::pop::ExecutorPop;
+    use crate::executor::push::ExecutorPush;
+    use crate::executor::retain::ExecutorRetain;
+    use crate::executor::search::ExecutorSearch;
+    use crate::executor::swap::ExecutorSwap;
+    use crate::query::actions::Query;
+    use crate::query::builder::QueryBuilder;
+    use crate::query::types::{QueryPushSuggest, QuerySearchCompletion, QuerySearchTimeout};
+    use crate::store::item::{StoreItemBuilder, StoreItemPart};
+    use crate::store::operation::StoreOperationError;
+
+    fn push(object: &str, text: &str) -> Result<(), StoreOperationError> {
+        if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+            QueryBuilder::push(
+                "c:test:40",
+                "b:test:40",
+                object,
+                text,
+                None,
+                QueryPushSuggest::Incremental,
+                None,
+                &[],
+                None,
+                false,
+            )
+        {
+            ExecutorPush::execute(store, lexer, suggest, boost, weights, snippet, verbose)
+                .map(|_| ())
+        } else {
+            panic!("push query could not be built");
+        }
+    }
+
+    fn pop(object: &str, text: &str) -> Result<u32, StoreOperationError> {
+        if let Ok(Query::Pop(store, lexer)) =
+            QueryBuilder::pop("c:test:40", "b:test:40", object, text)
+        {
+            ExecutorPop::execute(store, lexer)
+        } else {
+            panic!("pop query could not be built");
+        }
+    }
+
+    fn search(terms: &str) -> Vec<String> {
+        if let Ok(Query::Search(
+            store,
+            query_id,
+            lexer,
+            limit,
+            offset,
+            timeout,
+            typos,
+            completion,
+            snippets,
+            with_terms,
+        )) = QueryBuilder::search(
+            "id1",
+            "c:test:40",
+            "b:test:40",
+            terms,
+            10,
+            0,
+            None,
+            QuerySearchTimeout::Partial,
+            1,
+            QuerySearchCompletion::AllTerms,
+            false,
+            false,
+        ) {
+            ExecutorSearch::execute(
+                store, query_id, lexer, limit, offset, timeout, typos, completion, snippets,
+                with_terms,
+            )
+            .unwrap()
+            .unwrap_or_default()
+        } else {
+            panic!("search query could not be built");
+        }
+    }
+
+    fn freeze(immutable: bool) {
+        assert!(ExecutorFreeze::execute(
+            StoreItemBuilder::from_depth_2("c:test:40", "b:test:40").unwrap(),
+            immutable
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn it_rejects_writes_to_immutable_buckets() {
+        let bucket = || StoreItemBuilder::from_depth_2("c:test:40", "b:test:40").unwrap();
+        let object = || StoreItemBuilder::from_depth_3("c:test:40", "b:test:40", "o:1").unwrap();
+
+        freeze(false);
+
+        for bucket_name in &["b:test:40", "b:test:40:other"] {
+            assert!(ExecutorFlushB::execute(
+                StoreItemBuilder::from_depth_2("c:test:40", bucket_name).unwrap()
+            )
+            .is_ok());
+        }
+
+        assert!(push("o:1", "published dataset").is_ok());
+
+        freeze(true);
+
+        // All writes are rejected
+        assert_eq!(
+            push("o:2", "another dataset"),
+            Err(StoreOperationError::BucketImmutable)
+        );
+        assert_eq!(
+            pop("o:1", "dataset"),
+            Err(StoreOperationError::BucketImmutable)
+        );
+        assert_eq!(
+            ExecutorFlushO::execute(object()),
+            Err(StoreOperationError::BucketImmutable)
+        );
+        assert_eq!(
+            ExecutorFlushB::execute(bucket()),
+            Err(StoreOperationError::BucketImmutable)
+        );
+        assert_eq!(
+            ExecutorFlushC::execute(StoreItemBuilder::from_depth_1("c:test:40").unwrap()),
+            Err(StoreOperationError::BucketImmutable)
+        );
+        assert_eq!(
+            ExecutorMoveO::execute(
+                object(),
+                StoreItemPart::from_str("b:test:40:other").unwrap()
+            ),
+            Err(StoreOperationError::BucketImmutable)
+        );
+        assert_eq!(
+            ExecutorSwap::execute(
+                StoreItemBuilder::from_depth_2("c:test:40", "b:test:40:other").unwrap(),
+                StoreItemPart::from_str("b:test:40").unwrap()
+            ),
+            Err(StoreOperationError::BucketImmutable)
+        );
+        assert_eq!(
+            ExecutorRetain::execute(bucket(), Some(60), None),
+            Err(StoreOperationError::BucketImmutable)
+        );
+
+        // Reads are served as usual
+        assert_eq!(search("dataset"), vec!["o:1"]);
+
+        // Lifting immutability allows writes again
+        freeze(false);
+
+        assert_eq!(pop("o:1", "dataset"), Ok(1));
+        assert!(search("dataset").is_empty());
+    }
+}
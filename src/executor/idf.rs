@@ -0,0 +1,107 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use crate::store::item::StoreItem;
+use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
+
+pub struct ExecutorIDF;
+
+impl ExecutorIDF {
+    pub fn execute(store: StoreItem) -> Result<u32, StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            general_kv_access_lock_read!();
+
+            let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?;
+
+            // Important: acquire bucket store write lock
+            executor_kv_lock_write!(kv_store);
+
+            let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
+
+            // Database stalling writes? (reject write if configured to do so)
+            if kv_action.check_write_stall().is_err() {
+                return Err(StoreOperationError::StoreBusy);
+            }
+
+            // Compute and cache the IDF weight of all bucket terms (weights then get \
+            //   refreshed on consolidation, whenever the bucket changed significantly)
+            return kv_action
+                .batch_refresh_idf()
+                .map_err(StoreOperationError::from);
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::identifiers::StoreTermHash;
+    use crate::store::item::{StoreItemBuilder, StoreItemPart};
+
+    #[test]
+    fn it_caches_idf_weights() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:36").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:36").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        let (common, rare) = (StoreTermHash::from("common"), StoreTermHash::from("rare"));
+
+        // All objects hold the common term, while only one object holds the rare term
+        let push = |index| {
+            let iid = action
+                .get_or_assign_iid(&format!("o:{}", index), None)
+                .unwrap();
+
+            assert!(action.batch_link_term(common, iid, None).is_ok());
+
+            iid
+        };
+
+        for index in 0..9 {
+            push(index);
+        }
+
+        assert!(action.batch_link_term(rare, push(9), None).is_ok());
+
+        assert_eq!(
+            ExecutorIDF::execute(StoreItemBuilder::from_depth_2("c:test:36", "b:test:36").unwrap()),
+            Ok(2)
+        );
+        assert_eq!(action.get_idf_count(), Ok(Some(10)));
+
+        let (common_idf, rare_idf) = (
+            action.get_term_to_idf(common).unwrap().unwrap(),
+            action.get_term_to_idf(rare).unwrap().unwrap(),
+        );
+
+        assert!(rare_idf > common_idf);
+        assert_eq!(
+            action.get_terms_idf(&[common, rare]),
+            vec![common_idf, rare_idf]
+        );
+
+        // Cached weights get refreshed on consolidation once the bucket changed significantly
+        push(10);
+
+        StoreKVPool::consolidate(true);
+
+        assert_eq!(action.get_idf_count(), Ok(Some(10)));
+
+        push(11);
+
+        StoreKVPool::consolidate(true);
+
+        assert_eq!(action.get_idf_count(), Ok(Some(12)));
+        assert!(action.get_term_to_idf(rare).unwrap().unwrap() > rare_idf);
+    }
+}
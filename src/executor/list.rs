@@ -8,6 +8,7 @@ use crate::query::types::{QuerySearchID, QuerySearchLimit, QuerySearchOffset};
 use crate::store::fst::StoreFSTActionBuilder;
 use crate::store::fst::StoreFSTPool;
 use crate::store::item::StoreItem;
+use crate::store::operation::StoreOperationError;
 
 pub struct ExecutorList;
 
@@ -17,21 +18,21 @@ impl ExecutorList {
         _event_id: QuerySearchID,
         limit: QuerySearchLimit,
         offset: QuerySearchOffset,
-    ) -> Result<Vec<String>, ()> {
+    ) -> Result<Vec<String>, StoreOperationError> {
         if let StoreItem(collection, Some(bucket), None) = store {
             // Important: acquire graph access read lock, and reference it in context. This \
             //   prevents the graph from being erased while using it in this block.
             general_fst_access_lock_read!();
 
-            if let Ok(fst_store) = StoreFSTPool::acquire(collection, bucket) {
-                let fst_action = StoreFSTActionBuilder::access(fst_store);
+            let fst_store = StoreFSTPool::acquire(collection, bucket)?;
 
-                debug!("running list");
+            let fst_action = StoreFSTActionBuilder::access(fst_store);
 
-                return fst_action.list_words(limit as usize, offset as usize);
-            }
+            debug!("running list");
+
+            return Ok(fst_action.list_words(limit as usize, offset as usize)?);
         }
 
-        Err(())
+        Err(StoreOperationError::Failed)
     }
 }
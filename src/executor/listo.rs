@@ -0,0 +1,130 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use crate::query::types::{QuerySearchLimit, QuerySearchOffset};
+use crate::store::item::{StoreItem, StoreItemPart};
+use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
+use crate::APP_CONF;
+
+pub struct ExecutorListO;
+
+impl ExecutorListO {
+    pub fn execute(
+        store: StoreItem,
+        prefix: StoreItemPart,
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+    ) -> Result<Vec<String>, StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            general_kv_access_lock_read!();
+
+            let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?;
+
+            // Important: acquire bucket store read lock
+            executor_kv_lock_read!(kv_store);
+
+            let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
+
+            let prefix = Self::normalize_prefix(prefix.as_str(), APP_CONF.store.kv.oid_separator);
+
+            debug!("running object list with prefix: {}", prefix);
+
+            return kv_action
+                .list_oid_ordered_to_iid(&prefix, limit as usize, offset as usize)
+                .map_err(StoreOperationError::from);
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+
+    fn normalize_prefix(prefix: &str, separator: Option<char>) -> String {
+        // Make the prefix end with a single separator, if a separator is configured (this \
+        //   matches whole levels of hierarchical OIDs, eg. 'conversation' does not match \
+        //   'conversations:1')
+        if let Some(separator) = separator {
+            format!("{}{}", prefix.trim_end_matches(separator), separator)
+        } else {
+            prefix.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::item::StoreItemBuilder;
+
+    #[test]
+    fn it_normalizes_prefixes() {
+        assert_eq!(
+            ExecutorListO::normalize_prefix("conversation", Some(':')),
+            "conversation:"
+        );
+        assert_eq!(
+            ExecutorListO::normalize_prefix("conversation::", Some(':')),
+            "conversation:"
+        );
+        assert_eq!(
+            ExecutorListO::normalize_prefix("conversation:6501e83a/", Some('/')),
+            "conversation:6501e83a/"
+        );
+        assert_eq!(
+            ExecutorListO::normalize_prefix("conversation", None),
+            "conversation"
+        );
+    }
+
+    #[test]
+    fn it_lists_objects_by_prefix() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:29").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:29").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        for oid in &[
+            "conversation:2",
+            "message:1",
+            "conversation:1",
+            "conversations:1",
+            "conversation:3",
+        ] {
+            assert!(action.get_or_assign_iid(oid, None).is_ok());
+        }
+
+        // Delete an object (it should not be listed anymore)
+        let deleted_iid = action.get_oid_to_iid("conversation:3").unwrap().unwrap();
+
+        let mut batch = action.batch();
+
+        assert!(action
+            .batch_flush_bucket(&mut batch, deleted_iid, "conversation:3", &[])
+            .is_ok());
+        assert!(batch.commit().is_ok());
+
+        let list = |limit, offset| {
+            ExecutorListO::execute(
+                StoreItemBuilder::from_depth_2("c:test:29", "b:test:29").unwrap(),
+                StoreItemPart::from_str("conversation:").unwrap(),
+                limit,
+                offset,
+            )
+        };
+
+        assert_eq!(
+            list(10, 0),
+            Ok(vec![
+                "conversation:1".to_string(),
+                "conversation:2".to_string()
+            ])
+        );
+        assert_eq!(list(10, 1), Ok(vec!["conversation:2".to_string()]));
+        assert_eq!(list(1, 0), Ok(vec!["conversation:1".to_string()]));
+    }
+}
@@ -14,13 +14,28 @@ macro_rules! executor_ensure_op {
     };
 }
 
+#[macro_export]
+macro_rules! executor_require_op {
+    ($operation:expr) => {
+        if let Err(err) = $operation {
+            error!("executor operation failed: {}", err);
+
+            return Err(StoreOperationError::from(err));
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! executor_kv_lock_read {
     ($store:ident) => {
+        executor_kv_lock_read!($store, true);
+    };
+    ($store:ident, $is_locked:expr) => {
         let kv_store_reference = $store.clone();
 
         let _kv_store_lock = kv_store_reference
             .as_ref()
+            .filter(|_| $is_locked)
             .map(|inner| inner.lock.read().unwrap());
     };
 }
@@ -7,12 +7,22 @@
 #[macro_use]
 mod macros;
 
+pub mod cooccur;
 pub mod count;
 pub mod flushb;
 pub mod flushc;
 pub mod flusho;
+pub mod freeze;
+pub mod idf;
 pub mod list;
+pub mod listo;
+pub mod moveo;
 pub mod pop;
 pub mod push;
+pub mod retain;
+pub mod retention;
 pub mod search;
+pub mod snapshot;
 pub mod suggest;
+pub mod swap;
+pub mod terms;
@@ -0,0 +1,67 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use crate::store::item::{StoreItem, StoreItemPart};
+use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVAssignError, StoreKVPool};
+use crate::store::operation::StoreOperationError;
+use crate::APP_CONF;
+
+pub struct ExecutorMoveO;
+
+impl ExecutorMoveO {
+    pub fn execute<'a>(
+        store: StoreItem<'a>,
+        to_bucket: StoreItemPart<'a>,
+    ) -> Result<u32, StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), Some(object)) = store {
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            general_kv_access_lock_read!();
+
+            let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?;
+
+            // Important: acquire bucket store write lock (both buckets live in the same \
+            //   collection store, thus this locks both of them)
+            executor_kv_lock_write!(kv_store);
+
+            let (kv_action, to_kv_action) = (
+                StoreKVActionBuilder::access(bucket, kv_store.clone()),
+                StoreKVActionBuilder::access(to_bucket, kv_store),
+            );
+
+            // Any bucket marked as immutable? (reject write)
+            kv_action.check_mutable()?;
+            to_kv_action.check_mutable()?;
+
+            // Any bucket hash colliding with another bucket? (reject write)
+            kv_action.check_bucket()?;
+            to_kv_action.check_bucket()?;
+
+            // Database stalling writes? (reject write if configured to do so)
+            if kv_action.check_write_stall().is_err() {
+                return Err(StoreOperationError::StoreBusy);
+            }
+
+            // Move object (batch operation, as both buckets are written in a single batch)
+            // Notice: the object limit of the destination bucket applies there.
+            return match kv_action.batch_move_object(
+                &to_kv_action,
+                object.as_str(),
+                APP_CONF.store.kv.max_bucket_objects,
+            ) {
+                Ok(count) => Ok(count),
+                Err(StoreKVAssignError::BucketFull) => Err(StoreOperationError::BucketFull),
+                Err(StoreKVAssignError::Failed) => {
+                    error!("failed executing batch-move-object in moveo executor");
+
+                    Err(StoreOperationError::Failed)
+                }
+            };
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+}
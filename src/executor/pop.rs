@@ -12,135 +12,255 @@ use crate::store::fst::{StoreFSTActionBuilder, StoreFSTPool};
 use crate::store::identifiers::StoreTermHashed;
 use crate::store::item::StoreItem;
 use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
 
 pub struct ExecutorPop;
 
 impl ExecutorPop {
-    pub fn execute<'a>(store: StoreItem<'a>, lexer: TokenLexer<'a>) -> Result<u32, ()> {
+    pub fn execute<'a>(
+        store: StoreItem<'a>,
+        lexer: TokenLexer<'a>,
+    ) -> Result<u32, StoreOperationError> {
         if let StoreItem(collection, Some(bucket), Some(object)) = store {
             // Important: acquire database access read lock, and reference it in context. This \
             //   prevents the database from being erased while using it in this block.
             general_kv_access_lock_read!();
             general_fst_access_lock_read!();
 
-            if let (Ok(kv_store), Ok(fst_store)) = (
-                StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection),
-                StoreFSTPool::acquire(collection, bucket),
-            ) {
-                // Important: acquire bucket store write lock
-                executor_kv_lock_write!(kv_store);
-
-                let (kv_action, fst_action) = (
-                    StoreKVActionBuilder::access(bucket, kv_store),
-                    StoreFSTActionBuilder::access(fst_store),
-                );
-
-                // Try to resolve existing OID to IID (if it does not exist, there is nothing to \
-                //   be flushed)
-                let oid = object.as_str();
-
-                if let Ok(iid_value) = kv_action.get_oid_to_iid(oid) {
-                    let mut count_popped = 0;
-
-                    if let Some(iid) = iid_value {
-                        // Try to resolve existing search terms from IID, and perform an algebraic \
-                        //   AND on all popped terms to generate a list of terms to be cleaned up.
-                        if let Ok(Some(iid_terms_hashed_vec)) = kv_action.get_iid_to_terms(iid) {
-                            info!(
-                                "got pop executor stored iid-to-terms: {:?}",
-                                iid_terms_hashed_vec
-                            );
-
-                            let pop_terms: Vec<(String, StoreTermHashed)> = lexer.collect();
-
-                            let iid_terms_hashed: LinkedHashSet<StoreTermHashed> =
-                                LinkedHashSet::from_iter(iid_terms_hashed_vec.iter().copied());
-
-                            let remaining_terms: LinkedHashSet<StoreTermHashed> = iid_terms_hashed
-                                .difference(&LinkedHashSet::from_iter(
-                                    pop_terms.iter().map(|item| item.1),
-                                ))
-                                .copied()
-                                .collect();
-
-                            debug!(
-                                "got pop executor terms remaining terms: {:?} for iid: {}",
-                                remaining_terms, iid
-                            );
-
-                            count_popped = (iid_terms_hashed.len() - remaining_terms.len()) as u32;
-
-                            if count_popped > 0 {
-                                if remaining_terms.is_empty() {
-                                    info!("nuke whole bucket for pop executor");
-
-                                    // Flush bucket (batch operation, as it is shared w/ other \
-                                    //   executors)
-                                    executor_ensure_op!(kv_action.batch_flush_bucket(
-                                        iid,
-                                        oid,
-                                        &iid_terms_hashed_vec
-                                    ));
-                                } else {
-                                    info!("nuke only certain terms for pop executor");
-
-                                    // Nuke IID in Term-to-IIDs list
-                                    for (pop_term, pop_term_hashed) in &pop_terms {
-                                        // Check that term is linked to IID (and should be removed)
-                                        if iid_terms_hashed.contains(pop_term_hashed) {
-                                            if let Ok(Some(mut pop_term_iids)) =
-                                                kv_action.get_term_to_iids(*pop_term_hashed)
-                                            {
-                                                // Remove IID from list of IIDs to be popped
-                                                pop_term_iids.retain(|cur_iid| cur_iid != &iid);
-
-                                                if pop_term_iids.is_empty() {
-                                                    // IIDs list was empty, delete whole key
-                                                    executor_ensure_op!(kv_action
-                                                        .delete_term_to_iids(*pop_term_hashed));
-
-                                                    // Pop from FST graph (does not exist anymore)
-                                                    if fst_action.pop_word(pop_term) {
-                                                        debug!(
-                                                            "pop term hash nuked from graph: {}",
-                                                            pop_term_hashed
-                                                        );
-                                                    }
-                                                } else {
-                                                    // Re-build IIDs list w/o current IID
-                                                    executor_ensure_op!(kv_action
-                                                        .set_term_to_iids(
-                                                            *pop_term_hashed,
-                                                            &pop_term_iids,
-                                                        ));
+            let (kv_store, fst_store) = (
+                StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?,
+                StoreFSTPool::acquire(collection, bucket)?,
+            );
+
+            // Important: acquire bucket store write lock
+            executor_kv_lock_write!(kv_store);
+
+            let (kv_action, fst_action) = (
+                StoreKVActionBuilder::access(bucket, kv_store),
+                StoreFSTActionBuilder::access(fst_store),
+            );
+
+            // Bucket marked as immutable? (reject write)
+            kv_action.check_mutable()?;
+
+            // Bucket hash colliding with another bucket? (reject write)
+            kv_action.check_bucket()?;
+
+            // Database stalling writes? (reject write if configured to do so)
+            if kv_action.check_write_stall().is_err() {
+                return Err(StoreOperationError::StoreBusy);
+            }
+
+            // Try to resolve existing OID to IID (if it does not exist, there is nothing to \
+            //   be flushed)
+            let oid = object.as_str();
+
+            if let Ok(iid_value) = kv_action.get_oid_to_iid(oid) {
+                let mut count_popped = 0;
+
+                if let Some(iid) = iid_value {
+                    // Try to resolve existing search terms from IID, and perform an algebraic \
+                    //   AND on all popped terms to generate a list of terms to be cleaned up.
+                    if let Ok(Some(iid_terms_hashed_vec)) = kv_action.get_iid_to_terms(iid) {
+                        info!(
+                            "got pop executor stored iid-to-terms: {:?}",
+                            iid_terms_hashed_vec
+                        );
+
+                        let pop_terms: Vec<(String, StoreTermHashed)> = lexer.collect();
+
+                        let iid_terms_hashed: LinkedHashSet<StoreTermHashed> =
+                            LinkedHashSet::from_iter(iid_terms_hashed_vec.iter().copied());
+
+                        let remaining_terms: LinkedHashSet<StoreTermHashed> = iid_terms_hashed
+                            .difference(&LinkedHashSet::from_iter(
+                                pop_terms.iter().map(|item| item.1),
+                            ))
+                            .copied()
+                            .collect();
+
+                        debug!(
+                            "got pop executor terms remaining terms: {:?} for iid: {}",
+                            remaining_terms, iid
+                        );
+
+                        count_popped = (iid_terms_hashed.len() - remaining_terms.len()) as u32;
+
+                        if count_popped > 0 {
+                            let mut batch = kv_action.batch();
+
+                            if remaining_terms.is_empty() {
+                                info!("nuke whole bucket for pop executor");
+
+                                // Flush bucket (batch operation, as it is shared w/ other \
+                                //   executors)
+                                if kv_action
+                                    .batch_flush_bucket(&mut batch, iid, oid, &iid_terms_hashed_vec)
+                                    .is_err()
+                                {
+                                    error!("failed executing batch-flush-bucket in pop executor");
+
+                                    return Err(StoreOperationError::Failed);
+                                }
+                            } else {
+                                info!("nuke only certain terms for pop executor");
+
+                                let mut terms_removed = 0;
+
+                                // Nuke IID in Term-to-IIDs list
+                                for (pop_term, pop_term_hashed) in &pop_terms {
+                                    // Check that term is linked to IID (and should be removed)
+                                    if iid_terms_hashed.contains(pop_term_hashed) {
+                                        if let Ok(Some(mut pop_term_postings)) =
+                                            kv_action.get_term_to_postings(*pop_term_hashed)
+                                        {
+                                            // Remove IID from list of IIDs to be popped
+                                            pop_term_postings.remove(iid);
+
+                                            if pop_term_postings.is_empty() {
+                                                // IIDs list was empty, delete whole key
+                                                batch.delete_term_to_iids(*pop_term_hashed);
+
+                                                terms_removed += 1;
+
+                                                // Pop from FST graph (does not exist anymore)
+                                                if fst_action.pop_word(pop_term) {
+                                                    debug!(
+                                                        "pop term hash nuked from graph: {}",
+                                                        pop_term_hashed
+                                                    );
                                                 }
                                             } else {
-                                                error!(
-                                                    "failed getting term-to-iids in pop executor"
+                                                // Re-build IIDs list w/o current IID
+                                                batch.set_term_to_postings(
+                                                    *pop_term_hashed,
+                                                    &pop_term_postings,
                                                 );
                                             }
+                                        } else {
+                                            error!("failed getting term-to-iids in pop executor");
                                         }
                                     }
+                                }
+
+                                // Bump IID-to-Terms list
+                                let remaining_terms_vec: Vec<StoreTermHashed> =
+                                    Vec::from_iter(remaining_terms.into_iter());
 
-                                    // Bump IID-to-Terms list
-                                    let remaining_terms_vec: Vec<StoreTermHashed> =
-                                        Vec::from_iter(remaining_terms.into_iter());
+                                batch.set_iid_to_terms(iid, &remaining_terms_vec);
 
-                                    executor_ensure_op!(
-                                        kv_action.set_iid_to_terms(iid, &remaining_terms_vec)
-                                    );
+                                // Drop weights of popped terms (if any term was weighted)
+                                if let Ok(Some(mut iid_weights)) = kv_action.get_iid_to_weights(iid)
+                                {
+                                    iid_weights.retain(|(term_hashed, _)| {
+                                        remaining_terms_vec.contains(term_hashed)
+                                    });
+
+                                    batch.set_iid_to_weights(iid, &iid_weights);
                                 }
+
+                                // Account for terms removed from bucket in bucket term count
+                                batch.count_terms(0, terms_removed);
                             }
-                        } else {
-                            error!("failed getting iid-to-terms in pop executor");
+
+                            executor_ensure_op!(batch.commit());
                         }
+                    } else {
+                        error!("failed getting iid-to-terms in pop executor");
                     }
-
-                    return Ok(count_popped);
                 }
+
+                return Ok(count_popped);
             }
         }
 
-        Err(())
+        Err(StoreOperationError::Failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::flushb::ExecutorFlushB;
+    use crate::executor::push::ExecutorPush;
+    use crate::query::actions::Query;
+    use crate::query::builder::QueryBuilder;
+    use crate::query::types::QueryPushSuggest;
+    use crate::store::identifiers::StoreTermHash;
+    use crate::store::item::{StoreItemBuilder, StoreItemPart};
+
+    fn push(object: &str, text: &str) {
+        if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+            QueryBuilder::push(
+                "c:test:61",
+                "b:test:61",
+                object,
+                text,
+                None,
+                QueryPushSuggest::Incremental,
+                None,
+                &[],
+                None,
+                false,
+            )
+        {
+            assert!(
+                ExecutorPush::execute(store, lexer, suggest, boost, weights, snippet, verbose)
+                    .is_ok()
+            );
+        } else {
+            panic!("push query could not be built");
+        }
+    }
+
+    fn pop(object: &str, text: &str) -> Result<u32, StoreOperationError> {
+        if let Ok(Query::Pop(store, lexer)) =
+            QueryBuilder::pop("c:test:61", "b:test:61", object, text)
+        {
+            ExecutorPop::execute(store, lexer)
+        } else {
+            panic!("pop query could not be built");
+        }
+    }
+
+    #[test]
+    fn it_pops_terms_and_collects_emptied_objects() {
+        assert!(ExecutorFlushB::execute(
+            StoreItemBuilder::from_depth_2("c:test:61", "b:test:61").unwrap()
+        )
+        .is_ok());
+
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:61").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:61").unwrap(), store);
+
+        push("o:1", "falcon harbor");
+
+        let iid = action.get_oid_to_iid("o:1").unwrap().unwrap();
+
+        // Popping some terms only shrinks the object
+        assert_eq!(pop("o:1", "falcon"), Ok(1));
+        assert_eq!(pop("o:1", "falcon"), Ok(0));
+        assert_eq!(
+            action.get_term_to_iids(StoreTermHash::from("falcon")),
+            Ok(None)
+        );
+        assert_eq!(
+            action
+                .get_iid_to_terms(iid)
+                .unwrap()
+                .map(|terms| terms.len()),
+            Some(1)
+        );
+
+        // Popping the last term removes the object altogether
+        assert_eq!(pop("o:1", "harbor meadow"), Ok(1));
+        assert_eq!(action.get_oid_to_iid("o:1"), Ok(None));
+        assert_eq!(action.get_iid_to_oid(iid), Ok(None));
+        assert_eq!(action.get_iid_to_terms(iid), Ok(None));
+        assert_eq!(
+            action.get_term_to_iids(StoreTermHash::from("harbor")),
+            Ok(None)
+        );
     }
 }
@@ -4,165 +4,707 @@
 // Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
+use hashbrown::HashMap;
 use linked_hash_set::LinkedHashSet;
 use std::iter::FromIterator;
+use std::time::{Duration, Instant};
 
 use crate::lexer::token::TokenLexer;
-use crate::store::fst::{StoreFSTActionBuilder, StoreFSTPool};
-use crate::store::identifiers::{StoreMetaKey, StoreMetaValue, StoreTermHashed};
+use crate::query::types::{QueryPushBoost, QueryPushSuggest, QueryPushWeight};
+use crate::store::fst::{StoreFSTAction, StoreFSTActionBuilder, StoreFSTPool};
+use crate::store::identifiers::{StoreTermHashed, StoreTermWeight};
 use crate::store::item::StoreItem;
-use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::kv::{
+    StoreKVAcquireMode, StoreKVAction, StoreKVActionBuilder, StoreKVAssignError, StoreKVError,
+    StoreKVPending, StoreKVPool,
+};
+use crate::store::operation::StoreOperationError;
 use crate::APP_CONF;
 
 pub struct ExecutorPush;
 
-impl ExecutorPush {
-    pub fn execute<'a>(store: StoreItem<'a>, lexer: TokenLexer<'a>) -> Result<(), ()> {
-        if let StoreItem(collection, Some(bucket), Some(object)) = store {
-            // Important: acquire database access read lock, and reference it in context. This \
-            //   prevents the database from being erased while using it in this block.
-            general_kv_access_lock_read!();
-            general_fst_access_lock_read!();
-
-            if let (Ok(kv_store), Ok(fst_store)) = (
-                StoreKVPool::acquire(StoreKVAcquireMode::Any, collection),
-                StoreFSTPool::acquire(collection, bucket),
-            ) {
-                // Important: acquire bucket store write lock
-                executor_kv_lock_write!(kv_store);
+#[derive(Debug, PartialEq)]
+pub struct ExecutorPushStats {
+    pub tokens: u32,
+    pub terms_new: u32,
+    pub terms_existing: u32,
+    pub object_new: bool,
+    pub took: Duration,
+}
 
-                let (kv_action, fst_action) = (
-                    StoreKVActionBuilder::access(bucket, kv_store),
-                    StoreFSTActionBuilder::access(fst_store),
-                );
+pub type ExecutorPushRequest<'a> = (
+    StoreItem<'a>,
+    TokenLexer<'a>,
+    QueryPushSuggest,
+    Option<QueryPushBoost>,
+    Vec<(String, StoreTermHashed, QueryPushWeight)>,
+    Option<&'a str>,
+    bool,
+);
 
-                // Try to resolve existing OID to IID, otherwise initialize IID (store the \
-                //   bi-directional relationship)
-                let oid = object.as_str();
-                let iid = kv_action.get_oid_to_iid(oid).unwrap_or(None).or_else(|| {
-                    info!("must initialize push executor oid-to-iid and iid-to-oid");
-
-                    if let Ok(iid_incr) = kv_action.get_meta_to_value(StoreMetaKey::IIDIncr) {
-                        let iid_incr = if let Some(iid_incr) = iid_incr {
-                            match iid_incr {
-                                StoreMetaValue::IIDIncr(iid_incr) => iid_incr + 1,
-                            }
-                        } else {
-                            0
+type ExecutorPushResult = Result<Option<ExecutorPushStats>, StoreOperationError>;
+
+impl ExecutorPush {
+    pub fn execute<'a>(
+        store: StoreItem<'a>,
+        lexer: TokenLexer<'a>,
+        suggest: QueryPushSuggest,
+        boost: Option<QueryPushBoost>,
+        weights: Vec<(String, StoreTermHashed, QueryPushWeight)>,
+        snippet: Option<&'a str>,
+        verbose: bool,
+    ) -> ExecutorPushResult {
+        Self::execute_pipelined(vec![(
+            store, lexer, suggest, boost, weights, snippet, verbose,
+        )])
+        .and_then(|mut results| results.pop().unwrap_or(Err(StoreOperationError::Failed)))
+    }
+
+    pub fn execute_pipelined(
+        pushes: Vec<ExecutorPushRequest>,
+    ) -> Result<Vec<ExecutorPushResult>, StoreOperationError> {
+        // Push all objects, then write them to the database at once (pushes must all target \
+        //   the same collection; each push gets its own result, though a failed write fails \
+        //   all pushes, as none of them got written)
+        let collection = match pushes.first() {
+            Some((StoreItem(collection, _, _), ..)) => *collection,
+            None => return Ok(Vec::new()),
+        };
+
+        // Important: acquire database access read lock, and reference it in context. This \
+        //   prevents the database from being erased while using it in this block.
+        general_kv_access_lock_read!();
+        general_fst_access_lock_read!();
+
+        let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::Any, collection)?;
+
+        // Important: acquire bucket store write lock (until all pushes are written, as \
+        //   concurrent writes would otherwise be overwritten by pending writes)
+        executor_kv_lock_write!(kv_store);
+
+        let pending = StoreKVPending::default();
+
+        let results = pushes
+            .into_iter()
+            .map(
+                |(store, lexer, suggest, boost, weights, snippet, verbose)| match store {
+                    StoreItem(push_collection, Some(bucket), Some(object))
+                        if push_collection == collection =>
+                    {
+                        let fst_store = StoreFSTPool::acquire(collection, bucket)?;
+
+                        // Apply collection stop-words to lexed text? (if any)
+                        let lexer = match kv_store {
+                            Some(ref store) => lexer.with_stopwords(store.stopwords()),
+                            None => lexer,
                         };
 
-                        // Bump last stored increment
-                        if kv_action
-                            .set_meta_to_value(
-                                StoreMetaKey::IIDIncr,
-                                StoreMetaValue::IIDIncr(iid_incr),
-                            )
-                            .is_ok()
-                        {
-                            // Associate OID <> IID (bidirectional)
-                            executor_ensure_op!(kv_action.set_oid_to_iid(oid, iid_incr));
-                            executor_ensure_op!(kv_action.set_iid_to_oid(iid_incr, oid));
-
-                            Some(iid_incr)
+                        // Record term positions in lexed text? (if enabled for collection)
+                        let lexer = if Self::has_term_positions(collection.as_str()) {
+                            lexer.with_positions()
                         } else {
-                            error!("failed updating push executor meta-to-value iid increment");
+                            lexer
+                        };
 
-                            None
-                        }
-                    } else {
-                        error!("failed getting push executor meta-to-value iid increment");
+                        let (kv_action, fst_action) = (
+                            StoreKVActionBuilder::access(bucket, kv_store.clone()),
+                            StoreFSTActionBuilder::access(fst_store),
+                        );
 
-                        None
+                        Self::push(
+                            &kv_action.with_pending(&pending),
+                            &fst_action,
+                            object.as_str(),
+                            lexer,
+                            suggest,
+                            boost,
+                            weights,
+                            snippet,
+                            verbose,
+                        )
                     }
-                });
-
-                if let Some(iid) = iid {
-                    let mut has_commits = false;
-
-                    // Acquire list of terms for IID
-                    let mut iid_terms_hashed: LinkedHashSet<StoreTermHashed> =
-                        LinkedHashSet::from_iter(
-                            kv_action
-                                .get_iid_to_terms(iid)
-                                .unwrap_or(None)
-                                .unwrap_or_default(),
-                        );
+                    _ => Err(StoreOperationError::Failed),
+                },
+            )
+            .collect();
+
+        // Write all pushes at once? (if any push got queued)
+        if !pending.is_empty() {
+            executor_require_op!(kv_store
+                .as_ref()
+                .ok_or(StoreKVError::NotFound)
+                .and_then(|store| store.commit_pending(pending)));
+        }
 
-                    info!(
-                        "got push executor stored iid-to-terms: {:?}",
-                        iid_terms_hashed
-                    );
+        Ok(results)
+    }
 
-                    for (term, term_hashed) in lexer {
-                        // Check that term is not already linked to IID
-                        if !iid_terms_hashed.contains(&term_hashed) {
-                            if let Ok(term_iids) = kv_action.get_term_to_iids(term_hashed) {
-                                has_commits = true;
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        kv_action: &StoreKVAction,
+        fst_action: &StoreFSTAction,
+        oid: &str,
+        mut lexer: TokenLexer,
+        suggest: QueryPushSuggest,
+        boost: Option<QueryPushBoost>,
+        weights: Vec<(String, StoreTermHashed, QueryPushWeight)>,
+        snippet: Option<&str>,
+        verbose: bool,
+    ) -> ExecutorPushResult {
+        let push_start = Instant::now();
+
+        // Bucket marked as immutable? (reject write)
+        kv_action.check_mutable()?;
+
+        // Bucket hash colliding with another bucket? (reject write)
+        kv_action.check_bucket()?;
+
+        // Database stalling writes? (reject write if configured to do so)
+        if kv_action.check_write_stall().is_err() {
+            return Err(StoreOperationError::StoreBusy);
+        }
 
-                                // Add IID in first position in list for terms
-                                let mut term_iids = term_iids.unwrap_or_default();
+        // Check whether OID is already known? (only if push stats are to be returned, as this \
+        //   costs an extra read)
+        let object_new = verbose && kv_action.get_oid_to_iid(oid).unwrap_or(None).is_none();
 
-                                // Remove IID from list of IIDs to be popped before inserting in \
-                                //   first position?
-                                if term_iids.contains(&iid) {
-                                    term_iids.retain(|cur_iid| cur_iid != &iid);
-                                }
+        // Queue all object writes to a single batch (starting with the IID assignment)
+        let mut batch = kv_action.batch();
 
-                                info!("has push executor term-to-iids: {}", iid);
+        // Try to resolve existing OID to IID, otherwise initialize IID (the bucket object \
+        //   limit applies to new objects only)
+        let iid = match kv_action.assign_iid(&mut batch, oid, APP_CONF.store.kv.max_bucket_objects)
+        {
+            Ok(iid) => Some(iid),
+            Err(StoreKVAssignError::BucketFull) => {
+                return Err(StoreOperationError::BucketFull);
+            }
+            Err(StoreKVAssignError::Failed) => {
+                error!("failed getting push executor iid");
 
-                                term_iids.insert(0, iid);
+                None
+            }
+        };
 
-                                // Truncate IIDs linked to term? (ie. storage is too long)
-                                let truncate_limit = APP_CONF.store.kv.retain_word_objects;
+        if let Some(iid) = iid {
+            let mut has_commits = false;
+            let (mut tokens, mut terms_new, mut terms_existing, mut terms_added) = (0, 0, 0, 0);
 
-                                if term_iids.len() > truncate_limit {
-                                    info!(
-                                        "push executor term-to-iids object too long (limit: {})",
-                                        truncate_limit
-                                    );
+            // Update object push time (used by bucket retention policies)
+            batch.set_iid_to_time(iid, StoreKVAction::time_now());
 
-                                    // Drain overflowing IIDs (ie. oldest ones that overflow)
-                                    let term_iids_drain = term_iids.drain(truncate_limit..);
+            // Update object boost? (if any boost given; it is kept as-is otherwise)
+            if let Some(boost) = boost {
+                batch.set_iid_to_boost(iid, boost);
+            }
 
-                                    executor_ensure_op!(kv_action
-                                        .batch_truncate_object(term_hashed, term_iids_drain));
-                                }
+            // Update object snippet? (if any snippet given and snippets are enabled; it \
+            //   is kept as-is otherwise)
+            let snippet_length_maximum = APP_CONF.store.kv.snippet_length_maximum;
 
-                                executor_ensure_op!(
-                                    kv_action.set_term_to_iids(term_hashed, &term_iids)
-                                );
+            if let Some(snippet) = snippet.filter(|_| snippet_length_maximum > 0) {
+                batch.set_iid_to_snippet(
+                    iid,
+                    Self::truncate_snippet(snippet, snippet_length_maximum),
+                );
+            }
 
-                                // Insert term into IID to terms map
-                                iid_terms_hashed.insert(term_hashed);
-                            } else {
-                                error!("failed getting push executor term-to-iids");
-                            }
-                        }
+            // Update object locale? (if detected or hinted; it is kept as-is otherwise)
+            if let Some(locale) = lexer.locale() {
+                batch.set_iid_to_lang(iid, locale);
+            }
 
-                        // Push to FST graph? (this consumes the term; to avoid sub-clones)
-                        if fst_action.push_word(&term) {
-                            debug!("push term committed to graph: {}", term);
+            // Acquire list of terms for IID
+            let mut iid_terms_hashed: LinkedHashSet<StoreTermHashed> = LinkedHashSet::from_iter(
+                kv_action
+                    .get_iid_to_terms(iid)
+                    .unwrap_or(None)
+                    .unwrap_or_default(),
+            );
+
+            info!(
+                "got push executor stored iid-to-terms: {:?}",
+                iid_terms_hashed
+            );
+
+            // Weighted terms get linked to IID as well (as if they were part of text)
+            let mut weights_hashed: Vec<(StoreTermHashed, StoreTermWeight)> =
+                Vec::with_capacity(weights.len());
+
+            let weighted_terms = weights.into_iter().map(|(term, term_hashed, weight)| {
+                weights_hashed.push((term_hashed, weight));
+
+                (term, term_hashed)
+            });
+
+            // Notice: lexed terms are collected first, as the positions of a term are \
+            //   only all known once the whole text is lexed.
+            let lexed_terms: Vec<(String, StoreTermHashed)> = lexer.by_ref().collect();
+            let mut terms_positions = lexer.take_positions();
+
+            for (term, term_hashed) in lexed_terms.into_iter().chain(weighted_terms) {
+                tokens += 1;
+
+                // Check that term is not already linked to IID
+                if !iid_terms_hashed.contains(&term_hashed) {
+                    // Weighted terms that are not part of text get no positions
+                    let term_positions = terms_positions
+                        .as_mut()
+                        .map(|positions| positions.remove(&term_hashed).unwrap_or_default());
+
+                    // Link IID to term
+                    if let Ok(is_added) =
+                        kv_action.link_term(&mut batch, term_hashed, iid, term_positions)
+                    {
+                        has_commits = true;
+                        terms_new += 1;
+
+                        if is_added {
+                            terms_added += 1;
                         }
+
+                        // Insert term into IID to terms map
+                        iid_terms_hashed.insert(term_hashed);
+                    } else {
+                        error!("failed linking push executor term-to-iids");
                     }
+                } else {
+                    terms_existing += 1;
+                }
 
-                    // Commit updated list of terms for IID? (if any commit made)
-                    if has_commits {
-                        let collected_iids: Vec<StoreTermHashed> =
-                            iid_terms_hashed.into_iter().collect();
+                // Push to FST graph? (this consumes the term; to avoid sub-clones)
+                if fst_action.push_word(&term) {
+                    debug!("push term committed to graph: {}", term);
+                }
+            }
 
-                        info!(
-                            "has push executor iid-to-terms commits: {:?}",
-                            collected_iids
-                        );
+            // Hold FST graph consolidation back? (bulk load in progress, or ending)
+            match suggest {
+                QueryPushSuggest::Incremental => {}
+                QueryPushSuggest::Deferred => fst_action.hold_consolidate(),
+                QueryPushSuggest::Flush => fst_action.flush_consolidate(),
+            }
 
-                        executor_ensure_op!(kv_action.set_iid_to_terms(iid, &collected_iids));
-                    }
+            // Commit updated list of terms for IID? (if any commit made)
+            if has_commits {
+                let collected_iids: Vec<StoreTermHashed> = iid_terms_hashed.into_iter().collect();
+
+                info!(
+                    "has push executor iid-to-terms commits: {:?}",
+                    collected_iids
+                );
+
+                batch.set_iid_to_terms(iid, &collected_iids);
+            }
+
+            // Account for terms new to bucket in bucket term count
+            batch.count_terms(terms_added, 0);
+
+            // Commit updated term weights for IID? (new weights replace existing \
+            //   weights for the same terms, other existing weights are kept as-is)
+            if !weights_hashed.is_empty() {
+                let mut iid_weights: HashMap<StoreTermHashed, StoreTermWeight> = kv_action
+                    .get_iid_to_weights(iid)
+                    .unwrap_or(None)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect();
+
+                iid_weights.extend(weights_hashed);
+
+                let collected_weights: Vec<(StoreTermHashed, StoreTermWeight)> =
+                    iid_weights.into_iter().collect();
+
+                info!(
+                    "has push executor iid-to-weights commits: {:?}",
+                    collected_weights
+                );
+
+                batch.set_iid_to_weights(iid, &collected_weights);
+            }
+
+            executor_require_op!(batch.commit());
+
+            return Ok(if verbose {
+                Some(ExecutorPushStats {
+                    tokens,
+                    terms_new,
+                    terms_existing,
+                    object_new,
+                    took: push_start.elapsed(),
+                })
+            } else {
+                None
+            });
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+
+    fn has_term_positions(collection: &str) -> bool {
+        APP_CONF
+            .store
+            .kv
+            .term_positions_collections
+            .get(collection)
+            .copied()
+            .unwrap_or(APP_CONF.store.kv.term_positions)
+    }
+
+    fn truncate_snippet(snippet: &str, length_maximum: usize) -> &str {
+        // Truncate snippet to its maximum length in bytes, on a character boundary (as not to \
+        //   split a multi-byte character)
+        if snippet.len() <= length_maximum {
+            return snippet;
+        }
+
+        let mut length = length_maximum;
+
+        while !snippet.is_char_boundary(length) {
+            length -= 1;
+        }
+
+        &snippet[..length]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::flushb::ExecutorFlushB;
+    use crate::executor::pop::ExecutorPop;
+    use crate::query::actions::Query;
+    use crate::query::builder::QueryBuilder;
+    use crate::query::types::QueryGenericLang;
+    use crate::store::identifiers::StoreTermHash;
+    use crate::store::item::{StoreItemBuilder, StoreItemPart};
+    use whatlang::Lang;
+
+    fn push(bucket: &str, object: &str, text: &str) {
+        if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+            QueryBuilder::push(
+                "c:test:59",
+                bucket,
+                object,
+                text,
+                None,
+                QueryPushSuggest::Incremental,
+                None,
+                &[],
+                None,
+                false,
+            )
+        {
+            assert!(
+                ExecutorPush::execute(store, lexer, suggest, boost, weights, snippet, verbose)
+                    .is_ok()
+            );
+        } else {
+            panic!("push query could not be built");
+        }
+    }
+
+    #[test]
+    fn it_commits_object_writes_with_term_counts() {
+        assert!(ExecutorFlushB::execute(
+            StoreItemBuilder::from_depth_2("c:test:59", "b:test:59").unwrap()
+        )
+        .is_ok());
+
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:59").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:59").unwrap(), store);
+
+        push("b:test:59", "o:1", "falcon harbor lantern");
+
+        let iid = action.get_oid_to_iid("o:1").unwrap().unwrap();
+
+        assert_eq!(
+            action
+                .get_iid_to_terms(iid)
+                .unwrap()
+                .map(|terms| terms.len()),
+            Some(3)
+        );
+        assert!(action.get_iid_to_time(iid).unwrap().is_some());
+        assert_eq!(action.get_term_count(), Ok(3));
+
+        // Only terms new to the bucket are counted
+        push("b:test:59", "o:2", "falcon meadow");
+
+        assert_eq!(action.get_term_count(), Ok(4));
+
+        // Terms no longer linked to any object are uncounted
+        if let Ok(Query::Pop(store, lexer)) =
+            QueryBuilder::pop("c:test:59", "b:test:59", "o:1", "lantern falcon")
+        {
+            assert_eq!(ExecutorPop::execute(store, lexer), Ok(2));
+        } else {
+            panic!("pop query could not be built");
+        }
+
+        assert_eq!(
+            action
+                .get_iid_to_terms(iid)
+                .unwrap()
+                .map(|terms| terms.len()),
+            Some(1)
+        );
+        assert_eq!(action.get_term_count(), Ok(3));
+    }
 
-                    return Ok(());
+    #[test]
+    fn it_merges_terms_of_repeated_pushes() {
+        assert!(ExecutorFlushB::execute(
+            StoreItemBuilder::from_depth_2("c:test:59", "b:test:59:repeat").unwrap()
+        )
+        .is_ok());
+
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:59").unwrap();
+        let action = StoreKVActionBuilder::access(
+            StoreItemPart::from_str("b:test:59:repeat").unwrap(),
+            store,
+        );
+
+        push("b:test:59:repeat", "o:1", "falcon harbor");
+
+        let iid = action.get_oid_to_iid("o:1").unwrap().unwrap();
+
+        // Pushing the same object again reuses its IID, and keeps its existing terms
+        push("b:test:59:repeat", "o:1", "harbor meadow");
+        push("b:test:59:repeat", "o:1", "harbor meadow");
+
+        assert_eq!(action.get_oid_to_iid("o:1"), Ok(Some(iid)));
+        assert_eq!(
+            action
+                .get_iid_to_terms(iid)
+                .unwrap()
+                .map(|terms| terms.len()),
+            Some(3)
+        );
+        assert_eq!(action.get_term_count(), Ok(3));
+
+        for term in &["falcon", "harbor", "meadow"] {
+            assert_eq!(
+                action.get_term_to_iids(StoreTermHash::from(term)),
+                Ok(Some(vec![iid]))
+            );
+        }
+    }
+
+    #[test]
+    fn it_pushes_pipelined_objects_at_once() {
+        assert!(ExecutorFlushB::execute(
+            StoreItemBuilder::from_depth_2("c:test:59", "b:test:59:pipelined").unwrap()
+        )
+        .is_ok());
+
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:59").unwrap();
+        let action = StoreKVActionBuilder::access(
+            StoreItemPart::from_str("b:test:59:pipelined").unwrap(),
+            store,
+        );
+
+        let pushes = [
+            ("o:1", "falcon harbor"),
+            ("o:2", "falcon meadow"),
+            ("o:1", "lantern"),
+        ]
+        .iter()
+        .map(|(object, text)| {
+            match QueryBuilder::push(
+                "c:test:59",
+                "b:test:59:pipelined",
+                object,
+                text,
+                None,
+                QueryPushSuggest::Incremental,
+                None,
+                &[],
+                None,
+                false,
+            ) {
+                Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) => {
+                    (store, lexer, suggest, boost, weights, snippet, verbose)
                 }
+                _ => panic!("push query could not be built"),
             }
+        })
+        .collect();
+
+        let results = ExecutorPush::execute_pipelined(pushes).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|result| result.is_ok()));
+
+        // Later pushes see the writes of earlier pushes (IIDs, terms and counts)
+        let (iid_1, iid_2) = (
+            action.get_oid_to_iid("o:1").unwrap().unwrap(),
+            action.get_oid_to_iid("o:2").unwrap().unwrap(),
+        );
+
+        assert_ne!(iid_1, iid_2);
+        assert_eq!(
+            action
+                .get_iid_to_terms(iid_1)
+                .unwrap()
+                .map(|terms| terms.len()),
+            Some(3)
+        );
+        assert_eq!(action.get_term_count(), Ok(4));
+        assert_eq!(
+            action.get_term_to_iids(StoreTermHash::from("falcon")),
+            Ok(Some(vec![iid_2, iid_1]))
+        );
+    }
+
+    #[test]
+    fn it_truncates_snippets() {
+        assert_eq!(
+            ExecutorPush::truncate_snippet("A short preview", 64),
+            "A short preview"
+        );
+        assert_eq!(
+            ExecutorPush::truncate_snippet("A short preview", 7),
+            "A short"
+        );
+        assert_eq!(ExecutorPush::truncate_snippet("Café", 4), "Caf");
+    }
+
+    #[test]
+    fn it_stores_object_locales() {
+        assert!(ExecutorFlushB::execute(
+            StoreItemBuilder::from_depth_2("c:test:59", "b:test:59:lang").unwrap()
+        )
+        .is_ok());
+
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:59").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:59:lang").unwrap(), store);
+
+        let push = |object, text, lang| {
+            if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+                QueryBuilder::push(
+                    "c:test:59",
+                    "b:test:59:lang",
+                    object,
+                    text,
+                    lang,
+                    QueryPushSuggest::Incremental,
+                    None,
+                    &[],
+                    None,
+                    false,
+                )
+            {
+                assert!(ExecutorPush::execute(
+                    store, lexer, suggest, boost, weights, snippet, verbose
+                )
+                .is_ok());
+            } else {
+                panic!("push query could not be built");
+            }
+
+            action.get_iid_to_lang(action.get_oid_to_iid(object).unwrap().unwrap())
+        };
+
+        assert_eq!(
+            push(
+                "o:1",
+                "The quick brown fox jumps over the lazy dog, then runs away",
+                None
+            ),
+            Ok(Some(Lang::Eng))
+        );
+        assert_eq!(
+            push("o:2", "zebra", Some(QueryGenericLang::Enabled(Lang::Fra))),
+            Ok(Some(Lang::Fra))
+        );
+
+        // Locale is kept as-is when none is detected
+        assert_eq!(
+            push("o:2", "zebra", Some(QueryGenericLang::Disabled)),
+            Ok(Some(Lang::Fra))
+        );
+    }
+}
+
+#[cfg(all(feature = "benchmark", test))]
+mod benches {
+    extern crate test;
+
+    use super::*;
+    use crate::query::actions::Query;
+    use crate::query::builder::QueryBuilder;
+    use crate::query::types::QueryPushSuggest;
+    use test::Bencher;
+
+    const CORPUS_WORDS: &[&str] = &[
+        "lighthouse",
+        "harbor",
+        "keeper",
+        "night",
+        "storm",
+        "ship",
+        "anchor",
+        "coast",
+        "signal",
+        "wave",
+        "tide",
+        "beacon",
+        "sailor",
+        "fog",
+        "rock",
+        "island",
+    ];
+
+    fn corpus_text(index: usize) -> String {
+        // Generate a synthetic message, made of words picked from the corpus
+        (0..12)
+            .map(|word| CORPUS_WORDS[(index * 7 + word * 3) % CORPUS_WORDS.len()])
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    fn push(collection: &str, object: &str, text: &str) {
+        if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+            QueryBuilder::push(
+                collection,
+                "b:bench:1",
+                object,
+                text,
+                None,
+                QueryPushSuggest::Incremental,
+                None,
+                &[],
+                None,
+                false,
+            )
+        {
+            ExecutorPush::execute(store, lexer, suggest, boost, weights, snippet, verbose).ok();
         }
+    }
+
+    #[bench]
+    fn bench_push_new_objects(b: &mut Bencher) {
+        let mut index = 0;
+
+        // Notice: a new object is pushed on each iteration, thus the bucket grows.
+        b.iter(|| {
+            index += 1;
+
+            push(
+                "c:bench:push:1",
+                &format!("o:{}", index),
+                &corpus_text(index),
+            );
+        });
+    }
+
+    #[bench]
+    fn bench_push_existing_object(b: &mut Bencher) {
+        let text = corpus_text(0);
 
-        Err(())
+        b.iter(|| push("c:bench:push:2", "o:1", &text));
     }
 }
@@ -0,0 +1,51 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use crate::query::types::{QueryRetainAge, QueryRetainObjects};
+use crate::store::identifiers::StoreRetention;
+use crate::store::item::StoreItem;
+use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
+
+pub struct ExecutorRetain;
+
+impl ExecutorRetain {
+    pub fn execute(
+        store: StoreItem,
+        max_age: Option<QueryRetainAge>,
+        max_objects: Option<QueryRetainObjects>,
+    ) -> Result<(), StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            general_kv_access_lock_read!();
+
+            let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::Any, collection)?;
+
+            // Important: acquire bucket store write lock
+            executor_kv_lock_write!(kv_store);
+
+            let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
+
+            // Bucket marked as immutable? (reject write)
+            kv_action.check_mutable()?;
+
+            // Bucket hash colliding with another bucket? (reject write)
+            kv_action.check_bucket()?;
+
+            // Store bucket retention policy (see the database retention task, which enforces \
+            //   it; an empty policy removes the policy)
+            return kv_action
+                .set_retention(Some(StoreRetention {
+                    max_age,
+                    max_objects,
+                }))
+                .map_err(StoreOperationError::from);
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+}
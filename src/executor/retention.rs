@@ -0,0 +1,42 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use crate::store::identifiers::StoreRetention;
+use crate::store::item::StoreItem;
+use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
+
+pub struct ExecutorRetention;
+
+impl ExecutorRetention {
+    pub fn execute(store: StoreItem) -> Result<StoreRetention, StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            general_kv_access_lock_read!();
+
+            let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?;
+
+            // Important: acquire bucket store read lock
+            executor_kv_lock_read!(kv_store);
+
+            let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
+
+            // Read bucket retention policy (buckets without a policy retain all objects)
+            return kv_action
+                .get_retention()
+                .map(|retention| {
+                    retention.unwrap_or(StoreRetention {
+                        max_age: None,
+                        max_objects: None,
+                    })
+                })
+                .map_err(StoreOperationError::from);
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+}
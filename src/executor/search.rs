@@ -4,187 +4,2386 @@
 // Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
+use hashbrown::{HashMap, HashSet};
 use linked_hash_set::LinkedHashSet;
+use std::cmp::Ordering;
 use std::iter::FromIterator;
+use std::time::{Duration, Instant};
 
-use crate::lexer::token::TokenLexer;
-use crate::query::types::{QuerySearchID, QuerySearchLimit, QuerySearchOffset};
-use crate::store::fst::{StoreFSTActionBuilder, StoreFSTPool};
-use crate::store::identifiers::{StoreObjectIID, StoreTermHash};
+use crate::lexer::token::{TokenLexer, TokenLexerBuilder, TokenLexerMode};
+use crate::query::actions::Query;
+use crate::query::expression::QueryExpression;
+use crate::query::ranker::{Ranker, RankerBuilder, RankerDocument, RankerMatch, RankerTerm};
+use crate::query::types::{
+    QuerySearchCompletion, QuerySearchID, QuerySearchLimit, QuerySearchOffset, QuerySearchTimeout,
+    QuerySearchTypos,
+};
+use crate::store::fst::{StoreFSTAction, StoreFSTActionBuilder, StoreFSTPool};
+use crate::store::identifiers::{
+    StoreObjectBoost, StoreObjectIID, StoreTermHash, StoreTermHashed, StoreTermPosition,
+    STORE_OBJECT_BOOST_DEFAULT, STORE_TERM_WEIGHT_DEFAULT,
+};
 use crate::store::item::StoreItem;
-use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::kv::{
+    StoreKVAcquireMode, StoreKVAction, StoreKVActionBuilder, StoreKVPool, StoreKVSnapshot,
+    StoreKVTermPostings,
+};
+use crate::store::operation::StoreOperationError;
 use crate::APP_CONF;
 
 pub struct ExecutorSearch;
 
+const RESULTS_PARTIAL_MARKER: &str = "[partial]";
+
 impl ExecutorSearch {
+    #[allow(clippy::too_many_arguments)]
     pub fn execute<'a>(
+        store: StoreItem<'a>,
+        event_id: QuerySearchID,
+        lexer: TokenLexer<'a>,
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+        timeout: QuerySearchTimeout,
+        typos: QuerySearchTypos,
+        completion: QuerySearchCompletion,
+        snippets: bool,
+        with_terms: bool,
+    ) -> Result<Option<Vec<String>>, StoreOperationError> {
+        Self::execute_within(
+            store, event_id, lexer, limit, offset, timeout, typos, completion, snippets, with_terms,
+        )
+        .map(|(results, is_partial)| Self::mark_partial(results, is_partial))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn execute_within<'a>(
         store: StoreItem<'a>,
         _event_id: QuerySearchID,
         lexer: TokenLexer<'a>,
         limit: QuerySearchLimit,
         offset: QuerySearchOffset,
-    ) -> Result<Option<Vec<String>>, ()> {
+        timeout: QuerySearchTimeout,
+        typos: QuerySearchTypos,
+        completion: QuerySearchCompletion,
+        snippets: bool,
+        with_terms: bool,
+    ) -> Result<(Option<Vec<String>>, bool), StoreOperationError> {
+        // Run the query within its time budget (returns whether the query stopped before all \
+        //   its terms were read, along with its results)
         if let StoreItem(collection, Some(bucket), None) = store {
             // Important: acquire database access read lock, and reference it in context. This \
             //   prevents the database from being erased while using it in this block.
             general_kv_access_lock_read!();
             general_fst_access_lock_read!();
 
-            if let (Ok(kv_store), Ok(fst_store)) = (
-                StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection),
-                StoreFSTPool::acquire(collection, bucket),
-            ) {
-                // Important: acquire bucket store read lock
-                executor_kv_lock_read!(kv_store);
+            let (kv_store, fst_store) = (
+                StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?,
+                StoreFSTPool::acquire(collection, bucket)?,
+            );
 
-                let (kv_action, fst_action) = (
-                    StoreKVActionBuilder::access(bucket, kv_store),
-                    StoreFSTActionBuilder::access(fst_store),
-                );
+            // Important: acquire bucket store read lock, unless reading from a snapshot (the \
+            //   lock holds writes back until the query is done, thus all posting lists are \
+            //   read in the same state)
+            executor_kv_lock_read!(kv_store, !APP_CONF.channel.search.query_snapshot);
 
-                // Try to resolve existing search terms to IIDs, and perform an algebraic AND on \
-                //   all resulting IIDs for each given term.
-                let mut found_iids: LinkedHashSet<StoreObjectIID> = LinkedHashSet::new();
-
-                'lexing: for (term, term_hashed) in lexer {
-                    let mut iids = LinkedHashSet::from_iter(
-                        kv_action
-                            .get_term_to_iids(term_hashed)
-                            .unwrap_or(None)
-                            .unwrap_or_default()
-                            .into_iter(),
-                    );
-
-                    // No IIDs? Try to complete with a suggested alternate word
-                    // Notice: this may sound dirty to try generating as many results as the \
-                    //   'retain_word_objects' value, but as we do not know if another lexed word \
-                    //   comes next we need to exhaust all search space as to intersect it with \
-                    //   the (likely) upcoming word.
-                    let (higher_limit, alternates_try) = (
-                        APP_CONF.store.kv.retain_word_objects,
-                        APP_CONF.channel.search.query_alternates_try,
-                    );
-
-                    if iids.len() < higher_limit && alternates_try > 0 {
-                        debug!(
-                            "not enough iids were found ({}/{}), completing for term: {}",
-                            iids.len(),
-                            higher_limit,
-                            term
-                        );
+            // Apply collection stop-words to lexed text? (if any)
+            let lexer = match kv_store {
+                Some(ref store) => lexer.with_stopwords(store.stopwords()),
+                None => lexer,
+            };
 
-                        // Suggest N words, in case the first one is found in FST as an exact \
-                        //   match of term, we can pick next ones to complete search even further.
-                        // Notice: we add '1' to the 'alternates_try' number as to account for \
-                        //   exact match suggestion that comes as first result and is to be ignored.
-                        if let Some(suggested_words) =
-                            fst_action.suggest_words(&term, alternates_try + 1, Some(1))
-                        {
-                            let mut iids_new_len = iids.len();
-
-                            // This loop will be broken early if we get enough results at some \
-                            //   iteration
-                            'suggestions: for suggested_word in suggested_words {
-                                // Do not load base results twice for same term as base term
-                                if suggested_word == term {
-                                    continue 'suggestions;
-                                }
+            let (kv_action, fst_action) = (
+                StoreKVActionBuilder::access(bucket, kv_store),
+                StoreFSTActionBuilder::access(fst_store),
+            );
 
-                                debug!("got completed word: {} for term: {}", suggested_word, term);
-
-                                if let Some(suggested_iids) = kv_action
-                                    .get_term_to_iids(StoreTermHash::from(&suggested_word))
-                                    .unwrap_or(None)
-                                {
-                                    for suggested_iid in suggested_iids {
-                                        // Do not append the same IID twice (can happen a lot \
-                                        //   when completing from suggested results that point \
-                                        //   to the same end-OID)
-                                        if !iids.contains(&suggested_iid) {
-                                            iids.insert(suggested_iid);
-
-                                            iids_new_len += 1;
-
-                                            // Higher limit now reached? Stop acquiring new \
-                                            //   suggested IIDs now.
-                                            if iids_new_len >= higher_limit {
-                                                debug!(
-                                                    "got enough completed results for term: {}",
-                                                    term
-                                                );
-
-                                                break 'suggestions;
-                                            }
-                                        }
-                                    }
-                                }
+            let kv_snapshot = Self::snapshot(&kv_action);
+            let kv_action = kv_action.with_snapshot(kv_snapshot.as_ref());
+
+            // Bucket hash colliding with another bucket? (reject query)
+            kv_action.check_bucket()?;
+
+            // Notice: raw terms are looked up as-is, thus they are not completed with \
+            //   alternate words, and are not ignored if rare.
+            let is_raw = lexer.is_raw();
+
+            let terms: Vec<(String, StoreTermHashed)> = lexer.collect();
+
+            let terms_hashed: Vec<StoreTermHashed> =
+                terms.iter().map(|(_, term_hashed)| *term_hashed).collect();
+
+            // Keep last query term, as it may be the only one to complete (before terms get \
+            //   ordered by size)
+            let last_term_hashed = terms_hashed.last().copied();
+
+            // Keep query terms, to list the terms matched by each found object? (if requested)
+            let query_terms = if with_terms {
+                Some(terms.clone())
+            } else {
+                None
+            };
+
+            // Important: start the time budget before any term gets read, as the budget is \
+            //   shared by all term reads (including prefetched reads) and intersections.
+            let deadline = Self::time_budget().map(|budget| Instant::now() + budget);
+
+            // Prefetch IIDs for all terms at once? (multi-term queries only)
+            let prefetched_iids = if APP_CONF.channel.search.query_prefetch && terms.len() > 1 {
+                kv_action.get_terms_to_iids(&terms_hashed).ok()
+            } else {
+                None
+            };
+
+            // Time budget spent on prefetched reads? (fail right away; a partial query still \
+            //   intersects its first term, and stops there)
+            if timeout == QuerySearchTimeout::Error && Self::is_time_budget_spent(deadline) {
+                info!("failing search executor as time budget is spent on prefetched terms");
+
+                return Err(StoreOperationError::Timeout);
+            }
+
+            // Intersect terms from the smallest to the largest IID list? (multi-term \
+            //   queries only; sizes are taken from prefetched lists if any, or else from \
+            //   stored term sizes)
+            let (terms, prefetched_iids) =
+                if APP_CONF.channel.search.query_order_terms && terms.len() > 1 {
+                    let terms_size: Vec<usize> = match prefetched_iids {
+                        Some(ref prefetched_iids) => prefetched_iids
+                            .iter()
+                            .map(|iids| iids.as_ref().map(Vec::len).unwrap_or(0))
+                            .collect(),
+                        None => kv_action.get_terms_size(&terms_hashed),
+                    };
+
+                    Self::order_terms(terms, prefetched_iids, &terms_size)
+                } else {
+                    (terms, prefetched_iids)
+                };
+
+            // Ignore rare terms as noise? (if a minimum term frequency is configured)
+            let frequency_minimum = if is_raw {
+                0
+            } else {
+                Self::term_frequency_minimum(collection.as_str())
+            };
+
+            let (found_iids, read_count, is_partial) = Self::intersect_terms(
+                &kv_action,
+                if is_raw { None } else { Some(&fst_action) },
+                typos,
+                completion,
+                frequency_minimum,
+                last_term_hashed,
+                terms,
+                prefetched_iids,
+                deadline,
+                timeout,
+            )?;
+
+            debug!("search executor read {} iids in total", read_count);
+
+            return Ok((
+                Self::resolve_oids(
+                    &kv_action,
+                    Self::ranker(collection.as_str()),
+                    &found_iids,
+                    &terms_hashed,
+                    limit,
+                    offset,
+                    snippets,
+                    query_terms.as_deref(),
+                ),
+                is_partial,
+            ));
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+
+    pub fn execute_boolean<'a>(
+        store: StoreItem<'a>,
+        _event_id: QuerySearchID,
+        expression: QueryExpression<'a>,
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+        snippets: bool,
+        with_terms: bool,
+    ) -> Result<Option<Vec<String>>, StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            general_kv_access_lock_read!();
+            general_fst_access_lock_read!();
+
+            let (kv_store, fst_store) = (
+                StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?,
+                StoreFSTPool::acquire(collection, bucket)?,
+            );
+
+            // Important: acquire bucket store read lock, unless reading from a snapshot (the \
+            //   lock holds writes back until the query is done, thus all posting lists are \
+            //   read in the same state)
+            executor_kv_lock_read!(kv_store, !APP_CONF.channel.search.query_snapshot);
+
+            let (kv_action, fst_action) = (
+                StoreKVActionBuilder::access(bucket, kv_store),
+                StoreFSTActionBuilder::access(fst_store),
+            );
+
+            let kv_snapshot = Self::snapshot(&kv_action);
+            let kv_action = kv_action.with_snapshot(kv_snapshot.as_ref());
+
+            // Bucket hash colliding with another bucket? (reject query)
+            kv_action.check_bucket()?;
+
+            // Resolve each expression term to its IIDs, then let the expression tree combine \
+            //   them with unions, intersections and differences.
+            // Notice: terms are normalized, but stopwords are kept, as they were explicitly \
+            //   requested in the expression.
+            let mut terms: Vec<(String, StoreTermHashed)> = Vec::new();
+
+            let found_iids = expression.evaluate(&mut |expression_term| {
+                let mut term_iids: Option<LinkedHashSet<StoreObjectIID>> = None;
+
+                if let Ok(lexer) =
+                    TokenLexerBuilder::from(TokenLexerMode::NormalizeOnly, expression_term)
+                {
+                    // A term may still be split into multiple words by the lexer (eg. in \
+                    //   languages that do not use spaces as word separators)
+                    for (term, term_hashed) in lexer {
+                        let iids = Self::resolve_stored_term(
+                            &kv_action,
+                            Some(&fst_action),
+                            APP_CONF.channel.search.query_typo_tolerance,
+                            &term,
+                            term_hashed,
+                        );
+
+                        term_iids = Some(match term_iids {
+                            Some(previous_iids) => {
+                                previous_iids.intersection(&iids).copied().collect()
                             }
+                            None => iids,
+                        });
 
-                            debug!(
-                                "done completing results for term: {}, now {} results",
-                                term, iids_new_len
-                            );
-                        } else {
-                            debug!("did not get any completed word for term: {}", term);
-                        }
+                        terms.push((term, term_hashed));
                     }
+                }
+
+                term_iids.unwrap_or_default()
+            });
+
+            debug!(
+                "got search executor iids: {:?} for expression: {:?}",
+                found_iids, expression
+            );
+
+            let terms_hashed: Vec<StoreTermHashed> =
+                terms.iter().map(|(_, term_hashed)| *term_hashed).collect();
+
+            return Ok(Self::resolve_oids(
+                &kv_action,
+                Self::ranker(collection.as_str()),
+                &found_iids,
+                &terms_hashed,
+                limit,
+                offset,
+                snippets,
+                if with_terms { Some(&terms) } else { None },
+            ));
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+
+    pub fn execute_phrase<'a>(
+        store: StoreItem<'a>,
+        _event_id: QuerySearchID,
+        lexer: TokenLexer<'a>,
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+        snippets: bool,
+        with_terms: bool,
+    ) -> Result<Option<Vec<String>>, StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            general_kv_access_lock_read!();
+
+            let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?;
+
+            // Important: acquire bucket store read lock, unless reading from a snapshot (the \
+            //   lock holds writes back until the query is done, thus all posting lists are \
+            //   read in the same state)
+            executor_kv_lock_read!(kv_store, !APP_CONF.channel.search.query_snapshot);
+
+            // Apply collection stop-words to lexed text? (if any; as positions are counted \
+            //   over words that are not stop-words, both on push and on query)
+            let lexer = match kv_store {
+                Some(ref store) => lexer.with_stopwords(store.stopwords()),
+                None => lexer,
+            };
+
+            let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
+
+            let kv_snapshot = Self::snapshot(&kv_action);
+            let kv_action = kv_action.with_snapshot(kv_snapshot.as_ref());
+
+            // Bucket hash colliding with another bucket? (reject query)
+            kv_action.check_bucket()?;
+
+            let mut lexer = lexer.with_positions();
+
+            let terms: Vec<(String, StoreTermHashed)> = lexer.by_ref().collect();
+
+            // Order phrase words by position (repeated words are listed at each position)
+            let mut phrase: Vec<(StoreTermPosition, StoreTermHashed)> = lexer
+                .take_positions()
+                .unwrap_or_default()
+                .into_iter()
+                .flat_map(|(term_hashed, positions)| {
+                    positions
+                        .into_iter()
+                        .map(move |position| (position, term_hashed))
+                })
+                .collect();
+
+            phrase.sort_unstable();
 
-                    debug!("got search executor iids: {:?} for term: {}", iids, term);
+            // Notice: phrase words are not completed with alternate words, as they must \
+            //   be found next to each other as-is.
+            let mut terms_postings = HashMap::with_capacity(terms.len());
 
-                    // Intersect found IIDs with previous batch
-                    if found_iids.is_empty() {
-                        found_iids = iids;
-                    } else {
-                        found_iids = found_iids.intersection(&iids).copied().collect();
+            for (term, term_hashed) in &terms {
+                match kv_action.get_term_to_postings(*term_hashed) {
+                    Ok(Some(term_postings)) => {
+                        terms_postings.insert(*term_hashed, term_postings);
                     }
+                    Ok(None) => {
+                        info!(
+                            "stop search executor as phrase term was not found: {}",
+                            term
+                        );
+
+                        return Ok(None);
+                    }
+                    Err(err) => return Err(StoreOperationError::from(err)),
+                }
+            }
+
+            let found_iids = Self::match_phrase(&phrase, &terms_postings);
+
+            debug!("got search executor phrase iids: {:?}", found_iids);
+
+            let terms_hashed: Vec<StoreTermHashed> =
+                terms.iter().map(|(_, term_hashed)| *term_hashed).collect();
+
+            return Ok(Self::resolve_oids(
+                &kv_action,
+                Self::ranker(collection.as_str()),
+                &found_iids,
+                &terms_hashed,
+                limit,
+                offset,
+                snippets,
+                if with_terms { Some(&terms) } else { None },
+            ));
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+
+    pub fn execute_federated(
+        queries: Vec<Query>,
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+    ) -> Result<Option<Vec<String>>, StoreOperationError> {
+        // Run the query against each federated collection one after the other
+        // Notice: each collection query returns up to 'offset + limit' OIDs, so that the merged \
+        //   result set can be paginated once all collections have been queried.
+        // Notice: the merged result set is marked as partial if any collection query is.
+        let mut collections_oids = Vec::with_capacity(queries.len());
+        let mut is_partial = false;
+
+        for query in queries {
+            let collection_oids = match query {
+                Query::Search(
+                    store,
+                    query_id,
+                    lexer,
+                    limit,
+                    offset,
+                    timeout,
+                    typos,
+                    completion,
+                    snippets,
+                    with_terms,
+                ) => {
+                    let (collection_oids, is_collection_partial) = Self::execute_within(
+                        store, query_id, lexer, limit, offset, timeout, typos, completion,
+                        snippets, with_terms,
+                    )?;
+
+                    is_partial = is_partial || is_collection_partial;
+
+                    collection_oids
+                }
+                Query::SearchBoolean(
+                    store,
+                    query_id,
+                    expression,
+                    limit,
+                    offset,
+                    snippets,
+                    with_terms,
+                ) => Self::execute_boolean(
+                    store, query_id, expression, limit, offset, snippets, with_terms,
+                )?,
+                Query::SearchPhrase(
+                    store,
+                    query_id,
+                    lexer,
+                    limit,
+                    offset,
+                    snippets,
+                    with_terms,
+                ) => Self::execute_phrase(
+                    store, query_id, lexer, limit, offset, snippets, with_terms,
+                )?,
+                _ => return Err(StoreOperationError::Failed),
+            };
+
+            collections_oids.push(collection_oids.unwrap_or_default());
+        }
+
+        Ok(Self::mark_partial(
+            Self::merge_oids(collections_oids, limit, offset),
+            is_partial,
+        ))
+    }
+
+    fn mark_partial(results: Option<Vec<String>>, is_partial: bool) -> Option<Vec<String>> {
+        // Append partial results attribute? (as the last result, so that clients can tell \
+        //   results that stopped on the time budget apart from complete results)
+        if is_partial {
+            let mut results = results.unwrap_or_default();
+
+            results.push(RESULTS_PARTIAL_MARKER.to_string());
+
+            Some(results)
+        } else {
+            results
+        }
+    }
+
+    fn merge_oids(
+        collections_oids: Vec<Vec<String>>,
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+    ) -> Option<Vec<String>> {
+        // Interleave OIDs from each collection in turn, as to keep each collection ranking \
+        //   (an OID found in multiple collections is only listed once)
+        let mut merged_oids: LinkedHashSet<String> = LinkedHashSet::new();
+        let mut collections_oids: Vec<_> = collections_oids
+            .into_iter()
+            .map(|oids| oids.into_iter())
+            .collect();
+
+        'merging: loop {
+            let mut has_next = false;
+
+            for collection_oids in collections_oids.iter_mut() {
+                if let Some(oid) = collection_oids.next() {
+                    has_next = true;
+
+                    merged_oids.insert_if_absent(oid);
+                }
+            }
+
+            if !has_next {
+                break 'merging;
+            }
+        }
+
+        let result_oids: Vec<String> = merged_oids
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect();
+
+        info!("got search executor federated oids: {:?}", result_oids);
+
+        if !result_oids.is_empty() {
+            Some(result_oids)
+        } else {
+            None
+        }
+    }
+
+    fn time_budget() -> Option<Duration> {
+        // Notice: a zero budget disables the query time budget
+        match APP_CONF.channel.search.query_time_budget {
+            0 => None,
+            budget => Some(Duration::from_millis(budget)),
+        }
+    }
+
+    fn snapshot<'s>(kv_action: &'s StoreKVAction) -> Option<StoreKVSnapshot<'s>> {
+        // Read the bucket from a snapshot? (taken before any term gets read, and released once \
+        //   the query is done, so that pushes committed meanwhile are not seen halfway)
+        if APP_CONF.channel.search.query_snapshot {
+            kv_action.snapshot()
+        } else {
+            None
+        }
+    }
+
+    fn is_time_budget_spent(deadline: Option<Instant>) -> bool {
+        deadline
+            .map(|deadline| Instant::now() >= deadline)
+            .unwrap_or(false)
+    }
+
+    fn order_terms(
+        terms: Vec<(String, StoreTermHashed)>,
+        prefetched_iids: Option<Vec<Option<Vec<StoreObjectIID>>>>,
+        terms_size: &[usize],
+    ) -> (
+        Vec<(String, StoreTermHashed)>,
+        Option<Vec<Option<Vec<StoreObjectIID>>>>,
+    ) {
+        // Notice: the sort is stable, thus terms of the same size keep their query order.
+        let mut order: Vec<usize> = (0..terms.len()).collect();
+
+        order.sort_by_key(|index| terms_size.get(*index).copied().unwrap_or(0));
 
-                    debug!(
-                        "got search executor iid intersection: {:?} for term: {}",
-                        found_iids, term
-                    );
+        let mut terms: Vec<Option<(String, StoreTermHashed)>> =
+            terms.into_iter().map(Some).collect();
 
-                    // No IID found? (stop there)
-                    if found_iids.is_empty() {
+        let ordered_terms = order
+            .iter()
+            .filter_map(|index| terms[*index].take())
+            .collect();
+
+        let ordered_prefetched_iids = prefetched_iids.map(|mut prefetched_iids| {
+            order
+                .iter()
+                .map(|index| prefetched_iids[*index].take())
+                .collect()
+        });
+
+        (ordered_terms, ordered_prefetched_iids)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn intersect_terms(
+        kv_action: &StoreKVAction,
+        fst_action: Option<&StoreFSTAction>,
+        typos: QuerySearchTypos,
+        completion: QuerySearchCompletion,
+        frequency_minimum: usize,
+        last_term_hashed: Option<StoreTermHashed>,
+        terms: Vec<(String, StoreTermHashed)>,
+        mut prefetched_iids: Option<Vec<Option<Vec<StoreObjectIID>>>>,
+        deadline: Option<Instant>,
+        timeout: QuerySearchTimeout,
+    ) -> Result<(LinkedHashSet<StoreObjectIID>, usize, bool), StoreOperationError> {
+        // Try to resolve existing search terms to IIDs, and perform an algebraic AND on all \
+        //   resulting IIDs for each given term.
+        // Notice: the time budget is checked after each term, so that the first term is always \
+        //   read. A partial query only stops if terms are left to be read, while an erroring \
+        //   query fails even after its last term.
+        // Notice: the number of IIDs read for all terms, and whether the query stopped before \
+        //   reading all terms are returned along with found IIDs.
+        let terms_count = terms.len();
+
+        let mut found_iids: LinkedHashSet<StoreObjectIID> = LinkedHashSet::new();
+        let mut rare_iids: Vec<LinkedHashSet<StoreObjectIID>> = Vec::new();
+        let (mut read_count, mut is_partial, mut has_frequent) = (0, false, false);
+
+        'lexing: for (index, (term, term_hashed)) in terms.into_iter().enumerate() {
+            // Complete this term with alternate words? (depending on the query completion mode)
+            let term_fst_action = fst_action.filter(|_| match completion {
+                QuerySearchCompletion::AllTerms => true,
+                QuerySearchCompletion::LastTerm => last_term_hashed == Some(term_hashed),
+                QuerySearchCompletion::Disabled => false,
+            });
+
+            let iids = if let Some(ref mut prefetched_iids) = prefetched_iids {
+                Self::resolve_term(
+                    kv_action,
+                    term_fst_action,
+                    typos,
+                    &term,
+                    prefetched_iids[index].take().unwrap_or_default(),
+                )
+            } else {
+                Self::resolve_stored_term(kv_action, term_fst_action, typos, &term, term_hashed)
+            };
+
+            read_count += iids.len();
+
+            // Ignore rare term as noise? (its frequency is the number of objects it resolved \
+            //   to, thus including alternate words, so that a rare misspelt term still gets \
+            //   completed with its correction before being ignored)
+            if iids.len() < frequency_minimum {
+                debug!(
+                    "ignoring search executor term: {} as its frequency is {}/{}",
+                    term,
+                    iids.len(),
+                    frequency_minimum
+                );
+
+                rare_iids.push(iids);
+            } else if found_iids.is_empty() {
+                // Intersect found IIDs with previous batch
+                found_iids = iids;
+                has_frequent = true;
+            } else {
+                found_iids = found_iids.intersection(&iids).copied().collect();
+            }
+
+            debug!(
+                "got search executor iid intersection: {:?} for term: {}",
+                found_iids, term
+            );
+
+            // No IID found? (stop there)
+            if has_frequent && found_iids.is_empty() {
+                info!(
+                    "stop search executor as no iid was found in common for term: {}",
+                    term
+                );
+
+                break 'lexing;
+            }
+
+            // Time budget spent?
+            if Self::is_time_budget_spent(deadline) {
+                match timeout {
+                    QuerySearchTimeout::Partial if index + 1 < terms_count => {
                         info!(
-                            "stop search executor as no iid was found in common for term: {}",
+                            "stop search executor as time budget is spent after term: {}",
                             term
                         );
 
+                        is_partial = true;
+
                         break 'lexing;
                     }
+                    QuerySearchTimeout::Partial => {}
+                    QuerySearchTimeout::Error => {
+                        info!(
+                            "failing search executor as time budget is spent after term: {}",
+                            term
+                        );
+
+                        return Err(StoreOperationError::Timeout);
+                    }
                 }
+            }
+        }
 
-                // Resolve OIDs from IIDs
-                // Notice: we also proceed paging from there
-                let (limit_usize, offset_usize) = (limit as usize, offset as usize);
-                let mut result_oids = Vec::with_capacity(limit_usize);
+        // All terms ignored as noise? (either fail, or intersect them as if no minimum applied)
+        if !has_frequent && !rare_iids.is_empty() {
+            if APP_CONF.channel.search.query_term_frequency_strict {
+                info!("failing search executor as all terms are below minimum frequency");
 
-                'paging: for (index, found_iid) in found_iids.iter().skip(offset_usize).enumerate()
-                {
-                    // Stop there?
-                    if index >= limit_usize {
-                        break 'paging;
+                return Err(StoreOperationError::Failed);
+            }
+
+            found_iids = rare_iids
+                .into_iter()
+                .reduce(|found_iids, iids| found_iids.intersection(&iids).copied().collect())
+                .unwrap_or_default();
+        }
+
+        Ok((found_iids, read_count, is_partial))
+    }
+
+    fn match_phrase(
+        phrase: &[(StoreTermPosition, StoreTermHashed)],
+        terms_postings: &HashMap<StoreTermHashed, StoreKVTermPostings>,
+    ) -> LinkedHashSet<StoreObjectIID> {
+        let mut found_iids = LinkedHashSet::new();
+
+        let (first_position, first_term_hashed) = match phrase.first() {
+            Some(first) => *first,
+            None => return found_iids,
+        };
+
+        // Index stored positions of each phrase word per IID (IIDs with no stored positions \
+        //   cannot match a phrase)
+        let terms_iids_positions: HashMap<
+            StoreTermHashed,
+            HashMap<StoreObjectIID, &[StoreTermPosition]>,
+        > = terms_postings
+            .iter()
+            .map(|(term_hashed, term_postings)| {
+                (
+                    *term_hashed,
+                    term_postings
+                        .iter_positions()
+                        .filter_map(|(iid, positions)| positions.map(|positions| (iid, positions)))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        if let Some(first_term_postings) = terms_postings.get(&first_term_hashed) {
+            // Walk IIDs in order of the first phrase word (most recent first), and check that \
+            //   each following word is stored at its expected position
+            for iid in first_term_postings.iids() {
+                let is_match = |position: StoreTermPosition, term_hashed: &StoreTermHashed| {
+                    terms_iids_positions
+                        .get(term_hashed)
+                        .and_then(|iids_positions| iids_positions.get(iid))
+                        .map_or(false, |positions| positions.contains(&position))
+                };
+
+                let starts = terms_iids_positions
+                    .get(&first_term_hashed)
+                    .and_then(|iids_positions| iids_positions.get(iid));
+
+                if let Some(starts) = starts {
+                    let has_phrase = starts.iter().any(|start| {
+                        phrase.iter().all(|(position, term_hashed)| {
+                            is_match(start + (position - first_position), term_hashed)
+                        })
+                    });
+
+                    if has_phrase {
+                        found_iids.insert(*iid);
+                    }
+                }
+            }
+        }
+
+        found_iids
+    }
+
+    fn term_frequency_minimum(collection: &str) -> usize {
+        APP_CONF
+            .channel
+            .search
+            .query_term_frequency_collections
+            .get(collection)
+            .copied()
+            .unwrap_or(APP_CONF.channel.search.query_term_frequency_minimum)
+    }
+
+    fn resolve_stored_term(
+        kv_action: &StoreKVAction,
+        fst_action: Option<&StoreFSTAction>,
+        typos: QuerySearchTypos,
+        term: &str,
+        term_hashed: StoreTermHashed,
+    ) -> LinkedHashSet<StoreObjectIID> {
+        if APP_CONF.channel.search.query_zero_copy {
+            // Decode IIDs straight from the stored value (without an intermediate list)
+            let term_iids = kv_action
+                .get_term_to_iids_pinned(term_hashed)
+                .unwrap_or(None);
+
+            Self::resolve_term(
+                kv_action,
+                fst_action,
+                typos,
+                term,
+                term_iids.iter().flat_map(|term_iids| term_iids.iter()),
+            )
+        } else {
+            Self::resolve_term(
+                kv_action,
+                fst_action,
+                typos,
+                term,
+                kv_action
+                    .get_term_to_iids(term_hashed)
+                    .unwrap_or(None)
+                    .unwrap_or_default(),
+            )
+        }
+    }
+
+    fn resolve_term<T: IntoIterator<Item = StoreObjectIID>>(
+        kv_action: &StoreKVAction,
+        fst_action: Option<&StoreFSTAction>,
+        typos: QuerySearchTypos,
+        term: &str,
+        term_iids: T,
+    ) -> LinkedHashSet<StoreObjectIID> {
+        let mut iids = LinkedHashSet::from_iter(term_iids);
+
+        // No IIDs? Try to complete with a suggested alternate word
+        // Notice: this may sound dirty to try generating as many results as the \
+        //   'retain_word_objects' value, but as we do not know if another lexed word \
+        //   comes next we need to exhaust all search space as to intersect it with \
+        //   the (likely) upcoming word.
+        let (higher_limit, alternates_try) = (
+            APP_CONF.store.kv.retain_word_objects,
+            APP_CONF.channel.search.query_alternates_try,
+        );
+
+        if let Some(fst_action) =
+            fst_action.filter(|_| iids.len() < higher_limit && alternates_try > 0)
+        {
+            debug!(
+                "not enough iids were found ({}/{}), completing for term: {}",
+                iids.len(),
+                higher_limit,
+                term
+            );
+
+            // Suggest N words, in case the first one is found in FST as an exact \
+            //   match of term, we can pick next ones to complete search even further.
+            // Notice: we add '1' to the 'alternates_try' number as to account for \
+            //   exact match suggestion that comes as first result and is to be ignored.
+            if let Some(suggested_words) =
+                fst_action.suggest_words(term, alternates_try + 1, Some(typos as u32))
+            {
+                let mut iids_new_len = iids.len();
+
+                // This loop will be broken early if we get enough results at some \
+                //   iteration
+                'suggestions: for suggested_word in suggested_words {
+                    // Do not load base results twice for same term as base term
+                    if suggested_word == term {
+                        continue 'suggestions;
                     }
 
-                    // Read IID-to-OID for this found IID
-                    if let Ok(Some(oid)) = kv_action.get_iid_to_oid(*found_iid) {
-                        result_oids.push(oid);
-                    } else {
-                        error!("failed getting search executor iid-to-oid");
+                    debug!("got completed word: {} for term: {}", suggested_word, term);
+
+                    if let Some(suggested_iids) = kv_action
+                        .get_term_to_iids(StoreTermHash::from(&suggested_word))
+                        .unwrap_or(None)
+                    {
+                        for suggested_iid in suggested_iids {
+                            // Do not append the same IID twice (can happen a lot \
+                            //   when completing from suggested results that point \
+                            //   to the same end-OID)
+                            if !iids.contains(&suggested_iid) {
+                                iids.insert(suggested_iid);
+
+                                iids_new_len += 1;
+
+                                // Higher limit now reached? Stop acquiring new \
+                                //   suggested IIDs now.
+                                if iids_new_len >= higher_limit {
+                                    debug!("got enough completed results for term: {}", term);
+
+                                    break 'suggestions;
+                                }
+                            }
+                        }
                     }
                 }
 
-                info!("got search executor final oids: {:?}", result_oids);
+                debug!(
+                    "done completing results for term: {}, now {} results",
+                    term, iids_new_len
+                );
+            } else {
+                debug!("did not get any completed word for term: {}", term);
+            }
+        }
+
+        debug!("got search executor iids: {:?} for term: {}", iids, term);
+
+        iids
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn resolve_oids(
+        kv_action: &StoreKVAction,
+        ranker: &dyn Ranker,
+        found_iids: &LinkedHashSet<StoreObjectIID>,
+        terms_hashed: &[StoreTermHashed],
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+        snippets: bool,
+        terms: Option<&[(String, StoreTermHashed)]>,
+    ) -> Option<Vec<String>> {
+        // Resolve OIDs from IIDs
+        // Notice: we also proceed paging from there
+        let (limit_usize, offset_usize) = (limit as usize, offset as usize);
+        let mut result_oids = Vec::with_capacity(limit_usize);
+
+        let ranked_iids = Self::rank_iids(
+            kv_action,
+            ranker,
+            found_iids,
+            terms_hashed,
+            APP_CONF.channel.search.query_rank_candidates,
+        );
+
+        'paging: for (index, found_iid) in ranked_iids.iter().skip(offset_usize).enumerate() {
+            // Stop there?
+            if index >= limit_usize {
+                break 'paging;
+            }
+
+            // Read IID-to-OID for this found IID
+            if let Ok(Some(mut result)) = kv_action.get_iid_to_oid(*found_iid) {
+                // Append object snippet? (if requested; objects without a snippet get an \
+                //   empty one, as to keep results aligned)
+                if snippets {
+                    let snippet = kv_action
+                        .get_iid_to_snippet(*found_iid)
+                        .unwrap_or(None)
+                        .unwrap_or_default();
+
+                    result.push_str(&format!(" \"{}\"", Self::escape_snippet(&snippet)));
+                }
+
+                // Append query terms matched by object? (if requested)
+                if let Some(terms) = terms {
+                    let matched_terms = Self::match_terms(kv_action, *found_iid, terms);
+
+                    result.push_str(&format!(
+                        " \"{}\"",
+                        Self::escape_snippet(&matched_terms.join(" "))
+                    ));
+                }
+
+                result_oids.push(result);
+            } else {
+                error!("failed getting search executor iid-to-oid");
+            }
+        }
+
+        info!("got search executor final oids: {:?}", result_oids);
+
+        if !result_oids.is_empty() {
+            Some(result_oids)
+        } else {
+            None
+        }
+    }
+
+    fn match_terms<'t>(
+        kv_action: &StoreKVAction,
+        iid: StoreObjectIID,
+        terms: &'t [(String, StoreTermHashed)],
+    ) -> Vec<&'t str> {
+        // List query terms that the object is linked to, in query order
+        // Notice: objects found from alternate words only are not linked to the query term, \
+        //   thus this term is not listed for them.
+        let iid_terms = kv_action
+            .get_iid_to_terms(iid)
+            .unwrap_or(None)
+            .unwrap_or_default();
+
+        let mut matched_terms = Vec::new();
+
+        for (term, term_hashed) in terms {
+            if iid_terms.contains(term_hashed) && !matched_terms.contains(&term.as_str()) {
+                matched_terms.push(term.as_str());
+            }
+        }
+
+        matched_terms
+    }
+
+    fn escape_snippet(snippet: &str) -> String {
+        // Escape text boundaries and line breaks (a result must fit on a single line)
+        snippet.replace('"', "\\\"").replace('\n', "\\n")
+    }
+
+    fn ranker(collection: &str) -> &'static dyn Ranker {
+        // Use collection ranker, or default ranker (if collection ranker is not configured)
+        RankerBuilder::from(
+            APP_CONF
+                .channel
+                .search
+                .query_ranker_collections
+                .get(collection)
+                .copied()
+                .unwrap_or(APP_CONF.channel.search.query_ranker),
+        )
+    }
+
+    fn rank_iids(
+        kv_action: &StoreKVAction,
+        ranker: &dyn Ranker,
+        found_iids: &LinkedHashSet<StoreObjectIID>,
+        terms_hashed: &[StoreTermHashed],
+        candidates: usize,
+    ) -> Vec<StoreObjectIID> {
+        let uses_document_stats = ranker.uses_document_stats();
+
+        let mut has_ranking = uses_document_stats;
+
+        // Only rank the first found IIDs (ie. most recently pushed first), as ranking reads \
+        //   from the database for each ranked IID; other found IIDs come after, in base order
+        let candidates = if candidates > 0 {
+            candidates
+        } else {
+            found_iids.len()
+        };
+
+        let unranked_iids = found_iids.iter().skip(candidates);
+
+        // Read the boost and term weights of each found IID (as well as its length, if the \
+        //   ranker uses it)
+        let candidate_iids: Vec<StoreObjectIID> =
+            found_iids.iter().take(candidates).copied().collect();
+
+        let rankings = match kv_action.get_iids_to_ranking(&candidate_iids, uses_document_stats) {
+            Ok(rankings) => rankings,
+            Err(err) => {
+                error!("failed getting search executor iids ranking: {}", err);
+
+                return found_iids.iter().copied().collect();
+            }
+        };
+
+        let found_ranks: Vec<(StoreObjectIID, StoreObjectBoost, Vec<_>, usize)> = candidate_iids
+            .into_iter()
+            .zip(rankings)
+            .map(|(found_iid, ranking)| {
+                let length = ranking.terms.map(|iid_terms| iid_terms.len()).unwrap_or(0);
+
+                if ranking.boost != STORE_OBJECT_BOOST_DEFAULT || !ranking.weights.is_empty() {
+                    has_ranking = true;
+                }
+
+                (found_iid, ranking.boost, ranking.weights, length)
+            })
+            .collect();
+
+        // Nothing to rank? (keep base order, ie. most recently pushed first)
+        if !has_ranking {
+            return found_ranks
+                .into_iter()
+                .map(|(iid, _, _, _)| iid)
+                .chain(unranked_iids.copied())
+                .collect();
+        }
+
+        // Weigh searched terms by their rarity in bucket
+        let terms_stats: Vec<RankerTerm> = kv_action
+            .get_terms_idf(terms_hashed)
+            .into_iter()
+            .map(|idf| RankerTerm { idf })
+            .collect();
+
+        // Count how many times each found IID holds each searched term? (if the ranker uses it)
+        // Notice: occurrences are only known for terms pushed with positions, otherwise a \
+        //   linked term occurs once; found IIDs that are not linked to a term (eg. IIDs found \
+        //   from alternate words, or from other operands of a boolean query) do not match it.
+        let terms_frequencies: Vec<HashMap<StoreObjectIID, u32>> = if uses_document_stats {
+            let candidate_iids: HashSet<StoreObjectIID> =
+                found_ranks.iter().map(|(iid, _, _, _)| *iid).collect();
+
+            terms_hashed
+                .iter()
+                .map(|term_hashed| {
+                    Self::count_term_frequencies(kv_action, *term_hashed, &candidate_iids)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let average_length = if found_ranks.is_empty() {
+            0.0
+        } else {
+            found_ranks
+                .iter()
+                .map(|(_, _, _, length)| *length as f32)
+                .sum::<f32>()
+                / found_ranks.len() as f32
+        };
+
+        // Score each found IID with the ranker, from its boost and the weights of the searched \
+        //   terms (terms that were not pushed with a weight count with the default weight)
+        let mut scored_iids: Vec<(StoreObjectIID, f32)> = found_ranks
+            .into_iter()
+            .map(|(found_iid, boost, iid_weights, length)| {
+                let matches: Vec<RankerMatch> = terms_hashed
+                    .iter()
+                    .enumerate()
+                    .map(|(index, term_hashed)| RankerMatch {
+                        weight: iid_weights
+                            .iter()
+                            .find(|(weight_term_hashed, _)| weight_term_hashed == term_hashed)
+                            .map(|(_, weight)| *weight)
+                            .unwrap_or(STORE_TERM_WEIGHT_DEFAULT),
+                        frequency: terms_frequencies
+                            .get(index)
+                            .map(|frequencies| frequencies.get(&found_iid).copied().unwrap_or(0))
+                            .unwrap_or(1),
+                    })
+                    .collect();
+
+                let document = RankerDocument {
+                    boost,
+                    length,
+                    average_length,
+                };
+
+                (found_iid, ranker.score(&terms_stats, &document, &matches))
+            })
+            .collect();
+
+        // Rank best scored objects first
+        // Notice: this sort is stable, so objects with an equal score keep their base order \
+        //   (ie. most recently pushed first).
+        scored_iids.sort_by(|(_, score_a), (_, score_b)| {
+            score_b.partial_cmp(score_a).unwrap_or(Ordering::Equal)
+        });
+
+        scored_iids
+            .into_iter()
+            .map(|(iid, _)| iid)
+            .chain(unranked_iids.copied())
+            .collect()
+    }
+
+    fn count_term_frequencies(
+        kv_action: &StoreKVAction,
+        term_hashed: StoreTermHashed,
+        found_iids: &HashSet<StoreObjectIID>,
+    ) -> HashMap<StoreObjectIID, u32> {
+        kv_action
+            .get_term_to_postings(term_hashed)
+            .unwrap_or(None)
+            .map(|term_postings| {
+                term_postings
+                    .iter_positions()
+                    .filter(|(iid, _)| found_iids.contains(iid))
+                    .map(|(iid, positions)| {
+                        let frequency = positions
+                            .map(|positions| positions.len() as u32)
+                            .unwrap_or(0);
+
+                        (iid, frequency.max(1))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::push::ExecutorPush;
+    use crate::query::builder::QueryBuilder;
+    use crate::query::ranker::{RankerBM25, RankerIDF, RankerMatchCount};
+    use crate::query::types::{QueryPushSuggest, QuerySearchSyntax};
+    use crate::store::fst::CONSOLIDATE_TEST_LOCK;
+    use crate::store::item::StoreItemPart;
+
+    #[test]
+    fn it_ranks_iids_by_boost() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:6").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:6").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        // Those IIDs match the same terms (most recent first)
+        let found_iids = LinkedHashSet::from_iter(vec![3, 2, 1, 0]);
+
+        assert_eq!(
+            ExecutorSearch::rank_iids(&action, &RankerIDF, &found_iids, &[], 0),
+            vec![3, 2, 1, 0]
+        );
+
+        assert!(action.set_iid_to_boost(1, 2.0).is_ok());
+        assert!(action.set_iid_to_boost(2, 0.5).is_ok());
+        assert!(action.set_iid_to_boost(0, 2.0).is_ok());
+
+        assert_eq!(
+            ExecutorSearch::rank_iids(&action, &RankerIDF, &found_iids, &[], 0),
+            vec![1, 0, 3, 2]
+        );
+
+        // Only the first candidates get ranked, others keep their base order
+        assert_eq!(
+            ExecutorSearch::rank_iids(&action, &RankerIDF, &found_iids, &[], 3),
+            vec![1, 3, 2, 0]
+        );
+        assert_eq!(
+            ExecutorSearch::rank_iids(&action, &RankerIDF, &found_iids, &[], 2),
+            vec![3, 2, 1, 0]
+        );
+    }
+
+    #[test]
+    fn it_ranks_iids_with_rankers() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:41").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:41").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        for oid in &["o:0", "o:1", "o:2", "o:3"] {
+            assert!(action.get_or_assign_iid(oid, None).is_ok());
+        }
+
+        let (alpha, beta) = (StoreTermHash::from("alpha"), StoreTermHash::from("beta"));
+
+        // All IIDs hold the first term, while only one IID holds both terms
+        assert!(action.set_term_to_iids(alpha, &[3, 2, 1]).is_ok());
+        assert!(action.set_term_to_iids(beta, &[1]).is_ok());
+        assert!(action.set_iid_to_terms(3, &[alpha]).is_ok());
+        assert!(action.set_iid_to_terms(2, &[alpha]).is_ok());
+        assert!(action.set_iid_to_terms(1, &[alpha, beta]).is_ok());
+        assert!(action.set_iid_to_boost(3, 1.5).is_ok());
+
+        // Those IIDs were found for either term (eg. from a boolean query)
+        let found_iids = LinkedHashSet::from_iter(vec![3, 2, 1]);
+
+        let rank = |ranker: &dyn Ranker| {
+            ExecutorSearch::rank_iids(&action, ranker, &found_iids, &[alpha, beta], 0)
+        };
+
+        assert_eq!(rank(&RankerIDF), vec![3, 2, 1]);
+        assert_eq!(rank(&RankerMatchCount), vec![1, 3, 2]);
+        assert_eq!(rank(&RankerBM25), vec![1, 3, 2]);
+
+        // Ranking the same result set again gives the same order
+        for ranker in &[&RankerIDF as &dyn Ranker, &RankerMatchCount, &RankerBM25] {
+            assert_eq!(rank(*ranker), rank(*ranker));
+        }
+    }
+
+    #[test]
+    fn it_merges_federated_oids() {
+        let collections_oids = vec![
+            vec!["a:1".to_string(), "a:2".to_string(), "a:3".to_string()],
+            vec!["b:1".to_string(), "a:2".to_string()],
+            vec![],
+        ];
+
+        assert_eq!(
+            ExecutorSearch::merge_oids(collections_oids.clone(), 10, 0),
+            Some(vec![
+                "a:1".to_string(),
+                "b:1".to_string(),
+                "a:2".to_string(),
+                "a:3".to_string()
+            ])
+        );
+        assert_eq!(
+            ExecutorSearch::merge_oids(collections_oids.clone(), 2, 1),
+            Some(vec!["b:1".to_string(), "a:2".to_string()])
+        );
+        assert_eq!(ExecutorSearch::merge_oids(collections_oids, 10, 4), None);
+    }
+
+    #[test]
+    fn it_searches_federated_collections() {
+        let objects = vec![
+            ("c:test:11:1", vec![(0, "article:1"), (1, "article:2")]),
+            ("c:test:11:2", vec![(0, "page:1")]),
+        ];
+
+        for (collection, collection_objects) in objects {
+            let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, collection).unwrap();
+            let action =
+                StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:11").unwrap(), store);
+
+            assert!(action.batch_erase_bucket().is_ok());
+
+            for (iid, oid) in collection_objects {
+                assert!(action.set_iid_to_oid(iid, oid).is_ok());
+                assert!(action
+                    .batch_link_term(StoreTermHash::from("federated"), iid, None)
+                    .is_ok());
+            }
+        }
+
+        let query = QueryBuilder::search_federated(
+            "id1",
+            &["c:test:11:1", "c:test:11:2"],
+            "b:test:11",
+            "federated",
+            10,
+            0,
+            None,
+            QuerySearchSyntax::Plain,
+            QuerySearchTimeout::Partial,
+            1,
+            QuerySearchCompletion::AllTerms,
+        );
+
+        if let Ok(Query::SearchFederated(queries, limit, offset)) = query {
+            let mut oids = ExecutorSearch::execute_federated(queries, limit, offset)
+                .unwrap()
+                .unwrap();
+
+            oids.sort();
+
+            assert_eq!(oids, vec!["article:1", "article:2", "page:1"]);
+        } else {
+            panic!("federated query could not be built");
+        }
+    }
+
+    #[test]
+    fn it_searches_phrases() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:35").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:35").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        let link = |iid, oid, text, positioned| {
+            assert!(action.set_iid_to_oid(iid, oid).is_ok());
+
+            let mut lexer = TokenLexerBuilder::from(TokenLexerMode::NormalizeOnly, text)
+                .unwrap()
+                .with_positions();
+
+            let terms: Vec<(String, StoreTermHashed)> = lexer.by_ref().collect();
+            let mut positions = lexer.take_positions().unwrap();
 
-                return Ok(if !result_oids.is_empty() {
-                    Some(result_oids)
+            for (_, term_hashed) in terms {
+                let term_positions = if positioned {
+                    positions.remove(&term_hashed)
                 } else {
                     None
-                });
+                };
+
+                assert!(action
+                    .batch_link_term(term_hashed, iid, term_positions)
+                    .is_ok());
+            }
+        };
+
+        // Object linked with no positions first (terms get upgraded to positions afterwards)
+        link(3, "o:3", "quick brown fox", false);
+        link(1, "o:1", "quick brown fox", true);
+        link(2, "o:2", "brown fox quick", true);
+
+        let search = |terms| match QueryBuilder::search_phrase(
+            "id1",
+            "c:test:35",
+            "b:test:35",
+            terms,
+            10,
+            0,
+            None,
+            false,
+            false,
+        ) {
+            Ok(Query::SearchPhrase(
+                store,
+                query_id,
+                lexer,
+                limit,
+                offset,
+                snippets,
+                with_terms,
+            )) => ExecutorSearch::execute_phrase(
+                store, query_id, lexer, limit, offset, snippets, with_terms,
+            )
+            .unwrap()
+            .map(|mut oids| {
+                oids.sort();
+
+                oids
+            }),
+            _ => panic!("phrase query could not be built"),
+        };
+
+        assert_eq!(search("quick brown"), Some(vec!["o:1".to_string()]));
+        assert_eq!(
+            search("brown fox"),
+            Some(vec!["o:1".to_string(), "o:2".to_string()])
+        );
+        assert_eq!(search("fox quick"), Some(vec!["o:2".to_string()]));
+        assert_eq!(search("quick fox"), None);
+        assert_eq!(search("quick unknown"), None);
+    }
+
+    #[test]
+    fn it_ranks_iids_by_term_idf() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:37").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:37").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        let (common, rare) = (StoreTermHash::from("common"), StoreTermHash::from("rare"));
+
+        assert!(action.set_term_to_iids(common, &[4, 3, 2, 1, 0]).is_ok());
+        assert!(action.set_term_to_iids(rare, &[2, 1]).is_ok());
+
+        // Object 1 weighs the common term up, object 2 weighs the rare term up by as much, \
+        //   though the rare term contributes more to the score
+        assert!(action.set_iid_to_weights(1, &[(common, 2.0)]).is_ok());
+        assert!(action.set_iid_to_weights(2, &[(rare, 2.0)]).is_ok());
+
+        let found_iids = LinkedHashSet::from_iter(vec![1, 2]);
+
+        assert_eq!(
+            ExecutorSearch::rank_iids(&action, &RankerIDF, &found_iids, &[common, rare], 0),
+            vec![2, 1]
+        );
+
+        let found_iids = LinkedHashSet::from_iter(vec![2, 1]);
+
+        assert_eq!(
+            ExecutorSearch::rank_iids(&action, &RankerIDF, &found_iids, &[common, rare], 0),
+            vec![2, 1]
+        );
+    }
+
+    #[test]
+    fn it_ranks_iids_by_term_weight() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:17").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:17").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        let objects = vec![
+            (
+                "o:tagged",
+                "A story about a harbor",
+                vec![("lighthouse", 8.0)],
+            ),
+            ("o:body", "The lighthouse keeper story", vec![]),
+        ];
+
+        for (object, text, weights) in objects {
+            if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+                QueryBuilder::push(
+                    "c:test:17",
+                    "b:test:17",
+                    object,
+                    text,
+                    None,
+                    QueryPushSuggest::Incremental,
+                    None,
+                    &weights,
+                    None,
+                    false,
+                )
+            {
+                assert!(ExecutorPush::execute(
+                    store, lexer, suggest, boost, weights, snippet, verbose
+                )
+                .is_ok());
+            } else {
+                panic!("push query could not be built");
+            }
+        }
+
+        // The body-only object was pushed last, but the weighted tag ranks first
+        let search = |terms| {
+            if let Ok(Query::Search(
+                store,
+                query_id,
+                lexer,
+                limit,
+                offset,
+                timeout,
+                typos,
+                completion,
+                snippets,
+                with_terms,
+            )) = QueryBuilder::search(
+                "id1",
+                "c:test:17",
+                "b:test:17",
+                terms,
+                10,
+                0,
+                None,
+                QuerySearchTimeout::Partial,
+                1,
+                QuerySearchCompletion::AllTerms,
+                false,
+                false,
+            ) {
+                ExecutorSearch::execute(
+                    store, query_id, lexer, limit, offset, timeout, typos, completion, snippets,
+                    with_terms,
+                )
+                .unwrap()
+                .unwrap()
+            } else {
+                panic!("search query could not be built");
+            }
+        };
+
+        assert_eq!(search("lighthouse"), vec!["o:tagged", "o:body"]);
+        assert_eq!(search("story"), vec!["o:body", "o:tagged"]);
+    }
+
+    #[test]
+    fn it_searches_raw_terms_as_text() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:31").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:31").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        let objects = vec![
+            ("o:1", "The lighthouse keeper story"),
+            ("o:2", "A story about a harbor lighthouse"),
+            ("o:3", "Harbor cranes at night"),
+        ];
+
+        for (object, text) in objects {
+            if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+                QueryBuilder::push(
+                    "c:test:31",
+                    "b:test:31",
+                    object,
+                    text,
+                    None,
+                    QueryPushSuggest::Incremental,
+                    None,
+                    &[],
+                    None,
+                    false,
+                )
+            {
+                assert!(ExecutorPush::execute(
+                    store, lexer, suggest, boost, weights, snippet, verbose
+                )
+                .is_ok());
+            } else {
+                panic!("push query could not be built");
+            }
+        }
+
+        let search = |query| match query {
+            Ok(Query::Search(
+                store,
+                query_id,
+                lexer,
+                limit,
+                offset,
+                timeout,
+                typos,
+                completion,
+                snippets,
+                with_terms,
+            )) => ExecutorSearch::execute(
+                store, query_id, lexer, limit, offset, timeout, typos, completion, snippets,
+                with_terms,
+            )
+            .unwrap()
+            .unwrap_or_default(),
+            _ => panic!("search query could not be built"),
+        };
+
+        // Raw terms are the normalized form of the text query terms
+        for (text, terms) in &[
+            ("The Lighthouse STORY", "lighthouse story"),
+            ("Harbor!", "harbor"),
+            ("harbor keeper", "harbor keeper"),
+        ] {
+            let (text_oids, raw_oids) = (
+                search(QueryBuilder::search(
+                    "id1",
+                    "c:test:31",
+                    "b:test:31",
+                    text,
+                    10,
+                    0,
+                    None,
+                    QuerySearchTimeout::Partial,
+                    1,
+                    QuerySearchCompletion::AllTerms,
+                    false,
+                    false,
+                )),
+                search(QueryBuilder::search_raw(
+                    "id2",
+                    "c:test:31",
+                    "b:test:31",
+                    terms,
+                    10,
+                    0,
+                    QuerySearchTimeout::Partial,
+                    false,
+                    false,
+                )),
+            );
+
+            assert_eq!(text_oids, raw_oids);
+        }
+
+        // Raw terms are not normalized (thus, an upper-case term does not hit)
+        assert!(search(QueryBuilder::search_raw(
+            "id3",
+            "c:test:31",
+            "b:test:31",
+            "Harbor",
+            10,
+            0,
+            QuerySearchTimeout::Partial,
+            false,
+            false,
+        ))
+        .is_empty());
+    }
+
+    #[test]
+    fn it_returns_snippets_with_results() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:32").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:32").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        let snippet_long = "a".repeat(APP_CONF.store.kv.snippet_length_maximum + 10);
+
+        let objects = vec![
+            ("o:1", "The lighthouse keeper", None),
+            (
+                "o:2",
+                "Lighthouse at night",
+                Some("Lighthouse \"at\" night"),
+            ),
+            ("o:3", "Lighthouse story", Some(snippet_long.as_str())),
+        ];
+
+        for (object, text, snippet) in objects {
+            if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+                QueryBuilder::push(
+                    "c:test:32",
+                    "b:test:32",
+                    object,
+                    text,
+                    None,
+                    QueryPushSuggest::Incremental,
+                    None,
+                    &[],
+                    snippet,
+                    false,
+                )
+            {
+                assert!(ExecutorPush::execute(
+                    store, lexer, suggest, boost, weights, snippet, verbose
+                )
+                .is_ok());
+            } else {
+                panic!("push query could not be built");
+            }
+        }
+
+        let search = |snippets| {
+            if let Ok(Query::Search(
+                store,
+                query_id,
+                lexer,
+                limit,
+                offset,
+                timeout,
+                typos,
+                completion,
+                snippets,
+                with_terms,
+            )) = QueryBuilder::search(
+                "id1",
+                "c:test:32",
+                "b:test:32",
+                "lighthouse",
+                10,
+                0,
+                None,
+                QuerySearchTimeout::Partial,
+                1,
+                QuerySearchCompletion::AllTerms,
+                snippets,
+                false,
+            ) {
+                ExecutorSearch::execute(
+                    store, query_id, lexer, limit, offset, timeout, typos, completion, snippets,
+                    with_terms,
+                )
+                .unwrap()
+                .unwrap()
+            } else {
+                panic!("search query could not be built");
+            }
+        };
+
+        // Snippets are capped to their maximum length, and only returned when requested
+        assert_eq!(search(false), vec!["o:3", "o:2", "o:1"]);
+        assert_eq!(
+            search(true),
+            vec![
+                format!(
+                    "o:3 \"{}\"",
+                    &snippet_long[..APP_CONF.store.kv.snippet_length_maximum]
+                ),
+                "o:2 \"Lighthouse \\\"at\\\" night\"".to_string(),
+                "o:1 \"\"".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_returns_matched_terms_with_results() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:65").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:65").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        for (object, text) in &[
+            ("o:1", "The lighthouse keeper"),
+            ("o:2", "Lighthouse at night"),
+            ("o:3", "Keeper of the harbor"),
+        ] {
+            if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+                QueryBuilder::push(
+                    "c:test:65",
+                    "b:test:65",
+                    object,
+                    text,
+                    None,
+                    QueryPushSuggest::Incremental,
+                    None,
+                    &[],
+                    Some("Harbor log"),
+                    false,
+                )
+            {
+                assert!(ExecutorPush::execute(
+                    store, lexer, suggest, boost, weights, snippet, verbose
+                )
+                .is_ok());
+            } else {
+                panic!("push query could not be built");
+            }
+        }
+
+        let search = |terms, snippets| {
+            if let Ok(Query::SearchBoolean(
+                store,
+                query_id,
+                expression,
+                limit,
+                offset,
+                snippets,
+                with_terms,
+            )) = QueryBuilder::search_boolean(
+                "id1",
+                "c:test:65",
+                "b:test:65",
+                terms,
+                10,
+                0,
+                snippets,
+                true,
+            ) {
+                let mut oids = ExecutorSearch::execute_boolean(
+                    store, query_id, expression, limit, offset, snippets, with_terms,
+                )
+                .unwrap()
+                .unwrap();
+
+                oids.sort();
+
+                oids
+            } else {
+                panic!("search query could not be built");
+            }
+        };
+
+        // Matched terms are listed in query order, and come after the snippet (if requested)
+        assert_eq!(
+            search("keeper OR lighthouse", false),
+            vec![
+                "o:1 \"keeper lighthouse\"",
+                "o:2 \"lighthouse\"",
+                "o:3 \"keeper\""
+            ]
+        );
+        assert_eq!(
+            search("lighthouse AND NOT night", true),
+            vec!["o:1 \"Harbor log\" \"lighthouse\""]
+        );
+    }
+
+    #[test]
+    fn it_ignores_rare_terms() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:20").unwrap();
+        let kv_action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:20").unwrap(), store);
+
+        assert!(kv_action.batch_erase_bucket().is_ok());
+
+        let (common_hashed, rare_hashed, misspelt_hashed) = (
+            StoreTermHash::from("invoice"),
+            StoreTermHash::from("receipt"),
+            StoreTermHash::from("invoise"),
+        );
+
+        assert!(kv_action
+            .set_term_to_iids(common_hashed, &[3, 2, 1])
+            .is_ok());
+        assert!(kv_action.set_term_to_iids(rare_hashed, &[2]).is_ok());
+
+        // Consolidate the common word into the graph, as misspelt terms are corrected from \
+        //   the graph
+        {
+            let _consolidate = CONSOLIDATE_TEST_LOCK.lock().unwrap();
+
+            StoreFSTActionBuilder::access(StoreFSTPool::acquire("c:test:20", "b:test:20").unwrap())
+                .push_word("invoice");
+
+            StoreFSTPool::consolidate(true);
+        }
+
+        let fst_action =
+            StoreFSTActionBuilder::access(StoreFSTPool::acquire("c:test:20", "b:test:20").unwrap());
+
+        let intersect = |terms: &[(&str, StoreTermHashed)], frequency_minimum| {
+            ExecutorSearch::intersect_terms(
+                &kv_action,
+                Some(&fst_action),
+                1,
+                QuerySearchCompletion::AllTerms,
+                frequency_minimum,
+                None,
+                terms
+                    .iter()
+                    .map(|(term, term_hashed)| (term.to_string(), *term_hashed))
+                    .collect(),
+                None,
+                None,
+                QuerySearchTimeout::Error,
+            )
+            .map(|(found_iids, _, _)| found_iids)
+        };
+
+        // Rare terms are ignored, while a misspelt term is kept once corrected
+        assert_eq!(
+            intersect(&[("invoise", misspelt_hashed), ("receipt", rare_hashed)], 2),
+            Ok(LinkedHashSet::from_iter(vec![3, 2, 1]))
+        );
+        assert_eq!(
+            intersect(&[("invoise", misspelt_hashed), ("receipt", rare_hashed)], 1),
+            Ok(LinkedHashSet::from_iter(vec![2]))
+        );
+
+        // All terms are rare: they are intersected as if no minimum applied
+        assert_eq!(
+            intersect(&[("invoise", misspelt_hashed), ("receipt", rare_hashed)], 4),
+            Ok(LinkedHashSet::from_iter(vec![2]))
+        );
+    }
+
+    #[test]
+    fn it_stops_terms_once_time_budget_is_spent() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:23").unwrap();
+        let fst_store = StoreFSTPool::acquire("c:test:23", "b:test:23").unwrap();
+
+        let (kv_action, fst_action) = (
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:23").unwrap(), store),
+            StoreFSTActionBuilder::access(fst_store),
+        );
+
+        assert!(kv_action.batch_erase_bucket().is_ok());
+
+        let (first_hashed, second_hashed) = (
+            StoreTermHash::from("harbor"),
+            StoreTermHash::from("lighthouse"),
+        );
+
+        assert!(kv_action.set_term_to_iids(first_hashed, &[3, 2, 1]).is_ok());
+        assert!(kv_action.set_term_to_iids(second_hashed, &[2]).is_ok());
+
+        let terms = || {
+            vec![
+                ("harbor".to_string(), first_hashed),
+                ("lighthouse".to_string(), second_hashed),
+            ]
+        };
+
+        // No budget: all terms are read and intersected
+        assert_eq!(
+            ExecutorSearch::intersect_terms(
+                &kv_action,
+                Some(&fst_action),
+                1,
+                QuerySearchCompletion::AllTerms,
+                0,
+                None,
+                terms(),
+                None,
+                None,
+                QuerySearchTimeout::Error
+            ),
+            Ok((LinkedHashSet::from_iter(vec![2]), 4, false))
+        );
+
+        // Budget already spent: only the first term is read
+        assert_eq!(
+            ExecutorSearch::intersect_terms(
+                &kv_action,
+                Some(&fst_action),
+                1,
+                QuerySearchCompletion::AllTerms,
+                0,
+                None,
+                terms(),
+                None,
+                Some(Instant::now()),
+                QuerySearchTimeout::Partial
+            ),
+            Ok((LinkedHashSet::from_iter(vec![3, 2, 1]), 3, true))
+        );
+        assert_eq!(
+            ExecutorSearch::intersect_terms(
+                &kv_action,
+                Some(&fst_action),
+                1,
+                QuerySearchCompletion::AllTerms,
+                0,
+                None,
+                terms(),
+                None,
+                Some(Instant::now()),
+                QuerySearchTimeout::Error
+            ),
+            Err(StoreOperationError::Timeout)
+        );
+
+        // Budget spent while reading the last term: partial results are complete, while \
+        //   erroring queries still fail
+        assert_eq!(
+            ExecutorSearch::intersect_terms(
+                &kv_action,
+                Some(&fst_action),
+                1,
+                QuerySearchCompletion::AllTerms,
+                0,
+                None,
+                vec![("lighthouse".to_string(), second_hashed)],
+                None,
+                Some(Instant::now()),
+                QuerySearchTimeout::Partial
+            ),
+            Ok((LinkedHashSet::from_iter(vec![2]), 1, false))
+        );
+        assert_eq!(
+            ExecutorSearch::intersect_terms(
+                &kv_action,
+                Some(&fst_action),
+                1,
+                QuerySearchCompletion::AllTerms,
+                0,
+                None,
+                vec![("lighthouse".to_string(), second_hashed)],
+                None,
+                Some(Instant::now()),
+                QuerySearchTimeout::Error
+            ),
+            Err(StoreOperationError::Timeout)
+        );
+
+        // Budget spent on prefetched terms: only the first prefetched term is read
+        let prefetched_iids = || {
+            kv_action
+                .get_terms_to_iids(&[first_hashed, second_hashed])
+                .ok()
+        };
+
+        assert_eq!(
+            ExecutorSearch::intersect_terms(
+                &kv_action,
+                Some(&fst_action),
+                1,
+                QuerySearchCompletion::AllTerms,
+                0,
+                None,
+                terms(),
+                prefetched_iids(),
+                Some(Instant::now()),
+                QuerySearchTimeout::Partial
+            ),
+            Ok((LinkedHashSet::from_iter(vec![3, 2, 1]), 3, true))
+        );
+        assert_eq!(
+            ExecutorSearch::intersect_terms(
+                &kv_action,
+                Some(&fst_action),
+                1,
+                QuerySearchCompletion::AllTerms,
+                0,
+                None,
+                terms(),
+                prefetched_iids(),
+                Some(Instant::now()),
+                QuerySearchTimeout::Error
+            ),
+            Err(StoreOperationError::Timeout)
+        );
+    }
+
+    #[test]
+    fn it_marks_partial_results() {
+        assert_eq!(ExecutorSearch::mark_partial(None, false), None);
+        assert_eq!(
+            ExecutorSearch::mark_partial(Some(vec!["a".to_string()]), false),
+            Some(vec!["a".to_string()])
+        );
+        assert_eq!(
+            ExecutorSearch::mark_partial(None, true),
+            Some(vec![RESULTS_PARTIAL_MARKER.to_string()])
+        );
+        assert_eq!(
+            ExecutorSearch::mark_partial(Some(vec!["a".to_string()]), true),
+            Some(vec!["a".to_string(), RESULTS_PARTIAL_MARKER.to_string()])
+        );
+        assert!(!ExecutorSearch::is_time_budget_spent(None));
+        assert!(ExecutorSearch::is_time_budget_spent(Some(Instant::now())));
+    }
+
+    #[test]
+    fn it_completes_terms_per_query() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:74").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:74").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        for (object, text) in &[("o:1", "lighthouse keeper"), ("o:2", "lightship keeper")] {
+            if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+                QueryBuilder::push(
+                    "c:test:74",
+                    "b:test:74",
+                    object,
+                    text,
+                    None,
+                    QueryPushSuggest::Incremental,
+                    None,
+                    &[],
+                    None,
+                    false,
+                )
+            {
+                assert!(ExecutorPush::execute(
+                    store, lexer, suggest, boost, weights, snippet, verbose
+                )
+                .is_ok());
+            } else {
+                panic!("push query could not be built");
+            }
+        }
+
+        // Consolidate pushed words into the graph, as terms are completed from the graph
+        {
+            let _consolidate = CONSOLIDATE_TEST_LOCK.lock().unwrap();
+
+            StoreFSTPool::consolidate(true);
+        }
+
+        let search = |terms, completion| {
+            if let Ok(Query::Search(
+                store,
+                query_id,
+                lexer,
+                limit,
+                offset,
+                timeout,
+                typos,
+                completion,
+                snippets,
+                with_terms,
+            )) = QueryBuilder::search(
+                "id1",
+                "c:test:74",
+                "b:test:74",
+                terms,
+                10,
+                0,
+                None,
+                QuerySearchTimeout::Partial,
+                0,
+                completion,
+                false,
+                false,
+            ) {
+                let mut oids = ExecutorSearch::execute(
+                    store, query_id, lexer, limit, offset, timeout, typos, completion, snippets,
+                    with_terms,
+                )
+                .unwrap()
+                .unwrap_or_default();
+
+                oids.sort();
+
+                oids
+            } else {
+                panic!("search query could not be built");
+            }
+        };
+
+        // All terms get completed by default
+        assert_eq!(
+            search("keep light", QuerySearchCompletion::AllTerms),
+            vec!["o:1", "o:2"]
+        );
+
+        // A partial query only completes its last term
+        assert_eq!(
+            search("keeper light", QuerySearchCompletion::LastTerm),
+            vec!["o:1", "o:2"]
+        );
+        assert!(search("keep lighthouse", QuerySearchCompletion::LastTerm).is_empty());
+
+        // A query that is not partial completes none of its terms
+        assert!(search("keeper light", QuerySearchCompletion::Disabled).is_empty());
+        assert_eq!(
+            search("keeper lighthouse", QuerySearchCompletion::Disabled),
+            vec!["o:1"]
+        );
+    }
+
+    #[test]
+    fn it_orders_terms_by_list_size() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:38").unwrap();
+        let kv_action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:38").unwrap(), store);
+
+        assert!(kv_action.batch_erase_bucket().is_ok());
+
+        let (large_hashed, medium_hashed, small_hashed) = (
+            StoreTermHash::from("ocean"),
+            StoreTermHash::from("current"),
+            StoreTermHash::from("reef"),
+        );
+
+        let large_iids: Vec<StoreObjectIID> = (0..50).rev().collect();
+        let medium_iids: Vec<StoreObjectIID> = (10..50).rev().collect();
+
+        assert!(kv_action
+            .set_term_to_iids(large_hashed, &large_iids)
+            .is_ok());
+        assert!(kv_action
+            .set_term_to_iids(medium_hashed, &medium_iids)
+            .is_ok());
+        assert!(kv_action
+            .set_term_to_iids(small_hashed, &[101, 100])
+            .is_ok());
+
+        let terms = vec![
+            ("ocean".to_string(), large_hashed),
+            ("current".to_string(), medium_hashed),
+            ("reef".to_string(), small_hashed),
+        ];
+        let terms_hashed = vec![large_hashed, medium_hashed, small_hashed];
+
+        let terms_size = kv_action.get_terms_size(&terms_hashed);
+
+        assert_eq!(terms_size, vec![50, 40, 2]);
+
+        let (ordered_terms, ordered_prefetched_iids) =
+            ExecutorSearch::order_terms(terms.clone(), None, &terms_size);
+
+        assert_eq!(
+            ordered_terms,
+            vec![
+                ("reef".to_string(), small_hashed),
+                ("current".to_string(), medium_hashed),
+                ("ocean".to_string(), large_hashed),
+            ]
+        );
+        assert!(ordered_prefetched_iids.is_none());
+
+        // Both orders find no IID in common, but the ordered intersection stops early
+        assert_eq!(
+            ExecutorSearch::intersect_terms(
+                &kv_action,
+                None,
+                0,
+                QuerySearchCompletion::AllTerms,
+                0,
+                None,
+                terms,
+                None,
+                None,
+                QuerySearchTimeout::Error
+            ),
+            Ok((LinkedHashSet::new(), 92, false))
+        );
+        assert_eq!(
+            ExecutorSearch::intersect_terms(
+                &kv_action,
+                None,
+                0,
+                QuerySearchCompletion::AllTerms,
+                0,
+                None,
+                ordered_terms,
+                ordered_prefetched_iids,
+                None,
+                QuerySearchTimeout::Error
+            ),
+            Ok((LinkedHashSet::new(), 42, false))
+        );
+
+        // Prefetched lists are ordered along with their terms
+        let (_, ordered_prefetched_iids) = ExecutorSearch::order_terms(
+            vec![
+                ("ocean".to_string(), large_hashed),
+                ("reef".to_string(), small_hashed),
+            ],
+            Some(vec![Some(large_iids), None]),
+            &[50, 0],
+        );
+
+        assert_eq!(
+            ordered_prefetched_iids.map(|prefetched_iids| prefetched_iids
+                .iter()
+                .map(|iids| iids.as_ref().map(Vec::len))
+                .collect::<Vec<_>>()),
+            Some(vec![None, Some(50)])
+        );
+    }
+}
+
+#[cfg(all(feature = "benchmark", test))]
+mod benches {
+    extern crate test;
+
+    use super::*;
+    use crate::executor::push::ExecutorPush;
+    use crate::query::builder::QueryBuilder;
+    use crate::query::types::QueryPushSuggest;
+    use crate::store::item::StoreItemPart;
+    use test::Bencher;
+
+    const CORPUS_OBJECTS: usize = 1000;
+
+    const CORPUS_WORDS: &[&str] = &[
+        "lighthouse",
+        "harbor",
+        "keeper",
+        "night",
+        "storm",
+        "ship",
+        "anchor",
+        "coast",
+        "signal",
+        "wave",
+        "tide",
+        "beacon",
+        "sailor",
+        "fog",
+        "rock",
+        "island",
+    ];
+
+    fn build_corpus(collection: &str) {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, collection).unwrap();
+
+        StoreKVActionBuilder::access(StoreItemPart::from_str("b:bench:1").unwrap(), store)
+            .batch_erase_bucket()
+            .ok();
+
+        // Push synthetic messages, made of words picked from the corpus
+        for index in 0..CORPUS_OBJECTS {
+            let text = (0..12)
+                .map(|word| CORPUS_WORDS[(index * 7 + word * 3) % CORPUS_WORDS.len()])
+                .collect::<Vec<&str>>()
+                .join(" ");
+
+            if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+                QueryBuilder::push(
+                    collection,
+                    "b:bench:1",
+                    &format!("o:{}", index),
+                    &text,
+                    None,
+                    QueryPushSuggest::Incremental,
+                    None,
+                    &[],
+                    None,
+                    false,
+                )
+            {
+                ExecutorPush::execute(store, lexer, suggest, boost, weights, snippet, verbose).ok();
             }
         }
+    }
+
+    #[bench]
+    fn bench_search_plain(b: &mut Bencher) {
+        build_corpus("c:bench:search:1");
 
-        Err(())
+        b.iter(|| {
+            if let Ok(Query::Search(
+                store,
+                query_id,
+                lexer,
+                limit,
+                offset,
+                timeout,
+                typos,
+                completion,
+                snippets,
+                with_terms,
+            )) = QueryBuilder::search(
+                "bench1",
+                "c:bench:search:1",
+                "b:bench:1",
+                "lighthouse keeper",
+                10,
+                0,
+                None,
+                QuerySearchTimeout::Partial,
+                1,
+                QuerySearchCompletion::AllTerms,
+                false,
+                false,
+            ) {
+                ExecutorSearch::execute(
+                    store, query_id, lexer, limit, offset, timeout, typos, completion, snippets,
+                    with_terms,
+                )
+                .ok()
+            } else {
+                None
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_search_boolean(b: &mut Bencher) {
+        build_corpus("c:bench:search:2");
+
+        b.iter(|| {
+            if let Ok(Query::SearchBoolean(
+                store,
+                query_id,
+                expression,
+                limit,
+                offset,
+                snippets,
+                with_terms,
+            )) = QueryBuilder::search_boolean(
+                "bench2",
+                "c:bench:search:2",
+                "b:bench:1",
+                "(lighthouse OR beacon) AND NOT storm",
+                10,
+                0,
+                false,
+                false,
+            ) {
+                ExecutorSearch::execute_boolean(
+                    store, query_id, expression, limit, offset, snippets, with_terms,
+                )
+                .ok()
+            } else {
+                None
+            }
+        });
     }
 }
@@ -0,0 +1,142 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use crate::store::item::{StoreItem, StoreItemPart};
+use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
+use crate::store::snapshot::{StoreSnapshot, StoreSnapshotDiff, StoreSnapshotPool};
+
+pub struct ExecutorSnapshot;
+
+impl ExecutorSnapshot {
+    pub fn execute(store: StoreItem, name: StoreItemPart) -> Result<u32, StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            general_kv_access_lock_read!();
+
+            let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?;
+
+            // Important: acquire bucket store read lock (this holds writes back while the \
+            //   bucket objects are listed, making the snapshot consistent)
+            executor_kv_lock_read!(kv_store);
+
+            let kv_action = StoreKVActionBuilder::access(bucket, kv_store);
+
+            let snapshot = StoreSnapshot::from_objects(kv_action.get_objects_terms()?);
+            let count = snapshot.count() as u32;
+
+            info!(
+                "took snapshot: {} of bucket: {} with {} objects",
+                name.as_str(),
+                bucket.as_str(),
+                count
+            );
+
+            StoreSnapshotPool::insert(
+                collection.as_str(),
+                bucket.as_str(),
+                name.as_str(),
+                snapshot,
+            );
+
+            return Ok(count);
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+
+    pub fn execute_diff(
+        store: StoreItem,
+        from_name: StoreItemPart,
+        to_name: StoreItemPart,
+    ) -> Result<StoreSnapshotDiff, StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Diff both snapshots? (both must have been taken, and not dropped since)
+            if let (Some(from_snapshot), Some(to_snapshot)) = (
+                StoreSnapshotPool::get(collection.as_str(), bucket.as_str(), from_name.as_str()),
+                StoreSnapshotPool::get(collection.as_str(), bucket.as_str(), to_name.as_str()),
+            ) {
+                return Ok(from_snapshot.diff(&to_snapshot));
+            }
+
+            info!(
+                "could not diff snapshots: {} and {} of bucket: {} (not found)",
+                from_name.as_str(),
+                to_name.as_str(),
+                bucket.as_str()
+            );
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::identifiers::StoreTermHash;
+    use crate::store::item::StoreItemBuilder;
+
+    #[test]
+    fn it_diffs_bucket_snapshots() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:19").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:19").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        let snapshot = |name| {
+            ExecutorSnapshot::execute(
+                StoreItemBuilder::from_depth_2("c:test:19", "b:test:19").unwrap(),
+                StoreItemPart::from_str(name).unwrap(),
+            )
+        };
+
+        let (term_1, term_2) = (StoreTermHash::from("hello"), StoreTermHash::from("world"));
+
+        for (iid, oid) in [(0, "o:kept"), (1, "o:deleted")] {
+            assert!(action.set_iid_to_oid(iid, oid).is_ok());
+            assert!(action.set_oid_to_iid(oid, iid).is_ok());
+            assert!(action.set_iid_to_terms(iid, &[term_1]).is_ok());
+        }
+
+        assert_eq!(snapshot("s:before"), Ok(2));
+
+        // Insert an object, delete an object, and update the kept object terms
+        let mut batch = action.batch();
+
+        assert!(action
+            .batch_flush_bucket(&mut batch, 1, "o:deleted", &[term_1])
+            .is_ok());
+        assert!(batch.commit().is_ok());
+        assert!(action.set_iid_to_oid(2, "o:inserted").is_ok());
+        assert!(action.set_iid_to_terms(2, &[term_2]).is_ok());
+        assert!(action.set_iid_to_terms(0, &[term_1, term_2]).is_ok());
+
+        assert_eq!(snapshot("s:after"), Ok(2));
+
+        assert_eq!(
+            ExecutorSnapshot::execute_diff(
+                StoreItemBuilder::from_depth_2("c:test:19", "b:test:19").unwrap(),
+                StoreItemPart::from_str("s:before").unwrap(),
+                StoreItemPart::from_str("s:after").unwrap(),
+            ),
+            Ok(StoreSnapshotDiff {
+                added: vec!["o:inserted".to_string()],
+                removed: vec!["o:deleted".to_string()],
+                changed: vec![("o:kept".to_string(), 1, 0)],
+            })
+        );
+
+        assert!(ExecutorSnapshot::execute_diff(
+            StoreItemBuilder::from_depth_2("c:test:19", "b:test:19").unwrap(),
+            StoreItemPart::from_str("s:before").unwrap(),
+            StoreItemPart::from_str("s:unknown").unwrap(),
+        )
+        .is_err());
+    }
+}
@@ -4,10 +4,16 @@
 // Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
+use std::cmp::Reverse;
+
 use crate::lexer::token::TokenLexer;
 use crate::query::types::{QuerySearchID, QuerySearchLimit};
 use crate::store::fst::{StoreFSTActionBuilder, StoreFSTPool};
+use crate::store::identifiers::{StoreTermHash, StoreTermHashed};
 use crate::store::item::StoreItem;
+use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
+use crate::APP_CONF;
 
 pub struct ExecutorSuggest;
 
@@ -17,23 +23,126 @@ impl ExecutorSuggest {
         _event_id: QuerySearchID,
         mut lexer: TokenLexer<'a>,
         limit: QuerySearchLimit,
-    ) -> Result<Option<Vec<String>>, ()> {
+    ) -> Result<Option<Vec<String>>, StoreOperationError> {
         if let StoreItem(collection, Some(bucket), None) = store {
-            // Important: acquire graph access read lock, and reference it in context. This \
-            //   prevents the graph from being erased while using it in this block.
+            // Important: acquire database and graph access read locks, and reference them in \
+            //   context. This prevents the database and graph from being erased while using \
+            //   them in this block.
+            general_kv_access_lock_read!();
             general_fst_access_lock_read!();
 
-            if let Ok(fst_store) = StoreFSTPool::acquire(collection, bucket) {
-                let fst_action = StoreFSTActionBuilder::access(fst_store);
+            let (kv_store, fst_store) = (
+                StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?,
+                StoreFSTPool::acquire(collection, bucket)?,
+            );
+
+            // Important: acquire bucket store read lock
+            executor_kv_lock_read!(kv_store);
+
+            let (kv_action, fst_action) = (
+                StoreKVActionBuilder::access(bucket, kv_store),
+                StoreFSTActionBuilder::access(fst_store),
+            );
+
+            if let (Some(word), None) = (lexer.next(), lexer.next()) {
+                debug!("running suggest on word: {}", word.0);
+
+                // Notice: more words than requested are looked up, as words come out of the \
+                //   graph in lexicographic order, while the most frequent words are to be \
+                //   suggested first.
+                let candidates_limit =
+                    APP_CONF.channel.search.suggest_limit_maximum.max(limit) as usize;
 
-                if let (Some(word), None) = (lexer.next(), lexer.next()) {
-                    debug!("running suggest on word: {}", word.0);
+                return Ok(fst_action
+                    .suggest_words(&word.0, candidates_limit, None)
+                    .map(|words| {
+                        // Count objects linked to each word (words from a bucket with no \
+                        //   database have no frequency, and are kept in graph order)
+                        let terms_hashed: Vec<StoreTermHashed> =
+                            words.iter().map(|word| StoreTermHash::from(word)).collect();
 
-                    return Ok(fst_action.suggest_words(&word.0, limit as usize, None));
-                }
+                        let frequencies = kv_action.get_terms_size(&terms_hashed);
+
+                        Self::order_words(&word.0, words, frequencies, limit as usize)
+                    }));
             }
         }
 
-        Err(())
+        Err(StoreOperationError::Failed)
+    }
+
+    fn order_words(
+        from_word: &str,
+        words: Vec<String>,
+        frequencies: Vec<usize>,
+        limit: usize,
+    ) -> Vec<String> {
+        let mut ranked_words: Vec<(String, usize)> = words.into_iter().zip(frequencies).collect();
+
+        // Rank completions of the word first, then typo corrections (which the graph only \
+        //   returns when there are not enough completions), each by frequency
+        // Notice: this sort is stable, so that words of equal frequency keep their graph order.
+        ranked_words
+            .sort_by_key(|(word, frequency)| (!word.starts_with(from_word), Reverse(*frequency)));
+
+        ranked_words
+            .into_iter()
+            .take(limit)
+            .map(|(word, _)| word)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_orders_words_by_frequency() {
+        let words = || {
+            vec![
+                "falcon".to_string(),
+                "falconer".to_string(),
+                "falconry".to_string(),
+                "falcons".to_string(),
+            ]
+        };
+
+        assert_eq!(
+            ExecutorSuggest::order_words("falco", words(), vec![1, 4, 0, 4], 3),
+            vec![
+                "falconer".to_string(),
+                "falcons".to_string(),
+                "falcon".to_string()
+            ]
+        );
+        assert_eq!(
+            ExecutorSuggest::order_words("falco", words(), vec![0, 0, 0, 0], 2),
+            vec!["falcon".to_string(), "falconer".to_string()]
+        );
+    }
+
+    #[test]
+    fn it_orders_completions_before_typo_corrections() {
+        // Typo corrections come last, even when they are more frequent than completions
+        let words = vec![
+            "falcon".to_string(),
+            "falconer".to_string(),
+            "balcony".to_string(),
+            "falkon".to_string(),
+        ];
+
+        assert_eq!(
+            ExecutorSuggest::order_words("falco", words.clone(), vec![1, 2, 9, 5], 3),
+            vec![
+                "falconer".to_string(),
+                "falcon".to_string(),
+                "balcony".to_string()
+            ]
+        );
+        assert_eq!(
+            ExecutorSuggest::order_words("falco", words, vec![1, 2, 9, 5], 2),
+            vec!["falconer".to_string(), "falcon".to_string()]
+        );
     }
 }
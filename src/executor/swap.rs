@@ -0,0 +1,193 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use crate::store::fst::StoreFSTActionBuilder;
+use crate::store::item::{StoreItem, StoreItemPart};
+use crate::store::kv::{StoreKVAcquireMode, StoreKVActionBuilder, StoreKVPool};
+use crate::store::operation::StoreOperationError;
+
+pub struct ExecutorSwap;
+
+impl ExecutorSwap {
+    pub fn execute<'a>(
+        store: StoreItem<'a>,
+        with_bucket: StoreItemPart<'a>,
+    ) -> Result<u32, StoreOperationError> {
+        if let StoreItem(collection, Some(bucket), None) = store {
+            // Swapping a bucket with itself? (nothing to do)
+            if bucket.as_str() == with_bucket.as_str() {
+                return Ok(0);
+            }
+
+            // Important: acquire database access read lock, and reference it in context. This \
+            //   prevents the database from being erased while using it in this block.
+            // Notice: acquire FST lock in write mode, as we will swap graphs.
+            general_kv_access_lock_read!();
+            general_fst_access_lock_write!();
+
+            let kv_store = StoreKVPool::acquire(StoreKVAcquireMode::OpenOnly, collection)?;
+
+            // Important: acquire bucket store write lock (both buckets live in the same \
+            //   collection store, thus this locks both of them, and holds readers back \
+            //   until both buckets are swapped)
+            executor_kv_lock_write!(kv_store);
+
+            let (bucket_str, with_bucket_str) = (bucket.as_str(), with_bucket.as_str());
+
+            let (kv_action, with_kv_action) = (
+                StoreKVActionBuilder::access(bucket, kv_store.clone()),
+                StoreKVActionBuilder::access(with_bucket, kv_store),
+            );
+
+            // Any bucket marked as immutable? (reject write)
+            kv_action.check_mutable()?;
+            with_kv_action.check_mutable()?;
+
+            // Any bucket hash colliding with another bucket? (reject write)
+            kv_action.check_bucket()?;
+            with_kv_action.check_bucket()?;
+
+            // Database stalling writes? (reject write if configured to do so)
+            if kv_action.check_write_stall().is_err() {
+                return Err(StoreOperationError::StoreBusy);
+            }
+
+            // Swap graphs first, as this can be reverted should the database swap fail
+            if StoreFSTActionBuilder::swap(collection.as_str(), bucket_str, with_bucket_str).is_ok()
+            {
+                if let Ok(count) = kv_action.batch_swap_bucket(&with_kv_action) {
+                    return Ok(count);
+                }
+
+                error!("failed executing batch-swap-bucket in swap executor");
+
+                if StoreFSTActionBuilder::swap(collection.as_str(), bucket_str, with_bucket_str)
+                    .is_err()
+                {
+                    error!("failed reverting fst swap in swap executor");
+                }
+            }
+        }
+
+        Err(StoreOperationError::Failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::flushb::ExecutorFlushB;
+    use crate::executor::push::ExecutorPush;
+    use crate::executor::search::ExecutorSearch;
+    use crate::query::actions::Query;
+    use crate::query::builder::QueryBuilder;
+    use crate::query::types::{QueryPushSuggest, QuerySearchCompletion, QuerySearchTimeout};
+    use crate::store::item::StoreItemBuilder;
+
+    fn push(bucket: &str, object: &str, text: &str) {
+        if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+            QueryBuilder::push(
+                "c:test:33",
+                bucket,
+                object,
+                text,
+                None,
+                QueryPushSuggest::Incremental,
+                None,
+                &[],
+                None,
+                false,
+            )
+        {
+            assert!(
+                ExecutorPush::execute(store, lexer, suggest, boost, weights, snippet, verbose)
+                    .is_ok()
+            );
+        } else {
+            panic!("push query could not be built");
+        }
+    }
+
+    fn search(bucket: &str, terms: &str) -> Vec<String> {
+        if let Ok(Query::Search(
+            store,
+            query_id,
+            lexer,
+            limit,
+            offset,
+            timeout,
+            typos,
+            completion,
+            snippets,
+            with_terms,
+        )) = QueryBuilder::search(
+            "id1",
+            "c:test:33",
+            bucket,
+            terms,
+            10,
+            0,
+            None,
+            QuerySearchTimeout::Partial,
+            1,
+            QuerySearchCompletion::AllTerms,
+            false,
+            false,
+        ) {
+            ExecutorSearch::execute(
+                store, query_id, lexer, limit, offset, timeout, typos, completion, snippets,
+                with_terms,
+            )
+            .unwrap()
+            .unwrap_or_default()
+        } else {
+            panic!("search query could not be built");
+        }
+    }
+
+    fn swap(bucket: &str, with_bucket: &str) -> Result<u32, StoreOperationError> {
+        ExecutorSwap::execute(
+            StoreItemBuilder::from_depth_2("c:test:33", bucket).unwrap(),
+            StoreItemPart::from_str(with_bucket).unwrap(),
+        )
+    }
+
+    #[test]
+    fn it_swaps_buckets() {
+        for bucket in &["b:test:33:live", "b:test:33:next", "b:test:33:empty"] {
+            assert!(ExecutorFlushB::execute(
+                StoreItemBuilder::from_depth_2("c:test:33", bucket).unwrap()
+            )
+            .is_ok());
+        }
+
+        push("b:test:33:live", "o:old", "old content");
+        push("b:test:33:next", "o:new:1", "new content");
+        push("b:test:33:next", "o:new:2", "new stuff");
+
+        // Swap the freshly built bucket in
+        assert_eq!(swap("b:test:33:live", "b:test:33:next"), Ok(3));
+
+        assert_eq!(search("b:test:33:live", "content"), vec!["o:new:1"]);
+        assert!(search("b:test:33:live", "old").is_empty());
+        assert_eq!(search("b:test:33:next", "old"), vec!["o:old"]);
+
+        // Push to the swapped bucket (its objects must not be overwritten by new objects)
+        push("b:test:33:live", "o:new:3", "new content");
+
+        assert_eq!(
+            search("b:test:33:live", "new"),
+            vec!["o:new:3", "o:new:2", "o:new:1"]
+        );
+
+        // Swap with an empty bucket
+        assert_eq!(swap("b:test:33:next", "b:test:33:empty"), Ok(1));
+
+        assert!(search("b:test:33:next", "old").is_empty());
+        assert_eq!(search("b:test:33:empty", "old"), vec!["o:old"]);
+        assert_eq!(swap("b:test:33:live", "b:test:33:live"), Ok(0));
+    }
+}
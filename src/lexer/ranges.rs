@@ -7,7 +7,7 @@
 use std::fmt;
 use whatlang::{detect_script, Script};
 
-struct LexerRange;
+pub struct LexerRange;
 
 #[derive(PartialEq, Debug)]
 pub struct LexerRegexRange(&'static [(char, char)]);
@@ -43,6 +43,10 @@ const RANGE_MYANMAR: &[(char, char)] = &[('\u{1000}', '\u{109F}')];
 const RANGE_SINHALA: &[(char, char)] = &[('\u{0D80}', '\u{0DFF}')];
 const RANGE_KHMER: &[(char, char)] = &[('\u{1780}', '\u{17FF}'), ('\u{19E0}', '\u{19FF}')];
 
+// Ranges of scripts written without spaces between words (Chinese, Japanese and Thai)
+const RANGES_UNSPACED: &[&[(char, char)]] =
+    &[RANGE_MANDARIN, RANGE_HIRAGANA, RANGE_KATAKANA, RANGE_THAI];
+
 impl LexerRange {
     pub fn from(text: &str) -> Option<&'static [(char, char)]> {
         detect_script(text).map(|script| match script {
@@ -73,6 +77,14 @@ impl LexerRange {
             Script::Khmer => RANGE_KHMER,
         })
     }
+
+    pub fn is_unspaced(character: char) -> bool {
+        RANGES_UNSPACED.iter().any(|ranges| {
+            ranges
+                .iter()
+                .any(|range| character >= range.0 && character <= range.1)
+        })
+    }
 }
 
 impl LexerRegexRange {
@@ -115,6 +127,17 @@ mod tests {
         assert_eq!(LexerRange::from("Доброе утро."), Some(RANGE_CYRILLIC));
     }
 
+    #[test]
+    fn it_detects_unspaced_characters() {
+        assert!(LexerRange::is_unspaced('狐'));
+        assert!(LexerRange::is_unspaced('の'));
+        assert!(LexerRange::is_unspaced('ー'));
+        assert!(LexerRange::is_unspaced('ก'));
+        assert!(!LexerRange::is_unspaced('f'));
+        assert!(!LexerRange::is_unspaced('。'));
+        assert!(!LexerRange::is_unspaced('한'));
+    }
+
     #[test]
     fn it_gives_regex_range() {
         assert_eq!(
@@ -4,19 +4,21 @@
 // Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
-use hashbrown::HashSet;
+use hashbrown::{HashMap, HashSet};
+use std::str::SplitWhitespace;
+use std::sync::Arc;
 use std::time::Instant;
+use std::vec::IntoIter;
 use unicode_segmentation::{UnicodeSegmentation, UnicodeWords};
 use whatlang::{
     detect as lang_detect_all, detect_lang as lang_detect, detect_script as script_detect, Lang,
 };
 
-#[cfg(feature = "tokenizer-chinese")]
-use std::vec::IntoIter;
-
+use super::ranges::LexerRange;
 use super::stopwords::LexerStopWord;
 use crate::query::types::QueryGenericLang;
-use crate::store::identifiers::{StoreTermHash, StoreTermHashed};
+use crate::store::identifiers::{StoreTermHash, StoreTermHashed, StoreTermPosition};
+use crate::APP_CONF;
 
 pub struct TokenLexerBuilder;
 
@@ -24,17 +26,23 @@ pub struct TokenLexer<'a> {
     mode: TokenLexerMode,
     locale: Option<Lang>,
     words: TokenLexerWords<'a>,
+    stopwords: Option<Arc<HashSet<String>>>,
     yields: HashSet<StoreTermHashed>,
+    positions: Option<HashMap<StoreTermHashed, Vec<StoreTermPosition>>>,
+    position: StoreTermPosition,
 }
 
 #[derive(PartialEq)]
 pub enum TokenLexerMode {
     NormalizeAndCleanup(Option<Lang>),
     NormalizeOnly,
+    Raw,
 }
 
 enum TokenLexerWords<'a> {
     UAX29(UnicodeWords<'a>),
+    Whitespace(SplitWhitespace<'a>),
+    NGram(IntoIter<&'a str>),
 
     #[cfg(feature = "tokenizer-chinese")]
     JieBa(IntoIter<&'a str>),
@@ -46,6 +54,10 @@ enum TokenLexerWords<'a> {
 const TEXT_LANG_TRUNCATE_OVER_CHARS: usize = 200;
 const TEXT_LANG_DETECT_PROCEED_OVER_CHARS: usize = 20;
 const TEXT_LANG_DETECT_NGRAM_UNDER_CHARS: usize = 60;
+const POSITIONS_PER_TERM_MAXIMUM: usize = 64;
+
+const THAI_VOWELS_LEADING: &[char] = &['\u{0E40}', '\u{0E41}', '\u{0E42}', '\u{0E43}', '\u{0E44}'];
+const THAI_VOWELS_FOLLOWING: &[char] = &['\u{0E30}', '\u{0E32}', '\u{0E33}', '\u{0E45}'];
 
 #[cfg(feature = "tokenizer-chinese")]
 lazy_static! {
@@ -83,10 +95,10 @@ impl TokenLexerBuilder {
 
                 Some(lang)
             }
-            TokenLexerMode::NormalizeOnly => {
+            TokenLexerMode::NormalizeOnly | TokenLexerMode::Raw => {
                 debug!("not detecting locale from lexer text: {}", text);
 
-                // May be 'NormalizeOnly' mode; no need to perform a locale detection
+                // May be 'NormalizeOnly' or 'Raw' mode; no need to perform a locale detection
                 None
             }
         };
@@ -248,28 +260,140 @@ impl TokenLexerBuilder {
 impl<'a> TokenLexer<'a> {
     fn new(mode: TokenLexerMode, text: &'a str, locale: Option<Lang>) -> TokenLexer<'a> {
         // Tokenize words (depending on the locale)
-        let words = match locale {
-            #[cfg(feature = "tokenizer-chinese")]
-            Some(Lang::Cmn) => TokenLexerWords::JieBa(TOKENIZER_JIEBA.cut(text, false).into_iter()),
-            #[cfg(feature = "tokenizer-japanese")]
-            Some(Lang::Jpn) => match TOKENIZER_LINDERA.tokenize(text) {
-                Ok(tokens) => TokenLexerWords::Lindera(tokens.into_iter()),
-                Err(err) => {
-                    warn!("unable to tokenize japanese, falling back: {}", err);
-
-                    TokenLexerWords::UAX29(text.unicode_words())
+        // Notice: in 'Raw' mode, words are only split on spaces, as they are already tokenized.
+        let words = if mode == TokenLexerMode::Raw {
+            TokenLexerWords::Whitespace(text.split_whitespace())
+        } else if APP_CONF.lexer.cjk_ngram {
+            // Notice: scripts without spaces are split in n-grams regardless of the locale, as \
+            //   the locale detected from a query may differ from the one detected from pushed \
+            //   text; both must be split the same way for query words to match.
+            TokenLexerWords::NGram(Self::split_ngrams(text).into_iter())
+        } else {
+            match locale {
+                #[cfg(feature = "tokenizer-chinese")]
+                Some(Lang::Cmn) => {
+                    TokenLexerWords::JieBa(TOKENIZER_JIEBA.cut(text, false).into_iter())
                 }
-            },
-            _ => TokenLexerWords::UAX29(text.unicode_words()),
+                #[cfg(feature = "tokenizer-japanese")]
+                Some(Lang::Jpn) => match TOKENIZER_LINDERA.tokenize(text) {
+                    Ok(tokens) => TokenLexerWords::Lindera(tokens.into_iter()),
+                    Err(err) => {
+                        warn!("unable to tokenize japanese, falling back: {}", err);
+
+                        TokenLexerWords::UAX29(text.unicode_words())
+                    }
+                },
+                _ => TokenLexerWords::UAX29(text.unicode_words()),
+            }
         };
 
         TokenLexer {
             mode,
             locale,
             words,
+            stopwords: None,
             yields: HashSet::new(),
+            positions: None,
+            position: 0,
         }
     }
+
+    pub fn locale(&self) -> Option<Lang> {
+        self.locale
+    }
+
+    fn split_ngrams(text: &'a str) -> Vec<&'a str> {
+        // Split words on word boundaries, save for runs of characters from scripts without \
+        //   spaces, which are split in single characters and overlapping pairs of characters
+        let (mut words, mut run_start) = (Vec::new(), None);
+
+        for (offset, segment) in text.split_word_bound_indices() {
+            if segment.starts_with(LexerRange::is_unspaced) {
+                run_start.get_or_insert(offset);
+            } else {
+                if let Some(start) = run_start.take() {
+                    Self::split_ngrams_run(&text[start..offset], &mut words);
+                }
+
+                // Only keep words (ie. drop spaces and punctuation, as for UAX29 words)
+                if segment.chars().any(char::is_alphanumeric) {
+                    words.push(segment);
+                }
+            }
+        }
+
+        if let Some(start) = run_start {
+            Self::split_ngrams_run(&text[start..], &mut words);
+        }
+
+        words
+    }
+
+    fn split_ngrams_run(run: &'a str, words: &mut Vec<&'a str>) {
+        let mut units: Vec<(usize, usize)> = Vec::new();
+        let mut is_leading = false;
+
+        // Notice: Thai vowels written before their consonant are merged with the next \
+        //   character, while vowels that follow it are merged with the previous character \
+        //   (this roughly splits Thai text in syllables, instead of single letters).
+        for (offset, grapheme) in run.grapheme_indices(true) {
+            let end = offset + grapheme.len();
+
+            match units.last_mut() {
+                Some(unit) if is_leading || grapheme.starts_with(THAI_VOWELS_FOLLOWING) => {
+                    unit.1 = end
+                }
+                _ => units.push((offset, end)),
+            }
+
+            is_leading = grapheme.starts_with(THAI_VOWELS_LEADING);
+        }
+
+        // Notice: single units are kept along with pairs, as to match queries of a single \
+        //   character (eg. '京' matches '北京').
+        for (index, unit) in units.iter().enumerate() {
+            words.push(&run[unit.0..unit.1]);
+
+            if let Some(unit_next) = units.get(index + 1) {
+                words.push(&run[unit.0..unit_next.1]);
+            }
+        }
+    }
+
+    pub fn is_raw(&self) -> bool {
+        self.mode == TokenLexerMode::Raw
+    }
+
+    pub fn with_stopwords(mut self, stopwords: Arc<HashSet<String>>) -> TokenLexer<'a> {
+        // Use custom stop-words in addition to those of the locale? (if any)
+        if !stopwords.is_empty() {
+            self.stopwords = Some(stopwords);
+        }
+
+        self
+    }
+
+    pub fn with_positions(mut self) -> TokenLexer<'a> {
+        self.positions = Some(HashMap::new());
+
+        self
+    }
+
+    pub fn take_positions(&mut self) -> Option<HashMap<StoreTermHashed, Vec<StoreTermPosition>>> {
+        self.positions.take()
+    }
+
+    fn is_stopword(
+        word: &str,
+        locale: Option<Lang>,
+        stopwords: &Option<Arc<HashSet<String>>>,
+    ) -> bool {
+        LexerStopWord::is(word, locale)
+            || stopwords
+                .as_ref()
+                .map(|stopwords| stopwords.contains(word))
+                .unwrap_or(false)
+    }
 }
 
 impl TokenLexerMode {
@@ -298,22 +422,42 @@ impl<'a> Iterator for TokenLexer<'a> {
     //   - Text is split per-word in a script-aware way \
     //   - Words are normalized (ie. lower-case) \
     //   - Gibberish words are removed (ie. words that may just be junk) \
-    //   - Stop-words are removed
+    //   - Stop-words are removed \
+    // Notice: in 'Raw' mode, words are yielded as-is (only duplicate words are removed).
     fn next(&mut self) -> Option<Self::Item> {
         for word in &mut self.words {
             // Lower-case word
             // Notice: unfortunately, as Rust is unicode-aware, we need to convert the str slice \
             //   to a heap-indexed String; as lower-cased characters may change in bit size.
-            let word = word.to_lowercase();
+            // Notice: raw words are used as-is (they are expected to be normalized already).
+            let word = if self.mode == TokenLexerMode::Raw {
+                word.to_string()
+            } else {
+                word.to_lowercase()
+            };
 
             // Check if normalized word is a stop-word? (if should normalize and cleanup)
-            if self.mode == TokenLexerMode::NormalizeOnly || !LexerStopWord::is(&word, self.locale)
+            if self.mode == TokenLexerMode::NormalizeOnly
+                || self.mode == TokenLexerMode::Raw
+                || !Self::is_stopword(&word, self.locale, &self.stopwords)
             {
                 // Hash the term (this is used by all iterator consumers, as well as internally \
                 //   in the iterator to keep track of already-yielded words in a space-optimized \
                 //   manner, ie. by using 32-bit unsigned integer hashes)
                 let term_hash = StoreTermHash::from(&word);
 
+                // Record word position? (positions are counted over words that are not \
+                //   stop-words, and are recorded for repeated words as well)
+                if let Some(ref mut positions) = self.positions {
+                    let term_positions = positions.entry(term_hash).or_insert_with(Vec::new);
+
+                    if term_positions.len() < POSITIONS_PER_TERM_MAXIMUM {
+                        term_positions.push(self.position);
+                    }
+                }
+
+                self.position += 1;
+
                 // Check if word was not already yielded? (we return unique words)
                 if !self.yields.contains(&term_hash) {
                     debug!("lexer yielded word: {}", word);
@@ -345,6 +489,8 @@ impl<'a> Iterator for TokenLexerWords<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             TokenLexerWords::UAX29(token) => token.next(),
+            TokenLexerWords::Whitespace(token) => token.next(),
+            TokenLexerWords::NGram(token) => token.next(),
 
             #[cfg(feature = "tokenizer-chinese")]
             TokenLexerWords::JieBa(token) => token.next(),
@@ -415,6 +561,24 @@ mod tests {
         assert_eq!(token_cleaner.next(), None);
     }
 
+    #[test]
+    fn it_keeps_raw_tokens() {
+        let mut token_cleaner =
+            TokenLexerBuilder::from(TokenLexerMode::Raw, "the  Quick fox the").unwrap();
+
+        assert_eq!(token_cleaner.locale, None);
+        assert_eq!(
+            token_cleaner.next(),
+            Some(("the".to_string(), StoreTermHash::from("the")))
+        );
+        assert_eq!(
+            token_cleaner.next(),
+            Some(("Quick".to_string(), StoreTermHash::from("Quick")))
+        );
+        assert_eq!(token_cleaner.next(), Some(("fox".to_string(), 667256324)));
+        assert_eq!(token_cleaner.next(), None);
+    }
+
     #[cfg(feature = "tokenizer-chinese")]
     #[test]
     fn it_cleans_token_chinese_jieba() {
@@ -449,6 +613,52 @@ mod tests {
         assert_eq!(token_cleaner.next(), None);
     }
 
+    #[test]
+    fn it_splits_token_ngrams() {
+        assert_eq!(
+            TokenLexer::split_ngrams("Sonic: 北京大学, 狗とねこ! ภาษาไทย 快"),
+            vec![
+                "Sonic",
+                "北",
+                "北京",
+                "京",
+                "京大",
+                "大",
+                "大学",
+                "学",
+                "狗",
+                "狗と",
+                "と",
+                "とね",
+                "ね",
+                "ねこ",
+                "こ",
+                "ภา",
+                "ภาษา",
+                "ษา",
+                "ษาไท",
+                "ไท",
+                "ไทย",
+                "ย",
+                "快"
+            ]
+        );
+        assert_eq!(
+            TokenLexer::split_ngrams("The quick brown fox."),
+            vec!["The", "quick", "brown", "fox"]
+        );
+        assert!(TokenLexer::split_ngrams("  . ").is_empty());
+
+        // Queries of one or two characters match text containing them
+        let words = TokenLexer::split_ngrams("北京大学");
+
+        for query in &["京", "京大", "北京大"] {
+            assert!(TokenLexer::split_ngrams(query)
+                .iter()
+                .all(|word| words.contains(word)));
+        }
+    }
+
     #[cfg(feature = "tokenizer-japanese")]
     #[test]
     fn it_cleans_token_japanese_lindera_product() {
@@ -575,6 +785,78 @@ mod tests {
     fn it_doesnt_detect_lang_english_tiny() {
         assert_eq!(TokenLexerBuilder::detect_lang("The quick"), None);
     }
+
+    #[test]
+    fn it_cleans_token_custom_stopwords() {
+        let stopwords: HashSet<String> = vec!["acme".to_string()].into_iter().collect();
+
+        let mut token_cleaner = TokenLexerBuilder::from(
+            TokenLexerMode::NormalizeAndCleanup(Some(Lang::Eng)),
+            "The Acme anvil",
+        )
+        .unwrap()
+        .with_stopwords(Arc::new(stopwords.clone()));
+
+        assert_eq!(
+            token_cleaner.next(),
+            Some(("anvil".to_string(), 3483980559))
+        );
+        assert_eq!(token_cleaner.next(), None);
+
+        let mut token_normalizer =
+            TokenLexerBuilder::from(TokenLexerMode::NormalizeOnly, "Acme anvil")
+                .unwrap()
+                .with_stopwords(Arc::new(stopwords));
+
+        assert_eq!(
+            token_normalizer.next().map(|token| token.0),
+            Some("acme".to_string())
+        );
+    }
+
+    #[test]
+    fn it_cleans_token_replacement_characters() {
+        // Lossy-decoded text holds replacement characters in place of invalid UTF-8 bytes
+        let text = String::from_utf8_lossy(b"caf\xc3 lighthouse \xff\xfe");
+
+        let token_cleaner =
+            TokenLexerBuilder::from(TokenLexerMode::NormalizeAndCleanup(Some(Lang::Eng)), &text)
+                .unwrap();
+
+        assert_eq!(
+            token_cleaner
+                .map(|token| token.0)
+                .collect::<Vec<String>>()
+                .last(),
+            Some(&"lighthouse".to_string())
+        );
+    }
+
+    #[test]
+    fn it_records_token_positions() {
+        let mut token_cleaner = TokenLexerBuilder::from(
+            TokenLexerMode::NormalizeAndCleanup(Some(Lang::Eng)),
+            "The fox jumps over the other fox",
+        )
+        .unwrap()
+        .with_positions();
+
+        assert_eq!(
+            token_cleaner
+                .by_ref()
+                .map(|token| token.0)
+                .collect::<Vec<String>>(),
+            vec!["fox", "jumps"]
+        );
+
+        let positions = token_cleaner.take_positions().unwrap();
+
+        assert_eq!(
+            positions.get(&StoreTermHash::from("fox")),
+            Some(&vec![0, 2])
+        );
+        assert_eq!(positions.get(&StoreTermHash::from("jumps")), Some(&vec![1]));
+    }
 }
 
 #[cfg(all(feature = "benchmark", test))]
@@ -5,7 +5,8 @@
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
 #![cfg_attr(feature = "benchmark", feature(test))]
-#![deny(unstable_features, unused_imports, unused_qualifications, clippy::all)]
+#![cfg_attr(not(feature = "benchmark"), deny(unstable_features))]
+#![deny(unused_imports, unused_qualifications, clippy::all)]
 
 #[macro_use]
 extern crate log;
@@ -24,6 +25,8 @@ mod store;
 mod tasker;
 
 use std::ops::Deref;
+use std::path::Path;
+use std::process;
 use std::str::FromStr;
 use std::thread;
 use std::time::Duration;
@@ -37,12 +40,20 @@ use config::logger::ConfigLogger;
 use config::options::Config;
 use config::reader::ConfigReader;
 use store::fst::StoreFSTPool;
-use store::kv::StoreKVPool;
+use store::kv::{StoreKVBuilder, StoreKVPool};
 use tasker::runtime::TaskerBuilder;
 use tasker::shutdown::ShutdownSignal;
 
 struct AppArgs {
     config: String,
+    migrate: Option<AppArgsMigrate>,
+}
+
+struct AppArgsMigrate {
+    path: String,
+    buckets: Vec<String>,
+    from_version: u8,
+    to_version: Option<u8>,
 }
 
 #[cfg(unix)]
@@ -56,6 +67,9 @@ pub static THREAD_NAME_CHANNEL_MASTER: &str = "sonic-channel-master";
 pub static THREAD_NAME_CHANNEL_CLIENT: &str = "sonic-channel-client";
 pub static THREAD_NAME_TASKER: &str = "sonic-tasker";
 
+// Notice: key formats are checked by the argument parser, thus parsing them never fails.
+static MIGRATE_KEY_FORMATS: [&str; 2] = ["1", "2"];
+
 macro_rules! gen_spawn_managed {
     ($name:expr, $method:ident, $thread_name:ident, $managed_fn:ident) => {
         fn $method() {
@@ -99,7 +113,7 @@ gen_spawn_managed!(
 gen_spawn_managed!("tasker", spawn_tasker, THREAD_NAME_TASKER, TaskerBuilder);
 
 fn make_app_args() -> AppArgs {
-    let matches = App::new(clap::crate_name!())
+    let app = App::new(clap::crate_name!())
         .version(clap::crate_version!())
         .author(clap::crate_authors!())
         .about(clap::crate_description!())
@@ -111,11 +125,93 @@ fn make_app_args() -> AppArgs {
                 .default_value("./config.cfg")
                 .takes_value(true),
         )
-        .get_matches();
+        .subcommand(
+            App::new("migrate")
+                .about("Migrate a collection database to another key format (offline)")
+                .arg(
+                    Arg::new("path")
+                        .help("Path to the collection database")
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .help("Key format to migrate from")
+                        .default_value("1")
+                        .possible_values(MIGRATE_KEY_FORMATS)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .help("Key format to migrate to (defaults to the configured key format)")
+                        .possible_values(MIGRATE_KEY_FORMATS)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("bucket")
+                        .short('b')
+                        .long("bucket")
+                        .help("Name of a bucket to migrate, if not recorded in the database")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
+                ),
+        );
+
+    // Notice: test and benchmark harnesses are passed their own arguments, thus those are not \
+    //   parsed (default arguments are used instead).
+    #[cfg(test)]
+    let matches = app.get_matches_from([clap::crate_name!()]);
+    #[cfg(not(test))]
+    let matches = app.get_matches();
 
     // Generate owned app arguments
     AppArgs {
         config: String::from(matches.value_of("config").expect("invalid config value")),
+        migrate: matches
+            .subcommand_matches("migrate")
+            .map(|matches| AppArgsMigrate {
+                path: String::from(matches.value_of("path").expect("invalid path value")),
+                buckets: matches
+                    .values_of("bucket")
+                    .map(|buckets| buckets.map(String::from).collect())
+                    .unwrap_or_default(),
+                from_version: matches
+                    .value_of("from")
+                    .and_then(|version| version.parse().ok())
+                    .expect("invalid from value"),
+                to_version: matches
+                    .value_of("to")
+                    .map(|version| version.parse().expect("invalid to value")),
+            }),
+    }
+}
+
+fn migrate(args: &AppArgsMigrate) {
+    let to_version = args.to_version.unwrap_or_else(StoreKVBuilder::key_format);
+
+    let buckets: Vec<&str> = args.buckets.iter().map(String::as_str).collect();
+
+    // Notice: this runs while Sonic is stopped, as an open database cannot be opened twice \
+    //   (the migration gets refused if the database is in use).
+    match StoreKVBuilder::migrate(
+        Path::new(&args.path),
+        &buckets,
+        args.from_version,
+        to_version,
+    ) {
+        Ok(count_migrated) => {
+            info!(
+                "migrated {} keys to key format: {}",
+                count_migrated, to_version
+            );
+        }
+        Err(err) => {
+            error!("failed migrating database: {}", err);
+
+            process::exit(1);
+        }
     }
 }
 
@@ -132,6 +228,11 @@ fn main() {
         LevelFilter::from_str(&APP_CONF.server.log_level).expect("invalid log level"),
     );
 
+    // Migrate a database? (then stop, as Sonic does not get started)
+    if let Some(ref args) = APP_ARGS.migrate {
+        return migrate(args);
+    }
+
     let shutdown_signal = ShutdownSignal::new();
 
     info!("starting up");
@@ -153,13 +254,19 @@ fn main() {
         // Teardown Sonic Channel
         ChannelListen::teardown();
 
-        // Perform a KV flush (ensures all in-memory changes are synced on-disk before shutdown)
-        StoreKVPool::flush(true);
-
         // Perform a FST consolidation (ensures all in-memory items are synced on-disk before \
         //   shutdown; otherwise we would lose all non-consolidated FST changes)
         StoreFSTPool::consolidate(true);
 
+        // Close all KV stores (waits for in-flight operations to complete, then ensures all \
+        //   in-memory changes are synced on-disk before shutdown, and releases databases)
+        if let Err(failures) = StoreKVPool::close_all() {
+            error!(
+                "failed flushing {} kv stores before stopping",
+                failures.len()
+            );
+        }
+
         info!("stopped");
     });
 }
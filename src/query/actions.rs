@@ -4,9 +4,11 @@
 // Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
+use super::expression::QueryExpression;
 use super::types::*;
 use crate::lexer::token::TokenLexer;
-use crate::store::item::StoreItem;
+use crate::store::identifiers::StoreTermHashed;
+use crate::store::item::{StoreItem, StoreItemPart};
 
 pub enum Query<'a> {
     Search(
@@ -15,23 +17,81 @@ pub enum Query<'a> {
         TokenLexer<'a>,
         QuerySearchLimit,
         QuerySearchOffset,
+        QuerySearchTimeout,
+        QuerySearchTypos,
+        QuerySearchCompletion,
+        bool,
+        bool,
     ),
+    SearchBoolean(
+        StoreItem<'a>,
+        QuerySearchID<'a>,
+        QueryExpression<'a>,
+        QuerySearchLimit,
+        QuerySearchOffset,
+        bool,
+        bool,
+    ),
+    SearchPhrase(
+        StoreItem<'a>,
+        QuerySearchID<'a>,
+        TokenLexer<'a>,
+        QuerySearchLimit,
+        QuerySearchOffset,
+        bool,
+        bool,
+    ),
+    SearchFederated(Vec<Query<'a>>, QuerySearchLimit, QuerySearchOffset),
     Suggest(
         StoreItem<'a>,
         QuerySearchID<'a>,
         TokenLexer<'a>,
         QuerySearchLimit,
     ),
+    CoOccur(
+        StoreItem<'a>,
+        QuerySearchID<'a>,
+        TokenLexer<'a>,
+        QuerySearchLimit,
+    ),
     List(
         StoreItem<'a>,
         QuerySearchID<'a>,
         QuerySearchLimit,
         QuerySearchOffset,
     ),
-    Push(StoreItem<'a>, TokenLexer<'a>),
+    Push(
+        StoreItem<'a>,
+        TokenLexer<'a>,
+        QueryPushSuggest,
+        Option<QueryPushBoost>,
+        Vec<(String, StoreTermHashed, QueryPushWeight)>,
+        Option<&'a str>,
+        bool,
+    ),
     Pop(StoreItem<'a>, TokenLexer<'a>),
+    ListO(
+        StoreItem<'a>,
+        StoreItemPart<'a>,
+        QuerySearchLimit,
+        QuerySearchOffset,
+    ),
     Count(StoreItem<'a>),
+    CountObjects(StoreItem<'a>),
+    Terms(StoreItem<'a>),
+    RefreshIDF(StoreItem<'a>),
     FlushC(StoreItem<'a>),
     FlushB(StoreItem<'a>),
     FlushO(StoreItem<'a>),
+    MoveO(StoreItem<'a>, StoreItemPart<'a>),
+    Swap(StoreItem<'a>, StoreItemPart<'a>),
+    Retain(
+        StoreItem<'a>,
+        Option<QueryRetainAge>,
+        Option<QueryRetainObjects>,
+    ),
+    Retention(StoreItem<'a>),
+    Freeze(StoreItem<'a>, bool),
+    Snapshot(StoreItem<'a>, StoreItemPart<'a>),
+    Diff(StoreItem<'a>, StoreItemPart<'a>, StoreItemPart<'a>),
 }
@@ -5,15 +5,21 @@
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
 use super::actions::Query;
-use super::types::{QueryGenericLang, QuerySearchLimit, QuerySearchOffset};
+use super::expression::QueryExpressionBuilder;
+use super::types::{
+    QueryGenericLang, QueryPushBoost, QueryPushSuggest, QueryPushWeight, QueryRetainAge,
+    QueryRetainObjects, QuerySearchCompletion, QuerySearchLimit, QuerySearchOffset,
+    QuerySearchSyntax, QuerySearchTimeout, QuerySearchTypos,
+};
 use crate::lexer::token::{TokenLexerBuilder, TokenLexerMode};
-use crate::store::item::StoreItemBuilder;
+use crate::store::item::{StoreItemBuilder, StoreItemPart, STORE_ITEM_ATTRIBUTE_PREFIX};
 
 pub struct QueryBuilder;
 
 pub type QueryBuilderResult<'a> = Result<Query<'a>, ()>;
 
 impl QueryBuilder {
+    #[allow(clippy::too_many_arguments)]
     pub fn search<'a>(
         query_id: &'a str,
         collection: &'a str,
@@ -22,18 +28,185 @@ impl QueryBuilder {
         limit: QuerySearchLimit,
         offset: QuerySearchOffset,
         lang: Option<QueryGenericLang>,
+        timeout: QuerySearchTimeout,
+        typos: QuerySearchTypos,
+        completion: QuerySearchCompletion,
+        snippets: bool,
+        with_terms: bool,
     ) -> QueryBuilderResult<'a> {
         match (
             StoreItemBuilder::from_depth_2(collection, bucket),
             TokenLexerBuilder::from(TokenLexerMode::from_query_lang(lang), terms),
         ) {
-            (Ok(store), Ok(text_lexed)) => {
-                Ok(Query::Search(store, query_id, text_lexed, limit, offset))
-            }
+            (Ok(store), Ok(text_lexed)) => Ok(Query::Search(
+                store, query_id, text_lexed, limit, offset, timeout, typos, completion, snippets,
+                with_terms,
+            )),
+            _ => Err(()),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_boolean<'a>(
+        query_id: &'a str,
+        collection: &'a str,
+        bucket: &'a str,
+        terms: &'a str,
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+        snippets: bool,
+        with_terms: bool,
+    ) -> QueryBuilderResult<'a> {
+        match (
+            StoreItemBuilder::from_depth_2(collection, bucket),
+            QueryExpressionBuilder::from(terms),
+        ) {
+            (Ok(store), Ok(expression)) => Ok(Query::SearchBoolean(
+                store, query_id, expression, limit, offset, snippets, with_terms,
+            )),
+            _ => Err(()),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_raw<'a>(
+        query_id: &'a str,
+        collection: &'a str,
+        bucket: &'a str,
+        terms: &'a str,
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+        timeout: QuerySearchTimeout,
+        snippets: bool,
+        with_terms: bool,
+    ) -> QueryBuilderResult<'a> {
+        match (
+            StoreItemBuilder::from_depth_2(collection, bucket),
+            TokenLexerBuilder::from(TokenLexerMode::Raw, terms),
+        ) {
+            // Notice: raw terms are not completed with alternate words, thus no typo is allowed
+            (Ok(store), Ok(text_lexed)) => Ok(Query::Search(
+                store,
+                query_id,
+                text_lexed,
+                limit,
+                offset,
+                timeout,
+                0,
+                QuerySearchCompletion::Disabled,
+                snippets,
+                with_terms,
+            )),
             _ => Err(()),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_phrase<'a>(
+        query_id: &'a str,
+        collection: &'a str,
+        bucket: &'a str,
+        terms: &'a str,
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+        lang: Option<QueryGenericLang>,
+        snippets: bool,
+        with_terms: bool,
+    ) -> QueryBuilderResult<'a> {
+        match (
+            StoreItemBuilder::from_depth_2(collection, bucket),
+            TokenLexerBuilder::from(TokenLexerMode::from_query_lang(lang), terms),
+        ) {
+            (Ok(store), Ok(text_lexed)) => Ok(Query::SearchPhrase(
+                store, query_id, text_lexed, limit, offset, snippets, with_terms,
+            )),
+            _ => Err(()),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_federated<'a>(
+        query_id: &'a str,
+        collections: &[&'a str],
+        bucket: &'a str,
+        terms: &'a str,
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+        lang: Option<QueryGenericLang>,
+        syntax: QuerySearchSyntax,
+        timeout: QuerySearchTimeout,
+        typos: QuerySearchTypos,
+        completion: QuerySearchCompletion,
+    ) -> QueryBuilderResult<'a> {
+        // Each collection query must return enough OIDs for the merged result set to be \
+        //   paginated, so the offset is applied once merged only
+        // Notice: snippets and matched terms are not returned for federated queries, as merged \
+        //   results are OIDs.
+        let collection_limit = offset
+            .saturating_add(limit as QuerySearchOffset)
+            .min(QuerySearchLimit::MAX as QuerySearchOffset)
+            as QuerySearchLimit;
+
+        let mut queries = Vec::with_capacity(collections.len());
+
+        for collection in collections {
+            queries.push(match syntax {
+                QuerySearchSyntax::Plain => Self::search(
+                    query_id,
+                    collection,
+                    bucket,
+                    terms,
+                    collection_limit,
+                    0,
+                    lang.clone(),
+                    timeout,
+                    typos,
+                    completion,
+                    false,
+                    false,
+                )?,
+                QuerySearchSyntax::Boolean => Self::search_boolean(
+                    query_id,
+                    collection,
+                    bucket,
+                    terms,
+                    collection_limit,
+                    0,
+                    false,
+                    false,
+                )?,
+                QuerySearchSyntax::Raw => Self::search_raw(
+                    query_id,
+                    collection,
+                    bucket,
+                    terms,
+                    collection_limit,
+                    0,
+                    timeout,
+                    false,
+                    false,
+                )?,
+                QuerySearchSyntax::Phrase => Self::search_phrase(
+                    query_id,
+                    collection,
+                    bucket,
+                    terms,
+                    collection_limit,
+                    0,
+                    lang.clone(),
+                    false,
+                    false,
+                )?,
+            });
+        }
+
+        if queries.is_empty() {
+            Err(())
+        } else {
+            Ok(Query::SearchFederated(queries, limit, offset))
+        }
+    }
+
     pub fn suggest<'a>(
         query_id: &'a str,
         collection: &'a str,
@@ -50,6 +223,22 @@ impl QueryBuilder {
         }
     }
 
+    pub fn cooccur<'a>(
+        query_id: &'a str,
+        collection: &'a str,
+        bucket: &'a str,
+        terms: &'a str,
+        limit: QuerySearchLimit,
+    ) -> QueryBuilderResult<'a> {
+        match (
+            StoreItemBuilder::from_depth_2(collection, bucket),
+            TokenLexerBuilder::from(TokenLexerMode::NormalizeOnly, terms),
+        ) {
+            (Ok(store), Ok(text_lexed)) => Ok(Query::CoOccur(store, query_id, text_lexed, limit)),
+            _ => Err(()),
+        }
+    }
+
     pub fn list<'a>(
         query_id: &'a str,
         collection: &'a str,
@@ -63,18 +252,49 @@ impl QueryBuilder {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn push<'a>(
         collection: &'a str,
         bucket: &'a str,
         object: &'a str,
         text: &'a str,
         lang: Option<QueryGenericLang>,
+        suggest: QueryPushSuggest,
+        boost: Option<QueryPushBoost>,
+        weights: &[(&'a str, QueryPushWeight)],
+        snippet: Option<&'a str>,
+        verbose: bool,
     ) -> QueryBuilderResult<'a> {
+        // Normalize weighted terms the same way query terms get normalized (a weighted term \
+        //   may be made of multiple words, in which case all words share the same weight)
+        let mut weights_lexed = Vec::new();
+
+        // Object reserved for result attributes? (reject push)
+        if object.starts_with(STORE_ITEM_ATTRIBUTE_PREFIX) {
+            return Err(());
+        }
+
+        for (term, weight) in weights {
+            let term_lexed = TokenLexerBuilder::from(TokenLexerMode::NormalizeOnly, term)?;
+
+            for (word, word_hashed) in term_lexed {
+                weights_lexed.push((word, word_hashed, *weight));
+            }
+        }
+
         match (
             StoreItemBuilder::from_depth_3(collection, bucket, object),
             TokenLexerBuilder::from(TokenLexerMode::from_query_lang(lang), text),
         ) {
-            (Ok(store), Ok(text_lexed)) => Ok(Query::Push(store, text_lexed)),
+            (Ok(store), Ok(text_lexed)) => Ok(Query::Push(
+                store,
+                text_lexed,
+                suggest,
+                boost,
+                weights_lexed,
+                snippet,
+                verbose,
+            )),
             _ => Err(()),
         }
     }
@@ -94,6 +314,22 @@ impl QueryBuilder {
         }
     }
 
+    pub fn listo<'a>(
+        collection: &'a str,
+        bucket: &'a str,
+        prefix: &'a str,
+        limit: QuerySearchLimit,
+        offset: QuerySearchOffset,
+    ) -> QueryBuilderResult<'a> {
+        match (
+            StoreItemBuilder::from_depth_2(collection, bucket),
+            StoreItemPart::from_str(prefix),
+        ) {
+            (Ok(store), Ok(prefix_part)) => Ok(Query::ListO(store, prefix_part, limit, offset)),
+            _ => Err(()),
+        }
+    }
+
     pub fn count<'a>(
         collection: &'a str,
         bucket: Option<&'a str>,
@@ -113,6 +349,27 @@ impl QueryBuilder {
         }
     }
 
+    pub fn count_objects<'a>(collection: &'a str, bucket: &'a str) -> QueryBuilderResult<'a> {
+        match StoreItemBuilder::from_depth_2(collection, bucket) {
+            Ok(store) => Ok(Query::CountObjects(store)),
+            _ => Err(()),
+        }
+    }
+
+    pub fn terms<'a>(collection: &'a str, bucket: &'a str) -> QueryBuilderResult<'a> {
+        match StoreItemBuilder::from_depth_2(collection, bucket) {
+            Ok(store) => Ok(Query::Terms(store)),
+            _ => Err(()),
+        }
+    }
+
+    pub fn idf<'a>(collection: &'a str, bucket: &'a str) -> QueryBuilderResult<'a> {
+        match StoreItemBuilder::from_depth_2(collection, bucket) {
+            Ok(store) => Ok(Query::RefreshIDF(store)),
+            _ => Err(()),
+        }
+    }
+
     pub fn flushc(collection: &str) -> QueryBuilderResult {
         match StoreItemBuilder::from_depth_1(collection) {
             Ok(store) => Ok(Query::FlushC(store)),
@@ -137,6 +394,97 @@ impl QueryBuilder {
             _ => Err(()),
         }
     }
+
+    pub fn moveo<'a>(
+        collection: &'a str,
+        bucket: &'a str,
+        object: &'a str,
+        to_bucket: &'a str,
+    ) -> QueryBuilderResult<'a> {
+        match (
+            StoreItemBuilder::from_depth_3(collection, bucket, object),
+            StoreItemPart::from_str(to_bucket),
+        ) {
+            (Ok(store), Ok(to_bucket_part)) => Ok(Query::MoveO(store, to_bucket_part)),
+            _ => Err(()),
+        }
+    }
+
+    pub fn swap<'a>(
+        collection: &'a str,
+        bucket: &'a str,
+        with_bucket: &'a str,
+    ) -> QueryBuilderResult<'a> {
+        match (
+            StoreItemBuilder::from_depth_2(collection, bucket),
+            StoreItemPart::from_str(with_bucket),
+        ) {
+            (Ok(store), Ok(with_bucket_part)) => Ok(Query::Swap(store, with_bucket_part)),
+            _ => Err(()),
+        }
+    }
+
+    pub fn retain<'a>(
+        collection: &'a str,
+        bucket: &'a str,
+        max_age: Option<QueryRetainAge>,
+        max_objects: Option<QueryRetainObjects>,
+    ) -> QueryBuilderResult<'a> {
+        match StoreItemBuilder::from_depth_2(collection, bucket) {
+            Ok(store) => Ok(Query::Retain(store, max_age, max_objects)),
+            _ => Err(()),
+        }
+    }
+
+    pub fn retention<'a>(collection: &'a str, bucket: &'a str) -> QueryBuilderResult<'a> {
+        match StoreItemBuilder::from_depth_2(collection, bucket) {
+            Ok(store) => Ok(Query::Retention(store)),
+            _ => Err(()),
+        }
+    }
+
+    pub fn freeze<'a>(
+        collection: &'a str,
+        bucket: &'a str,
+        immutable: bool,
+    ) -> QueryBuilderResult<'a> {
+        match StoreItemBuilder::from_depth_2(collection, bucket) {
+            Ok(store) => Ok(Query::Freeze(store, immutable)),
+            _ => Err(()),
+        }
+    }
+
+    pub fn snapshot<'a>(
+        collection: &'a str,
+        bucket: &'a str,
+        name: &'a str,
+    ) -> QueryBuilderResult<'a> {
+        match (
+            StoreItemBuilder::from_depth_2(collection, bucket),
+            StoreItemPart::from_str(name),
+        ) {
+            (Ok(store), Ok(name_part)) => Ok(Query::Snapshot(store, name_part)),
+            _ => Err(()),
+        }
+    }
+
+    pub fn diff<'a>(
+        collection: &'a str,
+        bucket: &'a str,
+        from_name: &'a str,
+        to_name: &'a str,
+    ) -> QueryBuilderResult<'a> {
+        match (
+            StoreItemBuilder::from_depth_2(collection, bucket),
+            StoreItemPart::from_str(from_name),
+            StoreItemPart::from_str(to_name),
+        ) {
+            (Ok(store), Ok(from_name_part), Ok(to_name_part)) => {
+                Ok(Query::Diff(store, from_name_part, to_name_part))
+            }
+            _ => Err(()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -145,11 +493,143 @@ mod tests {
 
     #[test]
     fn it_builds_search_query() {
-        assert!(
-            QueryBuilder::search("id1", "c:test:1", "b:test:1", "Michael Dake", 10, 20, None)
-                .is_ok()
-        );
-        assert!(QueryBuilder::search("id2", "c:test:1", "", "Michael Dake", 1, 0, None).is_err());
+        assert!(QueryBuilder::search(
+            "id1",
+            "c:test:1",
+            "b:test:1",
+            "Michael Dake",
+            10,
+            20,
+            None,
+            QuerySearchTimeout::Partial,
+            1,
+            QuerySearchCompletion::AllTerms,
+            false,
+            false
+        )
+        .is_ok());
+        assert!(QueryBuilder::search(
+            "id2",
+            "c:test:1",
+            "",
+            "Michael Dake",
+            1,
+            0,
+            None,
+            QuerySearchTimeout::Partial,
+            1,
+            QuerySearchCompletion::AllTerms,
+            false,
+            false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn it_builds_search_boolean_query() {
+        assert!(QueryBuilder::search_boolean(
+            "id1",
+            "c:test:1",
+            "b:test:1",
+            "(Michael OR Mike) AND NOT Dake",
+            10,
+            0,
+            false,
+            false
+        )
+        .is_ok());
+        assert!(QueryBuilder::search_boolean(
+            "id2", "c:test:1", "b:test:1", "NOT Dake", 10, 0, false, false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn it_builds_search_raw_query() {
+        assert!(QueryBuilder::search_raw(
+            "id1",
+            "c:test:1",
+            "b:test:1",
+            "michael dake",
+            10,
+            0,
+            QuerySearchTimeout::Partial,
+            false,
+            false
+        )
+        .is_ok());
+        assert!(QueryBuilder::search_raw(
+            "id2",
+            "",
+            "b:test:1",
+            "michael dake",
+            10,
+            0,
+            QuerySearchTimeout::Partial,
+            false,
+            false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn it_builds_search_federated_query() {
+        assert!(QueryBuilder::search_federated(
+            "id1",
+            &["c:test:1", "c:test:2"],
+            "b:test:1",
+            "Michael Dake",
+            10,
+            20,
+            None,
+            QuerySearchSyntax::Plain,
+            QuerySearchTimeout::Partial,
+            1,
+            QuerySearchCompletion::AllTerms
+        )
+        .is_ok());
+        assert!(QueryBuilder::search_federated(
+            "id2",
+            &["c:test:1", "c:test:2"],
+            "b:test:1",
+            "Michael AND Dake",
+            10,
+            0,
+            None,
+            QuerySearchSyntax::Boolean,
+            QuerySearchTimeout::Partial,
+            1,
+            QuerySearchCompletion::AllTerms
+        )
+        .is_ok());
+        assert!(QueryBuilder::search_federated(
+            "id3",
+            &["c:test:1", ""],
+            "b:test:1",
+            "Michael Dake",
+            10,
+            0,
+            None,
+            QuerySearchSyntax::Plain,
+            QuerySearchTimeout::Partial,
+            1,
+            QuerySearchCompletion::AllTerms
+        )
+        .is_err());
+        assert!(QueryBuilder::search_federated(
+            "id4",
+            &[],
+            "b:test:1",
+            "Michael Dake",
+            10,
+            0,
+            None,
+            QuerySearchSyntax::Plain,
+            QuerySearchTimeout::Partial,
+            1,
+            QuerySearchCompletion::AllTerms
+        )
+        .is_err());
     }
 
     #[test]
@@ -158,6 +638,12 @@ mod tests {
         assert!(QueryBuilder::suggest("id2", "c:test:2", "", "Micha", 1).is_err());
     }
 
+    #[test]
+    fn it_builds_cooccur_query() {
+        assert!(QueryBuilder::cooccur("id1", "c:test:2", "b:test:2", "Michael", 5).is_ok());
+        assert!(QueryBuilder::cooccur("id2", "c:test:2", "", "Michael", 1).is_err());
+    }
+
     #[test]
     fn it_builds_list_query() {
         assert!(QueryBuilder::list("id1", "c:test:2", "b:test:2", 100, 0).is_ok());
@@ -171,13 +657,53 @@ mod tests {
             "b:test:3",
             "o:test:3",
             "My name is Michael Dake. I'm ordering in the US.",
-            None
+            None,
+            QueryPushSuggest::Incremental,
+            None,
+            &[],
+            None,
+            false
+        )
+        .is_ok());
+        assert!(QueryBuilder::push(
+            "c:test:3",
+            "b:test:3",
+            "o:test:3",
+            "I'm ordering in bulk.",
+            None,
+            QueryPushSuggest::Deferred,
+            Some(2.0),
+            &[("bulk", 4.0), ("order form", 2.0)],
+            Some("Ordering in bulk"),
+            true
         )
         .is_ok());
-        assert!(
-            QueryBuilder::push("c:test:3", "", "o:test:3", "My name is Michael Dake.", None)
-                .is_err()
-        );
+        assert!(QueryBuilder::push(
+            "c:test:3",
+            "",
+            "o:test:3",
+            "My name is Michael Dake.",
+            None,
+            QueryPushSuggest::Incremental,
+            None,
+            &[],
+            None,
+            false
+        )
+        .is_err());
+        assert!(QueryBuilder::push(
+            "c:test:3",
+            "b:test:3",
+            "[partial]",
+            "My name is Michael Dake.",
+            None,
+            QueryPushSuggest::Incremental,
+            None,
+            &[],
+            None,
+            false
+        )
+        .is_err());
     }
 
     #[test]
@@ -194,6 +720,18 @@ mod tests {
         assert!(QueryBuilder::count("c:test:5", Some(""), Some("o:test:5")).is_err());
     }
 
+    #[test]
+    fn it_builds_listo_query() {
+        assert!(QueryBuilder::listo("c:test:5", "b:test:5", "conversation:", 10, 0).is_ok());
+        assert!(QueryBuilder::listo("c:test:5", "b:test:5", "", 10, 0).is_err());
+    }
+
+    #[test]
+    fn it_builds_terms_query() {
+        assert!(QueryBuilder::terms("c:test:5", "b:test:5").is_ok());
+        assert!(QueryBuilder::terms("c:test:5", "").is_err());
+    }
+
     #[test]
     fn it_builds_flushc_query() {
         assert!(QueryBuilder::flushc("c:test:6").is_ok());
@@ -211,4 +749,37 @@ mod tests {
         assert!(QueryBuilder::flusho("c:test:8", "b:test:8", "o:test:8").is_ok());
         assert!(QueryBuilder::flusho("c:test:8", "b:test:8", "").is_err());
     }
+
+    #[test]
+    fn it_builds_moveo_query() {
+        assert!(QueryBuilder::moveo("c:test:9", "b:test:9", "o:test:9", "b:test:9:2").is_ok());
+        assert!(QueryBuilder::moveo("c:test:9", "b:test:9", "o:test:9", "").is_err());
+    }
+
+    #[test]
+    fn it_builds_swap_query() {
+        assert!(QueryBuilder::swap("c:test:9", "b:test:9", "b:test:9:2").is_ok());
+        assert!(QueryBuilder::swap("c:test:9", "b:test:9", "").is_err());
+    }
+
+    #[test]
+    fn it_builds_retain_query() {
+        assert!(QueryBuilder::retain("c:test:10", "b:test:10", Some(3600), None).is_ok());
+        assert!(QueryBuilder::retain("c:test:10", "b:test:10", None, None).is_ok());
+        assert!(QueryBuilder::retain("c:test:10", "", None, Some(100)).is_err());
+    }
+
+    #[test]
+    fn it_builds_retention_query() {
+        assert!(QueryBuilder::retention("c:test:10", "b:test:10").is_ok());
+        assert!(QueryBuilder::retention("c:test:10", "").is_err());
+    }
+
+    #[test]
+    fn it_builds_snapshot_queries() {
+        assert!(QueryBuilder::snapshot("c:test:19", "b:test:19", "s:1").is_ok());
+        assert!(QueryBuilder::snapshot("c:test:19", "b:test:19", "").is_err());
+        assert!(QueryBuilder::diff("c:test:19", "b:test:19", "s:1", "s:2").is_ok());
+        assert!(QueryBuilder::diff("c:test:19", "", "s:1", "s:2").is_err());
+    }
 }
@@ -0,0 +1,605 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use linked_hash_set::LinkedHashSet;
+use std::fmt;
+use std::hash::Hash;
+
+// Boolean expressions are built from terms, the 'AND', 'OR' and 'NOT' operators (uppercase only, \
+//   any other casing gets matched as a term) and '(' ')' groups. Precedence goes as follows, \
+//   from the tightest to the loosest binding: groups, then 'NOT', then 'AND', then 'OR'. Two \
+//   terms with no operator in-between are implicitly joined with 'AND'. For instance, \
+//   'a b OR NOT c d' reads as '(a AND b) OR ((NOT c) AND d)'.
+// Notice: as an expression is resolved against indexed objects only, it must not match \
+//   everything that is not indexed (eg. 'NOT a' or 'a OR NOT b' are rejected, while \
+//   'a AND NOT b' is accepted).
+const EXPRESSION_MAX_DEPTH: usize = 8;
+const EXPRESSION_MAX_TERMS: usize = 16;
+
+const EXPRESSION_GROUP_OPEN: char = '(';
+const EXPRESSION_GROUP_CLOSE: char = ')';
+
+static EXPRESSION_OPERATOR_AND: &str = "AND";
+static EXPRESSION_OPERATOR_OR: &str = "OR";
+static EXPRESSION_OPERATOR_NOT: &str = "NOT";
+
+pub struct QueryExpressionBuilder;
+
+#[derive(Debug, PartialEq)]
+pub enum QueryExpression<'a> {
+    Term(&'a str),
+    And(Box<QueryExpression<'a>>, Box<QueryExpression<'a>>),
+    Or(Box<QueryExpression<'a>>, Box<QueryExpression<'a>>),
+    Not(Box<QueryExpression<'a>>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum QueryExpressionError {
+    Empty,
+    UnbalancedGroup,
+    MissingOperand,
+    TooDeep,
+    TooManyTerms,
+    UnboundNegation,
+}
+
+// Parse error, with the position of the character of the expression where the error was found \
+//   (counted in characters from the start of the expression)
+#[derive(Debug, PartialEq)]
+pub struct QueryExpressionParseError {
+    pub error: QueryExpressionError,
+    pub position: usize,
+}
+
+#[derive(Debug, PartialEq)]
+enum QueryExpressionToken<'a> {
+    GroupOpen,
+    GroupClose,
+    And,
+    Or,
+    Not,
+    Term(&'a str),
+}
+
+struct QueryExpressionParser<'a> {
+    tokens: Vec<(QueryExpressionToken<'a>, usize)>,
+    length: usize,
+    cursor: usize,
+    depth: usize,
+    terms: usize,
+}
+
+// Resolved set of an expression node, where 'Excluded' holds the complement of the matched set \
+//   (ie. it matches everything but the contained items)
+enum QueryExpressionSet<T> {
+    Included(LinkedHashSet<T>),
+    Excluded(LinkedHashSet<T>),
+}
+
+impl QueryExpressionBuilder {
+    pub fn from(text: &str) -> Result<QueryExpression<'_>, QueryExpressionError> {
+        Self::parse(text).map_err(|parse_err| parse_err.error)
+    }
+
+    pub fn parse(text: &str) -> Result<QueryExpression<'_>, QueryExpressionParseError> {
+        let mut parser = QueryExpressionParser {
+            tokens: Self::tokenize(text),
+            length: text.chars().count(),
+            cursor: 0,
+            depth: 0,
+            terms: 0,
+        };
+
+        if parser.tokens.is_empty() {
+            return Err(parser.error(QueryExpressionError::Empty, 0));
+        }
+
+        let expression = parser.parse_or()?;
+
+        // Some tokens are left unparsed? (this can only be a dangling group close)
+        if parser.cursor < parser.tokens.len() {
+            return Err(parser.error(QueryExpressionError::UnbalancedGroup, parser.position()));
+        }
+
+        if expression.is_excluding() {
+            // Point to the first negation, as the expression is excluding due to negations
+            let position = parser
+                .tokens
+                .iter()
+                .find(|(token, _)| token == &QueryExpressionToken::Not)
+                .map(|(_, position)| *position)
+                .unwrap_or(0);
+
+            Err(parser.error(QueryExpressionError::UnboundNegation, position))
+        } else {
+            Ok(expression)
+        }
+    }
+
+    fn tokenize(text: &str) -> Vec<(QueryExpressionToken<'_>, usize)> {
+        let mut tokens = Vec::new();
+        let mut term_start = None;
+
+        for (position, (index, character)) in text.char_indices().enumerate() {
+            let is_group =
+                character == EXPRESSION_GROUP_OPEN || character == EXPRESSION_GROUP_CLOSE;
+
+            if is_group || character.is_whitespace() {
+                if let Some((start, start_position)) = term_start.take() {
+                    tokens.push((Self::make_word_token(&text[start..index]), start_position));
+                }
+
+                if character == EXPRESSION_GROUP_OPEN {
+                    tokens.push((QueryExpressionToken::GroupOpen, position));
+                } else if character == EXPRESSION_GROUP_CLOSE {
+                    tokens.push((QueryExpressionToken::GroupClose, position));
+                }
+            } else if term_start.is_none() {
+                term_start = Some((index, position));
+            }
+        }
+
+        if let Some((start, start_position)) = term_start {
+            tokens.push((Self::make_word_token(&text[start..]), start_position));
+        }
+
+        tokens
+    }
+
+    fn make_word_token(word: &str) -> QueryExpressionToken<'_> {
+        if word == EXPRESSION_OPERATOR_AND {
+            QueryExpressionToken::And
+        } else if word == EXPRESSION_OPERATOR_OR {
+            QueryExpressionToken::Or
+        } else if word == EXPRESSION_OPERATOR_NOT {
+            QueryExpressionToken::Not
+        } else {
+            QueryExpressionToken::Term(word)
+        }
+    }
+}
+
+impl QueryExpressionError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryExpressionError::Empty => "empty",
+            QueryExpressionError::UnbalancedGroup => "unbalanced_group",
+            QueryExpressionError::MissingOperand => "missing_operand",
+            QueryExpressionError::TooDeep => "too_deep",
+            QueryExpressionError::TooManyTerms => "too_many_terms",
+            QueryExpressionError::UnboundNegation => "unbound_negation",
+        }
+    }
+}
+
+impl<'a> QueryExpression<'a> {
+    pub fn evaluate<T, F>(&self, resolve: &mut F) -> LinkedHashSet<T>
+    where
+        T: Hash + Eq + Copy,
+        F: FnMut(&'a str) -> LinkedHashSet<T>,
+    {
+        // Notice: the builder ensures that the root node cannot be an excluding one
+        match self.resolve(resolve) {
+            QueryExpressionSet::Included(items) => items,
+            QueryExpressionSet::Excluded(_) => LinkedHashSet::new(),
+        }
+    }
+
+    fn resolve<T, F>(&self, resolve: &mut F) -> QueryExpressionSet<T>
+    where
+        T: Hash + Eq + Copy,
+        F: FnMut(&'a str) -> LinkedHashSet<T>,
+    {
+        match self {
+            QueryExpression::Term(term) => QueryExpressionSet::Included(resolve(term)),
+            QueryExpression::Not(inner) => match inner.resolve(resolve) {
+                QueryExpressionSet::Included(items) => QueryExpressionSet::Excluded(items),
+                QueryExpressionSet::Excluded(items) => QueryExpressionSet::Included(items),
+            },
+            QueryExpression::And(left, right) => {
+                let left_set = left.resolve(resolve);
+
+                // Nothing can match on the left side? Do not resolve the right side.
+                if let QueryExpressionSet::Included(ref items) = left_set {
+                    if items.is_empty() {
+                        return left_set;
+                    }
+                }
+
+                match (left_set, right.resolve(resolve)) {
+                    (QueryExpressionSet::Included(left), QueryExpressionSet::Included(right)) => {
+                        QueryExpressionSet::Included(Self::intersect(&left, &right))
+                    }
+                    (QueryExpressionSet::Included(left), QueryExpressionSet::Excluded(right)) => {
+                        QueryExpressionSet::Included(Self::subtract(&left, &right))
+                    }
+                    (QueryExpressionSet::Excluded(left), QueryExpressionSet::Included(right)) => {
+                        QueryExpressionSet::Included(Self::subtract(&right, &left))
+                    }
+                    (QueryExpressionSet::Excluded(left), QueryExpressionSet::Excluded(right)) => {
+                        QueryExpressionSet::Excluded(Self::unite(left, &right))
+                    }
+                }
+            }
+            QueryExpression::Or(left, right) => {
+                match (left.resolve(resolve), right.resolve(resolve)) {
+                    (QueryExpressionSet::Included(left), QueryExpressionSet::Included(right)) => {
+                        QueryExpressionSet::Included(Self::unite(left, &right))
+                    }
+                    (QueryExpressionSet::Included(left), QueryExpressionSet::Excluded(right)) => {
+                        QueryExpressionSet::Excluded(Self::subtract(&right, &left))
+                    }
+                    (QueryExpressionSet::Excluded(left), QueryExpressionSet::Included(right)) => {
+                        QueryExpressionSet::Excluded(Self::subtract(&left, &right))
+                    }
+                    (QueryExpressionSet::Excluded(left), QueryExpressionSet::Excluded(right)) => {
+                        QueryExpressionSet::Excluded(Self::intersect(&left, &right))
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_excluding(&self) -> bool {
+        match self {
+            QueryExpression::Term(_) => false,
+            QueryExpression::Not(inner) => !inner.is_excluding(),
+            QueryExpression::And(left, right) => left.is_excluding() && right.is_excluding(),
+            QueryExpression::Or(left, right) => left.is_excluding() || right.is_excluding(),
+        }
+    }
+
+    fn intersect<T: Hash + Eq + Copy>(
+        left: &LinkedHashSet<T>,
+        right: &LinkedHashSet<T>,
+    ) -> LinkedHashSet<T> {
+        left.iter()
+            .filter(|item| right.contains(item))
+            .copied()
+            .collect()
+    }
+
+    fn subtract<T: Hash + Eq + Copy>(
+        left: &LinkedHashSet<T>,
+        right: &LinkedHashSet<T>,
+    ) -> LinkedHashSet<T> {
+        left.iter()
+            .filter(|item| !right.contains(item))
+            .copied()
+            .collect()
+    }
+
+    fn unite<T: Hash + Eq + Copy>(
+        mut left: LinkedHashSet<T>,
+        right: &LinkedHashSet<T>,
+    ) -> LinkedHashSet<T> {
+        for item in right.iter() {
+            if !left.contains(item) {
+                left.insert(*item);
+            }
+        }
+
+        left
+    }
+}
+
+impl<'a> QueryExpressionParser<'a> {
+    fn parse_or(&mut self) -> Result<QueryExpression<'a>, QueryExpressionParseError> {
+        let mut expression = self.parse_and()?;
+
+        while self.peek() == Some(&QueryExpressionToken::Or) {
+            self.cursor += 1;
+
+            expression = QueryExpression::Or(Box::new(expression), Box::new(self.parse_and()?));
+        }
+
+        Ok(expression)
+    }
+
+    fn parse_and(&mut self) -> Result<QueryExpression<'a>, QueryExpressionParseError> {
+        let mut expression = self.parse_not()?;
+
+        loop {
+            match self.peek() {
+                Some(QueryExpressionToken::And) => {
+                    self.cursor += 1;
+                }
+                Some(QueryExpressionToken::Or) | Some(QueryExpressionToken::GroupClose) | None => {
+                    break;
+                }
+                _ => {
+                    // Implicit 'AND' between two consecutive operands
+                }
+            }
+
+            expression = QueryExpression::And(Box::new(expression), Box::new(self.parse_not()?));
+        }
+
+        Ok(expression)
+    }
+
+    fn parse_not(&mut self) -> Result<QueryExpression<'a>, QueryExpressionParseError> {
+        if self.peek() == Some(&QueryExpressionToken::Not) {
+            let position = self.position();
+
+            self.cursor += 1;
+
+            self.enter(position)?;
+
+            let inner = self.parse_not()?;
+
+            self.depth -= 1;
+
+            Ok(QueryExpression::Not(Box::new(inner)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<QueryExpression<'a>, QueryExpressionParseError> {
+        let position = self.position();
+
+        match self.peek() {
+            Some(QueryExpressionToken::Term(term)) => {
+                let term = *term;
+
+                self.cursor += 1;
+                self.terms += 1;
+
+                if self.terms > EXPRESSION_MAX_TERMS {
+                    Err(self.error(QueryExpressionError::TooManyTerms, position))
+                } else {
+                    Ok(QueryExpression::Term(term))
+                }
+            }
+            Some(QueryExpressionToken::GroupOpen) => {
+                self.cursor += 1;
+
+                self.enter(position)?;
+
+                let inner = self.parse_or()?;
+
+                if self.peek() == Some(&QueryExpressionToken::GroupClose) {
+                    self.cursor += 1;
+                    self.depth -= 1;
+
+                    Ok(inner)
+                } else {
+                    // Point to the group that was never closed
+                    Err(self.error(QueryExpressionError::UnbalancedGroup, position))
+                }
+            }
+            Some(QueryExpressionToken::GroupClose) if self.depth == 0 => {
+                Err(self.error(QueryExpressionError::UnbalancedGroup, position))
+            }
+            _ => Err(self.error(QueryExpressionError::MissingOperand, position)),
+        }
+    }
+
+    fn enter(&mut self, position: usize) -> Result<(), QueryExpressionParseError> {
+        self.depth += 1;
+
+        if self.depth > EXPRESSION_MAX_DEPTH {
+            Err(self.error(QueryExpressionError::TooDeep, position))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn peek(&self) -> Option<&QueryExpressionToken<'a>> {
+        self.tokens.get(self.cursor).map(|(token, _)| token)
+    }
+
+    fn position(&self) -> usize {
+        // Position of the next token to be parsed (or the end of the expression if none left)
+        self.tokens
+            .get(self.cursor)
+            .map(|(_, position)| *position)
+            .unwrap_or(self.length)
+    }
+
+    fn error(&self, error: QueryExpressionError, position: usize) -> QueryExpressionParseError {
+        QueryExpressionParseError { error, position }
+    }
+}
+
+impl<'a> fmt::Display for QueryExpression<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        // Notice: binary operations are always wrapped in a group, so that the structure of the \
+        //   expression is explicit (eg. 'a b OR c' is shown as '((a AND b) OR c)')
+        match self {
+            QueryExpression::Term(term) => write!(f, "{}", term),
+            QueryExpression::And(left, right) => write!(f, "({} AND {})", left, right),
+            QueryExpression::Or(left, right) => write!(f, "({} OR {})", left, right),
+            QueryExpression::Not(inner) => write!(f, "NOT {}", inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(text: &str) -> Vec<u32> {
+        let expression = QueryExpressionBuilder::from(text).expect("expression should be valid");
+
+        expression
+            .evaluate(&mut |term| {
+                let iids: &[u32] = match term {
+                    "a" => &[1, 2, 3, 4],
+                    "b" => &[3, 4, 5, 6],
+                    "c" => &[2, 4, 6, 8],
+                    "d" => &[8, 9],
+                    _ => &[],
+                };
+
+                iids.iter().copied().collect()
+            })
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn it_parses_expressions() {
+        assert_eq!(
+            QueryExpressionBuilder::from("a"),
+            Ok(QueryExpression::Term("a"))
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("(a OR b) AND NOT c"),
+            Ok(QueryExpression::And(
+                Box::new(QueryExpression::Or(
+                    Box::new(QueryExpression::Term("a")),
+                    Box::new(QueryExpression::Term("b"))
+                )),
+                Box::new(QueryExpression::Not(Box::new(QueryExpression::Term("c"))))
+            ))
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("a b"),
+            QueryExpressionBuilder::from("a AND b")
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("a or b"),
+            Ok(QueryExpression::And(
+                Box::new(QueryExpression::And(
+                    Box::new(QueryExpression::Term("a")),
+                    Box::new(QueryExpression::Term("or"))
+                )),
+                Box::new(QueryExpression::Term("b"))
+            ))
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_expressions() {
+        assert_eq!(
+            QueryExpressionBuilder::from(""),
+            Err(QueryExpressionError::Empty)
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("(a OR b"),
+            Err(QueryExpressionError::UnbalancedGroup)
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("a OR b)"),
+            Err(QueryExpressionError::UnbalancedGroup)
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("a AND"),
+            Err(QueryExpressionError::MissingOperand)
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("OR a"),
+            Err(QueryExpressionError::MissingOperand)
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("()"),
+            Err(QueryExpressionError::MissingOperand)
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("NOT a"),
+            Err(QueryExpressionError::UnboundNegation)
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("a OR NOT b"),
+            Err(QueryExpressionError::UnboundNegation)
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("(((((((((a)))))))))"),
+            Err(QueryExpressionError::TooDeep)
+        );
+        assert_eq!(
+            QueryExpressionBuilder::from("a b c d e f g h i j k l m n o p q"),
+            Err(QueryExpressionError::TooManyTerms)
+        );
+    }
+
+    #[test]
+    fn it_locates_invalid_expressions() {
+        let locate = |text| {
+            QueryExpressionBuilder::parse(text)
+                .map_err(|parse_err| (parse_err.error, parse_err.position))
+        };
+
+        assert_eq!(locate("  "), Err((QueryExpressionError::Empty, 0)));
+        assert_eq!(
+            locate("a AND (b OR c"),
+            Err((QueryExpressionError::UnbalancedGroup, 6))
+        );
+        assert_eq!(
+            locate("a OR b) c"),
+            Err((QueryExpressionError::UnbalancedGroup, 6))
+        );
+        assert_eq!(
+            locate("a AND NOT"),
+            Err((QueryExpressionError::MissingOperand, 9))
+        );
+        assert_eq!(
+            locate("a OR OR b"),
+            Err((QueryExpressionError::MissingOperand, 5))
+        );
+        assert_eq!(
+            locate("été AND (OR b)"),
+            Err((QueryExpressionError::MissingOperand, 9))
+        );
+        assert_eq!(
+            locate("a AND NOT b OR NOT c"),
+            Err((QueryExpressionError::UnboundNegation, 6))
+        );
+        assert_eq!(
+            locate("a AND (((((((((b)))))))))"),
+            Err((QueryExpressionError::TooDeep, 14))
+        );
+        assert_eq!(
+            locate("a b c d e f g h i j k l m n o p q r"),
+            Err((QueryExpressionError::TooManyTerms, 32))
+        );
+    }
+
+    #[test]
+    fn it_displays_expressions() {
+        let display =
+            |text| QueryExpressionBuilder::parse(text).map(|expression| expression.to_string());
+
+        assert_eq!(display("a"), Ok("a".to_string()));
+        assert_eq!(
+            display("(Michael OR Mike) Dake AND NOT (Smith OR Jones)"),
+            Ok("(((Michael OR Mike) AND Dake) AND NOT (Smith OR Jones))".to_string())
+        );
+        assert_eq!(
+            display("a b OR NOT c d"),
+            Ok("((a AND b) OR (NOT c AND d))".to_string())
+        );
+    }
+
+    #[test]
+    fn it_evaluates_precedence() {
+        assert_eq!(evaluate("a AND b OR d"), vec![3, 4, 8, 9]);
+        assert_eq!(evaluate("d OR a AND b"), vec![8, 9, 3, 4]);
+        assert_eq!(evaluate("a b OR c d"), vec![3, 4, 8]);
+        assert_eq!(evaluate("a AND NOT b OR d"), vec![1, 2, 8, 9]);
+    }
+
+    #[test]
+    fn it_evaluates_grouping() {
+        assert_eq!(evaluate("a AND (b OR d)"), vec![3, 4]);
+        assert_eq!(evaluate("(a OR d) AND c"), vec![2, 4, 8]);
+        assert_eq!(evaluate("(a OR b) AND (c OR d)"), vec![2, 4, 6]);
+        assert_eq!(evaluate("((a))"), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn it_evaluates_negation() {
+        assert_eq!(evaluate("a AND NOT b"), vec![1, 2]);
+        assert_eq!(evaluate("NOT b AND a"), vec![1, 2]);
+        assert_eq!(evaluate("(a OR b) AND NOT c"), vec![1, 3, 5]);
+        assert_eq!(evaluate("a AND NOT (b OR c)"), vec![1]);
+        assert_eq!(evaluate("a AND NOT NOT c"), vec![2, 4]);
+        assert_eq!(evaluate("a AND NOT (NOT b OR NOT c)"), vec![4]);
+        assert_eq!(evaluate("a AND NOT unknown"), vec![1, 2, 3, 4]);
+    }
+}
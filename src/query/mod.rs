@@ -6,4 +6,6 @@
 
 pub mod actions;
 pub mod builder;
+pub mod expression;
+pub mod ranker;
 pub mod types;
@@ -0,0 +1,220 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use crate::config::options::ConfigChannelSearchRanker;
+use crate::store::identifiers::{
+    StoreObjectBoost, StoreTermIDF, StoreTermWeight, STORE_TERM_WEIGHT_DEFAULT,
+};
+
+pub trait Ranker {
+    /// Whether scores depend on term frequencies and document lengths (those get read for \
+    ///   each ranked object, which costs extra database reads)
+    fn uses_document_stats(&self) -> bool {
+        false
+    }
+
+    /// Score an object from the stats of searched terms, the stats of the object and how the \
+    ///   object matches each searched term (matches are given in the order of terms)
+    fn score(
+        &self,
+        terms: &[RankerTerm],
+        document: &RankerDocument,
+        matches: &[RankerMatch],
+    ) -> f32;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankerTerm {
+    pub idf: StoreTermIDF,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankerDocument {
+    pub boost: StoreObjectBoost,
+    pub length: usize,
+    pub average_length: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankerMatch {
+    pub weight: StoreTermWeight,
+    pub frequency: u32,
+}
+
+pub struct RankerIDF;
+pub struct RankerBM25;
+pub struct RankerMatchCount;
+
+pub struct RankerBuilder;
+
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+
+static RANKER_IDF: RankerIDF = RankerIDF;
+static RANKER_BM25: RankerBM25 = RankerBM25;
+static RANKER_MATCH_COUNT: RankerMatchCount = RankerMatchCount;
+
+impl RankerBuilder {
+    pub fn from(ranker: ConfigChannelSearchRanker) -> &'static dyn Ranker {
+        match ranker {
+            ConfigChannelSearchRanker::Idf => &RANKER_IDF,
+            ConfigChannelSearchRanker::Bm25 => &RANKER_BM25,
+            ConfigChannelSearchRanker::MatchCount => &RANKER_MATCH_COUNT,
+        }
+    }
+}
+
+impl Ranker for RankerIDF {
+    fn score(
+        &self,
+        terms: &[RankerTerm],
+        document: &RankerDocument,
+        matches: &[RankerMatch],
+    ) -> f32 {
+        // Sum the weight of each searched term, scaled by the term IDF weight (ie. rare terms \
+        //   contribute more to the score; all searched terms are assumed to be matched)
+        let weight: StoreTermWeight = if terms.is_empty() {
+            STORE_TERM_WEIGHT_DEFAULT
+        } else {
+            terms
+                .iter()
+                .zip(matches.iter())
+                .map(|(term, term_match)| term_match.weight * term.idf)
+                .sum()
+        };
+
+        document.boost * weight
+    }
+}
+
+impl Ranker for RankerBM25 {
+    fn uses_document_stats(&self) -> bool {
+        true
+    }
+
+    fn score(
+        &self,
+        terms: &[RankerTerm],
+        document: &RankerDocument,
+        matches: &[RankerMatch],
+    ) -> f32 {
+        // Normalize term frequencies by the object length, relative to the average length of \
+        //   ranked objects (long objects that mention a term once score lower than short ones)
+        let length_ratio = if document.average_length > 0.0 {
+            document.length as f32 / document.average_length
+        } else {
+            1.0
+        };
+
+        let length_norm = BM25_K1 * (1.0 - BM25_B + BM25_B * length_ratio);
+
+        let weight: f32 = terms
+            .iter()
+            .zip(matches.iter())
+            .filter(|(_, term_match)| term_match.frequency > 0)
+            .map(|(term, term_match)| {
+                let frequency = term_match.frequency as f32;
+
+                term.idf * term_match.weight * (frequency * (BM25_K1 + 1.0))
+                    / (frequency + length_norm)
+            })
+            .sum();
+
+        document.boost * weight
+    }
+}
+
+impl Ranker for RankerMatchCount {
+    fn uses_document_stats(&self) -> bool {
+        true
+    }
+
+    fn score(
+        &self,
+        _terms: &[RankerTerm],
+        document: &RankerDocument,
+        matches: &[RankerMatch],
+    ) -> f32 {
+        // Count matched terms (each term counting for its weight, which defaults to 1)
+        let weight: f32 = matches
+            .iter()
+            .filter(|term_match| term_match.frequency > 0)
+            .map(|term_match| term_match.weight)
+            .sum();
+
+        document.boost * weight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(boost: StoreObjectBoost, length: usize) -> RankerDocument {
+        RankerDocument {
+            boost,
+            length,
+            average_length: 4.0,
+        }
+    }
+
+    fn matches(frequencies: &[u32]) -> Vec<RankerMatch> {
+        frequencies
+            .iter()
+            .map(|frequency| RankerMatch {
+                weight: STORE_TERM_WEIGHT_DEFAULT,
+                frequency: *frequency,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_scores_with_rankers() {
+        let terms = [RankerTerm { idf: 2.0 }, RankerTerm { idf: 0.5 }];
+
+        assert_eq!(
+            RankerIDF.score(&terms, &document(2.0, 4), &matches(&[1, 1])),
+            5.0
+        );
+        assert_eq!(
+            RankerMatchCount.score(&terms, &document(2.0, 4), &matches(&[3, 0])),
+            2.0
+        );
+
+        // BM25 favors frequent terms, and short objects
+        let bm25 = |length, frequencies: &[u32]| {
+            RankerBM25.score(&terms, &document(1.0, length), &matches(frequencies))
+        };
+
+        assert!((bm25(4, &[1, 0]) - 2.0).abs() < 0.0001);
+        assert!(bm25(4, &[2, 0]) > bm25(4, &[1, 0]));
+        assert!(bm25(2, &[1, 0]) > bm25(8, &[1, 0]));
+        assert!(bm25(4, &[1, 0]) > bm25(4, &[0, 1]));
+        assert_eq!(bm25(4, &[0, 0]), 0.0);
+    }
+
+    #[test]
+    fn it_scores_deterministically() {
+        let terms = [RankerTerm { idf: 1.7 }, RankerTerm { idf: 0.3 }];
+        let (document, matches) = (document(1.5, 7), matches(&[2, 1]));
+
+        for ranker in &[
+            ConfigChannelSearchRanker::Idf,
+            ConfigChannelSearchRanker::Bm25,
+            ConfigChannelSearchRanker::MatchCount,
+        ] {
+            let ranker = RankerBuilder::from(*ranker);
+            let score = ranker.score(&terms, &document, &matches);
+
+            for _ in 0..8 {
+                assert_eq!(
+                    ranker.score(&terms, &document, &matches).to_bits(),
+                    score.to_bits()
+                );
+            }
+        }
+    }
+}
@@ -6,21 +6,76 @@
 
 use whatlang::Lang;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum QueryGenericLang {
     Enabled(Lang),
     Disabled,
 }
 
+#[derive(Debug, PartialEq)]
+pub enum QuerySearchSyntax {
+    Plain,
+    Boolean,
+    Raw,
+    Phrase,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum QuerySearchTimeout {
+    Partial,
+    Error,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum QuerySearchCompletion {
+    AllTerms,
+    LastTerm,
+    Disabled,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum QueryPushSuggest {
+    Incremental,
+    Deferred,
+    Flush,
+}
+
 pub type QuerySearchID<'a> = &'a str;
 pub type QuerySearchLimit = u16;
 pub type QuerySearchOffset = u32;
+pub type QuerySearchTypos = u8;
+pub type QueryPushBoost = f32;
+pub type QueryPushWeight = f32;
+pub type QueryRetainAge = u32;
+pub type QueryRetainObjects = u32;
 
-pub type QueryMetaData = (
-    Option<QuerySearchLimit>,
-    Option<QuerySearchOffset>,
-    Option<QueryGenericLang>,
-);
+pub const QUERY_SEARCH_TYPOS_MAXIMUM: QuerySearchTypos = 2;
+
+#[derive(Debug, PartialEq)]
+pub enum QueryMetaData<'a> {
+    Limit(QuerySearchLimit),
+    Offset(QuerySearchOffset),
+    Lang(QueryGenericLang),
+    Syntax(QuerySearchSyntax),
+    Federate(Vec<&'a str>),
+    Timeout(QuerySearchTimeout),
+    Typos(QuerySearchTypos),
+    Partial(QuerySearchCompletion),
+    Snippets(bool),
+    Terms(bool),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PushMetaData<'a> {
+    Lang(QueryGenericLang),
+    Suggest(QueryPushSuggest),
+    Boost(QueryPushBoost),
+    Key(&'a str),
+    Weights(Vec<(&'a str, QueryPushWeight)>),
+    Verbose(bool),
+}
+
+pub type RetainMetaData = (Option<QueryRetainAge>, Option<QueryRetainObjects>);
 
 pub type ListMetaData = (Option<QuerySearchLimit>, Option<QuerySearchOffset>);
 
@@ -34,6 +89,51 @@ impl QueryGenericLang {
     }
 }
 
+impl QuerySearchSyntax {
+    pub fn from_value(value: &str) -> Option<QuerySearchSyntax> {
+        match value {
+            "plain" => Some(QuerySearchSyntax::Plain),
+            "boolean" => Some(QuerySearchSyntax::Boolean),
+            "raw" => Some(QuerySearchSyntax::Raw),
+            "phrase" => Some(QuerySearchSyntax::Phrase),
+            _ => None,
+        }
+    }
+}
+
+impl QuerySearchTimeout {
+    pub fn from_value(value: &str) -> Option<QuerySearchTimeout> {
+        match value {
+            "partial" => Some(QuerySearchTimeout::Partial),
+            "error" => Some(QuerySearchTimeout::Error),
+            _ => None,
+        }
+    }
+}
+
+impl QuerySearchCompletion {
+    pub fn from_value(value: &str) -> Option<QuerySearchCompletion> {
+        // Notice: a partial query completes its last term only (as it may be a word being \
+        //   typed), while a query that is not partial has none of its terms completed.
+        match value {
+            "true" => Some(QuerySearchCompletion::LastTerm),
+            "false" => Some(QuerySearchCompletion::Disabled),
+            _ => None,
+        }
+    }
+}
+
+impl QueryPushSuggest {
+    pub fn from_value(value: &str) -> Option<QueryPushSuggest> {
+        match value {
+            "incremental" => Some(QueryPushSuggest::Incremental),
+            "deferred" => Some(QueryPushSuggest::Deferred),
+            "flush" => Some(QueryPushSuggest::Flush),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,4 +150,47 @@ mod tests {
         );
         assert_eq!(QueryGenericLang::from_value("xxx"), None);
     }
+
+    #[test]
+    fn it_parses_search_syntax_from_value() {
+        assert_eq!(
+            QuerySearchSyntax::from_value("plain"),
+            Some(QuerySearchSyntax::Plain)
+        );
+        assert_eq!(
+            QuerySearchSyntax::from_value("boolean"),
+            Some(QuerySearchSyntax::Boolean)
+        );
+        assert_eq!(QuerySearchSyntax::from_value("regex"), None);
+    }
+
+    #[test]
+    fn it_parses_search_timeout_from_value() {
+        assert_eq!(
+            QuerySearchTimeout::from_value("partial"),
+            Some(QuerySearchTimeout::Partial)
+        );
+        assert_eq!(
+            QuerySearchTimeout::from_value("error"),
+            Some(QuerySearchTimeout::Error)
+        );
+        assert_eq!(QuerySearchTimeout::from_value("ignore"), None);
+    }
+
+    #[test]
+    fn it_parses_push_suggest_from_value() {
+        assert_eq!(
+            QueryPushSuggest::from_value("incremental"),
+            Some(QueryPushSuggest::Incremental)
+        );
+        assert_eq!(
+            QueryPushSuggest::from_value("deferred"),
+            Some(QueryPushSuggest::Deferred)
+        );
+        assert_eq!(
+            QueryPushSuggest::from_value("flush"),
+            Some(QueryPushSuggest::Flush)
+        );
+        assert_eq!(QueryPushSuggest::from_value("never"), None);
+    }
 }
@@ -16,20 +16,24 @@ use hashbrown::{HashMap, HashSet};
 use radix::RadixNum;
 use regex_syntax::escape as regex_escape;
 use std::collections::VecDeque;
+use std::error::Error;
 use std::fmt;
-use std::fs::{self, File};
+use std::fs::{self, File, OpenOptions};
 use std::io::{self, BufRead, BufReader, BufWriter, Write};
 use std::iter::FromIterator;
 use std::path::{Path, PathBuf};
 use std::str;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 
 use super::generic::{
     StoreGeneric, StoreGenericActionBuilder, StoreGenericBuilder, StoreGenericPool,
 };
+use super::identifiers::{StoreTermHash, StoreTermHashed};
 use super::keyer::StoreKeyerHasher;
+use crate::config::options::ConfigStoreFSTGraphWarmup;
 use crate::lexer::ranges::LexerRegexRange;
 use crate::APP_CONF;
 
@@ -42,6 +46,7 @@ pub struct StoreFST {
     pending: StoreFSTPending,
     last_used: Arc<RwLock<SystemTime>>,
     last_consolidated: Arc<RwLock<SystemTime>>,
+    last_held: Arc<RwLock<Option<SystemTime>>>,
 }
 
 #[derive(Default)]
@@ -64,11 +69,19 @@ pub struct StoreFSTKey {
 
 pub struct StoreFSTMisc;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StoreFSTError {
+    Graph,
+    Io(io::ErrorKind),
+    Lookup,
+}
+
 #[derive(Copy, Clone)]
 enum StoreFSTPathMode {
     Permanent,
     Temporary,
     Backup,
+    Journal,
 }
 
 type StoreFSTAtom = u32;
@@ -77,6 +90,16 @@ type StoreFSTBox = Arc<StoreFST>;
 const WORD_LIMIT_LENGTH: usize = 40;
 const ATOM_HASH_RADIX: usize = 16;
 
+const JOURNAL_OP_PUSH: u8 = b'+';
+const JOURNAL_OP_POP: u8 = b'-';
+
+// Notice: tests that run consolidation ticks must not run in parallel, as each tick also \
+//   picks up the graphs of other tests.
+#[cfg(test)]
+pub static CONSOLIDATE_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+static GRAPH_CONSOLIDATE_TOOK: AtomicU64 = AtomicU64::new(0);
+
 lazy_static! {
     pub static ref GRAPH_ACCESS_LOCK: Arc<RwLock<bool>> = Arc::new(RwLock::new(false));
     static ref GRAPH_ACQUIRE_LOCK: Arc<Mutex<()>> = Arc::new(Mutex::new(()));
@@ -93,6 +116,7 @@ impl StoreFSTPathMode {
             StoreFSTPathMode::Permanent => ".fst",
             StoreFSTPathMode::Temporary => ".fst.tmp",
             StoreFSTPathMode::Backup => ".fst.bck",
+            StoreFSTPathMode::Journal => ".fst.journal",
         }
     }
 }
@@ -105,7 +129,14 @@ impl StoreFSTPool {
         )
     }
 
-    pub fn acquire<'a, T: Into<&'a str>>(collection: T, bucket: T) -> Result<StoreFSTBox, ()> {
+    pub fn consolidate_took() -> u64 {
+        GRAPH_CONSOLIDATE_TOOK.load(Ordering::Relaxed)
+    }
+
+    pub fn acquire<'a, T: Into<&'a str>>(
+        collection: T,
+        bucket: T,
+    ) -> Result<StoreFSTBox, StoreFSTError> {
         let (collection_str, bucket_str) = (collection.into(), bucket.into());
 
         let pool_key = StoreFSTKey::from_str(collection_str, bucket_str);
@@ -118,7 +149,12 @@ impl StoreFSTPool {
         let graph_pool_read = GRAPH_POOL.read().unwrap();
 
         if let Some(store_fst) = graph_pool_read.get(&pool_key) {
-            Self::proceed_acquire_cache("fst", collection_str, pool_key, store_fst)
+            Ok(Self::proceed_acquire_cache(
+                "fst",
+                collection_str,
+                pool_key,
+                store_fst,
+            ))
         } else {
             info!(
                 "fst store not in pool for collection: {} <{:x?}> / bucket: {} <{:x?}>, opening it",
@@ -171,6 +207,22 @@ impl StoreFSTPool {
         )
     }
 
+    pub fn unsuggest<'a, T: Into<&'a str>>(
+        collection: T,
+        bucket: T,
+        words: &[String],
+    ) -> Result<u32, StoreFSTError> {
+        // Important: acquire graph access read lock, and reference it in context. This prevents \
+        //   the graph from being erased while popping words from it.
+        let _access = GRAPH_ACCESS_LOCK.read().unwrap();
+
+        let action = StoreFSTActionBuilder::access(Self::acquire(collection, bucket)?);
+
+        // Notice: words are popped through the pending 'pop' set, and thus only get removed \
+        //   from the graph upon its next consolidation. KV postings are left untouched.
+        Ok(words.iter().filter(|word| action.pop_word(word)).count() as u32)
+    }
+
     pub fn consolidate(force: bool) {
         debug!("scanning for fst store pool items to consolidate");
 
@@ -225,12 +277,23 @@ impl StoreFSTPool {
                         })
                         .as_secs();
 
+                    // Held back by a bulk load that did not end yet? (released by a flush, or \
+                    //   once no deferred push was received for a while)
+                    if !force && store.is_held() {
+                        debug!("fst key: {} held back by a bulk load, no consolidate", key);
+
+                        continue;
+                    }
+
                     if force || not_consolidated_for >= APP_CONF.store.fst.graph.consolidate_after {
                         info!(
                             "fst key: {} not consolidated for: {} seconds, may consolidate",
                             key, not_consolidated_for
                         );
 
+                        // Release any hold, as the bulk load is now consolidated (eg. if forced)
+                        *store.last_held.write().unwrap() = None;
+
                         keys_consolidate.push(*key);
                     } else {
                         debug!(
@@ -267,6 +330,8 @@ impl StoreFSTPool {
         // Step 3: Consolidate FSTs, one-by-one (sequential locking; this avoids global locks)
         let (mut count_moved, mut count_pushed, mut count_popped) = (0, 0, 0);
 
+        let consolidate_start = Instant::now();
+
         {
             for key in &keys_consolidate {
                 {
@@ -313,6 +378,12 @@ impl StoreFSTPool {
             }
         }
 
+        // Remember how long the last consolidation took (exposed in server statistics)
+        GRAPH_CONSOLIDATE_TOOK.store(
+            consolidate_start.elapsed().as_millis() as u64,
+            Ordering::Relaxed,
+        );
+
         info!(
             "done scanning for fst store pool items to consolidate (move: {}, push: {}, pop: {})",
             count_moved, count_pushed, count_popped
@@ -514,7 +585,7 @@ impl StoreFSTPool {
         Ok(())
     }
 
-    fn consolidate_item(store: &StoreFSTBox) -> (bool, usize, usize, usize) {
+    fn consolidate_item(store: &StoreFST) -> (bool, usize, usize, usize) {
         let (mut should_close, mut count_moved, mut count_pushed, mut count_popped) =
             (false, 0, 0, 0);
 
@@ -689,6 +760,9 @@ impl StoreFSTPool {
                                 // Proceed temporary FST to final FST path rename
                                 if fs::rename(&bucket_tmp_path, &bucket_final_path).is_ok() {
                                     info!("done consolidate fst at path: {:?}", bucket_final_path);
+
+                                    // Pending changes are now part of the FST (journal them out)
+                                    StoreFSTBuilder::clear_journal(store.target);
                                 } else {
                                     error!(
                                         "error consolidating fst at path: {:?}",
@@ -726,6 +800,9 @@ impl StoreFSTPool {
             // Reset all pending sets
             *pending_push_write = HashSet::new();
             *pending_pop_write = HashSet::new();
+        } else {
+            // Changes nulled each other out (the journal holds nothing left to be replayed)
+            StoreFSTBuilder::clear_journal(store.target);
         }
 
         (should_close, count_moved, count_pushed, count_popped)
@@ -793,8 +870,43 @@ impl StoreFSTBuilder {
     }
 }
 
-impl StoreGenericBuilder<StoreFSTKey, StoreFST> for StoreFSTBuilder {
-    fn build(pool_key: StoreFSTKey) -> Result<StoreFST, ()> {
+impl StoreFSTBuilder {
+    fn build_warmup(
+        pool_key: StoreFSTKey,
+        warmup: &ConfigStoreFSTGraphWarmup,
+    ) -> Result<StoreFST, StoreFSTError> {
+        let store = Self::make(pool_key)?;
+
+        // Rebuild stale graph? (if any journaled change was not consolidated, eg. after a crash)
+        if *warmup != ConfigStoreFSTGraphWarmup::Disabled {
+            if let Some(journal) = Self::read_journal(pool_key) {
+                info!(
+                    "fst key: {} is stale, replaying {} journaled changes",
+                    pool_key,
+                    journal.len()
+                );
+
+                store.replay_journal(journal);
+
+                if *warmup == ConfigStoreFSTGraphWarmup::Blocking {
+                    // Rebuild graph right away, then re-open it from its consolidated file
+                    if StoreFSTPool::consolidate_item(&store).0 {
+                        return Self::make(pool_key);
+                    }
+                } else {
+                    // Schedule target for the next consolidation tick (the stale graph is \
+                    //   served meanwhile)
+                    GRAPH_CONSOLIDATE.write().unwrap().insert(pool_key);
+
+                    *store.last_consolidated.write().unwrap() = SystemTime::UNIX_EPOCH;
+                }
+            }
+        }
+
+        Ok(store)
+    }
+
+    fn make(pool_key: StoreFSTKey) -> Result<StoreFST, StoreFSTError> {
         Self::open(pool_key.collection_hash, pool_key.bucket_hash)
             .map(|graph| {
                 let now = SystemTime::now();
@@ -805,12 +917,118 @@ impl StoreGenericBuilder<StoreFSTKey, StoreFST> for StoreFSTBuilder {
                     pending: StoreFSTPending::default(),
                     last_used: Arc::new(RwLock::new(now)),
                     last_consolidated: Arc::new(RwLock::new(now)),
+                    last_held: Arc::new(RwLock::new(None)),
                 }
             })
             .map_err(|err| {
                 error!("failed opening fst: {}", err);
+
+                StoreFSTError::from(err)
             })
     }
+
+    fn journal(target: StoreFSTKey, operation: u8, word: &[u8]) {
+        let journal_path = Self::path(
+            StoreFSTPathMode::Journal,
+            target.collection_hash,
+            Some(target.bucket_hash),
+        );
+
+        // Append change to journal (one change per line, prefixed with its operation)
+        let mut entry = Vec::with_capacity(word.len() + 2);
+
+        entry.push(operation);
+        entry.extend_from_slice(word);
+        entry.push(b'\n');
+
+        let result = journal_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&journal_path)
+            })
+            .and_then(|mut journal_file| journal_file.write_all(&entry));
+
+        if let Err(err) = result {
+            error!(
+                "failed journaling fst change at path: {:?}: {}",
+                journal_path, err
+            );
+        }
+    }
+
+    fn clear_journal(target: StoreFSTKey) {
+        let journal_path = Self::path(
+            StoreFSTPathMode::Journal,
+            target.collection_hash,
+            Some(target.bucket_hash),
+        );
+
+        // Notice: there is no guarantee this succeeds (eg. no change was journaled)
+        fs::remove_file(journal_path).ok();
+    }
+
+    fn read_journal(target: StoreFSTKey) -> Option<Vec<(u8, Vec<u8>)>> {
+        let (journal_path, graph_path) = (
+            Self::path(
+                StoreFSTPathMode::Journal,
+                target.collection_hash,
+                Some(target.bucket_hash),
+            ),
+            Self::path(
+                StoreFSTPathMode::Permanent,
+                target.collection_hash,
+                Some(target.bucket_hash),
+            ),
+        );
+
+        let journal_modified = fs::metadata(&journal_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()?;
+
+        // Graph is stale if its journal was written to after it was last consolidated (a journal \
+        //   older than its graph was already consolidated, eg. if the process stopped before \
+        //   clearing it)
+        let is_stale = fs::metadata(&graph_path)
+            .and_then(|metadata| metadata.modified())
+            .map_or(true, |graph_modified| journal_modified >= graph_modified);
+
+        if !is_stale {
+            Self::clear_journal(target);
+
+            return None;
+        }
+
+        match File::open(&journal_path) {
+            Ok(journal_file) => Some(
+                BufReader::new(journal_file)
+                    .split(b'\n')
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.len() > 1)
+                    .map(|entry| (entry[0], entry[1..].to_vec()))
+                    .collect(),
+            ),
+            Err(err) => {
+                error!(
+                    "failed reading fst journal at path: {:?}: {}",
+                    journal_path, err
+                );
+
+                None
+            }
+        }
+    }
+}
+
+impl StoreGenericBuilder<StoreFSTKey, StoreFST> for StoreFSTBuilder {
+    type Error = StoreFSTError;
+
+    fn build(pool_key: StoreFSTKey) -> Result<StoreFST, StoreFSTError> {
+        Self::build_warmup(pool_key, &APP_CONF.store.fst.graph.warmup)
+    }
 }
 
 impl StoreFST {
@@ -822,7 +1040,7 @@ impl StoreFST {
         self.graph.into_stream()
     }
 
-    pub fn lookup_begins(&self, word: &str) -> Result<FSTStream<Regex>, ()> {
+    pub fn lookup_begins(&self, word: &str) -> Result<FSTStream<Regex>, StoreFSTError> {
         // Notice: this regex maps over an unicode range, for speed reasons at scale. \
         //   We found out that the 'match any' syntax ('.*') was super-slow. Using the restrictive \
         //   syntax below divided the cost of eg. a search query by 2. The regex below has been \
@@ -845,7 +1063,7 @@ impl StoreFST {
                 word, err
             );
 
-            return Err(());
+            return Err(StoreFSTError::Lookup);
         }
 
         // Proceed word lookup
@@ -857,7 +1075,7 @@ impl StoreFST {
         if let Ok(regex) = Regex::new(&regex_str) {
             Ok(self.graph.search(regex).into_stream())
         } else {
-            Err(())
+            Err(StoreFSTError::Lookup)
         }
     }
 
@@ -865,7 +1083,7 @@ impl StoreFST {
         &self,
         word: &str,
         max_factor: Option<u32>,
-    ) -> Result<FSTStream<Levenshtein>, ()> {
+    ) -> Result<FSTStream<Levenshtein>, StoreFSTError> {
         // Allow more typos in word as the word gets longer, up to a maximum limit
         let mut typo_factor = match word.len() {
             1 | 2 | 3 => 0,
@@ -889,7 +1107,35 @@ impl StoreFST {
         if let Ok(fuzzy) = Levenshtein::new(word, typo_factor) {
             Ok(self.graph.search(fuzzy).into_stream())
         } else {
-            Err(())
+            Err(StoreFSTError::Lookup)
+        }
+    }
+
+    fn replay_journal(&self, journal: Vec<(u8, Vec<u8>)>) {
+        let (mut pending_push_write, mut pending_pop_write) = (
+            self.pending.push.write().unwrap(),
+            self.pending.pop.write().unwrap(),
+        );
+
+        // Replay changes in order (a later change on a word voids an earlier one)
+        for (operation, word) in journal {
+            match operation {
+                JOURNAL_OP_PUSH => {
+                    pending_pop_write.remove(&word);
+
+                    if !self.graph.contains(&word) {
+                        pending_push_write.insert(word);
+                    }
+                }
+                JOURNAL_OP_POP => {
+                    pending_push_write.remove(&word);
+
+                    if self.graph.contains(&word) {
+                        pending_pop_write.insert(word);
+                    }
+                }
+                _ => warn!("ignored unknown fst journal operation: {}", operation),
+            }
         }
     }
 
@@ -916,6 +1162,43 @@ impl StoreFST {
             );
         }
     }
+
+    pub fn should_hold_consolidate(&self) {
+        // Only hold if scheduled (there is nothing to hold otherwise)
+        if GRAPH_CONSOLIDATE.read().unwrap().contains(&self.target) {
+            // Bump 'last held' time on every deferred change, which holds consolidation back \
+            //   until the bulk load gets flushed, regardless of how long the load takes. \
+            //   Lookups keep being served from the pre-load graph meanwhile.
+            *self.last_held.write().unwrap() = Some(SystemTime::now());
+
+            debug!("graph consolidation held on pool key: {}", self.target);
+        }
+    }
+
+    pub fn should_flush_consolidate(&self) {
+        // Release hold (if any), and only flush if scheduled (there is nothing to flush otherwise)
+        *self.last_held.write().unwrap() = None;
+
+        if GRAPH_CONSOLIDATE.read().unwrap().contains(&self.target) {
+            // Reset 'last consolidated' time, effectively scheduling the single consolidation \
+            //   that ends the bulk load on the next consolidation tick.
+            *self.last_consolidated.write().unwrap() = SystemTime::UNIX_EPOCH;
+
+            info!("graph consolidation flushed on pool key: {}", self.target);
+        }
+    }
+
+    fn is_held(&self) -> bool {
+        // Important: be lenient with system clock going back to a past duration (see above); a \
+        //   hold that cannot be measured is released, as to never hold a graph forever.
+        match *self.last_held.read().unwrap() {
+            Some(last_held) => last_held
+                .elapsed()
+                .map(|held_for| held_for.as_secs() < APP_CONF.store.fst.hold_after())
+                .unwrap_or(false),
+            None => false,
+        }
+    }
 }
 
 impl StoreGeneric for StoreFST {
@@ -929,17 +1212,87 @@ impl StoreFSTActionBuilder {
         Self::build(store)
     }
 
-    pub fn erase<'a, T: Into<&'a str>>(collection: T, bucket: Option<T>) -> Result<u32, ()> {
+    pub fn erase<'a, T: Into<&'a str>>(
+        collection: T,
+        bucket: Option<T>,
+    ) -> Result<u32, StoreFSTError> {
         Self::dispatch_erase("fst", collection, bucket)
     }
 
+    pub fn swap(
+        collection_str: &str,
+        bucket_str: &str,
+        with_bucket_str: &str,
+    ) -> Result<(), StoreFSTError> {
+        // Important: the FST access lock must be acquired in write mode by the caller, as \
+        //   graph files get renamed there.
+        debug!(
+            "swapping fst bucket: {} with bucket: {} for collection: {}",
+            bucket_str, with_bucket_str, collection_str
+        );
+
+        let collection_atom = StoreKeyerHasher::to_compact(collection_str);
+        let bucket_atoms = [
+            StoreKeyerHasher::to_compact(bucket_str),
+            StoreKeyerHasher::to_compact(with_bucket_str),
+        ];
+
+        // Consolidate pending words of opened graphs, then force a FST graph close (as the \
+        //   graphs get swapped on-disk, opened graphs would point to the other bucket)
+        for bucket_atom in &bucket_atoms {
+            let target = StoreFSTKey::from_atom(collection_atom, *bucket_atom);
+            let store = GRAPH_POOL.read().unwrap().get(&target).cloned();
+
+            if let Some(store) = store {
+                StoreFSTPool::consolidate_item(&store);
+            }
+
+            StoreFSTBuilder::close(collection_atom, *bucket_atom);
+        }
+
+        // Swap FST graphs on-disk (along with their journaled changes, if any)
+        for path_mode in [StoreFSTPathMode::Permanent, StoreFSTPathMode::Journal].iter() {
+            let (bucket_path, with_bucket_path, swap_path) = (
+                StoreFSTBuilder::path(*path_mode, collection_atom, Some(bucket_atoms[0])),
+                StoreFSTBuilder::path(*path_mode, collection_atom, Some(bucket_atoms[1])),
+                StoreFSTBuilder::path(
+                    StoreFSTPathMode::Temporary,
+                    collection_atom,
+                    Some(bucket_atoms[0]),
+                ),
+            );
+
+            let swap_result = match (bucket_path.exists(), with_bucket_path.exists()) {
+                (true, true) => fs::rename(&bucket_path, &swap_path)
+                    .and_then(|_| fs::rename(&with_bucket_path, &bucket_path))
+                    .and_then(|_| fs::rename(&swap_path, &with_bucket_path)),
+                (true, false) => fs::rename(&bucket_path, &with_bucket_path),
+                (false, true) => fs::rename(&with_bucket_path, &bucket_path),
+                (false, false) => Ok(()),
+            };
+
+            if let Err(err) = swap_result {
+                error!(
+                    "failed swapping fst at path: {:?} with path: {:?}: {}",
+                    bucket_path, with_bucket_path, err
+                );
+
+                return Err(StoreFSTError::from(err));
+            }
+        }
+
+        Ok(())
+    }
+
     fn build(store: StoreFSTBox) -> StoreFSTAction {
         StoreFSTAction { store }
     }
 }
 
 impl StoreGenericActionBuilder for StoreFSTActionBuilder {
-    fn proceed_erase_collection(collection_str: &str) -> Result<u32, ()> {
+    type Error = StoreFSTError;
+
+    fn proceed_erase_collection(collection_str: &str) -> Result<u32, StoreFSTError> {
         let path_mode = StoreFSTPathMode::Permanent;
 
         let collection_atom = StoreKeyerHasher::to_compact(collection_str);
@@ -993,15 +1346,11 @@ impl StoreGenericActionBuilder for StoreFSTActionBuilder {
             );
 
             // Remove FST graph storage from filesystem
-            let erase_result = fs::remove_dir_all(&collection_path);
+            fs::remove_dir_all(&collection_path)?;
 
-            if erase_result.is_ok() {
-                debug!("done with fst collection erasure");
+            debug!("done with fst collection erasure");
 
-                Ok(1)
-            } else {
-                Err(())
-            }
+            Ok(1)
         } else {
             debug!(
                 "fst collection store does not exist, consider already erased: {}/* at path: {:?}",
@@ -1012,7 +1361,7 @@ impl StoreGenericActionBuilder for StoreFSTActionBuilder {
         }
     }
 
-    fn proceed_erase_bucket(collection_str: &str, bucket_str: &str) -> Result<u32, ()> {
+    fn proceed_erase_bucket(collection_str: &str, bucket_str: &str) -> Result<u32, StoreFSTError> {
         debug!(
             "sub-erase on fst bucket: {} for collection: {}",
             bucket_str, collection_str
@@ -1029,8 +1378,9 @@ impl StoreGenericActionBuilder for StoreFSTActionBuilder {
             Some(bucket_atom),
         );
 
-        // Force a FST graph close
+        // Force a FST graph close (and drop its journaled changes, if any)
         StoreFSTBuilder::close(collection_atom, bucket_atom);
+        StoreFSTBuilder::clear_journal(StoreFSTKey::from_atom(collection_atom, bucket_atom));
 
         // Remove FST on-disk
         if bucket_path.exists() {
@@ -1040,15 +1390,11 @@ impl StoreGenericActionBuilder for StoreFSTActionBuilder {
             );
 
             // Remove FST graph storage from filesystem
-            let erase_result = fs::remove_file(&bucket_path);
+            fs::remove_file(&bucket_path)?;
 
-            if erase_result.is_ok() {
-                debug!("done with fst bucket erasure");
+            debug!("done with fst bucket erasure");
 
-                Ok(1)
-            } else {
-                Err(())
-            }
+            Ok(1)
         } else {
             debug!(
                 "fst bucket graph does not exist, consider already erased: {}/{} at path: {:?}",
@@ -1091,6 +1437,8 @@ impl StoreFSTAction {
                 .unwrap()
                 .insert(word_bytes.to_vec());
 
+            self.journal(JOURNAL_OP_PUSH, word_bytes);
+
             self.store.should_consolidate();
 
             // Pushed
@@ -1125,6 +1473,8 @@ impl StoreFSTAction {
                 .unwrap()
                 .insert(word_bytes.to_vec());
 
+            self.journal(JOURNAL_OP_POP, word_bytes);
+
             self.store.should_consolidate();
 
             // Popped
@@ -1135,6 +1485,14 @@ impl StoreFSTAction {
         }
     }
 
+    pub fn hold_consolidate(&self) {
+        self.store.should_hold_consolidate();
+    }
+
+    pub fn flush_consolidate(&self) {
+        self.store.should_flush_consolidate();
+    }
+
     pub fn suggest_words(
         &self,
         from_word: &str,
@@ -1171,7 +1529,7 @@ impl StoreFSTAction {
         }
     }
 
-    pub fn list_words(&self, limit: usize, offset: usize) -> Result<Vec<String>, ()> {
+    pub fn list_words(&self, limit: usize, offset: usize) -> Result<Vec<String>, StoreFSTError> {
         let stream = self.store.as_stream();
 
         // Enumerate words from FST stream
@@ -1181,16 +1539,51 @@ impl StoreFSTAction {
         {
             Err(err) => {
                 debug!("conversion of stream failed: {}", err.to_string());
-                Err(())
+                Err(StoreFSTError::from(err))
             }
             Ok(words) => Ok(words),
         }
     }
 
+    pub fn find_hashed_words(
+        &self,
+        terms_hashed: &HashSet<StoreTermHashed>,
+    ) -> HashMap<StoreTermHashed, String> {
+        let mut found_words = HashMap::new();
+
+        if !terms_hashed.is_empty() {
+            let mut stream = self.store.as_stream();
+
+            // Hash all words from FST stream, until all requested hashes are found
+            while let Some(word) = stream.next() {
+                if let Ok(word_str) = str::from_utf8(word) {
+                    let word_hashed = StoreTermHash::from(word_str);
+
+                    if terms_hashed.contains(&word_hashed) {
+                        found_words.insert(word_hashed, word_str.to_string());
+
+                        if found_words.len() >= terms_hashed.len() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        found_words
+    }
+
     pub fn count_words(&self) -> usize {
         self.store.cardinality()
     }
 
+    fn journal(&self, operation: u8, word: &[u8]) {
+        // Journal change? (only if stale graphs are to be rebuilt on open)
+        if APP_CONF.store.fst.graph.warmup != ConfigStoreFSTGraphWarmup::Disabled {
+            StoreFSTBuilder::journal(self.store.target, operation, word);
+        }
+    }
+
     fn word_over_limit(word: &str) -> bool {
         if word.len() > WORD_LIMIT_LENGTH {
             debug!("got over-limit fst word: {}", word);
@@ -1224,7 +1617,9 @@ impl StoreFSTAction {
 }
 
 impl StoreFSTMisc {
-    pub fn count_collection_buckets<'a, T: Into<&'a str>>(collection: T) -> Result<usize, ()> {
+    pub fn count_collection_buckets<'a, T: Into<&'a str>>(
+        collection: T,
+    ) -> Result<usize, StoreFSTError> {
         let mut count = 0;
 
         let path_mode = StoreFSTPathMode::Permanent;
@@ -1234,25 +1629,24 @@ impl StoreFSTMisc {
 
         if collection_path.exists() {
             // Scan collection directory for contained buckets (count them)
-            if let Ok(entries) = fs::read_dir(&collection_path) {
-                let fst_extension = path_mode.extension();
-                let fst_extension_len = fst_extension.len();
+            let entries = fs::read_dir(&collection_path).map_err(|err| {
+                error!("failed reading directory for count: {:?}", collection_path);
 
-                for entry in entries.flatten() {
-                    if let Some(entry_name) = entry.file_name().to_str() {
-                        let entry_name_len = entry_name.len();
+                StoreFSTError::from(err)
+            })?;
 
-                        // FST file found? This is a bucket.
-                        if entry_name_len > fst_extension_len && entry_name.ends_with(fst_extension)
-                        {
-                            count += 1;
-                        }
+            let fst_extension = path_mode.extension();
+            let fst_extension_len = fst_extension.len();
+
+            for entry in entries.flatten() {
+                if let Some(entry_name) = entry.file_name().to_str() {
+                    let entry_name_len = entry_name.len();
+
+                    // FST file found? This is a bucket.
+                    if entry_name_len > fst_extension_len && entry_name.ends_with(fst_extension) {
+                        count += 1;
                     }
                 }
-            } else {
-                error!("failed reading directory for count: {:?}", collection_path);
-
-                return Err(());
             }
         }
 
@@ -1309,9 +1703,57 @@ impl fmt::Display for StoreFSTKey {
     }
 }
 
+impl fmt::Display for StoreFSTError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreFSTError::Graph => write!(f, "malformed graph"),
+            StoreFSTError::Io(kind) => write!(f, "io error: {:?}", kind),
+            StoreFSTError::Lookup => write!(f, "lookup automaton error"),
+        }
+    }
+}
+
+impl Error for StoreFSTError {}
+
+impl From<FSTError> for StoreFSTError {
+    fn from(err: FSTError) -> StoreFSTError {
+        match err {
+            FSTError::Fst(_) => StoreFSTError::Graph,
+            FSTError::Io(err) => StoreFSTError::Io(err.kind()),
+        }
+    }
+}
+
+impl From<io::Error> for StoreFSTError {
+    fn from(err: io::Error) -> StoreFSTError {
+        StoreFSTError::Io(err.kind())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::query::types::QueryPushSuggest;
+
+    fn tick_consolidate(store: &StoreFSTBox) -> bool {
+        let was_scheduled = GRAPH_CONSOLIDATE.read().unwrap().contains(&store.target);
+
+        StoreFSTPool::consolidate(false);
+
+        was_scheduled && !GRAPH_CONSOLIDATE.read().unwrap().contains(&store.target)
+    }
+
+    fn age_consolidate(store: &StoreFSTBox, seconds: u64) {
+        let mut last_consolidated = store.last_consolidated.write().unwrap();
+
+        *last_consolidated -= Duration::from_secs(seconds);
+    }
+
+    fn erase_graph(collection: &str, bucket: &str) {
+        let _access = GRAPH_ACCESS_LOCK.write().unwrap();
+
+        assert!(StoreFSTActionBuilder::erase(collection, Some(bucket)).is_ok());
+    }
 
     #[test]
     fn it_acquires_graph() {
@@ -1329,4 +1771,228 @@ mod tests {
 
         assert!(store.lookup_typos("valerien", None).is_ok());
     }
+
+    #[test]
+    fn it_defers_consolidation() {
+        let _consolidate = CONSOLIDATE_TEST_LOCK.lock().unwrap();
+
+        let consolidate_after = APP_CONF.store.fst.graph.consolidate_after;
+
+        // Simulate a bulk load made of 4 batches, with pauses longer than the consolidation \
+        //   delay in between, followed by a quiet period (returns suggestions and the batches \
+        //   after which the graph got rebuilt)
+        let load_bucket = |bucket: &str, suggest: QueryPushSuggest| {
+            let mut rebuilds = Vec::new();
+
+            assert!(StoreFSTActionBuilder::erase("c:test:5", Some(bucket)).is_ok());
+
+            for batch in 0..6 {
+                let store = StoreFSTPool::acquire("c:test:5", bucket).unwrap();
+                let action = StoreFSTActionBuilder::access(store.clone());
+
+                if batch < 4 {
+                    for word in ["alpha", "bravo", "charlie"].iter() {
+                        action.push_word(&format!("{}{}", word, batch));
+                    }
+
+                    // End the bulk load with its last batch
+                    match suggest {
+                        QueryPushSuggest::Deferred if batch == 3 => action.flush_consolidate(),
+                        QueryPushSuggest::Deferred => action.hold_consolidate(),
+                        _ => {}
+                    }
+                }
+
+                // Let time pass, then run a tasker tick
+                age_consolidate(&store, consolidate_after + 1);
+
+                if tick_consolidate(&store) {
+                    rebuilds.push(batch);
+                }
+            }
+
+            let store = StoreFSTPool::acquire("c:test:5", bucket).unwrap();
+
+            (
+                StoreFSTActionBuilder::access(store).list_words(100, 0),
+                rebuilds,
+            )
+        };
+
+        let (incremental_words, incremental_rebuilds) =
+            load_bucket("b:test:5:incremental", QueryPushSuggest::Incremental);
+        let (deferred_words, deferred_rebuilds) =
+            load_bucket("b:test:5:deferred", QueryPushSuggest::Deferred);
+
+        assert_eq!(incremental_words.as_ref().map(|words| words.len()), Ok(12));
+        assert_eq!(incremental_words, deferred_words);
+        assert_eq!(incremental_rebuilds, vec![0, 1, 2, 3]);
+        assert_eq!(deferred_rebuilds, vec![3]);
+    }
+
+    #[test]
+    fn it_releases_held_consolidation() {
+        let _consolidate = CONSOLIDATE_TEST_LOCK.lock().unwrap();
+
+        let graph_conf = &APP_CONF.store.fst.graph;
+
+        assert!(StoreFSTActionBuilder::erase("c:test:5", Some("b:test:5:held")).is_ok());
+
+        let store = StoreFSTPool::acquire("c:test:5", "b:test:5:held").unwrap();
+        let action = StoreFSTActionBuilder::access(store.clone());
+
+        // Start a bulk load that never gets flushed (eg. the loading client crashed)
+        action.push_word("delta");
+        action.hold_consolidate();
+
+        age_consolidate(&store, graph_conf.consolidate_after + 1);
+
+        assert!(!tick_consolidate(&store));
+
+        // Release the hold once no deferred push was received for long enough
+        let mut last_held = store.last_held.write().unwrap();
+
+        *last_held =
+            last_held.map(|held| held - Duration::from_secs(APP_CONF.store.fst.hold_after()));
+
+        drop(last_held);
+
+        assert!(tick_consolidate(&store));
+
+        let store = StoreFSTPool::acquire("c:test:5", "b:test:5:held").unwrap();
+
+        assert_eq!(
+            StoreFSTActionBuilder::access(store).list_words(100, 0),
+            Ok(vec!["delta".to_string()])
+        );
+    }
+
+    #[test]
+    fn it_unsuggests_words() {
+        let _consolidate = CONSOLIDATE_TEST_LOCK.lock().unwrap();
+
+        let consolidate_after = APP_CONF.store.fst.graph.consolidate_after;
+
+        let consolidate = || {
+            let store = StoreFSTPool::acquire("c:test:6", "b:test:6").unwrap();
+
+            age_consolidate(&store, consolidate_after);
+
+            assert!(tick_consolidate(&store));
+        };
+
+        let list_words = || {
+            let store = StoreFSTPool::acquire("c:test:6", "b:test:6").unwrap();
+
+            StoreFSTActionBuilder::access(store).list_words(100, 0)
+        };
+
+        erase_graph("c:test:6", "b:test:6");
+
+        {
+            let store = StoreFSTPool::acquire("c:test:6", "b:test:6").unwrap();
+            let action = StoreFSTActionBuilder::access(store);
+
+            for word in &["valerian", "valentine", "stale"] {
+                action.push_word(word);
+            }
+        }
+
+        consolidate();
+
+        // Unsuggest words, and check that only those which are in the graph get popped
+        assert_eq!(
+            StoreFSTPool::unsuggest(
+                "c:test:6",
+                "b:test:6",
+                &["valerian".to_string(), "unknown".to_string()]
+            ),
+            Ok(1)
+        );
+
+        // Check that words are still listed until the next consolidation
+        assert_eq!(list_words().map(|words| words.len()), Ok(3));
+
+        consolidate();
+
+        assert_eq!(
+            list_words(),
+            Ok(vec!["stale".to_string(), "valentine".to_string()])
+        );
+
+        let store = StoreFSTPool::acquire("c:test:6", "b:test:6").unwrap();
+
+        assert_eq!(
+            StoreFSTActionBuilder::access(store).suggest_words("vale", 10, None),
+            Some(vec!["valentine".to_string()])
+        );
+    }
+
+    #[test]
+    fn it_rebuilds_stale_graph_on_open() {
+        let _consolidate = CONSOLIDATE_TEST_LOCK.lock().unwrap();
+
+        let consolidate_after = APP_CONF.store.fst.graph.consolidate_after;
+        let pool_key = StoreFSTKey::from_str("c:test:7", "b:test:7");
+
+        // Consolidate an initial graph, then journal changes that never made it to the graph \
+        //   (as if the process stopped before the next consolidation)
+        let make_stale = || {
+            erase_graph("c:test:7", "b:test:7");
+
+            let store = StoreFSTPool::acquire("c:test:7", "b:test:7").unwrap();
+
+            StoreFSTActionBuilder::access(store.clone()).push_word("lighter");
+
+            age_consolidate(&store, consolidate_after);
+
+            assert!(tick_consolidate(&store));
+
+            GRAPH_POOL.write().unwrap().remove(&pool_key);
+
+            for (operation, word) in [
+                (JOURNAL_OP_PUSH, "lighthouse"),
+                (JOURNAL_OP_PUSH, "lightning"),
+                (JOURNAL_OP_PUSH, "lightyear"),
+                (JOURNAL_OP_POP, "lightyear"),
+                (JOURNAL_OP_POP, "lighter"),
+            ]
+            .iter()
+            {
+                StoreFSTBuilder::journal(pool_key, *operation, word.as_bytes());
+            }
+        };
+
+        let suggest_words = |store: StoreFSTBox| {
+            StoreFSTActionBuilder::access(store).suggest_words("ligh", 10, None)
+        };
+
+        let expected_words = Some(vec!["lighthouse".to_string(), "lightning".to_string()]);
+
+        // Rebuild graph while opening it
+        make_stale();
+
+        let store =
+            StoreFSTBuilder::build_warmup(pool_key, &ConfigStoreFSTGraphWarmup::Blocking).unwrap();
+
+        assert!(store.pending.push.read().unwrap().is_empty());
+        assert_eq!(suggest_words(Arc::new(store)), expected_words);
+        assert!(StoreFSTBuilder::read_journal(pool_key).is_none());
+
+        // Rebuild graph on the next consolidation tick
+        make_stale();
+
+        let store = Arc::new(
+            StoreFSTBuilder::build_warmup(pool_key, &ConfigStoreFSTGraphWarmup::Background)
+                .unwrap(),
+        );
+
+        GRAPH_POOL.write().unwrap().insert(pool_key, store.clone());
+
+        assert!(tick_consolidate(&store));
+        assert_eq!(
+            suggest_words(StoreFSTPool::acquire("c:test:7", "b:test:7").unwrap()),
+            expected_words
+        );
+    }
 }
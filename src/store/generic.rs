@@ -26,7 +26,7 @@ pub trait StoreGenericPool<
         collection_str: &str,
         pool_key: K,
         store: &Arc<S>,
-    ) -> Result<Arc<S>, ()> {
+    ) -> Arc<S> {
         debug!(
             "{} store acquired from pool for collection: {} (pool key: {})",
             kind, collection_str, pool_key
@@ -40,7 +40,7 @@ pub trait StoreGenericPool<
         // Perform an early drop of the lock (frees up write lock early)
         drop(last_used_value);
 
-        Ok(store.clone())
+        store.clone()
     }
 
     fn proceed_acquire_open(
@@ -48,7 +48,7 @@ pub trait StoreGenericPool<
         collection_str: &str,
         pool_key: K,
         pool: &Arc<RwLock<HashMap<K, Arc<S>>>>,
-    ) -> Result<Arc<S>, ()> {
+    ) -> Result<Arc<S>, B::Error> {
         match B::build(pool_key) {
             Ok(store) => {
                 // Acquire a thread-safe store pool reference in write mode
@@ -64,13 +64,13 @@ pub trait StoreGenericPool<
 
                 Ok(store_box)
             }
-            Err(_) => {
+            Err(err) => {
                 error!(
                     "failed opening {} store for collection: {} (pool key: {})",
                     kind, collection_str, pool_key
                 );
 
-                Err(())
+                Err(err)
             }
         }
     }
@@ -110,7 +110,15 @@ pub trait StoreGenericPool<
                 })
                 .as_secs();
 
-            if last_used_elapsed >= inactive_after {
+            // Important: only close stores that are not in use, ie. only referenced from the \
+            //   pool. A store referenced elsewhere is kept open until a later pass, as \
+            //   reopening it meanwhile would open the same database twice.
+            if last_used_elapsed >= inactive_after && Arc::strong_count(store) > 1 {
+                debug!(
+                    "found expired {} store pool item: {}, but it is still in use; elapsed time: {}s",
+                    kind, collection_bucket, last_used_elapsed
+                );
+            } else if last_used_elapsed >= inactive_after {
                 debug!(
                     "found expired {} store pool item: {}; elapsed time: {}s",
                     kind, collection_bucket, last_used_elapsed
@@ -145,19 +153,23 @@ pub trait StoreGenericPool<
 }
 
 pub trait StoreGenericBuilder<K, S> {
-    fn build(pool_key: K) -> Result<S, ()>;
+    type Error;
+
+    fn build(pool_key: K) -> Result<S, Self::Error>;
 }
 
 pub trait StoreGenericActionBuilder {
-    fn proceed_erase_collection(collection_str: &str) -> Result<u32, ()>;
+    type Error;
+
+    fn proceed_erase_collection(collection_str: &str) -> Result<u32, Self::Error>;
 
-    fn proceed_erase_bucket(collection_str: &str, bucket_str: &str) -> Result<u32, ()>;
+    fn proceed_erase_bucket(collection_str: &str, bucket_str: &str) -> Result<u32, Self::Error>;
 
     fn dispatch_erase<'a, T: Into<&'a str>>(
         kind: &str,
         collection: T,
         bucket: Option<T>,
-    ) -> Result<u32, ()> {
+    ) -> Result<u32, Self::Error> {
         let collection_str = collection.into();
 
         info!("{} erase requested on collection: {}", kind, collection_str);
@@ -10,21 +10,53 @@ use twox_hash::XxHash32;
 pub type StoreObjectIID = u32;
 pub type StoreObjectOID<'a> = &'a str;
 pub type StoreTermHashed = u32;
+pub type StoreObjectBoost = f32;
+pub type StoreObjectTime = u32;
+pub type StoreTermWeight = f32;
+pub type StoreTermPosition = u32;
+pub type StoreTermIDF = f32;
 
 pub struct StoreTermHash;
 
+pub const STORE_OBJECT_BOOST_DEFAULT: StoreObjectBoost = 1.0;
+pub const STORE_TERM_WEIGHT_DEFAULT: StoreTermWeight = 1.0;
+
 pub enum StoreMetaKey {
     IIDIncr,
+    OIDCount,
+    TermCount,
+    Retention,
+    IDFCount,
+    Immutable,
+    BucketName,
 }
 
 pub enum StoreMetaValue {
     IIDIncr(StoreObjectIID),
+    OIDCount(u32),
+    TermCount(u32),
+    Retention(StoreRetention),
+    IDFCount(u32),
+    Immutable(bool),
+    BucketName(String),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StoreRetention {
+    pub max_age: Option<u32>,
+    pub max_objects: Option<u32>,
 }
 
 impl StoreMetaKey {
     pub fn as_u32(&self) -> u32 {
         match self {
             StoreMetaKey::IIDIncr => 0,
+            StoreMetaKey::OIDCount => 1,
+            StoreMetaKey::TermCount => 2,
+            StoreMetaKey::Retention => 3,
+            StoreMetaKey::IDFCount => 4,
+            StoreMetaKey::Immutable => 5,
+            StoreMetaKey::BucketName => 6,
         }
     }
 }
@@ -46,6 +78,12 @@ mod tests {
     #[test]
     fn it_converts_meta_key_to_u32() {
         assert_eq!(StoreMetaKey::IIDIncr.as_u32(), 0);
+        assert_eq!(StoreMetaKey::OIDCount.as_u32(), 1);
+        assert_eq!(StoreMetaKey::TermCount.as_u32(), 2);
+        assert_eq!(StoreMetaKey::Retention.as_u32(), 3);
+        assert_eq!(StoreMetaKey::IDFCount.as_u32(), 4);
+        assert_eq!(StoreMetaKey::Immutable.as_u32(), 5);
+        assert_eq!(StoreMetaKey::BucketName.as_u32(), 6);
     }
 
     #[test]
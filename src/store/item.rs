@@ -28,6 +28,10 @@ pub enum StoreItemError {
 const STORE_ITEM_PART_LEN_MIN: usize = 0;
 const STORE_ITEM_PART_LEN_MAX: usize = 128;
 
+// Notice: pushed objects cannot start with this character, as it is reserved for attributes \
+//   trailing results (eg. '[partial]'), which thus never collide with an object.
+pub const STORE_ITEM_ATTRIBUTE_PREFIX: char = '[';
+
 impl<'a> StoreItemPart<'a> {
     pub fn from_str(part: &'a str) -> Result<Self, ()> {
         let len = part.len();
@@ -4,13 +4,15 @@
 // Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
-use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, LittleEndian};
 use std::fmt;
 use std::hash::Hasher;
-use std::io::Cursor;
-use twox_hash::XxHash32;
+use std::ops::Deref;
+use twox_hash::{XxHash32, XxHash64};
 
 use super::identifiers::*;
+use crate::config::options::ConfigStoreKVBucketHash;
+use crate::APP_CONF;
 
 pub struct StoreKeyerBuilder;
 
@@ -20,16 +22,42 @@ pub struct StoreKeyer {
 
 pub struct StoreKeyerHasher;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StoreKeyerLayout {
+    Narrow,
+    Wide,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct StoreKeyerKey {
+    bytes: [u8; KEY_SIZE_MAXIMUM],
+    size: usize,
+}
+
 enum StoreKeyerIdx<'a> {
     MetaToValue(&'a StoreMetaKey),
     TermToIIDs(StoreTermHashed),
     OIDToIID(StoreObjectOID<'a>),
     IIDToOID(StoreObjectIID),
     IIDToTerms(StoreObjectIID),
+    IIDToBoost(StoreObjectIID),
+    IIDToTime(StoreObjectIID),
+    IIDToWeights(StoreObjectIID),
+    CollectionToStopWords,
+    OIDOrderedToIID,
+    IIDToSnippet(StoreObjectIID),
+    TermToIDF(StoreTermHashed),
+    TermToSize(StoreTermHashed),
+    CollectionToOIDNormalization,
+    CollectionToKeyFormat,
+    IIDToLang(StoreObjectIID),
+    CollectionToName,
 }
 
-pub type StoreKeyerKey = [u8; 9];
-pub type StoreKeyerPrefix = [u8; 5];
+pub type StoreKeyerPrefix = StoreKeyerKey;
+
+const KEY_SIZE_MAXIMUM: usize = 13;
+const ROUTE_SIZE: usize = 4;
 
 impl<'a> StoreKeyerIdx<'a> {
     pub fn to_index(&self) -> u8 {
@@ -39,15 +67,87 @@ impl<'a> StoreKeyerIdx<'a> {
             StoreKeyerIdx::OIDToIID(_) => 2,
             StoreKeyerIdx::IIDToOID(_) => 3,
             StoreKeyerIdx::IIDToTerms(_) => 4,
+            StoreKeyerIdx::IIDToBoost(_) => 5,
+            StoreKeyerIdx::IIDToTime(_) => 6,
+            StoreKeyerIdx::IIDToWeights(_) => 7,
+            StoreKeyerIdx::CollectionToStopWords => 8,
+            StoreKeyerIdx::OIDOrderedToIID => 9,
+            StoreKeyerIdx::IIDToSnippet(_) => 10,
+            StoreKeyerIdx::TermToIDF(_) => 11,
+            StoreKeyerIdx::TermToSize(_) => 12,
+            StoreKeyerIdx::CollectionToOIDNormalization => 13,
+            StoreKeyerIdx::CollectionToKeyFormat => 14,
+            StoreKeyerIdx::IIDToLang(_) => 15,
+            StoreKeyerIdx::CollectionToName => 16,
         }
     }
 }
 
+impl StoreKeyerLayout {
+    pub fn configured() -> StoreKeyerLayout {
+        match APP_CONF.store.kv.bucket_hash {
+            ConfigStoreKVBucketHash::Xxh32 => StoreKeyerLayout::Narrow,
+            ConfigStoreKVBucketHash::Xxh64 => StoreKeyerLayout::Wide,
+        }
+    }
+
+    pub fn from_version(version: u8) -> Option<StoreKeyerLayout> {
+        match version {
+            1 => Some(StoreKeyerLayout::Narrow),
+            2 => Some(StoreKeyerLayout::Wide),
+            _ => None,
+        }
+    }
+
+    pub fn to_version(self) -> u8 {
+        match self {
+            StoreKeyerLayout::Narrow => 1,
+            StoreKeyerLayout::Wide => 2,
+        }
+    }
+
+    pub fn prefix_size(self) -> usize {
+        // Prefix format: [idx<1B> | bucket<4B>] (narrow) or [idx<1B> | bucket<8B>] (wide)
+        match self {
+            StoreKeyerLayout::Narrow => 5,
+            StoreKeyerLayout::Wide => 9,
+        }
+    }
+
+    pub fn key_size(self) -> usize {
+        self.prefix_size() + ROUTE_SIZE
+    }
+
+    pub fn encode_bucket(self, bucket: &str, encoded: &mut [u8]) {
+        // Notice: the encoded slice must be exactly as large as the bucket part of the prefix.
+        match self {
+            StoreKeyerLayout::Narrow => LittleEndian::write_u32(
+                encoded,
+                StoreKeyerHasher::to_compact_seeded(bucket, APP_CONF.store.kv.bucket_hash_seed),
+            ),
+            StoreKeyerLayout::Wide => LittleEndian::write_u64(
+                encoded,
+                StoreKeyerHasher::to_wide_seeded(bucket, APP_CONF.store.kv.bucket_hash_seed),
+            ),
+        }
+    }
+
+    pub fn is_route(self, key: &[u8], route: &[u8]) -> bool {
+        // Notice: only full keys match (eg. when filtering keys scanned from an index prefix)
+        key.len() == self.key_size() && key[self.prefix_size()..] == *route
+    }
+}
+
 impl StoreKeyerBuilder {
     pub fn meta_to_value<'a>(bucket: &'a str, meta: &'a StoreMetaKey) -> StoreKeyer {
         Self::make(StoreKeyerIdx::MetaToValue(meta), bucket)
     }
 
+    pub fn meta_to_value_any() -> [u8; 1] {
+        // Prefix format: [idx<1B>] (matches meta keys from all buckets)
+        [StoreKeyerIdx::MetaToValue(&StoreMetaKey::IIDIncr).to_index()]
+    }
+
     pub fn term_to_iids(bucket: &str, term_hash: StoreTermHashed) -> StoreKeyer {
         Self::make(StoreKeyerIdx::TermToIIDs(term_hash), bucket)
     }
@@ -64,36 +164,110 @@ impl StoreKeyerBuilder {
         Self::make(StoreKeyerIdx::IIDToTerms(iid), bucket)
     }
 
+    pub fn iid_to_boost(bucket: &str, iid: StoreObjectIID) -> StoreKeyer {
+        Self::make(StoreKeyerIdx::IIDToBoost(iid), bucket)
+    }
+
+    pub fn iid_to_time(bucket: &str, iid: StoreObjectIID) -> StoreKeyer {
+        Self::make(StoreKeyerIdx::IIDToTime(iid), bucket)
+    }
+
+    pub fn iid_to_weights(bucket: &str, iid: StoreObjectIID) -> StoreKeyer {
+        Self::make(StoreKeyerIdx::IIDToWeights(iid), bucket)
+    }
+
+    pub fn iid_to_snippet(bucket: &str, iid: StoreObjectIID) -> StoreKeyer {
+        Self::make(StoreKeyerIdx::IIDToSnippet(iid), bucket)
+    }
+
+    pub fn iid_to_lang(bucket: &str, iid: StoreObjectIID) -> StoreKeyer {
+        Self::make(StoreKeyerIdx::IIDToLang(iid), bucket)
+    }
+
+    pub fn term_to_idf(bucket: &str, term_hash: StoreTermHashed) -> StoreKeyer {
+        Self::make(StoreKeyerIdx::TermToIDF(term_hash), bucket)
+    }
+
+    pub fn term_to_idf_any() -> [u8; 1] {
+        // Prefix format: [idx<1B>] (matches term IDF weights from all buckets)
+        [StoreKeyerIdx::TermToIDF(0).to_index()]
+    }
+
+    pub fn term_to_size(bucket: &str, term_hash: StoreTermHashed) -> StoreKeyer {
+        Self::make(StoreKeyerIdx::TermToSize(term_hash), bucket)
+    }
+
+    pub fn collection_to_stopwords() -> StoreKeyer {
+        // Notice: this key is collection-wide, thus it is not scoped to any bucket (an empty \
+        //   bucket name is used, which is not a valid bucket name)
+        Self::make(StoreKeyerIdx::CollectionToStopWords, "")
+    }
+
+    pub fn collection_to_oid_normalization() -> StoreKeyer {
+        // Notice: this key is collection-wide, thus it is not scoped to any bucket (see stopwords)
+        Self::make(StoreKeyerIdx::CollectionToOIDNormalization, "")
+    }
+
+    pub fn collection_to_name() -> StoreKeyer {
+        // Notice: this key is collection-wide, thus it is not scoped to any bucket (see stopwords)
+        Self::make(StoreKeyerIdx::CollectionToName, "")
+    }
+
+    pub fn collection_to_key_format() -> [u8; 1] {
+        // Key format: [idx<1B>]
+        // Notice: this key holds the layout of all other keys, thus it does not depend on it.
+        [StoreKeyerIdx::CollectionToKeyFormat.to_index()]
+    }
+
+    pub fn oid_ordered_to_iid(bucket: &str, oid: StoreObjectOID) -> Vec<u8> {
+        // Key format: [idx<1B> | bucket<4B|8B> | oid<*B>]
+        // Notice: the OID is stored as-is instead of being hashed, which keeps keys ordered by \
+        //   OID (this allows for scanning objects by OID prefix)
+        let prefix = Self::oid_ordered_to_iid_prefix(bucket);
+
+        let mut key = Vec::with_capacity(prefix.len() + oid.len());
+
+        key.extend_from_slice(&prefix);
+        key.extend_from_slice(oid.as_bytes());
+
+        key
+    }
+
+    pub fn oid_ordered_to_iid_prefix(bucket: &str) -> StoreKeyerPrefix {
+        Self::make(StoreKeyerIdx::OIDOrderedToIID, bucket).as_prefix()
+    }
+
     fn make<'a>(idx: StoreKeyerIdx<'a>, bucket: &'a str) -> StoreKeyer {
         StoreKeyer {
-            key: Self::build_key(idx, bucket),
+            key: Self::build_key(idx, bucket, StoreKeyerLayout::configured()),
         }
     }
 
-    fn build_key<'a>(idx: StoreKeyerIdx<'a>, bucket: &'a str) -> StoreKeyerKey {
-        // Key format: [idx<1B> | bucket<4B> | route<4B>]
+    fn build_key<'a>(
+        idx: StoreKeyerIdx<'a>,
+        bucket: &'a str,
+        layout: StoreKeyerLayout,
+    ) -> StoreKeyerKey {
+        // Key format: [idx<1B> | bucket<4B> | route<4B>] (narrow) or \
+        //   [idx<1B> | bucket<8B> | route<4B>] (wide)
+        let mut key = StoreKeyerKey {
+            bytes: [0; KEY_SIZE_MAXIMUM],
+            size: layout.key_size(),
+        };
+
+        let route_at = layout.prefix_size();
 
-        // Encode key bucket + key route from u32 to array of u8 (ie. binary)
-        let (mut bucket_encoded, mut route_encoded) = ([0; 4], [0; 4]);
+        // Encode key bucket + key route to array of u8 (ie. binary)
+        key.bytes[0] = idx.to_index();
 
-        LittleEndian::write_u32(&mut bucket_encoded, StoreKeyerHasher::to_compact(bucket));
-        LittleEndian::write_u32(&mut route_encoded, Self::route_to_compact(&idx));
+        layout.encode_bucket(bucket, &mut key.bytes[1..route_at]);
 
-        // Generate final binary key
-        [
-            // [idx<1B>]
-            idx.to_index(),
-            // [bucket<4B>]
-            bucket_encoded[0],
-            bucket_encoded[1],
-            bucket_encoded[2],
-            bucket_encoded[3],
-            // [route<4B>]
-            route_encoded[0],
-            route_encoded[1],
-            route_encoded[2],
-            route_encoded[3],
-        ]
+        LittleEndian::write_u32(
+            &mut key.bytes[route_at..key.size],
+            Self::route_to_compact(&idx),
+        );
+
+        key
     }
 
     fn route_to_compact(idx: &StoreKeyerIdx) -> u32 {
@@ -103,6 +277,18 @@ impl StoreKeyerBuilder {
             StoreKeyerIdx::OIDToIID(route) => StoreKeyerHasher::to_compact(route),
             StoreKeyerIdx::IIDToOID(route) => *route,
             StoreKeyerIdx::IIDToTerms(route) => *route,
+            StoreKeyerIdx::IIDToBoost(route) => *route,
+            StoreKeyerIdx::IIDToTime(route) => *route,
+            StoreKeyerIdx::IIDToWeights(route) => *route,
+            StoreKeyerIdx::CollectionToStopWords => 0,
+            StoreKeyerIdx::OIDOrderedToIID => 0,
+            StoreKeyerIdx::IIDToSnippet(route) => *route,
+            StoreKeyerIdx::IIDToLang(route) => *route,
+            StoreKeyerIdx::TermToIDF(route) => *route,
+            StoreKeyerIdx::TermToSize(route) => *route,
+            StoreKeyerIdx::CollectionToOIDNormalization => 0,
+            StoreKeyerIdx::CollectionToKeyFormat => 0,
+            StoreKeyerIdx::CollectionToName => 0,
         }
     }
 }
@@ -113,38 +299,84 @@ impl StoreKeyer {
     }
 
     pub fn as_prefix(&self) -> StoreKeyerPrefix {
-        // Prefix format: [idx<1B> | bucket<4B>]
+        StoreKeyerKey {
+            bytes: self.key.bytes,
+            size: self.key.size - ROUTE_SIZE,
+        }
+    }
+}
+
+impl StoreKeyerKey {
+    pub fn with_route(&self, route: [u8; ROUTE_SIZE]) -> StoreKeyerKey {
+        // Notice: this turns a prefix into a full key (eg. to build key ranges).
+        let mut key = *self;
+
+        key.bytes[key.size..key.size + ROUTE_SIZE].copy_from_slice(&route);
+        key.size += ROUTE_SIZE;
+
+        key
+    }
+}
+
+impl Deref for StoreKeyerKey {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.size]
+    }
+}
 
-        [
-            self.key[0],
-            self.key[1],
-            self.key[2],
-            self.key[3],
-            self.key[4],
-        ]
+impl AsRef<[u8]> for StoreKeyerKey {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for StoreKeyerKey {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        **self == other[..]
+    }
+}
+
+impl fmt::Debug for StoreKeyerKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
     }
 }
 
 impl StoreKeyerHasher {
     #![allow(clippy::wrong_self_convention)]
     pub fn to_compact(part: &str) -> u32 {
-        let mut hasher = XxHash32::with_seed(0);
+        Self::to_compact_seeded(part, 0)
+    }
+
+    pub fn to_compact_seeded(part: &str, seed: u32) -> u32 {
+        let mut hasher = XxHash32::with_seed(seed);
 
         hasher.write(part.as_bytes());
         hasher.finish() as u32
     }
+
+    pub fn to_wide(part: &str) -> u64 {
+        Self::to_wide_seeded(part, 0)
+    }
+
+    pub fn to_wide_seeded(part: &str, seed: u32) -> u64 {
+        let mut hasher = XxHash64::with_seed(seed as u64);
+
+        hasher.write(part.as_bytes());
+        hasher.finish()
+    }
 }
 
 impl fmt::Display for StoreKeyer {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Convert to number
+        let route_at = self.key.len() - ROUTE_SIZE;
+
         let (key_bucket, key_route) = (
-            Cursor::new(&self.key[1..5])
-                .read_u32::<LittleEndian>()
-                .unwrap_or(0),
-            Cursor::new(&self.key[5..9])
-                .read_u32::<LittleEndian>()
-                .unwrap_or(0),
+            LittleEndian::read_uint(&self.key[1..route_at], route_at - 1),
+            LittleEndian::read_u32(&self.key[route_at..]),
         );
 
         write!(
@@ -159,6 +391,12 @@ impl fmt::Display for StoreKeyer {
 mod tests {
     use super::*;
 
+    fn wide_key(idx: StoreKeyerIdx, bucket: &str) -> StoreKeyer {
+        StoreKeyer {
+            key: StoreKeyerBuilder::build_key(idx, bucket, StoreKeyerLayout::Wide),
+        }
+    }
+
     #[test]
     fn it_keys_meta_to_value() {
         assert_eq!(
@@ -167,6 +405,11 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_keys_meta_to_value_any() {
+        assert_eq!(StoreKeyerBuilder::meta_to_value_any(), [0]);
+    }
+
     #[test]
     fn it_keys_term_to_iids() {
         assert_eq!(
@@ -177,6 +420,10 @@ mod tests {
             StoreKeyerBuilder::term_to_iids("bucket:2", 3582484684).as_bytes(),
             [1, 50, 220, 166, 65, 204, 96, 136, 213]
         );
+        assert_eq!(
+            wide_key(StoreKeyerIdx::TermToIIDs(772137347), "bucket:2").as_bytes(),
+            [1, 7, 34, 206, 164, 62, 233, 243, 87, 131, 225, 5, 46]
+        );
     }
 
     #[test]
@@ -186,6 +433,14 @@ mod tests {
                 .as_bytes(),
             [2, 171, 194, 213, 57, 31, 156, 118, 213]
         );
+        assert_eq!(
+            wide_key(
+                StoreKeyerIdx::OIDToIID(&"conversation:6501e83a".to_string()),
+                "bucket:3"
+            )
+            .as_bytes(),
+            [2, 157, 87, 231, 29, 54, 33, 242, 72, 31, 156, 118, 213]
+        );
     }
 
     #[test]
@@ -206,12 +461,162 @@ mod tests {
             StoreKeyerBuilder::iid_to_terms("bucket:5", 20).as_bytes(),
             [4, 137, 142, 73, 67, 20, 0, 0, 0]
         );
+        assert_eq!(
+            wide_key(StoreKeyerIdx::IIDToTerms(20), "bucket:5").as_bytes(),
+            [4, 184, 196, 76, 37, 156, 35, 175, 103, 20, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn it_keys_iid_to_boost() {
+        assert_eq!(
+            StoreKeyerBuilder::iid_to_boost("bucket:5", 1).as_bytes(),
+            [5, 137, 142, 73, 67, 1, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn it_keys_iid_to_time() {
+        assert_eq!(
+            StoreKeyerBuilder::iid_to_time("bucket:5", 1).as_bytes(),
+            [6, 137, 142, 73, 67, 1, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn it_keys_iid_to_weights() {
+        assert_eq!(
+            StoreKeyerBuilder::iid_to_weights("bucket:5", 1).as_bytes(),
+            [7, 137, 142, 73, 67, 1, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn it_keys_iid_to_snippet() {
+        assert_eq!(
+            StoreKeyerBuilder::iid_to_snippet("bucket:5", 1).as_bytes(),
+            [10, 137, 142, 73, 67, 1, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn it_keys_iid_to_lang() {
+        assert_eq!(
+            StoreKeyerBuilder::iid_to_lang("bucket:5", 1).as_bytes(),
+            [15, 137, 142, 73, 67, 1, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn it_keys_term_to_idf() {
+        assert_eq!(
+            StoreKeyerBuilder::term_to_idf("bucket:2", 772137347).as_bytes(),
+            [11, 50, 220, 166, 65, 131, 225, 5, 46]
+        );
+    }
+
+    #[test]
+    fn it_keys_term_to_idf_any() {
+        assert_eq!(StoreKeyerBuilder::term_to_idf_any(), [11]);
+    }
+
+    #[test]
+    fn it_keys_term_to_size() {
+        assert_eq!(
+            StoreKeyerBuilder::term_to_size("bucket:2", 772137347).as_bytes(),
+            [12, 50, 220, 166, 65, 131, 225, 5, 46]
+        );
+    }
+
+    #[test]
+    fn it_keys_collection_to_stopwords() {
+        assert_eq!(
+            StoreKeyerBuilder::collection_to_stopwords().as_bytes(),
+            [8, 5, 93, 204, 2, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn it_keys_collection_to_name() {
+        assert_eq!(
+            StoreKeyerBuilder::collection_to_name().as_bytes(),
+            [16, 5, 93, 204, 2, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn it_keys_collection_to_oid_normalization() {
+        assert_eq!(
+            StoreKeyerBuilder::collection_to_oid_normalization().as_bytes(),
+            [13, 5, 93, 204, 2, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn it_keys_oid_ordered_to_iid() {
+        assert_eq!(
+            StoreKeyerBuilder::oid_ordered_to_iid("bucket:6", "conversation:1"),
+            vec![
+                9, 73, 139, 25, 113, 99, 111, 110, 118, 101, 114, 115, 97, 116, 105, 111, 110, 58,
+                49
+            ]
+        );
+        assert_eq!(
+            StoreKeyerBuilder::oid_ordered_to_iid_prefix("bucket:6"),
+            [9, 73, 139, 25, 113]
+        );
+        assert_eq!(
+            wide_key(StoreKeyerIdx::OIDOrderedToIID, "bucket:6").as_prefix(),
+            [9, 116, 234, 146, 93, 212, 82, 245, 3]
+        );
+    }
+
+    #[test]
+    fn it_keys_collection_to_key_format() {
+        assert_eq!(StoreKeyerBuilder::collection_to_key_format(), [14]);
+    }
+
+    #[test]
+    fn it_sizes_key_layouts() {
+        for layout in &[StoreKeyerLayout::Narrow, StoreKeyerLayout::Wide] {
+            let key =
+                StoreKeyerBuilder::build_key(StoreKeyerIdx::IIDToTime(7), "bucket:7", *layout);
+
+            assert_eq!(key.len(), layout.key_size());
+            assert_eq!(StoreKeyer { key }.as_prefix().len(), layout.prefix_size());
+            assert!(layout.is_route(&key, &[7, 0, 0, 0]));
+            assert!(!layout.is_route(&key[..layout.prefix_size()], &[]));
+            assert_eq!(
+                StoreKeyerLayout::from_version(layout.to_version()),
+                Some(*layout)
+            );
+        }
+
+        assert_eq!(StoreKeyerLayout::from_version(0), None);
     }
 
     #[test]
     fn it_hashes_compact() {
         assert_eq!(StoreKeyerHasher::to_compact("key:1"), 3370353088);
         assert_eq!(StoreKeyerHasher::to_compact("key:2"), 1042559698);
+        assert_eq!(
+            StoreKeyerHasher::to_compact_seeded("key:1", 0),
+            StoreKeyerHasher::to_compact("key:1")
+        );
+        assert_ne!(
+            StoreKeyerHasher::to_compact_seeded("key:1", 1),
+            StoreKeyerHasher::to_compact("key:1")
+        );
+    }
+
+    #[test]
+    fn it_hashes_wide() {
+        assert_eq!(StoreKeyerHasher::to_wide("key:1"), 13639597086626496847);
+        assert_eq!(StoreKeyerHasher::to_wide("key:2"), 5044366185220410062);
+        assert_ne!(
+            StoreKeyerHasher::to_wide_seeded("key:1", 1),
+            StoreKeyerHasher::to_wide("key:1")
+        );
     }
 
     #[test]
@@ -227,6 +632,13 @@ mod tests {
             ),
             "'0:71198b49:0' [0, 73, 139, 25, 113, 0, 0, 0, 0]"
         );
+        assert_eq!(
+            &format!(
+                "{}",
+                wide_key(StoreKeyerIdx::TermToIIDs(72137347), "bucket:6")
+            ),
+            "'1:3f552d45d92ea74:44cba83' [1, 116, 234, 146, 93, 212, 82, 245, 3, 131, 186, 76, 4]"
+        );
     }
 }
 
@@ -251,6 +663,11 @@ mod benches {
         });
     }
 
+    #[bench]
+    fn bench_hash_wide_short(b: &mut Bencher) {
+        b.iter(|| StoreKeyerHasher::to_wide("key:bench:1"));
+    }
+
     #[bench]
     fn bench_key_meta_to_value(b: &mut Bencher) {
         b.iter(|| StoreKeyerBuilder::meta_to_value("bucket:bench:1", &StoreMetaKey::IIDIncr));
@@ -277,4 +694,19 @@ mod benches {
     fn bench_key_iid_to_terms(b: &mut Bencher) {
         b.iter(|| StoreKeyerBuilder::iid_to_terms("bucket:bench:5", 1));
     }
+
+    #[bench]
+    fn bench_key_iid_to_boost(b: &mut Bencher) {
+        b.iter(|| StoreKeyerBuilder::iid_to_boost("bucket:bench:6", 1));
+    }
+
+    #[bench]
+    fn bench_key_iid_to_time(b: &mut Bencher) {
+        b.iter(|| StoreKeyerBuilder::iid_to_time("bucket:bench:7", 1));
+    }
+
+    #[bench]
+    fn bench_key_iid_to_weights(b: &mut Bencher) {
+        b.iter(|| StoreKeyerBuilder::iid_to_weights("bucket:bench:8", 1));
+    }
 }
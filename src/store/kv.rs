@@ -5,41 +5,71 @@
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
 use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use radix::RadixNum;
 use rocksdb::backup::{
     BackupEngine as DBBackupEngine, BackupEngineOptions as DBBackupEngineOptions,
     RestoreOptions as DBRestoreOptions,
 };
+use rocksdb::checkpoint::Checkpoint as DBCheckpoint;
 use rocksdb::{
-    DBCompactionStyle, DBCompressionType, Env as DBEnv, Error as DBError, FlushOptions,
-    Options as DBOptions, WriteBatch, WriteOptions, DB,
+    BlockBasedOptions, BottommostLevelCompaction, Cache, CompactOptions, DBCompactionStyle,
+    DBCompressionType, DBPinnableSlice, Direction, Env as DBEnv, Error as DBError, FlushOptions,
+    IteratorMode, Options as DBOptions, ReadOptions, Snapshot as DBSnapshot, WriteBatch,
+    WriteOptions, DB,
 };
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::error::Error;
 use std::fmt;
 use std::fs;
 use std::io::{self, Cursor};
+use std::mem;
 use std::path::{Path, PathBuf};
+use std::slice::ChunksExact;
 use std::str;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use std::time::{Duration, SystemTime};
-use std::vec::Drain;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+use whatlang::Lang;
 
 use super::generic::{
     StoreGeneric, StoreGenericActionBuilder, StoreGenericBuilder, StoreGenericPool,
 };
 use super::identifiers::*;
 use super::item::StoreItemPart;
-use super::keyer::{StoreKeyerBuilder, StoreKeyerHasher, StoreKeyerKey, StoreKeyerPrefix};
+use super::keyer::{
+    StoreKeyerBuilder, StoreKeyerHasher, StoreKeyerKey, StoreKeyerLayout, StoreKeyerPrefix,
+};
+use crate::config::options::{
+    ConfigStoreKVDatabaseCompression, ConfigStoreKVDatabaseWriteStall,
+    ConfigStoreKVOIDNormalization,
+};
 use crate::APP_CONF;
 
 pub struct StoreKVPool;
 pub struct StoreKVBuilder;
 
+#[cfg(test)]
+pub struct StoreKVTemporary {
+    pub path: PathBuf,
+    pub store: Option<StoreKVBox>,
+}
+
 pub struct StoreKV {
     database: DB,
     last_used: Arc<RwLock<SystemTime>>,
     last_flushed: Arc<RwLock<SystemTime>>,
+    last_retained: Arc<RwLock<SystemTime>>,
+    last_consolidated: Arc<RwLock<SystemTime>>,
+    last_trimmed: Arc<RwLock<SystemTime>>,
+    last_compacted: Arc<RwLock<SystemTime>>,
+    stopwords: RwLock<Arc<HashSet<String>>>,
+    oid_normalization: StoreKVOIDNormalization,
+    iid_lock: Mutex<()>,
     pub lock: RwLock<bool>,
 }
 
@@ -48,6 +78,44 @@ pub struct StoreKVActionBuilder;
 pub struct StoreKVAction<'a> {
     store: Option<StoreKVBox>,
     bucket: StoreItemPart<'a>,
+    snapshot: Option<&'a StoreKVSnapshot<'a>>,
+    pending: Option<&'a StoreKVPending>,
+}
+
+pub type StoreKVSnapshot<'a> = DBSnapshot<'a>;
+
+#[derive(Default)]
+pub struct StoreKVPending {
+    batch: RefCell<WriteBatch>,
+    writes: RefCell<HashMap<Vec<u8>, Option<Vec<u8>>>>,
+}
+
+type StoreKVPendingWrite = (Vec<u8>, Option<Vec<u8>>);
+
+pub struct StoreKVBatch<'a, 'b> {
+    action: &'b StoreKVAction<'a>,
+    batch: WriteBatch,
+    is_malformed: bool,
+    terms_counted: (u32, u32),
+    objects_counted: (u32, u32),
+    objects_truncated: HashMap<StoreObjectIID, Vec<StoreTermHashed>>,
+    writes: Option<Vec<StoreKVPendingWrite>>,
+}
+
+pub struct StoreKVIIDList<'a> {
+    value: DBPinnableSlice<'a>,
+    positioned: bool,
+}
+
+pub struct StoreKVIIDCursor<'a> {
+    chunks: ChunksExact<'a, u8>,
+    positioned: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct StoreKVTermPostings {
+    iids: Vec<StoreObjectIID>,
+    positions: Option<Vec<Vec<StoreTermPosition>>>,
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
@@ -61,30 +129,255 @@ pub enum StoreKVAcquireMode {
     OpenOnly,
 }
 
+#[derive(Debug, PartialEq)]
+pub enum StoreKVAssignError {
+    Failed,
+    BucketFull,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StoreKVWriteStall {
+    Delayed,
+    Stopped,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct StoreKVStats {
+    pub estimated_keys: Option<u64>,
+    pub live_files_size: Option<u64>,
+    pub pending_compaction_bytes: Option<u64>,
+    pub memtable_size: Option<u64>,
+}
+
+pub struct StoreKVBlockCacheUsage {
+    pub capacity: usize,
+    pub usage: usize,
+    pub pinned_usage: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreKVError {
+    Database(DBError),
+    Io(io::ErrorKind),
+    Serialization,
+    NotFound,
+    Incompatible,
+    Immutable,
+    Collision,
+    ReadOnly,
+    Invalid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StoreKVOIDNormalization {
+    trim: bool,
+    lowercase: bool,
+    nfc: bool,
+}
+
+pub struct StoreKVIIDRanking {
+    pub boost: StoreObjectBoost,
+    pub weights: Vec<(StoreTermHashed, StoreTermWeight)>,
+    pub terms: Option<Vec<StoreTermHashed>>,
+}
+
+pub struct StoreKVIDFCacheUsage {
+    pub entries: u64,
+    pub buckets: u32,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+struct StoreKVBlockCache {
+    cache: Cache,
+    capacity: usize,
+}
+
 type StoreKVAtom = u32;
 type StoreKVBox = Arc<StoreKV>;
 
 const ATOM_HASH_RADIX: usize = 16;
+const BLOCK_CACHE_CAPACITY_MINIMUM: usize = 1048576;
+
+// Notice: this is the RocksDB default compression window size (it is only used by zlib, which \
+//   is not a supported codec), which must be passed along with the compression level.
+const COMPRESSION_WINDOW_BITS: i32 = -14;
+
+// Notice: term values holding IIDs only are a plain list of IIDs (their length is a multiple \
+//   of 4 bytes), while term values holding IIDs and positions are prefixed with a format byte.
+const TERM_FORMAT_POSITIONS: u8 = 0x01;
+
+// Notice: cached IDF weights are considered stale once the bucket object count changed by more \
+//   than this ratio since they were computed.
+const IDF_STALE_CHANGE_RATIO: f64 = 0.1;
+
+// Notice: migrated keys are written in chunks of this many keys, which bounds memory usage \
+//   while migrating large databases.
+const MIGRATE_CHUNK_KEYS: usize = 1000;
+
+// Notice: swapped buckets are rewritten in chunks of this many keys, which bounds memory usage \
+//   while swapping large buckets.
+const SWAP_CHUNK_KEYS: usize = 1000;
+
+// Notice: the registry of collection names is a file at the root of the KV store path, holding \
+//   one collection name per line (collection names never contain whitespaces).
+const REGISTRY_FILE_NAME: &str = "collections";
+
+static STORE_WRITE_STALLS: AtomicU64 = AtomicU64::new(0);
+static STORE_IDF_CACHE_HITS: AtomicU64 = AtomicU64::new(0);
+static STORE_IDF_CACHE_MISSES: AtomicU64 = AtomicU64::new(0);
 
 lazy_static! {
     pub static ref STORE_ACCESS_LOCK: Arc<RwLock<bool>> = Arc::new(RwLock::new(false));
     static ref STORE_ACQUIRE_LOCK: Arc<Mutex<()>> = Arc::new(Mutex::new(()));
     static ref STORE_FLUSH_LOCK: Arc<Mutex<()>> = Arc::new(Mutex::new(()));
+    static ref STORE_RETAIN_LOCK: Arc<Mutex<()>> = Arc::new(Mutex::new(()));
+    static ref STORE_RECOMPRESS_LOCK: Arc<Mutex<()>> = Arc::new(Mutex::new(()));
+    static ref STORE_REGISTRY: Arc<Mutex<HashSet<String>>> = Arc::new(Mutex::new(HashSet::new()));
     static ref STORE_POOL: Arc<RwLock<HashMap<StoreKVKey, StoreKVBox>>> =
         Arc::new(RwLock::new(HashMap::new()));
+    static ref STORE_BLOCK_CACHE: RwLock<StoreKVBlockCache> = RwLock::new(StoreKVBlockCache::new(
+        APP_CONF.store.kv.database.block_cache * 1024
+    ));
 }
 
 impl StoreKVPool {
+    pub fn block_cache() -> StoreKVBlockCacheUsage {
+        let block_cache = STORE_BLOCK_CACHE.read().unwrap();
+
+        StoreKVBlockCacheUsage {
+            capacity: block_cache.capacity,
+            usage: block_cache.cache.get_usage(),
+            pinned_usage: block_cache.cache.get_pinned_usage(),
+        }
+    }
+
+    pub fn resize_block_cache(capacity: usize) -> Result<(), StoreKVError> {
+        if capacity < BLOCK_CACHE_CAPACITY_MINIMUM {
+            return Err(StoreKVError::Invalid);
+        }
+
+        info!("resizing kv block cache to capacity: {} bytes", capacity);
+
+        let mut block_cache = STORE_BLOCK_CACHE.write().unwrap();
+
+        // Notice: when shrinking the cache, RocksDB evicts blocks down to the new capacity, \
+        //   save for blocks that are pinned in use (their memory is released once unpinned).
+        block_cache.cache.set_capacity(capacity);
+        block_cache.capacity = capacity;
+
+        Ok(())
+    }
+
+    pub fn clear_block_cache() {
+        info!("clearing kv block cache");
+
+        let mut block_cache = STORE_BLOCK_CACHE.write().unwrap();
+        let capacity = block_cache.capacity;
+
+        // Notice: shrinking the cache to nothing evicts all blocks, save for blocks that are \
+        //   pinned in use; the cache then refills from subsequent database reads.
+        block_cache.cache.set_capacity(0);
+        block_cache.cache.set_capacity(capacity);
+    }
+
+    pub fn idf_cache(collection: Option<&str>) -> Result<StoreKVIDFCacheUsage, StoreKVError> {
+        // Important: acquire database access read lock, and reference it in context. This \
+        //   prevents the database from being erased while using it in this block.
+        let _access = STORE_ACCESS_LOCK.read().unwrap();
+
+        let mut usage = StoreKVIDFCacheUsage {
+            entries: 0,
+            buckets: 0,
+            hits: STORE_IDF_CACHE_HITS.load(AtomicOrdering::Relaxed),
+            misses: STORE_IDF_CACHE_MISSES.load(AtomicOrdering::Relaxed),
+        };
+
+        for store in Self::list_stores(collection)? {
+            let _lock = store.lock.read().unwrap();
+
+            usage.entries += store
+                .scan_prefix(&StoreKeyerBuilder::term_to_idf_any(), None)
+                .count() as u64;
+            usage.buckets += Self::list_idf_buckets(&store).len() as u32;
+        }
+
+        Ok(usage)
+    }
+
+    pub fn clear_idf_cache(collection: Option<&str>) -> Result<u64, StoreKVError> {
+        info!(
+            "clearing kv idf cache on collection: {}",
+            collection.unwrap_or("all")
+        );
+
+        // Acquire retain lock, and reference it in context
+        // Notice: this prevents an IDF consolidation from refreshing weights while they get \
+        //   cleared (see consolidate).
+        let _retain = STORE_RETAIN_LOCK.lock().unwrap();
+
+        // Important: acquire database access read lock, and reference it in context. This \
+        //   prevents the database from being erased while using it in this block.
+        let _access = STORE_ACCESS_LOCK.read().unwrap();
+
+        let mut count_cleared = 0;
+
+        for store in Self::list_stores(collection)? {
+            // Important: acquire store write lock (cleared buckets then fall back to weights \
+            //   computed on the fly, until refreshed with IDF)
+            let _lock = store.lock.write().unwrap();
+
+            for (bucket, _) in Self::list_idf_buckets(&store) {
+                if let Ok(bucket_part) = StoreItemPart::from_str(&bucket) {
+                    let action = StoreKVActionBuilder::access(bucket_part, Some(store.clone()));
+
+                    count_cleared += action.batch_clear_idf()? as u64;
+                }
+            }
+        }
+
+        Ok(count_cleared)
+    }
+
     pub fn count() -> usize {
         STORE_POOL.read().unwrap().len()
     }
 
+    pub fn stats() -> Vec<(StoreKVKey, StoreKVStats)> {
+        // Notice: only open stores are reported, as reading stats must not open databases.
+        STORE_POOL
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, store)| (*key, store.stats()))
+            .collect()
+    }
+
+    pub fn count_write_stalls() -> u64 {
+        STORE_WRITE_STALLS.load(AtomicOrdering::Relaxed)
+    }
+
     pub fn acquire<'a, T: Into<&'a str>>(
         mode: StoreKVAcquireMode,
         collection: T,
-    ) -> Result<Option<StoreKVBox>, ()> {
+    ) -> Result<Option<StoreKVBox>, StoreKVError> {
         let collection_str = collection.into();
-        let pool_key = StoreKVKey::from_str(collection_str);
+
+        Self::acquire_key(
+            mode,
+            StoreKVKey::from_str(collection_str),
+            Some(collection_str),
+        )
+    }
+
+    fn acquire_key(
+        mode: StoreKVAcquireMode,
+        pool_key: StoreKVKey,
+        collection: Option<&str>,
+    ) -> Result<Option<StoreKVBox>, StoreKVError> {
+        // Notice: stores can be acquired from their collection hash only (eg. when listing \
+        //   collections), in which case their name is not recorded.
+        let collection_str = collection.unwrap_or_default();
 
         // Freeze acquire lock, and reference it in context
         // Notice: this prevents two databases on the same collection to be opened at the same time.
@@ -94,7 +387,12 @@ impl StoreKVPool {
         let store_pool_read = STORE_POOL.read().unwrap();
 
         if let Some(store_kv) = store_pool_read.get(&pool_key) {
-            Self::proceed_acquire_cache("kv", collection_str, pool_key, store_kv).map(Some)
+            Ok(Some(Self::proceed_acquire_cache(
+                "kv",
+                collection_str,
+                pool_key,
+                store_kv,
+            )))
         } else {
             info!(
                 "kv store not in pool for collection: {} {}, opening it",
@@ -116,13 +414,89 @@ impl StoreKVPool {
             //   the database does not exist yet on disk and we are just looking to read data from \
             //   it)
             if can_open_db {
-                Self::proceed_acquire_open("kv", collection_str, pool_key, &*STORE_POOL).map(Some)
+                // Close least recently used databases first? (if too many are open)
+                // Notice: evicted databases are flushed and closed once the pool lock is \
+                //   released, as flushing may take a while. The acquire lock is still held \
+                //   there, thus an evicted database is closed before it can be opened again.
+                if let Some(max_open) = APP_CONF.store.kv.pool.max_open {
+                    let evicted = Self::evict(&mut STORE_POOL.write().unwrap(), max_open);
+
+                    Self::close_evicted(evicted);
+                }
+
+                let store_kv =
+                    Self::proceed_acquire_open("kv", collection_str, pool_key, &*STORE_POOL)?;
+
+                // Record the collection name (as collection listings read names from the \
+                //   collection, which is stored by its hash)
+                if let Some(collection) = collection {
+                    if let Err(err) = store_kv.record_name(collection) {
+                        error!(
+                            "failed recording kv store name for collection: {} {}: {}",
+                            collection, pool_key, err
+                        );
+                    }
+
+                    if let Err(err) = Self::register(collection) {
+                        error!(
+                            "failed registering kv store for collection: {} {}: {}",
+                            collection, pool_key, err
+                        );
+                    }
+                }
+
+                Ok(Some(store_kv))
             } else {
                 Ok(None)
             }
         }
     }
 
+    fn evict(
+        pool: &mut HashMap<StoreKVKey, StoreKVBox>,
+        max_open: usize,
+    ) -> Vec<(StoreKVKey, StoreKVBox)> {
+        let mut evicted = Vec::new();
+
+        // Make room for the database that is about to be opened
+        while !pool.is_empty() && pool.len() >= max_open {
+            // Important: only close databases that are not in use, ie. only referenced from the \
+            //   pool. As references are only handed out while holding the acquire lock, which \
+            //   the caller holds, a database that is not in use cannot get used meanwhile.
+            let least_used = pool
+                .iter()
+                .filter(|(_, store)| Arc::strong_count(store) == 1)
+                .min_by_key(|(_, store)| *store.last_used.read().unwrap())
+                .map(|(key, _)| *key);
+
+            if let Some(key) = least_used {
+                if let Some(store) = pool.remove(&key) {
+                    evicted.push((key, store));
+                }
+            } else {
+                warn!(
+                    "could not close any kv store as all are in use, now has {} items open",
+                    pool.len()
+                );
+
+                break;
+            }
+        }
+
+        evicted
+    }
+
+    fn close_evicted(evicted: Vec<(StoreKVKey, StoreKVBox)>) {
+        for (key, store) in evicted {
+            // Flush pending writes to disk before closing the database
+            if let Err(err) = store.flush() {
+                error!("kv key: {} flush before close failed: {}", key, err);
+            }
+
+            info!("closed least recently used kv store: {}", key);
+        }
+    }
+
     pub fn janitor() {
         Self::proceed_janitor(
             "kv",
@@ -132,6 +506,33 @@ impl StoreKVPool {
         )
     }
 
+    pub fn migrate(
+        collection: &str,
+        buckets: &[&str],
+        from_version: u8,
+        to_version: u8,
+    ) -> Result<usize, StoreKVError> {
+        let pool_key = StoreKVKey::from_str(collection);
+
+        // Freeze acquire lock, and reference it in context
+        // Notice: this prevents the database from being opened while it gets migrated.
+        let _acquire = STORE_ACQUIRE_LOCK.lock().unwrap();
+
+        // Important: refuse to migrate an open database, as it cannot be opened twice.
+        if STORE_POOL.read().unwrap().contains_key(&pool_key) {
+            error!("refusing to migrate open kv store: {}", pool_key);
+
+            return Err(StoreKVError::Invalid);
+        }
+
+        StoreKVBuilder::migrate(
+            &StoreKVBuilder::path(pool_key.collection_hash),
+            buckets,
+            from_version,
+            to_version,
+        )
+    }
+
     pub fn backup(path: &Path) -> Result<(), io::Error> {
         debug!("backing up all kv stores to path: {:?}", path);
 
@@ -145,6 +546,10 @@ impl StoreKVPool {
     pub fn restore(path: &Path) -> Result<(), io::Error> {
         debug!("restoring all kv stores from path: {:?}", path);
 
+        // Remove the registry of collection names (it gets rebuilt from restored collections on \
+        //   the next listing)
+        Self::unregister_all()?;
+
         // Proceed dump action (restore)
         Self::dump_action(
             "restore",
@@ -252,990 +657,6650 @@ impl StoreKVPool {
         );
     }
 
-    fn dump_action(
-        action: &str,
-        read_path: &Path,
-        write_path: &Path,
-        fn_item: &dyn Fn(&Path, &Path, &str) -> Result<(), io::Error>,
-    ) -> Result<(), io::Error> {
-        // Iterate on KV collections
-        for collection in fs::read_dir(read_path)? {
-            let collection = collection?;
+    pub fn trim(force: bool) {
+        debug!("scanning for kv store pool items to trim from memory");
 
-            // Actual collection found?
-            if let (Ok(collection_file_type), Some(collection_name)) =
-                (collection.file_type(), collection.file_name().to_str())
-            {
-                if collection_file_type.is_dir() {
-                    debug!("kv collection ongoing {}: {}", action, collection_name);
+        // Acquire flush lock, and reference it in context
+        // Notice: trimming flushes memtables, thus it must not run along a flush operation.
+        let _flush = STORE_FLUSH_LOCK.lock().unwrap();
 
-                    fn_item(write_path, &collection.path(), collection_name)?;
-                }
-            }
-        }
+        // Step 1: List idle stores that were used since they were last trimmed
+        let mut keys_trim: Vec<StoreKVKey> = Vec::new();
 
-        Ok(())
-    }
+        {
+            let store_pool_read = STORE_POOL.read().unwrap();
 
-    fn backup_item(
-        backup_path: &Path,
-        _origin_path: &Path,
-        collection_name: &str,
-    ) -> Result<(), io::Error> {
-        // Acquire access lock (in blocking write mode), and reference it in context
-        // Notice: this prevents store to be acquired from any context
-        let _access = STORE_ACCESS_LOCK.write().unwrap();
+            for (key, store) in &*store_pool_read {
+                let last_used = *store.last_used.read().unwrap();
 
-        // Generate path to KV backup
-        let kv_backup_path = backup_path.join(collection_name);
+                // Notice: be lenient with system clock going back to a past duration (see flush)
+                let not_used_for = last_used
+                    .elapsed()
+                    .unwrap_or_else(|_| Duration::from_secs(0))
+                    .as_secs();
 
-        debug!(
-            "kv collection: {} backing up to path: {:?}",
-            collection_name, kv_backup_path
-        );
+                if force
+                    || (not_used_for >= APP_CONF.store.kv.pool.trim_after
+                        && *store.last_trimmed.read().unwrap() <= last_used)
+                {
+                    debug!(
+                        "kv key: {} not used for: {} seconds, may trim",
+                        key, not_used_for
+                    );
 
-        // Erase any previously-existing KV backup
-        if kv_backup_path.exists() {
-            fs::remove_dir_all(&kv_backup_path)?;
+                    keys_trim.push(*key);
+                }
+            }
         }
 
-        // Create backup folder for collection
-        fs::create_dir_all(backup_path.join(collection_name))?;
+        // Step 2: Trim KVs, one-by-one (sequential locking; this avoids global locks)
+        // Notice: databases are kept open, only their memtables are flushed to disk, which \
+        //   releases the memory they hold.
+        let mut count_trimmed = 0;
 
-        // Convert names to hashes (as names are hashes encoded as base-16 strings, but we need \
-        //   them as proper integers)
-        if let Ok(collection_radix) = RadixNum::from_str(collection_name, ATOM_HASH_RADIX) {
-            if let Ok(collection_hash) = collection_radix.as_decimal() {
-                let origin_kv = StoreKVBuilder::open(collection_hash as StoreKVAtom)
-                    .map_err(|_| io_error!("database open failure"))?;
+        for key in &keys_trim {
+            {
+                // Acquire access lock (in blocking write mode), and reference it in context
+                // Notice: this prevents store to be acquired from any context
+                let _access = STORE_ACCESS_LOCK.write().unwrap();
 
-                // Initialize KV database backup engine
-                let kv_backup_options = DBBackupEngineOptions::new(&kv_backup_path)
-                    .map_err(|_| io_error!("backup engine options acquire failure"))?;
-                let kv_backup_environment = DBEnv::new()
-                    .map_err(|_| io_error!("backup engine environment acquire failure"))?;
+                if let Some(store) = STORE_POOL.read().unwrap().get(key) {
+                    debug!("kv key: {} trim started", key);
 
-                let mut kv_backup_engine =
-                    DBBackupEngine::open(&kv_backup_options, &kv_backup_environment)
-                        .map_err(|_| io_error!("backup engine failure"))?;
+                    if let Err(err) = store.flush() {
+                        error!("kv key: {} trim failed: {}", key, err);
+                    } else {
+                        count_trimmed += 1;
 
-                // Proceed actual KV database backup
-                kv_backup_engine
-                    .create_new_backup(&origin_kv)
-                    .map_err(|_| io_error!("database backup failure"))?;
+                        debug!("kv key: {} trim complete", key);
+                    }
 
-                info!(
-                    "kv collection: {} backed up to path: {:?}",
-                    collection_name, kv_backup_path
-                );
+                    // Bump 'last trimmed' and 'last flushed' times
+                    let now = SystemTime::now();
+
+                    *store.last_trimmed.write().unwrap() = now;
+                    *store.last_flushed.write().unwrap() = now;
+                }
             }
+
+            // Give a bit of time to other threads before continuing
+            thread::yield_now();
         }
 
-        Ok(())
+        info!(
+            "done scanning for kv store pool items to trim from memory (trimmed: {})",
+            count_trimmed
+        );
     }
 
-    fn restore_item(
-        _backup_path: &Path,
-        origin_path: &Path,
-        collection_name: &str,
-    ) -> Result<(), io::Error> {
-        // Acquire access lock (in blocking write mode), and reference it in context
-        // Notice: this prevents store to be acquired from any context
-        let _access = STORE_ACCESS_LOCK.write().unwrap();
+    pub fn compact(force: bool) {
+        debug!("scanning for kv store pool items to compact on disk");
 
-        debug!(
-            "kv collection: {} restoring from path: {:?}",
-            collection_name, origin_path
-        );
+        // Acquire recompress lock, and reference it in context
+        // Notice: this prevents compactions from running along a full compaction (eg. a \
+        //   recompression), as they would compete for disk bandwidth with no benefit.
+        let _recompress = STORE_RECOMPRESS_LOCK.lock().unwrap();
 
-        // Convert names to hashes (as names are hashes encoded as base-16 strings, but we need \
-        //   them as proper integers)
-        if let Ok(collection_radix) = RadixNum::from_str(collection_name, ATOM_HASH_RADIX) {
-            if let Ok(collection_hash) = collection_radix.as_decimal() {
-                // Force a KV store close
-                StoreKVBuilder::close(collection_hash as StoreKVAtom);
+        // Step 1: List idle stores that were used since they were last compacted
+        let mut keys_compact: Vec<StoreKVKey> = Vec::new();
 
-                // Generate path to KV
-                let kv_path = StoreKVBuilder::path(collection_hash as StoreKVAtom);
+        {
+            let store_pool_read = STORE_POOL.read().unwrap();
 
-                // Remove existing KV database data?
-                if kv_path.exists() {
-                    fs::remove_dir_all(&kv_path)?;
+            for (key, store) in &*store_pool_read {
+                let last_used = *store.last_used.read().unwrap();
+
+                // Notice: be lenient with system clock going back to a past duration (see flush)
+                let not_used_for = last_used
+                    .elapsed()
+                    .unwrap_or_else(|_| Duration::from_secs(0))
+                    .as_secs();
+
+                if force
+                    || (not_used_for >= APP_CONF.store.kv.pool.compact_after()
+                        && *store.last_compacted.read().unwrap() <= last_used)
+                {
+                    debug!(
+                        "kv key: {} not used for: {} seconds, may compact",
+                        key, not_used_for
+                    );
+
+                    keys_compact.push(*key);
                 }
+            }
+        }
 
-                // Create KV folder for collection
-                fs::create_dir_all(&kv_path)?;
+        // Step 2: Compact KVs, one-by-one (sequential locking; this avoids global locks)
+        // Notice: compactions run along reads and writes, thus the access lock is only held in \
+        //   read mode (this prevents the database from being erased while being compacted), \
+        //   and the store is not removed from the pool.
+        let mut count_compacted = 0;
 
-                // Initialize KV database backup engine
-                let kv_backup_options = DBBackupEngineOptions::new(&origin_path)
-                    .map_err(|_| io_error!("backup engine options acquire failure"))?;
-                let kv_backup_environment = DBEnv::new()
-                    .map_err(|_| io_error!("backup engine environment acquire failure"))?;
+        for key in &keys_compact {
+            {
+                // Acquire access lock (in read mode), and reference it in context
+                let _access = STORE_ACCESS_LOCK.read().unwrap();
 
-                let mut kv_backup_engine =
-                    DBBackupEngine::open(&kv_backup_options, &kv_backup_environment)
-                        .map_err(|_| io_error!("backup engine failure"))?;
+                let store = STORE_POOL.read().unwrap().get(key).cloned();
 
-                kv_backup_engine
-                    .restore_from_latest_backup(&kv_path, &kv_path, &DBRestoreOptions::default())
-                    .map_err(|_| io_error!("database restore failure"))?;
+                if let Some(store) = store {
+                    debug!("kv key: {} compaction started", key);
 
-                info!(
-                    "kv collection: {} restored to path: {:?} from backup: {:?}",
-                    collection_name, kv_path, origin_path
-                );
+                    store.compact();
+
+                    count_compacted += 1;
+
+                    debug!("kv key: {} compaction complete", key);
+
+                    // Bump 'last compacted' time
+                    *store.last_compacted.write().unwrap() = SystemTime::now();
+                }
             }
+
+            // Give a bit of time to other threads before continuing
+            thread::yield_now();
         }
 
-        Ok(())
+        info!(
+            "done scanning for kv store pool items to compact on disk (compacted: {})",
+            count_compacted
+        );
     }
-}
 
-impl StoreGenericPool<StoreKVKey, StoreKV, StoreKVBuilder> for StoreKVPool {}
+    pub fn get_stopwords<'a, T: Into<&'a str>>(collection: T) -> Result<Vec<String>, StoreKVError> {
+        // Important: acquire database access read lock, and reference it in context. This \
+        //   prevents the database from being erased while using it in this block.
+        let _access = STORE_ACCESS_LOCK.read().unwrap();
 
-impl StoreKVBuilder {
-    fn open(collection_hash: StoreKVAtom) -> Result<DB, DBError> {
-        debug!(
-            "opening key-value database for collection: <{:x?}>",
-            collection_hash
-        );
+        match Self::acquire(StoreKVAcquireMode::OpenOnly, collection)? {
+            Some(store) => {
+                let mut stopwords: Vec<String> = store.stopwords().iter().cloned().collect();
 
-        // Configure database options
-        let db_options = Self::configure();
+                stopwords.sort_unstable();
 
-        // Open database at path for collection
-        DB::open(&db_options, Self::path(collection_hash))
+                Ok(stopwords)
+            }
+            None => Ok(Vec::new()),
+        }
     }
 
-    fn close(collection_hash: StoreKVAtom) {
-        debug!(
-            "closing key-value database for collection: <{:x?}>",
-            collection_hash
-        );
-
-        let mut store_pool_write = STORE_POOL.write().unwrap();
+    pub fn set_stopwords<'a, T: Into<&'a str>>(
+        collection: T,
+        stopwords: HashSet<String>,
+    ) -> Result<(), StoreKVError> {
+        // Important: acquire database access read lock, and reference it in context. This \
+        //   prevents the database from being erased while using it in this block.
+        let _access = STORE_ACCESS_LOCK.read().unwrap();
 
-        let collection_target = StoreKVKey::from_atom(collection_hash);
+        if let Some(store) = Self::acquire(StoreKVAcquireMode::Any, collection)? {
+            store.set_stopwords(stopwords).map_err(|err| {
+                error!("failed setting collection stopwords: {}", err);
 
-        store_pool_write.remove(&collection_target);
+                StoreKVError::Database(err)
+            })
+        } else {
+            Err(StoreKVError::NotFound)
+        }
     }
 
-    fn path(collection_hash: StoreKVAtom) -> PathBuf {
-        APP_CONF
-            .store
-            .kv
-            .path
-            .join(format!("{:x?}", collection_hash))
-    }
+    pub fn flush_all() -> Result<(), Vec<(StoreKVKey, StoreKVError)>> {
+        debug!("flushing all open kv stores to disk");
 
-    fn configure() -> DBOptions {
-        debug!("configuring key-value database");
+        // Acquire flush lock, and reference it in context
+        // Notice: this prevents two flush operations to be executed at the same time.
+        let _flush = STORE_FLUSH_LOCK.lock().unwrap();
 
-        // Make database options
-        let mut db_options = DBOptions::default();
+        // Step 1: List open stores (the pool lock is not held while flushing)
+        let stores: Vec<(StoreKVKey, StoreKVBox)> = STORE_POOL
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, store)| (*key, store.clone()))
+            .collect();
 
-        // Set static options
-        db_options.create_if_missing(true);
-        db_options.set_use_fsync(false);
-        db_options.set_compaction_style(DBCompactionStyle::Level);
-        db_options.set_min_write_buffer_number(1);
-        db_options.set_max_write_buffer_number(2);
+        // Step 2: Flush each store (a failed flush does not prevent flushing other stores)
+        let mut failures = Vec::new();
 
-        // Set dynamic options
-        db_options.set_compression_type(if APP_CONF.store.kv.database.compress {
-            DBCompressionType::Zstd
-        } else {
-            DBCompressionType::None
-        });
+        for (key, store) in stores {
+            if let Err(err) = store.flush() {
+                error!("kv key: {} flush failed: {}", key, err);
 
-        db_options.set_max_open_files(if let Some(value) = APP_CONF.store.kv.database.max_files {
-            value as i32
-        } else {
-            -1
-        });
+                failures.push((key, err.into()));
+            } else {
+                *store.last_flushed.write().unwrap() = SystemTime::now();
 
-        db_options.increase_parallelism(APP_CONF.store.kv.database.parallelism as i32);
-        db_options.set_max_subcompactions(APP_CONF.store.kv.database.max_compactions as u32);
-        db_options.set_max_background_jobs(
-            (APP_CONF.store.kv.database.max_compactions + APP_CONF.store.kv.database.max_flushes)
-                as i32,
+                debug!("kv key: {} flush complete", key);
+            }
+        }
+
+        info!(
+            "done flushing all kv stores to disk (failed: {})",
+            failures.len()
         );
-        db_options.set_write_buffer_size(APP_CONF.store.kv.database.write_buffer * 1024);
 
-        db_options
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
     }
-}
 
-impl StoreGenericBuilder<StoreKVKey, StoreKV> for StoreKVBuilder {
-    fn build(pool_key: StoreKVKey) -> Result<StoreKV, ()> {
-        Self::open(pool_key.collection_hash)
-            .map(|db| {
-                let now = SystemTime::now();
-
-                StoreKV {
-                    database: db,
-                    last_used: Arc::new(RwLock::new(now)),
-                    last_flushed: Arc::new(RwLock::new(now)),
-                    lock: RwLock::new(false),
-                }
-            })
-            .map_err(|err| {
-                error!("failed opening kv: {}", err);
-            })
-    }
-}
+    pub fn close_all() -> Result<(), Vec<(StoreKVKey, StoreKVError)>> {
+        debug!("closing all open kv stores");
 
-impl StoreKV {
-    pub fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, DBError> {
-        self.database.get(key)
-    }
+        // Acquire access lock (in blocking write mode), and reference it in context
+        // Notice: this waits for in-flight operations on stores to complete, and prevents \
+        //   stores to be acquired from any context afterwards.
+        let _access = STORE_ACCESS_LOCK.write().unwrap();
 
-    pub fn put(&self, key: &[u8], data: &[u8]) -> Result<(), DBError> {
-        let mut batch = WriteBatch::default();
+        // Flush stores to disk, then close them (databases get closed once dropped from pool)
+        let result = Self::flush_all();
 
-        batch.put(key, data);
+        let count_closed = {
+            let mut store_pool_write = STORE_POOL.write().unwrap();
+            let count_closed = store_pool_write.len();
 
-        self.do_write(batch)
-    }
+            store_pool_write.clear();
 
-    pub fn delete(&self, key: &[u8]) -> Result<(), DBError> {
-        let mut batch = WriteBatch::default();
+            count_closed
+        };
 
-        batch.delete(key);
+        info!("done closing all kv stores (closed: {})", count_closed);
 
-        self.do_write(batch)
+        result
     }
 
-    fn flush(&self) -> Result<(), DBError> {
-        // Generate flush options
-        let mut flush_options = FlushOptions::default();
+    pub fn sync() -> Result<(), StoreKVError> {
+        debug!("syncing all open kv stores to disk");
 
-        flush_options.set_wait(true);
+        // Step 1: List open stores (the pool lock is not held while syncing)
+        let stores: Vec<(StoreKVKey, StoreKVBox)> = STORE_POOL
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, store)| (*key, store.clone()))
+            .collect();
 
-        // Perform flush (in blocking mode)
-        self.database.flush_opt(&flush_options)
-    }
+        // Step 2: Sync each store WAL to disk (or flush its memtables, if the WAL is disabled, \
+        //   as writes would be lost on crash otherwise)
+        let (mut count_failed, mut last_error) = (0, None);
 
-    fn do_write(&self, batch: WriteBatch) -> Result<(), DBError> {
-        // Configure this write
-        let mut write_options = WriteOptions::default();
+        for (key, store) in &stores {
+            if let Err(err) = Self::sync_store(store, APP_CONF.store.kv.database.write_ahead_log) {
+                error!("kv key: {} sync failed: {}", key, err);
 
-        // WAL disabled?
-        if !APP_CONF.store.kv.database.write_ahead_log {
-            debug!("ignoring wal for kv write");
+                count_failed += 1;
+                last_error = Some(err);
+            } else {
+                debug!("kv key: {} sync complete", key);
+            }
+        }
 
-            write_options.disable_wal(true);
-        } else {
-            debug!("using wal for kv write");
+        info!(
+            "done syncing kv stores to disk (synced: {}, failed: {})",
+            stores.len() - count_failed,
+            count_failed
+        );
 
-            write_options.disable_wal(false);
+        match last_error {
+            Some(err) => Err(StoreKVError::Database(err)),
+            None => Ok(()),
         }
-
-        // Commit this write
-        self.database.write_opt(batch, &write_options)
     }
-}
 
-impl StoreGeneric for StoreKV {
-    fn ref_last_used(&self) -> &RwLock<SystemTime> {
-        &self.last_used
+    fn sync_store(store: &StoreKV, write_ahead_log: bool) -> Result<(), DBError> {
+        // Notice: writes that skipped the WAL only get persisted once memtables are flushed.
+        if write_ahead_log {
+            store.database.flush_wal(true)
+        } else {
+            store.flush()
+        }
     }
-}
 
-impl StoreKVActionBuilder {
-    pub fn access(bucket: StoreItemPart, store: Option<StoreKVBox>) -> StoreKVAction {
-        Self::build(bucket, store)
-    }
+    pub fn recompress<'a, T: Into<&'a str>>(collection: T) -> Result<(u64, u64), StoreKVError> {
+        let collection_str = collection.into();
 
-    pub fn erase<'a, T: Into<&'a str>>(collection: T, bucket: Option<T>) -> Result<u32, ()> {
-        Self::dispatch_erase("kv", collection, bucket)
-    }
+        debug!("recompressing kv store for collection: {}", collection_str);
 
-    fn build(bucket: StoreItemPart, store: Option<StoreKVBox>) -> StoreKVAction {
-        StoreKVAction { store, bucket }
+        // Acquire recompress lock, and reference it in context
+        // Notice: this prevents two full compactions to be executed at the same time, as they \
+        //   would compete for disk bandwidth with no benefit.
+        let _recompress = STORE_RECOMPRESS_LOCK.lock().unwrap();
+
+        // Acquire access lock (in read mode), and reference it in context
+        // Notice: this prevents the database from being erased while being compacted.
+        let _access = STORE_ACCESS_LOCK.read().unwrap();
+
+        if let Some(store) = Self::acquire(StoreKVAcquireMode::OpenOnly, collection_str)? {
+            let path = StoreKVBuilder::path(StoreKVKey::from_str(collection_str).collection_hash);
+
+            Self::recompress_database(&store.database, &path).map_err(|err| {
+                error!(
+                    "failed recompressing kv store for collection: {} because: {}",
+                    collection_str, err
+                );
+
+                StoreKVError::Database(err)
+            })
+        } else {
+            Err(StoreKVError::NotFound)
+        }
     }
-}
 
-impl StoreGenericActionBuilder for StoreKVActionBuilder {
-    fn proceed_erase_collection(collection_str: &str) -> Result<u32, ()> {
-        let collection_atom = StoreKeyerHasher::to_compact(collection_str);
-        let collection_path = StoreKVBuilder::path(collection_atom);
+    fn recompress_database(database: &DB, path: &Path) -> Result<(u64, u64), DBError> {
+        // Flush memtables first, so that all data gets accounted for and gets rewritten
+        let mut flush_options = FlushOptions::default();
 
-        // Force a KV store close
-        StoreKVBuilder::close(collection_atom);
+        flush_options.set_wait(true);
 
-        if collection_path.exists() {
-            debug!(
-                "kv collection store exists, erasing: {}/* at path: {:?}",
-                collection_str, &collection_path
-            );
+        database.flush_opt(&flush_options)?;
 
-            // Remove KV store storage from filesystem
-            let erase_result = fs::remove_dir_all(&collection_path);
+        let size_before = Self::database_size(path);
 
-            if erase_result.is_ok() {
-                debug!("done with kv collection erasure");
+        // Compact the whole key range, bottommost level included (all tables get rewritten \
+        //   using the compression codec that the database was opened with)
+        let mut compact_options = CompactOptions::default();
 
-                Ok(1)
-            } else {
-                Err(())
-            }
-        } else {
-            debug!(
-                "kv collection store does not exist, consider already erased: {}/* at path: {:?}",
-                collection_str, &collection_path
-            );
+        compact_options.set_exclusive_manual_compaction(true);
+        compact_options.set_bottommost_level_compaction(BottommostLevelCompaction::Force);
 
-            Ok(0)
-        }
+        database.compact_range_opt(None::<&[u8]>, None::<&[u8]>, &compact_options);
+
+        let size_after = Self::database_size(path);
+
+        info!(
+            "done recompressing kv store at path: {:?} (size before: {}, after: {})",
+            path, size_before, size_after
+        );
+
+        Ok((size_before, size_after))
     }
 
-    fn proceed_erase_bucket(_collection: &str, _bucket: &str) -> Result<u32, ()> {
-        // This one is not implemented, as we need to acquire the collection; which would cause \
-        //   a party-killer dead-lock.
-        Err(())
+    fn database_size(path: &Path) -> u64 {
+        // Sum sizes of all files in database directory
+        fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok()?.metadata().ok())
+                    .filter(|metadata| metadata.is_file())
+                    .map(|metadata| metadata.len())
+                    .sum()
+            })
+            .unwrap_or(0)
     }
-}
 
-impl<'a> StoreKVAction<'a> {
-    /// Meta-to-Value mapper
-    ///
-    /// [IDX=0] ((meta)) ~> ((value))
-    pub fn get_meta_to_value(&self, meta: StoreMetaKey) -> Result<Option<StoreMetaValue>, ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::meta_to_value(self.bucket.as_str(), &meta);
+    pub fn retain(force: bool) {
+        debug!("scanning for kv store pool items to enforce retention on");
 
-            debug!("store get meta-to-value: {}", store_key);
+        // Acquire retain lock, and reference it in context
+        // Notice: this prevents two retain operations to be executed at the same time.
+        let _retain = STORE_RETAIN_LOCK.lock().unwrap();
 
-            match store.get(&store_key.as_bytes()) {
-                Ok(Some(value)) => {
-                    debug!("got meta-to-value: {}", store_key);
+        // Step 1: List stores due for retention
+        let mut stores_retain: Vec<(StoreKVKey, StoreKVBox)> = Vec::new();
 
-                    Ok(if let Ok(value) = str::from_utf8(&value) {
-                        match meta {
-                            StoreMetaKey::IIDIncr => value
-                                .parse::<StoreObjectIID>()
-                                .ok()
-                                .map(StoreMetaValue::IIDIncr)
-                                .or(None),
-                        }
-                    } else {
-                        None
-                    })
-                }
-                Ok(None) => {
-                    debug!("no meta-to-value found: {}", store_key);
+        {
+            let store_pool_read = STORE_POOL.read().unwrap();
 
-                    Ok(None)
-                }
-                Err(err) => {
-                    error!(
-                        "error getting meta-to-value: {} with trace: {}",
-                        store_key, err
-                    );
+            for (key, store) in &*store_pool_read {
+                // Notice: be lenient with system clock going back to a past duration (see flush)
+                let not_retained_for = store
+                    .last_retained
+                    .read()
+                    .unwrap()
+                    .elapsed()
+                    .unwrap_or_else(|_| Duration::from_secs(0))
+                    .as_secs();
 
-                    Err(())
+                if force || not_retained_for >= APP_CONF.store.kv.database.retention_after {
+                    stores_retain.push((*key, store.clone()));
                 }
             }
-        } else {
-            Ok(None)
         }
+
+        // Step 2: Enforce retention policies, store-by-store (sequential locking)
+        let mut count_purged = 0;
+
+        for (key, store) in &stores_retain {
+            {
+                // Important: acquire database access read lock, and store write lock
+                let _access = STORE_ACCESS_LOCK.read().unwrap();
+                let _lock = store.lock.write().unwrap();
+
+                debug!("kv key: {} retention started", key);
+
+                count_purged += Self::retain_store(store, StoreKVAction::time_now());
+
+                // Bump 'last retained' time
+                *store.last_retained.write().unwrap() = SystemTime::now();
+            }
+
+            // Give a bit of time to other threads before continuing
+            thread::yield_now();
+        }
+
+        info!(
+            "done scanning for kv store pool items to enforce retention on (purged: {})",
+            count_purged
+        );
     }
 
-    pub fn set_meta_to_value(&self, meta: StoreMetaKey, value: StoreMetaValue) -> Result<(), ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::meta_to_value(self.bucket.as_str(), &meta);
+    fn retain_store(store: &StoreKVBox, now: StoreObjectTime) -> u32 {
+        let mut count_purged = 0;
+
+        // List buckets having a retention policy (from all bucket meta keys)
+        // Notice: as keys only hold bucket hashes, the bucket name is read from the policy.
+        let policy_route = Self::encode_route(StoreMetaKey::Retention.as_u32());
+        let layout = StoreKeyerLayout::configured();
+
+        let policies: Vec<(String, StoreRetention)> = store
+            .scan_prefix(&StoreKeyerBuilder::meta_to_value_any(), None)
+            .filter(|(key, _)| layout.is_route(&key, &policy_route))
+            .filter_map(|(_, value)| {
+                str::from_utf8(&value)
+                    .ok()
+                    .and_then(StoreKVAction::decode_retention)
+                    .map(|(retention, bucket)| (bucket.to_string(), retention))
+            })
+            .collect();
 
-            debug!("store set meta-to-value: {}", store_key);
+        for (bucket, retention) in &policies {
+            if let Ok(bucket_part) = StoreItemPart::from_str(bucket) {
+                let action = StoreKVActionBuilder::access(bucket_part, Some(store.clone()));
 
-            let value_string = match value {
-                StoreMetaValue::IIDIncr(iid_incr) => iid_incr.to_string(),
-            };
+                // Bucket marked as immutable? (its objects are never purged)
+                if action.check_mutable().is_err() {
+                    continue;
+                }
 
-            store
-                .put(&store_key.as_bytes(), value_string.as_bytes())
-                .or(Err(()))
-        } else {
-            Err(())
+                match action.batch_retain_bucket(retention, now) {
+                    Ok(count) => count_purged += count,
+                    Err(_) => error!("failed enforcing kv retention on bucket: {}", bucket),
+                }
+            }
         }
+
+        count_purged
     }
 
-    /// Term-to-IIDs mapper
-    ///
-    /// [IDX=1] ((term)) ~> [((iid))]
-    pub fn get_term_to_iids(
-        &self,
-        term_hashed: StoreTermHashed,
-    ) -> Result<Option<Vec<StoreObjectIID>>, ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::term_to_iids(self.bucket.as_str(), term_hashed);
+    pub fn consolidate(force: bool) {
+        debug!("scanning for kv store pool items to consolidate idf weights on");
 
-            debug!("store get term-to-iids: {}", store_key);
+        // Acquire retain lock, and reference it in context
+        // Notice: this prevents two IDF consolidations (or a retention and a consolidation) to \
+        //   be executed at the same time, which would both scan and write the same buckets.
+        let _retain = STORE_RETAIN_LOCK.lock().unwrap();
 
-            match store.get(&store_key.as_bytes()) {
-                Ok(Some(value)) => {
-                    debug!(
-                        "got term-to-iids: {} with encoded value: {:?}",
-                        store_key, &*value
-                    );
+        // Step 1: List stores due for consolidation
+        let mut stores_consolidate: Vec<(StoreKVKey, StoreKVBox)> = Vec::new();
 
-                    Self::decode_u32_list(&*value)
-                        .or(Err(()))
-                        .map(|value_decoded| {
-                            debug!(
-                                "got term-to-iids: {} with decoded value: {:?}",
-                                store_key, &value_decoded
-                            );
+        {
+            let store_pool_read = STORE_POOL.read().unwrap();
 
-                            Some(value_decoded)
-                        })
-                }
-                Ok(None) => {
-                    debug!("no term-to-iids found: {}", store_key);
+            for (key, store) in &*store_pool_read {
+                // Notice: be lenient with system clock going back to a past duration (see flush)
+                let not_consolidated_for = store
+                    .last_consolidated
+                    .read()
+                    .unwrap()
+                    .elapsed()
+                    .unwrap_or_else(|_| Duration::from_secs(0))
+                    .as_secs();
 
-                    Ok(None)
+                if force || not_consolidated_for >= APP_CONF.store.fst.graph.consolidate_after {
+                    stores_consolidate.push((*key, store.clone()));
                 }
-                Err(err) => {
-                    error!(
-                        "error getting term-to-iids: {} with trace: {}",
-                        store_key, err
-                    );
+            }
+        }
 
-                    Err(())
-                }
+        // Step 2: Refresh stale IDF weights, store-by-store (sequential locking)
+        let mut count_refreshed = 0;
+
+        for (key, store) in &stores_consolidate {
+            {
+                // Important: acquire database access read lock, and store write lock
+                let _access = STORE_ACCESS_LOCK.read().unwrap();
+                let _lock = store.lock.write().unwrap();
+
+                debug!("kv key: {} idf consolidation started", key);
+
+                count_refreshed += Self::consolidate_store(store);
+
+                // Bump 'last consolidated' time
+                *store.last_consolidated.write().unwrap() = SystemTime::now();
             }
-        } else {
-            Ok(None)
+
+            // Give a bit of time to other threads before continuing
+            thread::yield_now();
         }
+
+        info!(
+            "done scanning for kv store pool items to consolidate idf weights on (refreshed: {})",
+            count_refreshed
+        );
     }
 
-    pub fn set_term_to_iids(
-        &self,
-        term_hashed: StoreTermHashed,
-        iids: &[StoreObjectIID],
-    ) -> Result<(), ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::term_to_iids(self.bucket.as_str(), term_hashed);
+    fn consolidate_store(store: &StoreKVBox) -> u32 {
+        let mut count_refreshed = 0;
 
-            debug!("store set term-to-iids: {}", store_key);
+        for (bucket, idf_count) in &Self::list_idf_buckets(store) {
+            if let Ok(bucket_part) = StoreItemPart::from_str(bucket) {
+                let action = StoreKVActionBuilder::access(bucket_part, Some(store.clone()));
 
-            // Encode IID list into storage serialized format
-            let iids_encoded = Self::encode_u32_list(iids);
+                // Bucket did not change significantly? (keep cached weights)
+                match action.get_oid_count() {
+                    Ok(oid_count) if !StoreKVAction::is_idf_stale(*idf_count, oid_count) => {
+                        continue;
+                    }
+                    Ok(_) => {}
+                    Err(_) => continue,
+                }
 
-            debug!(
-                "store set term-to-iids: {} with encoded value: {:?}",
-                store_key, iids_encoded
-            );
+                match action.batch_refresh_idf() {
+                    Ok(_) => count_refreshed += 1,
+                    Err(_) => error!("failed refreshing kv idf weights on bucket: {}", bucket),
+                }
+            }
+        }
 
-            store.put(&store_key.as_bytes(), &iids_encoded).or(Err(()))
-        } else {
-            Err(())
+        count_refreshed
+    }
+
+    fn list_idf_buckets(store: &StoreKVBox) -> Vec<(String, u32)> {
+        // List buckets having cached IDF weights (from all bucket meta keys)
+        // Notice: as keys only hold bucket hashes, the bucket name is read from the value.
+        let idf_route = Self::encode_route(StoreMetaKey::IDFCount.as_u32());
+        let layout = StoreKeyerLayout::configured();
+
+        store
+            .scan_prefix(&StoreKeyerBuilder::meta_to_value_any(), None)
+            .filter(|(key, _)| layout.is_route(&key, &idf_route))
+            .filter_map(|(_, value)| {
+                str::from_utf8(&value)
+                    .ok()
+                    .and_then(StoreKVAction::decode_idf_count)
+                    .map(|(idf_count, bucket)| (bucket.to_string(), idf_count))
+            })
+            .collect()
+    }
+
+    fn list_stores(collection: Option<&str>) -> Result<Vec<StoreKVBox>, StoreKVError> {
+        // Notice: without a collection, only open collections are listed (as for consolidation).
+        match collection {
+            Some(collection) => Ok(Self::acquire(StoreKVAcquireMode::OpenOnly, collection)?
+                .into_iter()
+                .collect()),
+            None => Ok(STORE_POOL.read().unwrap().values().cloned().collect()),
         }
     }
 
-    pub fn delete_term_to_iids(&self, term_hashed: StoreTermHashed) -> Result<(), ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::term_to_iids(self.bucket.as_str(), term_hashed);
+    pub fn has_immutable_buckets(collection: &str) -> Result<bool, StoreKVError> {
+        // Notice: the store reference is dropped once done, as the collection may be erased \
+        //   right after (see the collection flush executor).
+        let immutable_route = Self::encode_route(StoreMetaKey::Immutable.as_u32());
+        let layout = StoreKeyerLayout::configured();
+
+        match Self::acquire(StoreKVAcquireMode::OpenOnly, collection)? {
+            Some(store) => Ok(store
+                .scan_prefix(&StoreKeyerBuilder::meta_to_value_any(), None)
+                .any(|(key, _)| layout.is_route(&key, &immutable_route))),
+            None => Ok(false),
+        }
+    }
 
-            debug!("store delete term-to-iids: {}", store_key);
+    pub fn list_collections() -> Result<Vec<String>, StoreKVError> {
+        // Important: acquire database access read lock, and reference it in context. This \
+        //   prevents the database from being erased while using it in this block.
+        let _access = STORE_ACCESS_LOCK.read().unwrap();
 
-            store.delete(&store_key.as_bytes()).or(Err(()))
-        } else {
-            Err(())
+        // Notice: collections are stored by their hash, thus names are read from the registry \
+        //   of collection names (a collection is only listed once it got opened by name).
+        let registry = {
+            let _registry = STORE_REGISTRY.lock().unwrap();
+
+            Self::read_registry()?
+        };
+
+        let mut collections: Vec<String> = match registry {
+            Some(registry) => registry,
+            None => Self::rebuild_registry()?,
         }
+        .into_iter()
+        .filter(|collection| {
+            StoreKVBuilder::path(StoreKeyerHasher::to_compact(collection)).exists()
+        })
+        .collect();
+
+        collections.sort_unstable();
+
+        Ok(collections)
     }
 
-    /// OID-to-IID mapper
-    ///
-    /// [IDX=2] ((oid)) ~> ((iid))
-    pub fn get_oid_to_iid(&self, oid: StoreObjectOID<'a>) -> Result<Option<StoreObjectIID>, ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::oid_to_iid(self.bucket.as_str(), oid);
+    pub fn list_buckets(collection: &str) -> Result<Vec<String>, StoreKVError> {
+        // Important: acquire database access read lock, and reference it in context. This \
+        //   prevents the database from being erased while using it in this block.
+        let _access = STORE_ACCESS_LOCK.read().unwrap();
 
-            debug!("store get oid-to-iid: {}", store_key);
+        // Notice: as keys only hold bucket hashes, bucket names are read from values (buckets \
+        //   get named once an object is pushed to them).
+        let name_route = Self::encode_route(StoreMetaKey::BucketName.as_u32());
+        let layout = StoreKeyerLayout::configured();
 
-            match store.get(&store_key.as_bytes()) {
-                Ok(Some(value)) => {
-                    debug!(
-                        "got oid-to-iid: {} with encoded value: {:?}",
-                        store_key, &*value
-                    );
+        match Self::acquire(StoreKVAcquireMode::OpenOnly, collection)? {
+            Some(store) => {
+                let _lock = store.lock.read().unwrap();
 
-                    Self::decode_u32(&*value).or(Err(())).map(|value_decoded| {
-                        debug!(
-                            "got oid-to-iid: {} with decoded value: {:?}",
-                            store_key, &value_decoded
-                        );
+                let mut buckets: Vec<String> = store
+                    .scan_prefix(&StoreKeyerBuilder::meta_to_value_any(), None)
+                    .filter(|(key, _)| layout.is_route(key, &name_route))
+                    .filter_map(|(_, value)| str::from_utf8(&value).ok().map(str::to_string))
+                    .collect();
 
-                        Some(value_decoded)
-                    })
-                }
-                Ok(None) => {
-                    debug!("no oid-to-iid found: {}", store_key);
+                buckets.sort_unstable();
 
-                    Ok(None)
-                }
-                Err(err) => {
-                    error!(
-                        "error getting oid-to-iid: {} with trace: {}",
-                        store_key, err
-                    );
+                Ok(buckets)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn register(collection: &str) -> Result<(), io::Error> {
+        let mut registered = STORE_REGISTRY.lock().unwrap();
+
+        // Notice: the registry file is only read once per collection name, as this happens \
+        //   every time the database gets opened. If there is no registry file yet, names are \
+        //   only kept in memory, until the registry gets built (see 'rebuild_registry').
+        if registered.insert(collection.to_string()) {
+            if let Some(mut registry) = Self::read_registry()? {
+                if !registry
+                    .iter()
+                    .any(|registry_name| registry_name == collection)
+                {
+                    registry.push(collection.to_string());
 
-                    Err(())
+                    Self::write_registry(&registry)?;
                 }
             }
-        } else {
-            Ok(None)
         }
+
+        Ok(())
     }
 
-    pub fn set_oid_to_iid(&self, oid: StoreObjectOID<'a>, iid: StoreObjectIID) -> Result<(), ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::oid_to_iid(self.bucket.as_str(), oid);
+    fn unregister(collection: &str) -> Result<(), io::Error> {
+        let mut registered = STORE_REGISTRY.lock().unwrap();
 
-            debug!("store set oid-to-iid: {}", store_key);
+        registered.remove(collection);
 
-            // Encode IID
-            let iid_encoded = Self::encode_u32(iid);
+        if let Some(mut registry) = Self::read_registry()? {
+            let registry_size = registry.len();
 
-            debug!(
-                "store set oid-to-iid: {} with encoded value: {:?}",
-                store_key, iid_encoded
-            );
+            registry.retain(|registry_name| registry_name != collection);
 
-            store.put(&store_key.as_bytes(), &iid_encoded).or(Err(()))
-        } else {
-            Err(())
+            if registry.len() != registry_size {
+                Self::write_registry(&registry)?;
+            }
         }
+
+        Ok(())
     }
 
-    pub fn delete_oid_to_iid(&self, oid: StoreObjectOID<'a>) -> Result<(), ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::oid_to_iid(self.bucket.as_str(), oid);
+    fn unregister_all() -> Result<(), io::Error> {
+        let mut registered = STORE_REGISTRY.lock().unwrap();
 
-            debug!("store delete oid-to-iid: {}", store_key);
+        registered.clear();
 
-            store.delete(&store_key.as_bytes()).or(Err(()))
-        } else {
-            Err(())
+        match fs::remove_file(Self::registry_path()) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
         }
     }
 
-    /// IID-to-OID mapper
-    ///
-    /// [IDX=3] ((iid)) ~> ((oid))
-    pub fn get_iid_to_oid(&self, iid: StoreObjectIID) -> Result<Option<String>, ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::iid_to_oid(self.bucket.as_str(), iid);
+    fn rebuild_registry() -> Result<Vec<String>, io::Error> {
+        info!("building kv store registry of collection names");
 
-            debug!("store get iid-to-oid: {}", store_key);
+        // Notice: each collection gets opened to read its name, as collections created before \
+        //   the registry existed are not registered.
+        let mut registry = Vec::new();
 
-            match store.get(&store_key.as_bytes()) {
-                Ok(Some(value)) => Ok(str::from_utf8(&value).ok().map(|value| value.to_string())),
-                Ok(None) => Ok(None),
-                Err(_) => Err(()),
+        if APP_CONF.store.kv.path.exists() {
+            for collection in fs::read_dir(&APP_CONF.store.kv.path)? {
+                let collection = collection?;
+
+                if let (Ok(collection_file_type), Some(collection_name)) =
+                    (collection.file_type(), collection.file_name().to_str())
+                {
+                    let collection_hash =
+                        StoreKVAtom::from_str_radix(collection_name, ATOM_HASH_RADIX as u32);
+
+                    if let (true, Ok(collection_hash)) =
+                        (collection_file_type.is_dir(), collection_hash)
+                    {
+                        let pool_key = StoreKVKey::from_atom(collection_hash);
+
+                        // Notice: collections that cannot be opened are skipped (eg. \
+                        //   collections with another key format), as they cannot be used anyway.
+                        match Self::acquire_key(StoreKVAcquireMode::OpenOnly, pool_key, None) {
+                            Ok(Some(store)) => registry.extend(store.name()),
+                            Ok(None) => {}
+                            Err(_) => warn!("skipped registering kv store: {}", pool_key),
+                        }
+                    }
+                }
             }
-        } else {
-            Ok(None)
         }
-    }
-
-    pub fn set_iid_to_oid(&self, iid: StoreObjectIID, oid: StoreObjectOID<'a>) -> Result<(), ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::iid_to_oid(self.bucket.as_str(), iid);
 
-            debug!("store set iid-to-oid: {}", store_key);
+        // Merge collection names registered while building the registry
+        let registered = STORE_REGISTRY.lock().unwrap();
 
-            store.put(&store_key.as_bytes(), oid.as_bytes()).or(Err(()))
-        } else {
-            Err(())
+        for collection in registered.iter() {
+            if !registry.contains(collection) {
+                registry.push(collection.to_owned());
+            }
         }
-    }
 
-    pub fn delete_iid_to_oid(&self, iid: StoreObjectIID) -> Result<(), ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::iid_to_oid(self.bucket.as_str(), iid);
+        Self::write_registry(&registry)?;
 
-            debug!("store delete iid-to-oid: {}", store_key);
+        Ok(registry)
+    }
 
-            store.delete(&store_key.as_bytes()).or(Err(()))
-        } else {
-            Err(())
+    fn read_registry() -> Result<Option<Vec<String>>, io::Error> {
+        // Important: the registry lock must be held by the caller (as well as when writing).
+        match fs::read_to_string(Self::registry_path()) {
+            Ok(registry) => Ok(Some(registry.lines().map(str::to_string).collect())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
         }
     }
 
-    /// IID-to-Terms mapper
-    ///
-    /// [IDX=4] ((iid)) ~> [((term))]
-    pub fn get_iid_to_terms(
-        &self,
-        iid: StoreObjectIID,
-    ) -> Result<Option<Vec<StoreTermHashed>>, ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::iid_to_terms(self.bucket.as_str(), iid);
+    fn write_registry(registry: &[String]) -> Result<(), io::Error> {
+        let registry_path = Self::registry_path();
+        let registry_path_temporary = StoreKVBuilder::path_with_suffix(&registry_path, "tmp");
+
+        fs::create_dir_all(&APP_CONF.store.kv.path)?;
+
+        // Write the registry to a temporary file first, which then replaces the registry (thus \
+        //   the registry is never read partially written)
+        fs::write(
+            &registry_path_temporary,
+            registry
+                .iter()
+                .map(|collection| format!("{}\n", collection))
+                .collect::<String>(),
+        )?;
+        fs::rename(&registry_path_temporary, registry_path)
+    }
 
-            debug!("store get iid-to-terms: {}", store_key);
+    fn registry_path() -> PathBuf {
+        APP_CONF.store.kv.path.join(REGISTRY_FILE_NAME)
+    }
 
-            match store.get(&store_key.as_bytes()) {
-                Ok(Some(value)) => {
-                    debug!(
-                        "got iid-to-terms: {} with encoded value: {:?}",
-                        store_key, &*value
-                    );
+    fn encode_route(route: u32) -> [u8; 4] {
+        let mut encoded = [0; 4];
 
-                    Self::decode_u32_list(&*value)
-                        .or(Err(()))
-                        .map(|value_decoded| {
-                            debug!(
-                                "got iid-to-terms: {} with decoded value: {:?}",
-                                store_key, &value_decoded
-                            );
+        LittleEndian::write_u32(&mut encoded, route);
 
-                            if !value_decoded.is_empty() {
-                                Some(value_decoded)
-                            } else {
-                                None
-                            }
-                        })
+        encoded
+    }
+
+    fn dump_action(
+        action: &str,
+        read_path: &Path,
+        write_path: &Path,
+        fn_item: &dyn Fn(&Path, &Path, &str) -> Result<(), io::Error>,
+    ) -> Result<(), io::Error> {
+        // Iterate on KV collections
+        for collection in fs::read_dir(read_path)? {
+            let collection = collection?;
+
+            // Actual collection found?
+            if let (Ok(collection_file_type), Some(collection_name)) =
+                (collection.file_type(), collection.file_name().to_str())
+            {
+                if collection_file_type.is_dir() {
+                    debug!("kv collection ongoing {}: {}", action, collection_name);
+
+                    fn_item(write_path, &collection.path(), collection_name)?;
                 }
-                Ok(None) => Ok(None),
-                Err(_) => Err(()),
             }
-        } else {
-            Ok(None)
         }
+
+        Ok(())
     }
 
-    pub fn set_iid_to_terms(
-        &self,
-        iid: StoreObjectIID,
-        terms_hashed: &[StoreTermHashed],
-    ) -> Result<(), ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::iid_to_terms(self.bucket.as_str(), iid);
+    fn backup_item(
+        backup_path: &Path,
+        _origin_path: &Path,
+        collection_name: &str,
+    ) -> Result<(), io::Error> {
+        // Acquire access lock (in blocking write mode), and reference it in context
+        // Notice: this prevents store to be acquired from any context
+        let _access = STORE_ACCESS_LOCK.write().unwrap();
+
+        // Generate path to KV backup
+        let kv_backup_path = backup_path.join(collection_name);
 
-            debug!("store set iid-to-terms: {}", store_key);
+        debug!(
+            "kv collection: {} backing up to path: {:?}",
+            collection_name, kv_backup_path
+        );
 
-            // Encode term list into storage serialized format
-            let terms_hashed_encoded = Self::encode_u32_list(terms_hashed);
+        // Erase any previously-existing KV backup
+        if kv_backup_path.exists() {
+            fs::remove_dir_all(&kv_backup_path)?;
+        }
 
-            debug!(
-                "store set iid-to-terms: {} with encoded value: {:?}",
-                store_key, terms_hashed_encoded
-            );
+        // Create backup folder for collection
+        fs::create_dir_all(backup_path.join(collection_name))?;
 
-            store
-                .put(&store_key.as_bytes(), &terms_hashed_encoded)
-                .or(Err(()))
-        } else {
-            Err(())
+        // Convert names to hashes (as names are hashes encoded as base-16 strings, but we need \
+        //   them as proper integers)
+        if let Ok(collection_radix) = RadixNum::from_str(collection_name, ATOM_HASH_RADIX) {
+            if let Ok(collection_hash) = collection_radix.as_decimal() {
+                let origin_kv = StoreKVBuilder::open(collection_hash as StoreKVAtom)
+                    .map_err(|_| io_error!("database open failure"))?;
+
+                // Initialize KV database backup engine
+                let kv_backup_options = DBBackupEngineOptions::new(&kv_backup_path)
+                    .map_err(|_| io_error!("backup engine options acquire failure"))?;
+                let kv_backup_environment = DBEnv::new()
+                    .map_err(|_| io_error!("backup engine environment acquire failure"))?;
+
+                let mut kv_backup_engine =
+                    DBBackupEngine::open(&kv_backup_options, &kv_backup_environment)
+                        .map_err(|_| io_error!("backup engine failure"))?;
+
+                // Proceed actual KV database backup
+                kv_backup_engine
+                    .create_new_backup(&origin_kv)
+                    .map_err(|_| io_error!("database backup failure"))?;
+
+                info!(
+                    "kv collection: {} backed up to path: {:?}",
+                    collection_name, kv_backup_path
+                );
+            }
         }
+
+        Ok(())
     }
 
-    pub fn delete_iid_to_terms(&self, iid: StoreObjectIID) -> Result<(), ()> {
-        if let Some(ref store) = self.store {
-            let store_key = StoreKeyerBuilder::iid_to_terms(self.bucket.as_str(), iid);
+    fn restore_item(
+        _backup_path: &Path,
+        origin_path: &Path,
+        collection_name: &str,
+    ) -> Result<(), io::Error> {
+        // Acquire access lock (in blocking write mode), and reference it in context
+        // Notice: this prevents store to be acquired from any context
+        let _access = STORE_ACCESS_LOCK.write().unwrap();
+
+        debug!(
+            "kv collection: {} restoring from path: {:?}",
+            collection_name, origin_path
+        );
+
+        // Convert names to hashes (as names are hashes encoded as base-16 strings, but we need \
+        //   them as proper integers)
+        if let Ok(collection_radix) = RadixNum::from_str(collection_name, ATOM_HASH_RADIX) {
+            if let Ok(collection_hash) = collection_radix.as_decimal() {
+                // Force a KV store close
+                StoreKVBuilder::close(collection_hash as StoreKVAtom);
+
+                // Generate path to KV
+                let kv_path = StoreKVBuilder::path(collection_hash as StoreKVAtom);
+
+                // Remove existing KV database data?
+                if kv_path.exists() {
+                    fs::remove_dir_all(&kv_path)?;
+                }
+
+                // Create KV folder for collection
+                fs::create_dir_all(&kv_path)?;
+
+                // Initialize KV database backup engine
+                let kv_backup_options = DBBackupEngineOptions::new(&origin_path)
+                    .map_err(|_| io_error!("backup engine options acquire failure"))?;
+                let kv_backup_environment = DBEnv::new()
+                    .map_err(|_| io_error!("backup engine environment acquire failure"))?;
+
+                let mut kv_backup_engine =
+                    DBBackupEngine::open(&kv_backup_options, &kv_backup_environment)
+                        .map_err(|_| io_error!("backup engine failure"))?;
+
+                kv_backup_engine
+                    .restore_from_latest_backup(&kv_path, &kv_path, &DBRestoreOptions::default())
+                    .map_err(|_| io_error!("database restore failure"))?;
+
+                info!(
+                    "kv collection: {} restored to path: {:?} from backup: {:?}",
+                    collection_name, kv_path, origin_path
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl StoreGenericPool<StoreKVKey, StoreKV, StoreKVBuilder> for StoreKVPool {}
+
+impl StoreKVBuilder {
+    fn open(collection_hash: StoreKVAtom) -> Result<DB, DBError> {
+        debug!(
+            "opening key-value database for collection: <{:x?}>",
+            collection_hash
+        );
+
+        // Open database at path for collection
+        Self::open_path(&Self::path(collection_hash), Self::compression())
+    }
+
+    fn open_path(path: &Path, compression: DBCompressionType) -> Result<DB, DBError> {
+        // Configure database options
+        let db_options = Self::configure(compression, APP_CONF.store.kv.database.compression_level);
+
+        DB::open(&db_options, path)
+    }
+
+    pub fn open_at(path: &Path) -> Result<StoreKV, StoreKVError> {
+        debug!("opening key-value database at path: {:?}", path);
+
+        // Notice: the database is opened on its own, outside of the store pool (eg. this opens \
+        //   checkpoints, which are full databases keeping their recorded key format and OID \
+        //   normalization, thus both must match the configured ones).
+        let database = Self::open_path(path, Self::compression())?;
+
+        Self::build_store(
+            database,
+            StoreKVOIDNormalization::from_config(&APP_CONF.store.kv.oid_normalization),
+        )
+    }
+
+    #[cfg(test)]
+    pub fn open_temporary(name: &str) -> StoreKVTemporary {
+        let path = std::env::temp_dir().join(format!("sonic-test-kv-{}", name));
+
+        // Start from an empty database (a previous run may have left it behind)
+        let _ = fs::remove_dir_all(&path);
+
+        let store = Self::open_at(&path).expect("temporary kv store could not be opened");
+
+        StoreKVTemporary {
+            path,
+            store: Some(Arc::new(store)),
+        }
+    }
+
+    pub fn key_format() -> u8 {
+        StoreKeyerLayout::configured().to_version()
+    }
+
+    pub fn migrate(
+        path: &Path,
+        buckets: &[&str],
+        from_version: u8,
+        to_version: u8,
+    ) -> Result<usize, StoreKVError> {
+        info!(
+            "migrating key-value database at path: {:?} from key format: {} to: {}",
+            path, from_version, to_version
+        );
+
+        let (from, to) = match (
+            StoreKeyerLayout::from_version(from_version),
+            StoreKeyerLayout::from_version(to_version),
+        ) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return Err(StoreKVError::Incompatible),
+        };
+
+        // Rewrite all keys to a new database next to the former database, which then replaces \
+        //   it (thus a failed migration leaves the former database untouched)
+        let (migrate_path, former_path) = (
+            Self::path_with_suffix(path, "migrate"),
+            Self::path_with_suffix(path, "former"),
+        );
+
+        // Remove any new database left over by a previous failed migration
+        if migrate_path.exists() {
+            fs::remove_dir_all(&migrate_path)?;
+        }
+
+        let count_migrated = {
+            let database = Self::open_path(path, Self::compression())?;
+            let migrate_database = Self::open_path(&migrate_path, Self::compression())?;
+
+            Self::migrate_keys(&database, &migrate_database, buckets, from, to)
+        };
+
+        let count_migrated = match count_migrated {
+            Ok(count_migrated) => count_migrated,
+            Err(err) => {
+                fs::remove_dir_all(&migrate_path).ok();
+
+                return Err(err);
+            }
+        };
+
+        // Swap databases (databases are closed at this point)
+        fs::rename(path, &former_path)?;
+        fs::rename(&migrate_path, path)?;
+        fs::remove_dir_all(&former_path)?;
+
+        info!(
+            "migrated key-value database at path: {:?} (migrated {} keys)",
+            path, count_migrated
+        );
+
+        Ok(count_migrated)
+    }
+
+    fn migrate_keys(
+        database: &DB,
+        migrate_database: &DB,
+        buckets: &[&str],
+        from: StoreKeyerLayout,
+        to: StoreKeyerLayout,
+    ) -> Result<usize, StoreKVError> {
+        let format_key = StoreKeyerBuilder::collection_to_key_format();
+
+        // Notice: databases created before key formats were recorded use narrow keys (an \
+        //   empty database has no keys to migrate, thus is migrated as well). Bucket hashes \
+        //   keep the configured seed, which the database must have been created with.
+        let seed = APP_CONF.store.kv.bucket_hash_seed;
+
+        let recorded = match database.get(format_key)? {
+            Some(value) => StoreKV::decode_key_format(&value),
+            None => Some((StoreKeyerLayout::Narrow, 0)),
+        };
+
+        if recorded != Some((from, seed)) {
+            error!(
+                "refusing to migrate kv store with key format and seed: {:?} (expected: {:?})",
+                recorded,
+                (from, seed)
+            );
+
+            return Err(StoreKVError::Incompatible);
+        }
+
+        // Map bucket hashes from the former layout to the new layout
+        // Important: as keys only hold bucket hashes, bucket names must be known. Names \
+        //   recorded in the database are mapped along with the provided names, and the empty \
+        //   bucket name is always mapped, as collection-wide keys are stored with it.
+        let (from_bucket_size, to_bucket_size) = (from.prefix_size() - 1, to.prefix_size() - 1);
+
+        let name_route = StoreKVPool::encode_route(StoreMetaKey::BucketName.as_u32());
+
+        let meta_prefix = StoreKeyerBuilder::meta_to_value_any();
+
+        let buckets_recorded: Vec<String> = database
+            .iterator(IteratorMode::From(&meta_prefix, Direction::Forward))
+            .filter_map(|item| item.ok())
+            .take_while(|(key, _)| key.starts_with(&meta_prefix))
+            .filter(|(key, _)| from.is_route(key, &name_route))
+            .filter_map(|(_, value)| str::from_utf8(&value).ok().map(str::to_string))
+            .collect();
+
+        let bucket_hashes: HashMap<Vec<u8>, Vec<u8>> = buckets
+            .iter()
+            .copied()
+            .chain(buckets_recorded.iter().map(String::as_str))
+            .chain(std::iter::once(""))
+            .map(|bucket| {
+                let (mut from_hash, mut to_hash) =
+                    (vec![0; from_bucket_size], vec![0; to_bucket_size]);
+
+                from.encode_bucket(bucket, &mut from_hash);
+                to.encode_bucket(bucket, &mut to_hash);
+
+                (from_hash, to_hash)
+            })
+            .collect();
+
+        // Rewrite all keys under the new layout, in chunks
+        let (mut batch, mut count_migrated) = (WriteBatch::default(), 0);
+
+        for item in database.iterator(IteratorMode::Start) {
+            let (key, value) = item?;
+
+            if *key == format_key {
+                continue;
+            }
+
+            match key
+                .get(1..from.prefix_size())
+                .and_then(|bucket_hash| bucket_hashes.get(bucket_hash))
+            {
+                Some(to_hash) => {
+                    let mut to_key =
+                        Vec::with_capacity(key.len() - from_bucket_size + to_bucket_size);
+
+                    to_key.push(key[0]);
+                    to_key.extend_from_slice(to_hash);
+                    to_key.extend_from_slice(&key[from.prefix_size()..]);
+
+                    batch.put(to_key, value);
+
+                    count_migrated += 1;
+                }
+                None => {
+                    error!(
+                        "refusing to migrate kv store key from an unknown bucket: {:?}",
+                        key
+                    );
+
+                    return Err(StoreKVError::Incompatible);
+                }
+            }
+
+            if batch.len() >= MIGRATE_CHUNK_KEYS {
+                migrate_database.write(mem::take(&mut batch))?;
+            }
+        }
+
+        migrate_database.write(batch)?;
+
+        // Important: the key format is recorded last, once all keys got migrated, thus an \
+        //   interrupted migration never leaves a database that looks fully migrated.
+        migrate_database.put(format_key, StoreKV::encode_key_format(to, seed))?;
+
+        Ok(count_migrated)
+    }
+
+    fn path_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+        let mut path = path.as_os_str().to_owned();
+
+        path.push(".");
+        path.push(suffix);
+
+        PathBuf::from(path)
+    }
+
+    fn close(collection_hash: StoreKVAtom) {
+        debug!(
+            "closing key-value database for collection: <{:x?}>",
+            collection_hash
+        );
+
+        let mut store_pool_write = STORE_POOL.write().unwrap();
+
+        let collection_target = StoreKVKey::from_atom(collection_hash);
+
+        store_pool_write.remove(&collection_target);
+    }
+
+    fn path(collection_hash: StoreKVAtom) -> PathBuf {
+        APP_CONF
+            .store
+            .kv
+            .path
+            .join(format!("{:x?}", collection_hash))
+    }
+
+    fn compression() -> DBCompressionType {
+        if APP_CONF.store.kv.database.compress {
+            match APP_CONF.store.kv.database.compression {
+                ConfigStoreKVDatabaseCompression::Zstd => DBCompressionType::Zstd,
+                ConfigStoreKVDatabaseCompression::Lz4 => DBCompressionType::Lz4,
+                ConfigStoreKVDatabaseCompression::Snappy => DBCompressionType::Snappy,
+            }
+        } else {
+            DBCompressionType::None
+        }
+    }
+
+    fn build_store(
+        database: DB,
+        oid_normalization: StoreKVOIDNormalization,
+    ) -> Result<StoreKV, StoreKVError> {
+        let now = SystemTime::now();
+
+        // Notice: whether the database is new is checked before anything gets recorded in it.
+        let is_new = database.iterator(IteratorMode::Start).next().is_none();
+
+        StoreKV::open_key_format(
+            &database,
+            StoreKeyerLayout::configured(),
+            APP_CONF.store.kv.bucket_hash_seed,
+            is_new,
+        )?;
+
+        let stopwords = StoreKV::read_stopwords(&database);
+        let oid_normalization =
+            StoreKV::open_oid_normalization(&database, oid_normalization, is_new)?;
+
+        Ok(StoreKV {
+            database,
+            last_used: Arc::new(RwLock::new(now)),
+            last_flushed: Arc::new(RwLock::new(now)),
+            last_retained: Arc::new(RwLock::new(now)),
+            last_consolidated: Arc::new(RwLock::new(now)),
+            last_trimmed: Arc::new(RwLock::new(now)),
+            last_compacted: Arc::new(RwLock::new(now)),
+            stopwords: RwLock::new(Arc::new(stopwords)),
+            oid_normalization,
+            iid_lock: Mutex::new(()),
+            lock: RwLock::new(false),
+        })
+    }
+
+    fn oid_normalization(pool_key: StoreKVKey) -> StoreKVOIDNormalization {
+        // Notice: overrides are keyed by collection name, while stores are opened from their \
+        //   collection hash (thus, the hash of each overridden collection is compared).
+        let steps = APP_CONF
+            .store
+            .kv
+            .oid_normalization_collections
+            .iter()
+            .find(|(collection, _)| StoreKVKey::from_str(collection) == pool_key)
+            .map(|(_, steps)| steps)
+            .unwrap_or(&APP_CONF.store.kv.oid_normalization);
+
+        StoreKVOIDNormalization::from_config(steps)
+    }
+
+    fn configure(compression: DBCompressionType, compression_level: Option<i32>) -> DBOptions {
+        debug!("configuring key-value database");
+
+        // Make database options
+        let mut db_options = DBOptions::default();
+
+        // Set static options
+        db_options.create_if_missing(true);
+        db_options.set_use_fsync(false);
+        db_options.set_compaction_style(DBCompactionStyle::Level);
+        db_options.set_min_write_buffer_number(1);
+
+        // Set dynamic options
+        db_options.set_compression_type(compression);
+
+        // Set compression level? (if any; otherwise the default level of the codec is used)
+        // Notice: the other compression options are left to their RocksDB default values.
+        if let Some(level) = compression_level {
+            db_options.set_compression_options(COMPRESSION_WINDOW_BITS, level, 0, 0);
+        }
+
+        db_options.set_max_open_files(if let Some(value) = APP_CONF.store.kv.database.max_files {
+            value as i32
+        } else {
+            -1
+        });
+
+        db_options.increase_parallelism(APP_CONF.store.kv.database.parallelism as i32);
+        db_options.set_max_subcompactions(APP_CONF.store.kv.database.max_compactions as u32);
+        db_options.set_max_background_jobs(
+            (APP_CONF.store.kv.database.max_compactions + APP_CONF.store.kv.database.max_flushes)
+                as i32,
+        );
+        db_options.set_write_buffer_size(APP_CONF.store.kv.database.write_buffer * 1024);
+        db_options.set_max_write_buffer_number(APP_CONF.store.kv.database.max_write_buffers as i32);
+
+        // Use the block cache shared by all databases (this bounds its memory usage, whatever \
+        //   the number of opened collections)
+        let mut block_options = BlockBasedOptions::default();
+
+        block_options.set_block_cache(&STORE_BLOCK_CACHE.read().unwrap().cache);
+
+        db_options.set_block_based_table_factory(&block_options);
+
+        db_options
+    }
+}
+
+impl StoreGenericBuilder<StoreKVKey, StoreKV> for StoreKVBuilder {
+    type Error = StoreKVError;
+
+    fn build(pool_key: StoreKVKey) -> Result<StoreKV, StoreKVError> {
+        Self::open(pool_key.collection_hash)
+            .map_err(StoreKVError::Database)
+            .and_then(|db| Self::build_store(db, Self::oid_normalization(pool_key)))
+            .map_err(|err| {
+                error!("failed opening kv: {}", err);
+
+                err
+            })
+    }
+}
+
+impl StoreKV {
+    // Notice: reads given a snapshot see the store as it was when the snapshot was taken, \
+    //   ignoring any write committed since.
+    pub fn get(
+        &self,
+        key: &[u8],
+        snapshot: Option<&StoreKVSnapshot>,
+    ) -> Result<Option<Vec<u8>>, DBError> {
+        match snapshot {
+            Some(snapshot) => self.database.get_opt(key, &Self::read_options(snapshot)),
+            None => self.database.get(key),
+        }
+    }
+
+    pub fn get_pinned(
+        &self,
+        key: &[u8],
+        snapshot: Option<&StoreKVSnapshot>,
+    ) -> Result<Option<DBPinnableSlice<'_>>, DBError> {
+        match snapshot {
+            Some(snapshot) => self
+                .database
+                .get_pinned_opt(key, &Self::read_options(snapshot)),
+            None => self.database.get_pinned(key),
+        }
+    }
+
+    pub fn multi_get(
+        &self,
+        keys: &[StoreKeyerKey],
+        snapshot: Option<&StoreKVSnapshot>,
+    ) -> Vec<Result<Option<Vec<u8>>, DBError>> {
+        match snapshot {
+            Some(snapshot) => self
+                .database
+                .multi_get_opt(keys, &Self::read_options(snapshot)),
+            None => self.database.multi_get(keys),
+        }
+    }
+
+    pub fn scan_prefix<'p, P: AsRef<[u8]> + 'p>(
+        &'p self,
+        prefix: P,
+        snapshot: Option<&StoreKVSnapshot>,
+    ) -> impl Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'p {
+        let mode = IteratorMode::From(prefix.as_ref(), Direction::Forward);
+
+        match snapshot {
+            Some(snapshot) => self
+                .database
+                .iterator_opt(mode, Self::read_options(snapshot)),
+            None => self.database.iterator(mode),
+        }
+        .filter_map(|item| item.ok())
+        .take_while(move |(key, _)| key.starts_with(prefix.as_ref()))
+    }
+
+    pub fn snapshot(&self) -> StoreKVSnapshot<'_> {
+        self.database.snapshot()
+    }
+
+    pub fn commit_pending(&self, pending: StoreKVPending) -> Result<(), StoreKVError> {
+        let batch = pending.batch.into_inner();
+
+        debug!("store commit pending batch: {} operations", batch.len());
+
+        self.do_write(batch).map_err(StoreKVError::Database)
+    }
+
+    fn read_options(snapshot: &StoreKVSnapshot) -> ReadOptions {
+        let mut read_options = ReadOptions::default();
+
+        read_options.set_snapshot(snapshot);
+
+        read_options
+    }
+
+    pub fn put(&self, key: &[u8], data: &[u8]) -> Result<(), DBError> {
+        let mut batch = WriteBatch::default();
+
+        batch.put(key, data);
+
+        self.do_write(batch)
+    }
+
+    pub fn delete(&self, key: &[u8]) -> Result<(), DBError> {
+        let mut batch = WriteBatch::default();
+
+        batch.delete(key);
+
+        self.do_write(batch)
+    }
+
+    pub fn write_stall(&self) -> Option<StoreKVWriteStall> {
+        // Notice: 'is-write-stopped' is set when writes are fully stopped, while the actual \
+        //   delayed write rate is non-zero when writes are being slowed down.
+        Self::write_stall_from_properties(
+            self.database
+                .property_int_value("rocksdb.is-write-stopped")
+                .unwrap_or(None),
+            self.database
+                .property_int_value("rocksdb.actual-delayed-write-rate")
+                .unwrap_or(None),
+        )
+    }
+
+    pub fn stopwords(&self) -> Arc<HashSet<String>> {
+        self.stopwords.read().unwrap().clone()
+    }
+
+    pub fn set_stopwords(&self, stopwords: HashSet<String>) -> Result<(), DBError> {
+        let store_key = StoreKeyerBuilder::collection_to_stopwords();
+
+        // Store stop-words as a list of newline-separated words (or remove them, if empty)
+        if stopwords.is_empty() {
+            self.database.delete(store_key.as_bytes())?;
+        } else {
+            let mut stopwords_sorted: Vec<&str> =
+                stopwords.iter().map(|word| word.as_str()).collect();
+
+            stopwords_sorted.sort_unstable();
+
+            self.database
+                .put(store_key.as_bytes(), stopwords_sorted.join("\n").as_bytes())?;
+        }
+
+        *self.stopwords.write().unwrap() = Arc::new(stopwords);
+
+        Ok(())
+    }
+
+    fn read_stopwords(database: &DB) -> HashSet<String> {
+        let store_key = StoreKeyerBuilder::collection_to_stopwords();
+
+        match database.get(store_key.as_bytes()) {
+            Ok(Some(value)) => str::from_utf8(&value)
+                .map(|value| value.split('\n').map(|word| word.to_string()).collect())
+                .unwrap_or_else(|_| {
+                    error!("got invalid collection stopwords: {}", store_key);
+
+                    HashSet::new()
+                }),
+            Ok(None) => HashSet::new(),
+            Err(err) => {
+                error!(
+                    "error getting collection stopwords: {} with trace: {}",
+                    store_key, err
+                );
+
+                HashSet::new()
+            }
+        }
+    }
+
+    pub fn oid_normalization(&self) -> StoreKVOIDNormalization {
+        self.oid_normalization
+    }
+
+    pub fn name(&self) -> Option<String> {
+        let store_key = StoreKeyerBuilder::collection_to_name();
+
+        match self.database.get(store_key.as_bytes()) {
+            Ok(Some(value)) => String::from_utf8(value).ok(),
+            Ok(None) => None,
+            Err(err) => {
+                error!(
+                    "error getting collection name: {} with trace: {}",
+                    store_key, err
+                );
+
+                None
+            }
+        }
+    }
+
+    fn record_name(&self, collection: &str) -> Result<(), DBError> {
+        // Notice: the collection name is only written if it changed, as this happens every \
+        //   time the database gets opened.
+        if self.name().as_deref() != Some(collection) {
+            self.database.put(
+                StoreKeyerBuilder::collection_to_name().as_bytes(),
+                collection.as_bytes(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn backup(&self, destination: &Path) -> Result<(), StoreKVError> {
+        debug!(
+            "creating key-value database checkpoint at path: {:?}",
+            destination
+        );
+
+        // Notice: a checkpoint is a consistent point-in-time copy of the database (made of \
+        //   hard links to database files where possible), that can be created while writes \
+        //   are ongoing. It gets refused if the destination path already exists.
+        DBCheckpoint::new(&self.database)?.create_checkpoint(destination)?;
+
+        info!(
+            "created key-value database checkpoint at path: {:?}",
+            destination
+        );
+
+        Ok(())
+    }
+
+    fn open_key_format(
+        database: &DB,
+        layout: StoreKeyerLayout,
+        seed: u32,
+        is_new: bool,
+    ) -> Result<(), StoreKVError> {
+        let store_key = StoreKeyerBuilder::collection_to_key_format();
+
+        // Notice: databases created before key formats were recorded use narrow keys.
+        let (recorded, is_recorded) = match database.get(store_key)? {
+            Some(value) => (Self::decode_key_format(&value), true),
+            None if is_new => (Some((layout, seed)), false),
+            None => (Some((StoreKeyerLayout::Narrow, 0)), false),
+        };
+
+        // Important: refuse to open a database with keys in another format, as its keys would \
+        //   not be found anymore (reads would return nothing, and writes would be mixed up \
+        //   with existing keys).
+        if recorded != Some((layout, seed)) {
+            error!(
+                "refusing to open kv store with key format and seed: {:?} (configured: {:?}), \
+                 migrate it first with: sonic migrate",
+                recorded,
+                (layout, seed)
+            );
+
+            return Err(StoreKVError::Incompatible);
+        }
+
+        if !is_recorded {
+            database.put(store_key, Self::encode_key_format(layout, seed))?;
+        }
+
+        Ok(())
+    }
+
+    fn encode_key_format(layout: StoreKeyerLayout, seed: u32) -> Vec<u8> {
+        // Key format: [version<1B>] (default seed) or [version<1B> | seed<4B>] (custom seed)
+        let mut value = vec![layout.to_version()];
+
+        if seed != 0 {
+            value.extend_from_slice(&seed.to_le_bytes());
+        }
+
+        value
+    }
+
+    fn decode_key_format(value: &[u8]) -> Option<(StoreKeyerLayout, u32)> {
+        match value {
+            [version] => StoreKeyerLayout::from_version(*version).map(|layout| (layout, 0)),
+            [version, seed @ ..] if seed.len() == 4 => StoreKeyerLayout::from_version(*version)
+                .map(|layout| (layout, LittleEndian::read_u32(seed))),
+            _ => None,
+        }
+    }
+
+    fn open_oid_normalization(
+        database: &DB,
+        oid_normalization: StoreKVOIDNormalization,
+        is_new: bool,
+    ) -> Result<StoreKVOIDNormalization, StoreKVError> {
+        let store_key = StoreKeyerBuilder::collection_to_oid_normalization();
+
+        // Notice: collections created before normalizations were recorded keep their OIDs \
+        //   as-is, as those got stored without any normalization.
+        let (recorded, is_recorded) = match database.get(store_key.as_bytes())? {
+            Some(value) => (
+                str::from_utf8(&value)
+                    .ok()
+                    .and_then(StoreKVOIDNormalization::decode),
+                true,
+            ),
+            None if is_new => (Some(oid_normalization), false),
+            None => (Some(StoreKVOIDNormalization::default()), false),
+        };
+
+        // Important: refuse to open a collection with another normalization, as stored OIDs \
+        //   would not be found anymore once looked up with a different normalization.
+        if recorded != Some(oid_normalization) {
+            error!(
+                "refusing to open kv store with oid normalization: ({}) (configured: ({}))",
+                recorded
+                    .map(|recorded| recorded.encode())
+                    .unwrap_or_else(|| "unknown".to_string()),
+                oid_normalization.encode()
+            );
+
+            return Err(StoreKVError::Incompatible);
+        }
+
+        if !is_recorded {
+            database.put(store_key.as_bytes(), oid_normalization.encode().as_bytes())?;
+        }
+
+        Ok(oid_normalization)
+    }
+
+    pub fn memtable_size(&self) -> Option<u64> {
+        self.int_property("rocksdb.cur-size-all-mem-tables")
+    }
+
+    pub fn stats(&self) -> StoreKVStats {
+        StoreKVStats {
+            estimated_keys: self.int_property("rocksdb.estimate-num-keys"),
+            live_files_size: self.int_property("rocksdb.live-sst-files-size"),
+            pending_compaction_bytes: self
+                .int_property("rocksdb.estimate-pending-compaction-bytes"),
+            memtable_size: self.memtable_size(),
+        }
+    }
+
+    fn int_property(&self, name: &str) -> Option<u64> {
+        // Notice: a property that cannot be read (or parsed) is reported as missing, as to \
+        //   not fail reading the other properties.
+        self.database.property_int_value(name).unwrap_or(None)
+    }
+
+    fn write_stall_from_properties(
+        is_write_stopped: Option<u64>,
+        delayed_write_rate: Option<u64>,
+    ) -> Option<StoreKVWriteStall> {
+        match (is_write_stopped, delayed_write_rate) {
+            (Some(is_write_stopped), _) if is_write_stopped > 0 => Some(StoreKVWriteStall::Stopped),
+            (_, Some(delayed_write_rate)) if delayed_write_rate > 0 => {
+                Some(StoreKVWriteStall::Delayed)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn compact(&self) {
+        // Compact the whole key range (unlike recompressions, the bottommost level only gets \
+        //   compacted if needed)
+        self.database.compact_range(None::<&[u8]>, None::<&[u8]>);
+    }
+
+    pub fn flush(&self) -> Result<(), DBError> {
+        // Generate flush options
+        let mut flush_options = FlushOptions::default();
+
+        flush_options.set_wait(true);
+
+        // Perform flush (in blocking mode)
+        self.database.flush_opt(&flush_options)?;
+
+        // Sync the WAL to disk (as writes are not fsync-ed, anything written after the \
+        //   memtables got flushed would otherwise only live in the OS page cache)
+        if APP_CONF.store.kv.database.write_ahead_log {
+            self.database.flush_wal(true)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn do_write(&self, batch: WriteBatch) -> Result<(), DBError> {
+        // Configure this write
+        let mut write_options = WriteOptions::default();
+
+        // WAL disabled?
+        if !APP_CONF.store.kv.database.write_ahead_log {
+            debug!("ignoring wal for kv write");
+
+            write_options.disable_wal(true);
+        } else {
+            debug!("using wal for kv write");
+
+            write_options.disable_wal(false);
+        }
+
+        // Commit this write
+        self.database.write_opt(batch, &write_options)
+    }
+}
+
+impl StoreGeneric for StoreKV {
+    fn ref_last_used(&self) -> &RwLock<SystemTime> {
+        &self.last_used
+    }
+}
+
+impl StoreKVActionBuilder {
+    pub fn access(bucket: StoreItemPart, store: Option<StoreKVBox>) -> StoreKVAction {
+        Self::build(bucket, store)
+    }
+
+    pub fn erase<'a, T: Into<&'a str>>(
+        collection: T,
+        bucket: Option<T>,
+    ) -> Result<u32, StoreKVError> {
+        Self::dispatch_erase("kv", collection, bucket)
+    }
+
+    fn build(bucket: StoreItemPart, store: Option<StoreKVBox>) -> StoreKVAction {
+        StoreKVAction {
+            store,
+            bucket,
+            snapshot: None,
+            pending: None,
+        }
+    }
+}
+
+impl StoreGenericActionBuilder for StoreKVActionBuilder {
+    type Error = StoreKVError;
+
+    fn proceed_erase_collection(collection_str: &str) -> Result<u32, StoreKVError> {
+        let collection_atom = StoreKeyerHasher::to_compact(collection_str);
+        let collection_path = StoreKVBuilder::path(collection_atom);
+
+        // Force a KV store close
+        StoreKVBuilder::close(collection_atom);
+
+        if collection_path.exists() {
+            debug!(
+                "kv collection store exists, erasing: {}/* at path: {:?}",
+                collection_str, &collection_path
+            );
+
+            // Remove KV store storage from filesystem
+            fs::remove_dir_all(&collection_path)?;
+
+            // Remove the collection from the registry of collection names
+            StoreKVPool::unregister(collection_str)?;
+
+            debug!("done with kv collection erasure");
+
+            Ok(1)
+        } else {
+            debug!(
+                "kv collection store does not exist, consider already erased: {}/* at path: {:?}",
+                collection_str, &collection_path
+            );
+
+            Ok(0)
+        }
+    }
+
+    fn proceed_erase_bucket(_collection: &str, _bucket: &str) -> Result<u32, StoreKVError> {
+        // This one is not implemented, as we need to acquire the collection; which would cause \
+        //   a party-killer dead-lock.
+        Err(StoreKVError::Invalid)
+    }
+}
+
+impl<'a> StoreKVAction<'a> {
+    pub fn snapshot(&self) -> Option<StoreKVSnapshot<'_>> {
+        self.store.as_ref().map(|store| store.snapshot())
+    }
+
+    pub fn with_snapshot<'s>(
+        &'s self,
+        snapshot: Option<&'s StoreKVSnapshot<'s>>,
+    ) -> StoreKVAction<'s> {
+        // Read the bucket from a snapshot? (all reads from the returned action then see the \
+        //   same state of the store; the snapshot is released once the caller drops it, thus it \
+        //   is scoped to the query)
+        // Notice: as writes would be based on the bucket as it was when the snapshot was taken, \
+        //   writes from the returned action are refused (see 'check_writable').
+        StoreKVAction {
+            store: self.store.clone(),
+            bucket: self.bucket,
+            snapshot: snapshot.or(self.snapshot),
+            pending: self.pending,
+        }
+    }
+
+    pub fn with_pending<'s>(&'s self, pending: &'s StoreKVPending) -> StoreKVAction<'s> {
+        // Queue committed batches to pending writes? (batches committed from the returned \
+        //   action are not written to the database, but queued to pending writes, which reads \
+        //   from the returned action see; pending writes then get written all at once)
+        // Notice: pinned reads, batched reads and scans only see the database, thus the \
+        //   returned action must only be used for operations that read single values (eg. \
+        //   pushes).
+        StoreKVAction {
+            store: self.store.clone(),
+            bucket: self.bucket,
+            snapshot: self.snapshot,
+            pending: Some(pending),
+        }
+    }
+
+    fn check_writable(&self) -> Result<(), StoreKVError> {
+        if self.snapshot.is_some() {
+            error!(
+                "refusing to write to bucket: {} from a snapshot",
+                self.bucket.as_str()
+            );
+
+            Err(StoreKVError::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn batch(&self) -> StoreKVBatch<'a, '_> {
+        // Notice: all writes making up an operation (eg. a push, a pop or a move) are queued to \
+        //   a single batch, as RocksDB commits a batch atomically. Committing them one by one \
+        //   would leave objects half-indexed upon failure.
+        StoreKVBatch {
+            action: self,
+            batch: WriteBatch::default(),
+            is_malformed: false,
+            terms_counted: (0, 0),
+            objects_counted: (0, 0),
+            objects_truncated: HashMap::new(),
+            writes: self.pending.map(|_| Vec::new()),
+        }
+    }
+
+    pub fn normalize_oid<'o>(&self, oid: &'o str) -> Cow<'o, str> {
+        match self.store {
+            Some(ref store) => store.oid_normalization.normalize(oid),
+            None => Cow::Borrowed(oid),
+        }
+    }
+
+    pub fn check_write_stall(&self) -> Result<(), StoreKVWriteStall> {
+        if let Some(ref store) = self.store {
+            if let Some(stall) = store.write_stall() {
+                return Self::handle_write_stall(stall, &APP_CONF.store.kv.database.write_stall);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_write_stall(
+        stall: StoreKVWriteStall,
+        mode: &ConfigStoreKVDatabaseWriteStall,
+    ) -> Result<(), StoreKVWriteStall> {
+        STORE_WRITE_STALLS.fetch_add(1, AtomicOrdering::Relaxed);
+
+        match mode {
+            ConfigStoreKVDatabaseWriteStall::Block => {
+                warn!("kv store writes stalled ({:?}), blocking write", stall);
+
+                Ok(())
+            }
+            ConfigStoreKVDatabaseWriteStall::Reject => {
+                warn!("kv store writes stalled ({:?}), rejecting write", stall);
+
+                Err(stall)
+            }
+        }
+    }
+
+    /// Meta-to-Value mapper
+    ///
+    /// [IDX=0] ((meta)) ~> ((value))
+    pub fn get_meta_to_value(
+        &self,
+        meta: StoreMetaKey,
+    ) -> Result<Option<StoreMetaValue>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::meta_to_value(self.bucket.as_str(), &meta);
+
+            debug!("store get meta-to-value: {}", store_key);
+
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => {
+                    debug!("got meta-to-value: {}", store_key);
+
+                    Ok(if let Ok(value) = str::from_utf8(&value) {
+                        match meta {
+                            StoreMetaKey::IIDIncr => value
+                                .parse::<StoreObjectIID>()
+                                .ok()
+                                .map(StoreMetaValue::IIDIncr)
+                                .or(None),
+                            StoreMetaKey::OIDCount => value
+                                .parse::<u32>()
+                                .ok()
+                                .map(StoreMetaValue::OIDCount)
+                                .or(None),
+                            StoreMetaKey::TermCount => value
+                                .parse::<u32>()
+                                .ok()
+                                .map(StoreMetaValue::TermCount)
+                                .or(None),
+                            StoreMetaKey::Retention => Self::decode_retention(value)
+                                .map(|(retention, _)| StoreMetaValue::Retention(retention)),
+                            StoreMetaKey::IDFCount => Self::decode_idf_count(value)
+                                .map(|(idf_count, _)| StoreMetaValue::IDFCount(idf_count)),
+                            StoreMetaKey::Immutable => {
+                                value.parse::<bool>().ok().map(StoreMetaValue::Immutable)
+                            }
+                            StoreMetaKey::BucketName => {
+                                Some(StoreMetaValue::BucketName(value.to_string()))
+                            }
+                        }
+                    } else {
+                        None
+                    })
+                }
+                Ok(None) => {
+                    debug!("no meta-to-value found: {}", store_key);
+
+                    Ok(None)
+                }
+                Err(err) => {
+                    error!(
+                        "error getting meta-to-value: {} with trace: {}",
+                        store_key, err
+                    );
+
+                    Err(err.into())
+                }
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_meta_to_value(
+        &self,
+        meta: StoreMetaKey,
+        value: StoreMetaValue,
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_meta_to_value(meta, value);
+
+        batch.commit()
+    }
+
+    pub fn delete_meta_to_value(&self, meta: StoreMetaKey) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_meta_to_value(meta);
+
+        batch.commit()
+    }
+
+    /// Term-to-IIDs mapper
+    ///
+    /// [IDX=1] ((term)) ~> [((iid))]
+    pub fn get_term_to_iids(
+        &self,
+        term_hashed: StoreTermHashed,
+    ) -> Result<Option<Vec<StoreObjectIID>>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::term_to_iids(self.bucket.as_str(), term_hashed);
+
+            debug!("store get term-to-iids: {}", store_key);
+
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => {
+                    debug!(
+                        "got term-to-iids: {} with encoded value: {:?}",
+                        store_key, &*value
+                    );
+
+                    StoreKVTermPostings::decode(&value)
+                        .map(StoreKVTermPostings::into_iids)
+                        .map(|value_decoded| {
+                            debug!(
+                                "got term-to-iids: {} with decoded value: {:?}",
+                                store_key, &value_decoded
+                            );
+
+                            Some(value_decoded)
+                        })
+                }
+                Ok(None) => {
+                    debug!("no term-to-iids found: {}", store_key);
+
+                    Ok(None)
+                }
+                Err(err) => {
+                    error!(
+                        "error getting term-to-iids: {} with trace: {}",
+                        store_key, err
+                    );
+
+                    Err(err.into())
+                }
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn get_term_to_iids_pinned(
+        &self,
+        term_hashed: StoreTermHashed,
+    ) -> Result<Option<StoreKVIIDList<'_>>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::term_to_iids(self.bucket.as_str(), term_hashed);
+
+            debug!("store get term-to-iids pinned: {}", store_key);
+
+            // Notice: the value is not copied out of the database, decoding is deferred to \
+            //   the iteration of the returned list (the list must thus be dropped early).
+            match store.get_pinned(&store_key.as_bytes(), self.snapshot) {
+                Ok(Some(value)) => {
+                    let positioned = value.len() % 4 == 1 && value[0] == TERM_FORMAT_POSITIONS;
+
+                    if positioned || value.len() % 4 == 0 {
+                        Ok(Some(StoreKVIIDList { value, positioned }))
+                    } else {
+                        error!("got invalid term-to-iids pinned value: {}", store_key);
+
+                        Err(StoreKVError::Serialization)
+                    }
+                }
+                Ok(None) => {
+                    debug!("no term-to-iids pinned found: {}", store_key);
+
+                    Ok(None)
+                }
+                Err(err) => {
+                    error!(
+                        "error getting term-to-iids pinned: {} with trace: {}",
+                        store_key, err
+                    );
+
+                    Err(err.into())
+                }
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn get_terms_to_iids(
+        &self,
+        terms_hashed: &[StoreTermHashed],
+    ) -> Result<Vec<Option<Vec<StoreObjectIID>>>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_keys: Vec<StoreKeyerKey> = terms_hashed
+                .iter()
+                .map(|term_hashed| {
+                    StoreKeyerBuilder::term_to_iids(self.bucket.as_str(), *term_hashed).as_bytes()
+                })
+                .collect();
+
+            debug!("store get terms-to-iids: {:?}", terms_hashed);
+
+            // Read all terms at once, then decode each value
+            // Notice: if a term could not be read, fall back to reading it on its own.
+            store
+                .multi_get(&store_keys, self.snapshot)
+                .into_iter()
+                .zip(terms_hashed)
+                .map(|(value, term_hashed)| match value {
+                    Ok(Some(value)) => StoreKVTermPostings::decode(&value)
+                        .map(StoreKVTermPostings::into_iids)
+                        .map(Some),
+                    Ok(None) => Ok(None),
+                    Err(err) => {
+                        warn!(
+                            "error getting terms-to-iids for: {} with trace: {}, retrying",
+                            term_hashed, err
+                        );
+
+                        self.get_term_to_iids(*term_hashed)
+                    }
+                })
+                .collect()
+        } else {
+            Ok(vec![None; terms_hashed.len()])
+        }
+    }
+
+    pub fn set_term_to_iids(
+        &self,
+        term_hashed: StoreTermHashed,
+        iids: &[StoreObjectIID],
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_term_to_iids(term_hashed, iids);
+
+        batch.commit()
+    }
+
+    pub fn get_term_to_postings(
+        &self,
+        term_hashed: StoreTermHashed,
+    ) -> Result<Option<StoreKVTermPostings>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::term_to_iids(self.bucket.as_str(), term_hashed);
+
+            debug!("store get term-to-postings: {}", store_key);
+
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => StoreKVTermPostings::decode(&value).map(Some),
+                Ok(None) => Ok(None),
+                Err(err) => {
+                    error!(
+                        "error getting term-to-postings: {} with trace: {}",
+                        store_key, err
+                    );
+
+                    Err(err.into())
+                }
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_term_to_postings(
+        &self,
+        term_hashed: StoreTermHashed,
+        postings: &StoreKVTermPostings,
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_term_to_postings(term_hashed, postings);
+
+        batch.commit()
+    }
+
+    pub fn delete_term_to_iids(&self, term_hashed: StoreTermHashed) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_term_to_iids(term_hashed);
+
+        // Account for removed term in bucket term count (if it was there)
+        if self.get_term_to_iids(term_hashed)?.is_some() {
+            batch.count_terms(0, 1);
+        }
+
+        batch.commit()
+    }
+
+    pub fn scan_terms(&self) -> impl Iterator<Item = (StoreTermHashed, StoreKVTermPostings)> + '_ {
+        // Notice: terms are stored hashed, thus terms cannot be scanned by text prefix (this is \
+        //   what the FST graph is for).
+        self.scan_index(
+            StoreKeyerBuilder::term_to_iids(self.bucket.as_str(), 0).as_prefix(),
+            StoreKVTermPostings::decode,
+        )
+    }
+
+    /// Term-to-IDF mapper
+    ///
+    /// [IDX=11] ((term)) ~> ((idf))
+    pub fn get_term_to_idf(
+        &self,
+        term_hashed: StoreTermHashed,
+    ) -> Result<Option<StoreTermIDF>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::term_to_idf(self.bucket.as_str(), term_hashed);
+
+            debug!("store get term-to-idf: {}", store_key);
+
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => Self::decode_f32(&value).map(Some),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn delete_term_to_idf(&self, term_hashed: StoreTermHashed) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_term_to_idf(term_hashed);
+
+        batch.commit()
+    }
+
+    /// Term-to-Size mapper
+    ///
+    /// [IDX=12] ((term)) ~> ((size))
+    pub fn get_term_to_size(
+        &self,
+        term_hashed: StoreTermHashed,
+    ) -> Result<Option<u32>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::term_to_size(self.bucket.as_str(), term_hashed);
+
+            debug!("store get term-to-size: {}", store_key);
+
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => Self::decode_u32(&value).map(Some),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn get_terms_size(&self, terms_hashed: &[StoreTermHashed]) -> Vec<usize> {
+        // Notice: terms with no stored size (eg. terms pushed before sizes were stored) get \
+        //   their size counted from their IID list.
+        terms_hashed
+            .iter()
+            .map(|term_hashed| match self.get_term_to_size(*term_hashed) {
+                Ok(Some(size)) => size as usize,
+                _ => self
+                    .get_term_to_iids_pinned(*term_hashed)
+                    .unwrap_or(None)
+                    .map(|term_iids| term_iids.count())
+                    .unwrap_or(0),
+            })
+            .collect()
+    }
+
+    pub fn get_idf_count(&self) -> Result<Option<u32>, StoreKVError> {
+        match self.get_meta_to_value(StoreMetaKey::IDFCount)? {
+            Some(StoreMetaValue::IDFCount(idf_count)) => Ok(Some(idf_count)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn get_terms_idf(&self, terms_hashed: &[StoreTermHashed]) -> Vec<StoreTermIDF> {
+        let oid_count = self.get_oid_count().unwrap_or(0);
+
+        // Use cached IDF weights? (only if they were computed for a similar bucket)
+        let is_cached = match self.get_idf_count() {
+            Ok(Some(idf_count)) => !Self::is_idf_stale(idf_count, oid_count),
+            _ => false,
+        };
+
+        let mut count_hits = 0;
+
+        // Notice: terms with no cached weight (eg. terms pushed after the last refresh) get \
+        //   their weight computed from the number of objects they are linked to.
+        let terms_idf = terms_hashed
+            .iter()
+            .map(|term_hashed| {
+                let cached_idf = if is_cached {
+                    self.get_term_to_idf(*term_hashed).unwrap_or(None)
+                } else {
+                    None
+                };
+
+                if cached_idf.is_some() {
+                    count_hits += 1;
+                }
+
+                cached_idf.unwrap_or_else(|| {
+                    let term_objects = self
+                        .get_term_to_iids_pinned(*term_hashed)
+                        .unwrap_or(None)
+                        .map(|term_iids| term_iids.count())
+                        .unwrap_or(0);
+
+                    Self::compute_idf(term_objects, oid_count)
+                })
+            })
+            .collect();
+
+        STORE_IDF_CACHE_HITS.fetch_add(count_hits, AtomicOrdering::Relaxed);
+        STORE_IDF_CACHE_MISSES.fetch_add(
+            terms_hashed.len() as u64 - count_hits,
+            AtomicOrdering::Relaxed,
+        );
+
+        terms_idf
+    }
+
+    pub fn is_immutable(&self) -> Result<bool, StoreKVError> {
+        match self.get_meta_to_value(StoreMetaKey::Immutable)? {
+            Some(StoreMetaValue::Immutable(immutable)) => Ok(immutable),
+            _ => Ok(false),
+        }
+    }
+
+    pub fn set_immutable(&self, immutable: bool) -> Result<(), StoreKVError> {
+        if immutable {
+            self.set_meta_to_value(StoreMetaKey::Immutable, StoreMetaValue::Immutable(true))
+        } else {
+            self.delete_meta_to_value(StoreMetaKey::Immutable)
+        }
+    }
+
+    pub fn check_mutable(&self) -> Result<(), StoreKVError> {
+        // Notice: a bucket whose state cannot be read is considered immutable, as to never \
+        //   alter a bucket that might be immutable.
+        match self.is_immutable() {
+            Ok(false) => Ok(()),
+            Ok(true) => {
+                info!(
+                    "rejecting write to immutable bucket: {}",
+                    self.bucket.as_str()
+                );
+
+                Err(StoreKVError::Immutable)
+            }
+            Err(err) => {
+                error!(
+                    "failed reading immutable state of bucket: {}, rejecting write",
+                    self.bucket.as_str()
+                );
+
+                Err(err)
+            }
+        }
+    }
+
+    pub fn check_bucket(&self) -> Result<(), StoreKVError> {
+        // Notice: the bucket name gets compared with the name recorded under the bucket hash \
+        //   (if enabled), as another bucket with the same hash would share all its keys. Buckets \
+        //   with no recorded name yet (eg. empty buckets) are not checked.
+        if APP_CONF.store.kv.bucket_hash_guard {
+            self.check_bucket_name()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_bucket_name(&self) -> Result<(), StoreKVError> {
+        match self.get_meta_to_value(StoreMetaKey::BucketName) {
+            Ok(Some(StoreMetaValue::BucketName(name))) if name != self.bucket.as_str() => {
+                error!(
+                    "rejecting access to bucket: {} colliding with bucket: {}",
+                    self.bucket.as_str(),
+                    name
+                );
+
+                Err(StoreKVError::Collision)
+            }
+            Ok(_) => Ok(()),
+            Err(err) => {
+                error!(
+                    "failed reading recorded name of bucket: {}, rejecting access",
+                    self.bucket.as_str()
+                );
+
+                Err(err)
+            }
+        }
+    }
+
+    pub fn get_term_count(&self) -> Result<u32, StoreKVError> {
+        match self.get_meta_to_value(StoreMetaKey::TermCount)? {
+            Some(StoreMetaValue::TermCount(term_count)) => Ok(term_count),
+            _ => Ok(0),
+        }
+    }
+
+    /// OID-to-IID mapper
+    ///
+    /// [IDX=2] ((oid)) ~> ((iid))
+    pub fn get_oid_to_iid(
+        &self,
+        oid: StoreObjectOID<'a>,
+    ) -> Result<Option<StoreObjectIID>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let oid = &*store.oid_normalization.normalize(oid);
+
+            let store_key = StoreKeyerBuilder::oid_to_iid(self.bucket.as_str(), oid);
+
+            debug!("store get oid-to-iid: {}", store_key);
+
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => {
+                    debug!(
+                        "got oid-to-iid: {} with encoded value: {:?}",
+                        store_key, &*value
+                    );
+
+                    Self::decode_u32(&*value).map(|value_decoded| {
+                        debug!(
+                            "got oid-to-iid: {} with decoded value: {:?}",
+                            store_key, &value_decoded
+                        );
+
+                        Some(value_decoded)
+                    })
+                }
+                Ok(None) => {
+                    debug!("no oid-to-iid found: {}", store_key);
+
+                    Ok(None)
+                }
+                Err(err) => {
+                    error!(
+                        "error getting oid-to-iid: {} with trace: {}",
+                        store_key, err
+                    );
+
+                    Err(err.into())
+                }
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_oid_to_iid(
+        &self,
+        oid: StoreObjectOID<'a>,
+        iid: StoreObjectIID,
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_oid_to_iid(oid, iid);
+
+        batch.commit()
+    }
+
+    pub fn delete_oid_to_iid(&self, oid: StoreObjectOID<'a>) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_oid_to_iid(oid);
+
+        batch.commit()
+    }
+
+    /// IID-to-OID mapper
+    ///
+    /// [IDX=3] ((iid)) ~> ((oid))
+    pub fn get_iid_to_oid(&self, iid: StoreObjectIID) -> Result<Option<String>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::iid_to_oid(self.bucket.as_str(), iid);
+
+            debug!("store get iid-to-oid: {}", store_key);
+
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => Ok(str::from_utf8(&value).ok().map(|value| value.to_string())),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_iid_to_oid(
+        &self,
+        iid: StoreObjectIID,
+        oid: StoreObjectOID<'a>,
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_iid_to_oid(iid, oid);
+
+        batch.commit()
+    }
+
+    pub fn delete_iid_to_oid(&self, iid: StoreObjectIID) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_iid_to_oid(iid);
+
+        batch.commit()
+    }
+
+    pub fn scan_iid_to_oid(&self) -> impl Iterator<Item = (StoreObjectIID, String)> + '_ {
+        self.scan_index(
+            StoreKeyerBuilder::iid_to_oid(self.bucket.as_str(), 0).as_prefix(),
+            |value| {
+                str::from_utf8(value)
+                    .map(|oid| oid.to_string())
+                    .or(Err(StoreKVError::Serialization))
+            },
+        )
+    }
+
+    /// IID-to-Terms mapper
+    ///
+    /// [IDX=4] ((iid)) ~> [((term))]
+    pub fn get_iid_to_terms(
+        &self,
+        iid: StoreObjectIID,
+    ) -> Result<Option<Vec<StoreTermHashed>>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::iid_to_terms(self.bucket.as_str(), iid);
+
+            debug!("store get iid-to-terms: {}", store_key);
+
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => {
+                    debug!(
+                        "got iid-to-terms: {} with encoded value: {:?}",
+                        store_key, &*value
+                    );
+
+                    Self::decode_u32_list(&*value).map(|value_decoded| {
+                        debug!(
+                            "got iid-to-terms: {} with decoded value: {:?}",
+                            store_key, &value_decoded
+                        );
+
+                        if !value_decoded.is_empty() {
+                            Some(value_decoded)
+                        } else {
+                            None
+                        }
+                    })
+                }
+                Ok(None) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_iid_to_terms(
+        &self,
+        iid: StoreObjectIID,
+        terms_hashed: &[StoreTermHashed],
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_iid_to_terms(iid, terms_hashed);
+
+        batch.commit()
+    }
+
+    pub fn delete_iid_to_terms(&self, iid: StoreObjectIID) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_iid_to_terms(iid);
+
+        batch.commit()
+    }
+
+    pub fn scan_iid_to_terms(
+        &self,
+    ) -> impl Iterator<Item = (StoreObjectIID, Vec<StoreTermHashed>)> + '_ {
+        // Notice: terms are stored hashed, thus scanned terms are yielded as stored (the \
+        //   original term text cannot be reconstructed from its hash).
+        self.scan_index(
+            StoreKeyerBuilder::iid_to_terms(self.bucket.as_str(), 0).as_prefix(),
+            Self::decode_u32_list,
+        )
+    }
+
+    /// IID-to-Boost mapper
+    ///
+    /// [IDX=5] ((iid)) ~> ((boost))
+    pub fn get_iid_to_boost(&self, iid: StoreObjectIID) -> Result<StoreObjectBoost, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::iid_to_boost(self.bucket.as_str(), iid);
+
+            debug!("store get iid-to-boost: {}", store_key);
+
+            // Notice: objects are not boosted by default, hence the default boost when no \
+            //   boost is stored for the object.
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => Self::decode_f32(&value),
+                Ok(None) => Ok(STORE_OBJECT_BOOST_DEFAULT),
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            Ok(STORE_OBJECT_BOOST_DEFAULT)
+        }
+    }
+
+    pub fn set_iid_to_boost(
+        &self,
+        iid: StoreObjectIID,
+        boost: StoreObjectBoost,
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_iid_to_boost(iid, boost);
+
+        batch.commit()
+    }
+
+    pub fn delete_iid_to_boost(&self, iid: StoreObjectIID) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_iid_to_boost(iid);
+
+        batch.commit()
+    }
+
+    pub fn get_iids_to_ranking(
+        &self,
+        iids: &[StoreObjectIID],
+        with_terms: bool,
+    ) -> Result<Vec<StoreKVIIDRanking>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let bucket = self.bucket.as_str();
+
+            // Read the boost, weights (and terms, if requested) of all IIDs at once
+            let keys_per_iid = if with_terms { 3 } else { 2 };
+
+            let store_keys: Vec<StoreKeyerKey> = iids
+                .iter()
+                .flat_map(|iid| {
+                    let mut store_keys = vec![
+                        StoreKeyerBuilder::iid_to_boost(bucket, *iid).as_bytes(),
+                        StoreKeyerBuilder::iid_to_weights(bucket, *iid).as_bytes(),
+                    ];
+
+                    if with_terms {
+                        store_keys.push(StoreKeyerBuilder::iid_to_terms(bucket, *iid).as_bytes());
+                    }
+
+                    store_keys
+                })
+                .collect();
+
+            debug!("store get iids-to-ranking: {:?}", iids);
+
+            // Decode each value
+            // Notice: if a value could not be read, fall back to reading it on its own.
+            store
+                .multi_get(&store_keys, self.snapshot)
+                .chunks_exact(keys_per_iid)
+                .zip(iids)
+                .map(|(values, iid)| {
+                    let boost = match &values[0] {
+                        Ok(Some(value)) => Self::decode_f32(value)?,
+                        Ok(None) => STORE_OBJECT_BOOST_DEFAULT,
+                        Err(_) => self.get_iid_to_boost(*iid)?,
+                    };
+
+                    let weights = match &values[1] {
+                        Ok(Some(value)) => Self::decode_weights(value)?,
+                        Ok(None) => Vec::new(),
+                        Err(_) => self.get_iid_to_weights(*iid)?.unwrap_or_default(),
+                    };
+
+                    let terms = match values.get(2) {
+                        Some(Ok(Some(value))) => Some(Self::decode_u32_list(value)?)
+                            .filter(|terms: &Vec<StoreTermHashed>| !terms.is_empty()),
+                        Some(Ok(None)) | None => None,
+                        Some(Err(_)) => self.get_iid_to_terms(*iid)?,
+                    };
+
+                    Ok(StoreKVIIDRanking {
+                        boost,
+                        weights,
+                        terms,
+                    })
+                })
+                .collect()
+        } else {
+            Ok(iids
+                .iter()
+                .map(|_| StoreKVIIDRanking {
+                    boost: STORE_OBJECT_BOOST_DEFAULT,
+                    weights: Vec::new(),
+                    terms: None,
+                })
+                .collect())
+        }
+    }
+
+    /// IID-to-Time mapper
+    ///
+    /// [IDX=6] ((iid)) ~> ((time))
+    pub fn get_iid_to_time(
+        &self,
+        iid: StoreObjectIID,
+    ) -> Result<Option<StoreObjectTime>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::iid_to_time(self.bucket.as_str(), iid);
+
+            debug!("store get iid-to-time: {}", store_key);
+
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => Self::decode_u32(&value).map(Some),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_iid_to_time(
+        &self,
+        iid: StoreObjectIID,
+        time: StoreObjectTime,
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_iid_to_time(iid, time);
+
+        batch.commit()
+    }
+
+    pub fn delete_iid_to_time(&self, iid: StoreObjectIID) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_iid_to_time(iid);
+
+        batch.commit()
+    }
+
+    /// IID-to-Weights mapper
+    ///
+    /// [IDX=7] ((iid)) ~> [((term, weight))]
+    pub fn get_iid_to_weights(
+        &self,
+        iid: StoreObjectIID,
+    ) -> Result<Option<Vec<(StoreTermHashed, StoreTermWeight)>>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::iid_to_weights(self.bucket.as_str(), iid);
+
+            debug!("store get iid-to-weights: {}", store_key);
+
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => Self::decode_weights(&value).map(Some),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_iid_to_weights(
+        &self,
+        iid: StoreObjectIID,
+        weights: &[(StoreTermHashed, StoreTermWeight)],
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_iid_to_weights(iid, weights);
+
+        batch.commit()
+    }
+
+    pub fn delete_iid_to_weights(&self, iid: StoreObjectIID) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_iid_to_weights(iid);
+
+        batch.commit()
+    }
+
+    /// IID-to-Snippet mapper
+    ///
+    /// [IDX=10] ((iid)) ~> ((snippet))
+    pub fn get_iid_to_snippet(&self, iid: StoreObjectIID) -> Result<Option<String>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::iid_to_snippet(self.bucket.as_str(), iid);
+
+            debug!("store get iid-to-snippet: {}", store_key);
+
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => Ok(str::from_utf8(&value).ok().map(|value| value.to_string())),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_iid_to_snippet(
+        &self,
+        iid: StoreObjectIID,
+        snippet: &str,
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_iid_to_snippet(iid, snippet);
+
+        batch.commit()
+    }
+
+    pub fn delete_iid_to_snippet(&self, iid: StoreObjectIID) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_iid_to_snippet(iid);
+
+        batch.commit()
+    }
+
+    /// IID-to-Lang mapper
+    ///
+    /// [IDX=15] ((iid)) ~> ((lang))
+    pub fn get_iid_to_lang(&self, iid: StoreObjectIID) -> Result<Option<Lang>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let store_key = StoreKeyerBuilder::iid_to_lang(self.bucket.as_str(), iid);
+
+            debug!("store get iid-to-lang: {}", store_key);
+
+            // Notice: an unknown locale code (eg. stored by a later version) reads as no locale.
+            match self.read(store, &store_key.as_bytes()) {
+                Ok(Some(value)) => Ok(str::from_utf8(&value).ok().and_then(Lang::from_code)),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_iid_to_lang(&self, iid: StoreObjectIID, lang: Lang) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_iid_to_lang(iid, lang);
+
+        batch.commit()
+    }
+
+    pub fn delete_iid_to_lang(&self, iid: StoreObjectIID) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_iid_to_lang(iid);
+
+        batch.commit()
+    }
+
+    /// OID-Ordered-to-IID mapper
+    ///
+    /// [IDX=9] ((oid)) ~> ((iid))
+    pub fn list_oid_ordered_to_iid(
+        &self,
+        prefix: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<String>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            let prefix = &*store.oid_normalization.normalize(prefix);
+            let store_prefix = StoreKeyerBuilder::oid_ordered_to_iid(self.bucket.as_str(), prefix);
+            let oid_at = StoreKeyerBuilder::oid_ordered_to_iid_prefix(self.bucket.as_str()).len();
+
+            debug!("store list oid-ordered-to-iid: {:?}", store_prefix);
+
+            // Scan OIDs starting with prefix (in OID order)
+            Ok(store
+                .scan_prefix(&store_prefix, self.snapshot)
+                .skip(offset)
+                .take(limit)
+                .filter_map(|(key, _)| {
+                    str::from_utf8(&key[oid_at..])
+                        .ok()
+                        .map(|oid| oid.to_string())
+                })
+                .collect())
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    pub fn set_oid_ordered_to_iid(
+        &self,
+        oid: StoreObjectOID<'a>,
+        iid: StoreObjectIID,
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.set_oid_ordered_to_iid(oid, iid);
+
+        batch.commit()
+    }
+
+    pub fn delete_oid_ordered_to_iid(&self, oid: StoreObjectOID<'a>) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        batch.delete_oid_ordered_to_iid(oid);
+
+        batch.commit()
+    }
+
+    pub fn get_retention(&self) -> Result<Option<StoreRetention>, StoreKVError> {
+        match self.get_meta_to_value(StoreMetaKey::Retention)? {
+            Some(StoreMetaValue::Retention(retention)) => Ok(Some(retention)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn set_retention(&self, retention: Option<StoreRetention>) -> Result<(), StoreKVError> {
+        // No limit? Remove the policy (this also removes the bucket from retention scans)
+        match retention {
+            Some(retention) if retention.max_age.is_some() || retention.max_objects.is_some() => {
+                self.set_meta_to_value(
+                    StoreMetaKey::Retention,
+                    StoreMetaValue::Retention(retention),
+                )
+            }
+            _ => self.delete_meta_to_value(StoreMetaKey::Retention),
+        }
+    }
+
+    pub fn get_or_assign_iid(
+        &self,
+        oid: StoreObjectOID<'a>,
+        objects_limit: Option<u32>,
+    ) -> Result<StoreObjectIID, StoreKVAssignError> {
+        let mut batch = self.batch();
+
+        let iid = self.assign_iid(&mut batch, oid, objects_limit)?;
+
+        // Commit association? (if the object is new)
+        if batch.is_empty() || batch.commit().is_ok() {
+            Ok(iid)
+        } else {
+            error!("failed associating oid-to-iid and iid-to-oid");
+
+            Err(StoreKVAssignError::Failed)
+        }
+    }
+
+    pub fn assign_iid(
+        &self,
+        batch: &mut StoreKVBatch<'a, '_>,
+        oid: StoreObjectOID,
+        objects_limit: Option<u32>,
+    ) -> Result<StoreObjectIID, StoreKVAssignError> {
+        // Try to resolve existing OID to IID, otherwise initialize IID (queue the \
+        //   bi-directional relationship to the batch)
+        if let Some(iid) = self.get_oid_to_iid(oid).unwrap_or(None) {
+            return Ok(iid);
+        }
+
+        info!("must initialize oid-to-iid and iid-to-oid");
+
+        let oid_count = self.get_oid_count().or(Err(StoreKVAssignError::Failed))?;
+
+        // Bucket already holds as many objects as allowed? Reject the new object.
+        if let Some(objects_limit) = objects_limit {
+            if oid_count >= objects_limit {
+                info!(
+                    "rejected oid assignment as bucket is full ({}/{})",
+                    oid_count, objects_limit
+                );
+
+                return Err(StoreKVAssignError::BucketFull);
+            }
+        }
+
+        if let Ok(iid_incr) = self.pop_next_iid() {
+            // Associate OID <> IID (bidirectional, and ordered by OID) and account for the new \
+            //   object
+            // Notice: an IID popped for an association that fails to commit is lost, which is \
+            //   harmless as IIDs never get reused.
+            batch
+                .set_oid_to_iid(oid, iid_incr)
+                .set_iid_to_oid(iid_incr, oid)
+                .set_oid_ordered_to_iid(oid, iid_incr)
+                .count_objects(1, 0);
+
+            Ok(iid_incr)
+        } else {
+            error!("failed getting meta-to-value iid increment");
+
+            Err(StoreKVAssignError::Failed)
+        }
+    }
+
+    pub fn pop_next_iid(&self) -> Result<StoreObjectIID, StoreKVError> {
+        if let Some(ref store) = self.store {
+            // Important: acquire IID allocation lock, as the increment is read then written \
+            //   back (concurrent allocations would otherwise hand out the same IID twice)
+            let _iid_lock = store.iid_lock.lock().unwrap();
+
+            // Notice: the increment is missing from new buckets, though it may also have been \
+            //   lost while objects remain in the bucket; it then gets recovered from stored \
+            //   IIDs, so that no IID gets handed out twice.
+            let iid_incr = match self.get_meta_to_value(StoreMetaKey::IIDIncr)? {
+                Some(StoreMetaValue::IIDIncr(iid_incr)) => iid_incr + 1,
+                _ => self.recover_next_iid(),
+            };
+
+            // Bump last stored increment (this is persisted before the IID gets handed out, \
+            //   so that IIDs stay monotonic across restarts)
+            // Notice: the bucket name is written along, as bucket listings read names from \
+            //   values (keys only hold bucket hashes).
+            let mut batch = self.batch();
+
+            batch
+                .set_meta_to_value(StoreMetaKey::IIDIncr, StoreMetaValue::IIDIncr(iid_incr))
+                .set_meta_to_value(
+                    StoreMetaKey::BucketName,
+                    StoreMetaValue::BucketName(self.bucket.as_str().to_string()),
+                );
+
+            batch.commit()?;
+
+            Ok(iid_incr)
+        } else {
+            Err(StoreKVError::NotFound)
+        }
+    }
+
+    fn recover_next_iid(&self) -> StoreObjectIID {
+        // Resume after the highest stored IID (IIDs are not scanned in order, as they are \
+        //   stored little-endian)
+        match self.scan_iid_to_oid().map(|(iid, _)| iid).max() {
+            Some(iid_last) => {
+                warn!(
+                    "recovered missing iid increment from stored iids, resuming after: {}",
+                    iid_last
+                );
+
+                iid_last + 1
+            }
+            None => 0,
+        }
+    }
+
+    pub fn get_oid_count(&self) -> Result<u32, StoreKVError> {
+        match self.get_meta_to_value(StoreMetaKey::OIDCount)? {
+            Some(StoreMetaValue::OIDCount(oid_count)) => Ok(oid_count),
+            _ => Ok(0),
+        }
+    }
+
+    pub fn get_objects_terms(&self) -> Result<Vec<(String, Vec<StoreTermHashed>)>, StoreKVError> {
+        if let Some(ref store) = self.store {
+            // List all objects from bucket, along with their terms
+            let (k_iid_to_oid, k_iid_to_terms) = (
+                StoreKeyerBuilder::iid_to_oid(self.bucket.as_str(), 0).as_prefix(),
+                StoreKeyerBuilder::iid_to_terms(self.bucket.as_str(), 0).as_prefix(),
+            );
+
+            let mut objects_terms: HashMap<StoreObjectIID, Vec<StoreTermHashed>> = store
+                .scan_prefix(&k_iid_to_terms, self.snapshot)
+                .filter_map(|(key, value)| {
+                    match (
+                        Self::decode_u32(&key[k_iid_to_terms.len()..]),
+                        Self::decode_u32_list(&value),
+                    ) {
+                        (Ok(iid), Ok(terms_hashed)) => Some((iid, terms_hashed)),
+                        _ => None,
+                    }
+                })
+                .collect();
+
+            let objects = store
+                .scan_prefix(&k_iid_to_oid, self.snapshot)
+                .filter_map(|(key, value)| {
+                    match (
+                        Self::decode_u32(&key[k_iid_to_oid.len()..]),
+                        str::from_utf8(&value),
+                    ) {
+                        (Ok(iid), Ok(oid)) => Some((
+                            oid.to_string(),
+                            objects_terms.remove(&iid).unwrap_or_default(),
+                        )),
+                        _ => None,
+                    }
+                })
+                .collect();
+
+            Ok(objects)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    pub fn batch_flush_bucket(
+        &self,
+        batch: &mut StoreKVBatch<'a, '_>,
+        iid: StoreObjectIID,
+        oid: StoreObjectOID,
+        iid_terms_hashed: &[StoreTermHashed],
+    ) -> Result<u32, StoreKVError> {
+        let mut count = 0;
+
+        debug!(
+            "store batch flush bucket: {} with hashed terms: {:?}",
+            iid, iid_terms_hashed
+        );
+
+        // Delete OID <> IID association, and account for removed object in bucket object count
+        batch
+            .delete_oid_to_iid(oid)
+            .delete_iid_to_oid(iid)
+            .delete_oid_ordered_to_iid(oid)
+            .delete_iid_to_terms(iid)
+            .delete_iid_to_boost(iid)
+            .delete_iid_to_time(iid)
+            .delete_iid_to_weights(iid)
+            .delete_iid_to_snippet(iid)
+            .delete_iid_to_lang(iid)
+            .count_objects(0, 1);
+
+        // Delete IID from each associated term
+        // Notice: a term list that cannot be read fails the whole flush, as the IID would \
+        //   otherwise be left dangling in there.
+        let mut terms_removed = 0;
+
+        for iid_term in iid_terms_hashed {
+            if let Some(mut iid_term_postings) = self.get_term_to_postings(*iid_term)? {
+                // Remove IID from list of IIDs
+                if iid_term_postings.remove(iid) {
+                    count += 1;
+                }
+
+                if iid_term_postings.is_empty() {
+                    batch.delete_term_to_iids(*iid_term);
+
+                    terms_removed += 1;
+                } else {
+                    batch.set_term_to_postings(*iid_term, &iid_term_postings);
+                }
+            }
+        }
+
+        // Account for removed terms in bucket term count
+        batch.count_terms(0, terms_removed);
+
+        Ok(count)
+    }
+
+    pub fn batch_link_term(
+        &self,
+        term_hashed: StoreTermHashed,
+        iid: StoreObjectIID,
+        positions: Option<Vec<StoreTermPosition>>,
+    ) -> Result<(), StoreKVError> {
+        let mut batch = self.batch();
+
+        // Account for new term in bucket term count? (first write)
+        if self.link_term(&mut batch, term_hashed, iid, positions)? {
+            batch.count_terms(1, 0);
+        }
+
+        batch.commit()
+    }
+
+    pub fn link_term(
+        &self,
+        batch: &mut StoreKVBatch<'a, '_>,
+        term_hashed: StoreTermHashed,
+        iid: StoreObjectIID,
+        positions: Option<Vec<StoreTermPosition>>,
+    ) -> Result<bool, StoreKVError> {
+        // Queue the updated list of IIDs for term to the batch (returns whether the term is \
+        //   new to the bucket, which the caller accounts for in the bucket term count)
+        let term_postings = self.get_term_to_postings(term_hashed)?;
+        let is_new = term_postings.is_none();
+
+        // Add IID in first position in list for terms (any previous occurrence of the IID in \
+        //   the list gets removed first)
+        let mut term_postings = term_postings.unwrap_or_default();
+
+        debug!("store batch link term: {} to iid: {}", term_hashed, iid);
+
+        term_postings.insert_first(iid, positions);
+
+        // Truncate IIDs linked to term? (ie. storage is too long)
+        let truncate_limit = APP_CONF.store.kv.retain_word_objects;
+
+        if term_postings.len() > truncate_limit {
+            info!(
+                "store batch link term-to-iids object too long (limit: {})",
+                truncate_limit
+            );
+
+            // Drain overflowing IIDs (ie. oldest ones that overflow)
+            // Notice: drained objects get updated when the batch is committed, as an object may \
+            //   be drained from several term lists within the same batch.
+            let term_iids_drain = term_postings.truncate(truncate_limit);
+
+            batch.truncate_objects(term_hashed, term_iids_drain);
+        }
+
+        batch.set_term_to_postings(term_hashed, &term_postings);
+
+        Ok(is_new)
+    }
+
+    pub fn batch_move_object(
+        &self,
+        to_action: &StoreKVAction<'a>,
+        oid: StoreObjectOID<'a>,
+        objects_limit: Option<u32>,
+    ) -> Result<u32, StoreKVAssignError> {
+        // Resolve IID and terms in source bucket (if object does not exist, there is nothing to \
+        //   be moved)
+        let iid = match self.get_oid_to_iid(oid) {
+            Ok(Some(iid)) => iid,
+            Ok(None) => return Ok(0),
+            Err(_) => return Err(StoreKVAssignError::Failed),
+        };
+
+        let (iid_terms_hashed, iid_boost, iid_time, iid_weights, iid_snippet, iid_lang) = match (
+            self.get_iid_to_terms(iid),
+            self.get_iid_to_boost(iid),
+            self.get_iid_to_time(iid),
+            self.get_iid_to_weights(iid),
+            self.get_iid_to_snippet(iid),
+            self.get_iid_to_lang(iid),
+        ) {
+            (
+                Ok(iid_terms_hashed),
+                Ok(iid_boost),
+                Ok(iid_time),
+                Ok(iid_weights),
+                Ok(iid_snippet),
+                Ok(iid_lang),
+            ) => (
+                iid_terms_hashed.unwrap_or_default(),
+                iid_boost,
+                iid_time,
+                iid_weights,
+                iid_snippet,
+                iid_lang,
+            ),
+            _ => return Err(StoreKVAssignError::Failed),
+        };
+
+        // Object already exists in destination bucket? (abort, merging objects is not supported)
+        match to_action.get_oid_to_iid(oid) {
+            Ok(None) => {}
+            _ => return Err(StoreKVAssignError::Failed),
+        }
+
+        debug!(
+            "store batch move object: {} from: {} to: {}",
+            oid,
+            self.bucket.as_str(),
+            to_action.bucket.as_str()
+        );
+
+        // Assign a new IID in destination bucket, and index object terms there
+        // Notice: the whole move is queued to a single batch, which spans both buckets (they \
+        //   live in the same store), thus upon failure nothing gets written to any bucket.
+        let mut batch = to_action.batch();
+
+        let to_iid = to_action.assign_iid(&mut batch, oid, objects_limit)?;
+
+        batch
+            .set_iid_to_boost(to_iid, iid_boost)
+            .set_iid_to_terms(to_iid, &iid_terms_hashed);
+
+        if let Some(iid_time) = iid_time {
+            batch.set_iid_to_time(to_iid, iid_time);
+        }
+
+        if let Some(iid_weights) = iid_weights {
+            batch.set_iid_to_weights(to_iid, &iid_weights);
+        }
+
+        if let Some(iid_snippet) = iid_snippet {
+            batch.set_iid_to_snippet(to_iid, &iid_snippet);
+        }
+
+        if let Some(iid_lang) = iid_lang {
+            batch.set_iid_to_lang(to_iid, iid_lang);
+        }
+
+        for term_hashed in &iid_terms_hashed {
+            // Carry the positions of the term in the object over (if any is stored)
+            let term_positions = self
+                .get_term_to_postings(*term_hashed)
+                .or(Err(StoreKVAssignError::Failed))?
+                .and_then(|term_postings| {
+                    term_postings
+                        .positions_of(iid)
+                        .map(|positions| positions.to_vec())
+                });
+
+            // Account for new term in destination bucket term count? (first write)
+            if to_action
+                .link_term(&mut batch, *term_hashed, to_iid, term_positions)
+                .or(Err(StoreKVAssignError::Failed))?
+            {
+                batch.count_terms(1, 0);
+            }
+        }
+
+        // Flush object from source bucket, then commit the whole move
+        let mut batch = batch.switch(self).or(Err(StoreKVAssignError::Failed))?;
+
+        let is_ok = self
+            .batch_flush_bucket(&mut batch, iid, oid, &iid_terms_hashed)
+            .is_ok()
+            && batch.commit().is_ok();
+
+        if is_ok {
+            Ok(1)
+        } else {
+            error!("failed in store batch move object: {}", oid);
+
+            Err(StoreKVAssignError::Failed)
+        }
+    }
+
+    pub fn batch_retain_bucket(
+        &self,
+        retention: &StoreRetention,
+        now: StoreObjectTime,
+    ) -> Result<u32, StoreKVError> {
+        if let Some(ref store) = self.store {
+            // Nothing to enforce? (skip listing objects if the bucket is within its bounds)
+            let objects_limit = match retention.max_objects {
+                Some(max_objects) if self.get_oid_count()? > max_objects => Some(max_objects),
+                _ => None,
+            };
+
+            if retention.max_age.is_none() && objects_limit.is_none() {
+                return Ok(0);
+            }
+
+            // List all objects from bucket, along with their last push time
+            let (k_iid_to_oid, k_iid_to_time) = (
+                StoreKeyerBuilder::iid_to_oid(self.bucket.as_str(), 0).as_prefix(),
+                StoreKeyerBuilder::iid_to_time(self.bucket.as_str(), 0).as_prefix(),
+            );
+
+            let objects_time: HashMap<StoreObjectIID, StoreObjectTime> = store
+                .scan_prefix(&k_iid_to_time, self.snapshot)
+                .filter_map(|(key, value)| {
+                    match (
+                        Self::decode_u32(&key[k_iid_to_time.len()..]),
+                        Self::decode_u32(&value),
+                    ) {
+                        (Ok(iid), Ok(time)) => Some((iid, time)),
+                        _ => None,
+                    }
+                })
+                .collect();
+
+            let mut objects: Vec<(Option<StoreObjectTime>, StoreObjectIID, String)> = store
+                .scan_prefix(&k_iid_to_oid, self.snapshot)
+                .filter_map(|(key, value)| {
+                    match (
+                        Self::decode_u32(&key[k_iid_to_oid.len()..]),
+                        str::from_utf8(&value),
+                    ) {
+                        (Ok(iid), Ok(oid)) => {
+                            Some((objects_time.get(&iid).copied(), iid, oid.to_string()))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect();
+
+            // Sort objects from oldest to most recent (objects pushed before push times were \
+            //   stored come first, ordered by IID, which increments as objects get created)
+            objects.sort_by_key(|(time, iid, _)| (time.unwrap_or(0), *iid));
+
+            let objects_excess = objects_limit
+                .map(|max_objects| objects.len().saturating_sub(max_objects as usize))
+                .unwrap_or(0);
+
+            let mut count = 0;
+
+            for (index, (time, iid, oid)) in objects.iter().enumerate() {
+                // Notice: objects with an unknown push time never expire by age.
+                let is_expired = index < objects_excess
+                    || match (retention.max_age, time) {
+                        (Some(max_age), Some(time)) => now.saturating_sub(*time) > max_age,
+                        _ => false,
+                    };
+
+                if is_expired {
+                    debug!("store batch retain bucket: purging object: {}", oid);
+
+                    let iid_terms_hashed = self.get_iid_to_terms(*iid)?.unwrap_or_default();
+
+                    // Notice: each object is committed in its own batch, as objects sharing \
+                    //   terms would otherwise update the same term lists from stale reads.
+                    let mut batch = self.batch();
+
+                    self.batch_flush_bucket(&mut batch, *iid, oid, &iid_terms_hashed)?;
+
+                    batch.commit()?;
+
+                    count += 1;
+                }
+            }
+
+            info!(
+                "store batch retain bucket: {} purged {} objects",
+                self.bucket.as_str(),
+                count
+            );
+
+            Ok(count)
+        } else {
+            Ok(0)
+        }
+    }
+
+    pub fn batch_truncate_object(
+        &self,
+        batch: &mut StoreKVBatch<'a, '_>,
+        objects_truncated: HashMap<StoreObjectIID, Vec<StoreTermHashed>>,
+    ) -> Result<u32, StoreKVError> {
+        let mut count = 0;
+
+        for (term_iid_drain, terms_hashed_drain) in objects_truncated {
+            debug!("store batch truncate object iid: {}", term_iid_drain);
+
+            // Nuke terms in IID to Terms list
+            if let Ok(Some(mut term_iid_drain_terms)) = self.get_iid_to_terms(term_iid_drain) {
+                count += 1;
+
+                term_iid_drain_terms.retain(|cur_term| !terms_hashed_drain.contains(cur_term));
+
+                // IID to Terms list is empty? Flush whole object.
+                if term_iid_drain_terms.is_empty() {
+                    // Acquire OID for this drained IID
+                    if let Ok(Some(term_iid_drain_oid)) = self.get_iid_to_oid(term_iid_drain) {
+                        self.batch_flush_bucket(batch, term_iid_drain, &term_iid_drain_oid, &[])?;
+                    } else {
+                        error!("failed getting store batch truncate object iid-to-oid");
+                    }
+                } else {
+                    // Update IID to Terms list
+                    batch.set_iid_to_terms(term_iid_drain, &term_iid_drain_terms);
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    pub fn batch_refresh_idf(&self) -> Result<u32, StoreKVError> {
+        self.check_writable()?;
+
+        if let Some(ref store) = self.store {
+            let oid_count = self.get_oid_count()?;
+
+            let mut batch = WriteBatch::default();
+            let mut count = 0;
+
+            // Compute the IDF weight of each term in bucket, from the number of objects it is \
+            //   linked to (all weights are written in a single batch, along with the object \
+            //   count they were computed for)
+            let k_term_to_iids =
+                StoreKeyerBuilder::term_to_iids(self.bucket.as_str(), 0).as_prefix();
+
+            for (key, value) in store.scan_prefix(&k_term_to_iids, self.snapshot) {
+                if let (Ok(term_hashed), Ok(term_postings)) = (
+                    Self::decode_u32(&key[k_term_to_iids.len()..]),
+                    StoreKVTermPostings::decode(&value),
+                ) {
+                    let idf = Self::compute_idf(term_postings.len(), oid_count);
+
+                    batch.put(
+                        StoreKeyerBuilder::term_to_idf(self.bucket.as_str(), term_hashed)
+                            .as_bytes(),
+                        Self::encode_f32(idf),
+                    );
+
+                    count += 1;
+                }
+            }
+
+            batch.put(
+                StoreKeyerBuilder::meta_to_value(self.bucket.as_str(), &StoreMetaKey::IDFCount)
+                    .as_bytes(),
+                Self::encode_idf_count(oid_count, self.bucket.as_str()),
+            );
+
+            debug!(
+                "store batch refresh idf: {} with {} terms for {} objects",
+                self.bucket.as_str(),
+                count,
+                oid_count
+            );
+
+            // Commit operation to database
+            if let Err(err) = store.do_write(batch) {
+                error!(
+                    "failed in store batch refresh idf: {} with error: {}",
+                    self.bucket.as_str(),
+                    err
+                );
+
+                Err(StoreKVError::Database(err))
+            } else {
+                Ok(count)
+            }
+        } else {
+            Ok(0)
+        }
+    }
+
+    pub fn batch_clear_idf(&self) -> Result<u32, StoreKVError> {
+        self.check_writable()?;
+
+        if let Some(ref store) = self.store {
+            let mut batch = WriteBatch::default();
+            let mut count = 0;
+
+            // Delete the IDF weight of each term in bucket, along with the object count they \
+            //   were computed for (in a single batch, so that weights are never partly cleared)
+            let k_term_to_idf = StoreKeyerBuilder::term_to_idf(self.bucket.as_str(), 0).as_prefix();
+
+            for (key, _) in store.scan_prefix(&k_term_to_idf, self.snapshot) {
+                batch.delete(&key);
+
+                count += 1;
+            }
+
+            batch.delete(
+                StoreKeyerBuilder::meta_to_value(self.bucket.as_str(), &StoreMetaKey::IDFCount)
+                    .as_bytes(),
+            );
+
+            debug!(
+                "store batch clear idf: {} with {} terms",
+                self.bucket.as_str(),
+                count
+            );
+
+            // Commit operation to database
+            if let Err(err) = store.do_write(batch) {
+                error!(
+                    "failed in store batch clear idf: {} with error: {}",
+                    self.bucket.as_str(),
+                    err
+                );
+
+                Err(StoreKVError::Database(err))
+            } else {
+                Ok(count)
+            }
+        } else {
+            Ok(0)
+        }
+    }
+
+    pub fn batch_swap_bucket(&self, with_action: &StoreKVAction) -> Result<u32, StoreKVError> {
+        self.check_writable()?;
+        with_action.check_writable()?;
+
+        if let Some(ref store) = self.store {
+            let mut batch = WriteBatch::default();
+            let (mut count_keys, mut count_objects) = (0, 0);
+            let (mut is_named, mut with_is_named) = (false, false);
+
+            // Notice: retention policies, IDF object counts and bucket names are bound to bucket \
+            //   names (not to bucket contents), thus they are not swapped.
+            let k_iid_to_oid = StoreKeyerBuilder::iid_to_oid(self.bucket.as_str(), 0).as_prefix();
+
+            let bound_routes = [
+                StoreKeyerBuilder::meta_to_value(self.bucket.as_str(), &StoreMetaKey::Retention)
+                    .as_bytes(),
+                StoreKeyerBuilder::meta_to_value(self.bucket.as_str(), &StoreMetaKey::IDFCount)
+                    .as_bytes(),
+                StoreKeyerBuilder::meta_to_value(self.bucket.as_str(), &StoreMetaKey::BucketName)
+                    .as_bytes(),
+            ];
+
+            let key_prefixes = (
+                Self::bucket_key_prefixes(self.bucket.as_str()),
+                Self::bucket_key_prefixes(with_action.bucket.as_str()),
+            );
+
+            // Scan the keys of both buckets side by side (in key order), and swap the values \
+            //   stored at each key suffix between both buckets
+            // Notice: keys are written in chunks, and both keys of a suffix always get written \
+            //   in the same chunk. Keys are only ever written at suffixes that were already \
+            //   scanned, thus scanning is not disturbed by written chunks.
+            for (prefix, with_prefix) in key_prefixes.0.iter().zip(key_prefixes.1.iter()) {
+                let (mut entries, mut with_entries) = (
+                    store.scan_prefix(prefix, None).peekable(),
+                    store.scan_prefix(with_prefix, None).peekable(),
+                );
+
+                loop {
+                    let suffix_order = match (entries.peek(), with_entries.peek()) {
+                        (Some((key, _)), Some((with_key, _))) => {
+                            key[prefix.len()..].cmp(&with_key[with_prefix.len()..])
+                        }
+                        (Some(_), None) => Ordering::Less,
+                        (None, Some(_)) => Ordering::Greater,
+                        (None, None) => break,
+                    };
+
+                    let (entry, with_entry) = match suffix_order {
+                        Ordering::Less => (entries.next(), None),
+                        Ordering::Greater => (None, with_entries.next()),
+                        Ordering::Equal => (entries.next(), with_entries.next()),
+                    };
+
+                    let suffix = match (&entry, &with_entry) {
+                        (Some((key, _)), _) => key[prefix.len()..].to_vec(),
+                        (None, Some((with_key, _))) => with_key[with_prefix.len()..].to_vec(),
+                        (None, None) => break,
+                    };
+
+                    if bound_routes.iter().any(|bound_route| {
+                        prefix[0] == bound_route[0]
+                            && prefix.len() + suffix.len() == bound_route.len()
+                            && suffix[..] == bound_route[prefix.len()..]
+                    }) {
+                        continue;
+                    }
+
+                    if prefix[0] == k_iid_to_oid[0] {
+                        count_objects += entry.is_some() as u32 + with_entry.is_some() as u32;
+                    }
+
+                    let (key, with_key) = (
+                        [&prefix[..], &suffix].concat(),
+                        [&with_prefix[..], &suffix].concat(),
+                    );
+
+                    match with_entry {
+                        Some((_, ref value)) => batch.put(&key, value),
+                        None => batch.delete(&key),
+                    }
+
+                    match entry {
+                        Some((_, ref value)) => batch.put(&with_key, value),
+                        None => batch.delete(&with_key),
+                    }
+
+                    is_named |= with_entry.is_some();
+                    with_is_named |= entry.is_some();
+
+                    count_keys += entry.is_some() as usize + with_entry.is_some() as usize;
+
+                    if batch.len() >= SWAP_CHUNK_KEYS {
+                        self.write_swap_chunk(store, with_action, mem::take(&mut batch))?;
+                    }
+                }
+            }
+
+            debug!(
+                "store batch swap bucket: {} with bucket: {} moved {} keys",
+                self.bucket.as_str(),
+                with_action.bucket.as_str(),
+                count_keys
+            );
+
+            // Name each bucket receiving keys (it may not be named yet, eg. if it was empty), \
+            //   while a bucket receiving no keys is left empty, thus is not listed anymore
+            for (bucket, is_named) in &[
+                (self.bucket.as_str(), is_named),
+                (with_action.bucket.as_str(), with_is_named),
+            ] {
+                let name_key = StoreKeyerBuilder::meta_to_value(bucket, &StoreMetaKey::BucketName);
+
+                if *is_named {
+                    batch.put(name_key.as_bytes(), bucket.as_bytes());
+                } else {
+                    batch.delete(name_key.as_bytes());
+                }
+            }
+
+            self.write_swap_chunk(store, with_action, batch)?;
+
+            info!(
+                "done processing store batch swap bucket: {} with bucket: {}",
+                self.bucket.as_str(),
+                with_action.bucket.as_str()
+            );
+
+            Ok(count_objects)
+        } else {
+            // Store does not exist, thus both buckets are empty (nothing to swap)
+            Ok(0)
+        }
+    }
+
+    fn write_swap_chunk(
+        &self,
+        store: &StoreKV,
+        with_action: &StoreKVAction,
+        batch: WriteBatch,
+    ) -> Result<(), StoreKVError> {
+        // Commit chunk to database
+        // Important: a failed chunk leaves both buckets partially swapped, as previous chunks \
+        //   are already written.
+        store.do_write(batch).map_err(|err| {
+            error!(
+                "failed in store batch swap bucket: {} with bucket: {} with error: {}",
+                self.bucket.as_str(),
+                with_action.bucket.as_str(),
+                err
+            );
+
+            StoreKVError::Database(err)
+        })
+    }
+
+    pub fn batch_erase_bucket(&self) -> Result<u32, StoreKVError> {
+        self.check_writable()?;
+
+        if let Some(ref store) = self.store {
+            let key_prefixes = Self::bucket_key_prefixes(self.bucket.as_str());
+
+            // Scan all keys per-prefix and nuke them right away
+            for key_prefix in &key_prefixes {
+                debug!(
+                    "store batch erase bucket: {} for prefix: {:?}",
+                    self.bucket.as_str(),
+                    key_prefix
+                );
+
+                // Generate start and end prefix for batch delete (in other words, the minimum \
+                //   key value possible, and the highest key value possible)
+                let (key_prefix_start, key_prefix_end) = (
+                    key_prefix.with_route([0; 4]),
+                    key_prefix.with_route([255; 4]),
+                );
+
+                // Batch-delete keys matching range
+                let mut batch = WriteBatch::default();
+
+                batch.delete_range(&key_prefix_start, &key_prefix_end);
+
+                // Commit operation to database
+                if let Err(err) = store.do_write(batch) {
+                    error!(
+                        "failed in store batch erase bucket: {} with error: {}",
+                        self.bucket.as_str(),
+                        err
+                    );
+                } else {
+                    // Ensure last key is deleted (as RocksDB end key is exclusive; while \
+                    //   start key is inclusive, we need to ensure the end-of-range key is \
+                    //   deleted)
+                    store.delete(&key_prefix_end).ok();
+
+                    debug!(
+                        "succeeded in store batch erase bucket: {}",
+                        self.bucket.as_str()
+                    );
+                }
+            }
+
+            info!(
+                "done processing store batch erase bucket: {}",
+                self.bucket.as_str()
+            );
+
+            Ok(1)
+        } else {
+            Err(StoreKVError::NotFound)
+        }
+    }
+
+    fn bucket_key_prefixes(bucket: &str) -> [StoreKeyerPrefix; 13] {
+        // Generate all key prefix values (with dummy post-prefix values; we dont care)
+        [
+            StoreKeyerBuilder::meta_to_value(bucket, &StoreMetaKey::IIDIncr).as_prefix(),
+            StoreKeyerBuilder::term_to_iids(bucket, 0).as_prefix(),
+            StoreKeyerBuilder::oid_to_iid(bucket, &String::new()).as_prefix(),
+            StoreKeyerBuilder::iid_to_oid(bucket, 0).as_prefix(),
+            StoreKeyerBuilder::iid_to_terms(bucket, 0).as_prefix(),
+            StoreKeyerBuilder::iid_to_boost(bucket, 0).as_prefix(),
+            StoreKeyerBuilder::iid_to_time(bucket, 0).as_prefix(),
+            StoreKeyerBuilder::iid_to_weights(bucket, 0).as_prefix(),
+            StoreKeyerBuilder::oid_ordered_to_iid_prefix(bucket),
+            StoreKeyerBuilder::iid_to_snippet(bucket, 0).as_prefix(),
+            StoreKeyerBuilder::term_to_idf(bucket, 0).as_prefix(),
+            StoreKeyerBuilder::term_to_size(bucket, 0).as_prefix(),
+            StoreKeyerBuilder::iid_to_lang(bucket, 0).as_prefix(),
+        ]
+    }
+
+    fn read(&self, store: &StoreKV, key: &[u8]) -> Result<Option<Vec<u8>>, DBError> {
+        // Read from pending writes first? (if any; as they are not in the database yet)
+        if let Some(value) = self.pending.and_then(|pending| pending.get(key)) {
+            return Ok(value);
+        }
+
+        store.get(key, self.snapshot)
+    }
+
+    fn scan_index<T: 'static>(
+        &self,
+        prefix: StoreKeyerPrefix,
+        decode_value: fn(&[u8]) -> Result<T, StoreKVError>,
+    ) -> impl Iterator<Item = (u32, T)> + '_ {
+        // Walk all keys of an index for the bucket, yielding their routes (ie. IIDs or term \
+        //   hashes); the prefix holds both the index type and the bucket, thus the scan stops \
+        //   at the end of the bucket index range.
+        self.store
+            .iter()
+            .flat_map(move |store| store.scan_prefix(prefix, self.snapshot))
+            .filter_map(move |(key, value)| {
+                match (Self::decode_u32(&key[prefix.len()..]), decode_value(&value)) {
+                    (Ok(route), Ok(value_decoded)) => Some((route, value_decoded)),
+                    _ => None,
+                }
+            })
+    }
+
+    fn encode_u32(decoded: u32) -> [u8; 4] {
+        let mut encoded = [0; 4];
+
+        LittleEndian::write_u32(&mut encoded, decoded);
+
+        encoded
+    }
+
+    fn decode_u32(encoded: &[u8]) -> Result<u32, StoreKVError> {
+        Cursor::new(encoded)
+            .read_u32::<LittleEndian>()
+            .or(Err(StoreKVError::Serialization))
+    }
+
+    pub fn time_now() -> StoreObjectTime {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as StoreObjectTime)
+            .unwrap_or(0)
+    }
+
+    fn encode_retention(retention: &StoreRetention, bucket: &str) -> String {
+        // Notice: the bucket name is stored along with the policy, as retention scans list \
+        //   policies from keys, which only hold bucket hashes.
+        format!(
+            "{}:{}:{}",
+            retention.max_age.unwrap_or(0),
+            retention.max_objects.unwrap_or(0),
+            bucket
+        )
+    }
+
+    fn encode_idf_count(idf_count: u32, bucket: &str) -> String {
+        // Notice: the bucket name is stored along with the count, as IDF consolidation scans \
+        //   list counts from keys, which only hold bucket hashes (see retention policies).
+        format!("{}:{}", idf_count, bucket)
+    }
+
+    fn decode_idf_count(encoded: &str) -> Option<(u32, &str)> {
+        let mut parts = encoded.splitn(2, ':');
+
+        match (
+            parts.next().and_then(|part| part.parse::<u32>().ok()),
+            parts.next(),
+        ) {
+            (Some(idf_count), Some(bucket)) => Some((idf_count, bucket)),
+            _ => None,
+        }
+    }
+
+    fn compute_idf(term_objects: usize, oid_count: u32) -> StoreTermIDF {
+        // Smoothed inverse document frequency (the object count may be missing, eg. on buckets \
+        //   created before it was introduced, hence the term objects count as a floor)
+        let (term_objects, oid_count) = (
+            term_objects as f64,
+            (oid_count as f64).max(term_objects as f64),
+        );
+
+        (1.0 + (oid_count - term_objects + 0.5) / (term_objects + 0.5)).ln() as StoreTermIDF
+    }
+
+    fn is_idf_stale(idf_count: u32, oid_count: u32) -> bool {
+        (oid_count as f64 - idf_count as f64).abs() > idf_count as f64 * IDF_STALE_CHANGE_RATIO
+    }
+
+    fn decode_retention(encoded: &str) -> Option<(StoreRetention, &str)> {
+        let mut parts = encoded.splitn(3, ':');
+
+        match (
+            parts.next().and_then(|part| part.parse::<u32>().ok()),
+            parts.next().and_then(|part| part.parse::<u32>().ok()),
+            parts.next(),
+        ) {
+            (Some(max_age), Some(max_objects), Some(bucket)) => Some((
+                StoreRetention {
+                    max_age: if max_age > 0 { Some(max_age) } else { None },
+                    max_objects: if max_objects > 0 {
+                        Some(max_objects)
+                    } else {
+                        None
+                    },
+                },
+                bucket,
+            )),
+            _ => None,
+        }
+    }
+
+    fn encode_f32(decoded: f32) -> [u8; 4] {
+        let mut encoded = [0; 4];
+
+        LittleEndian::write_f32(&mut encoded, decoded);
+
+        encoded
+    }
+
+    fn decode_f32(encoded: &[u8]) -> Result<f32, StoreKVError> {
+        Cursor::new(encoded)
+            .read_f32::<LittleEndian>()
+            .or(Err(StoreKVError::Serialization))
+    }
+
+    fn encode_weights(decoded: &[(StoreTermHashed, StoreTermWeight)]) -> Vec<u8> {
+        // Weights are encoded as a list of [term<4B> | weight<4B>] pairs
+        let mut encoded = Vec::with_capacity(decoded.len() * 8);
+
+        for (term_hashed, weight) in decoded {
+            encoded.extend(&Self::encode_u32(*term_hashed));
+            encoded.extend(&Self::encode_f32(*weight));
+        }
+
+        encoded
+    }
+
+    fn decode_weights(
+        encoded: &[u8],
+    ) -> Result<Vec<(StoreTermHashed, StoreTermWeight)>, StoreKVError> {
+        if encoded.len() % 8 != 0 {
+            return Err(StoreKVError::Serialization);
+        }
+
+        encoded
+            .chunks_exact(8)
+            .map(|encoded_pair| {
+                Ok((
+                    Self::decode_u32(&encoded_pair[0..4])?,
+                    Self::decode_f32(&encoded_pair[4..8])?,
+                ))
+            })
+            .collect()
+    }
+
+    fn encode_u32_list(decoded: &[u32]) -> Vec<u8> {
+        // Pre-reserve required capacity as to avoid heap resizes (50% performance gain relative \
+        //   to initializing this with a zero-capacity)
+        let mut encoded = Vec::with_capacity(decoded.len() * 4);
+
+        for decoded_item in decoded {
+            encoded.extend(&Self::encode_u32(*decoded_item))
+        }
+
+        encoded
+    }
+
+    fn decode_u32_list(encoded: &[u8]) -> Result<Vec<u32>, StoreKVError> {
+        // Pre-reserve required capacity as to avoid heap resizes (50% performance gain relative \
+        //   to initializing this with a zero-capacity)
+        let mut decoded = Vec::with_capacity(encoded.len() / 4);
+
+        for encoded_chunk in encoded.chunks(4) {
+            if let Ok(decoded_chunk) = Self::decode_u32(encoded_chunk) {
+                decoded.push(decoded_chunk);
+            } else {
+                return Err(StoreKVError::Serialization);
+            }
+        }
+
+        Ok(decoded)
+    }
+}
+
+impl<'a, 'b> StoreKVBatch<'a, 'b> {
+    pub fn commit(mut self) -> Result<(), StoreKVError> {
+        self.action.check_writable()?;
+        self.settle()?;
+
+        // Important: a malformed operation fails the whole batch, which then gets dropped \
+        //   without writing anything (RocksDB applies a write batch atomically, thus either all \
+        //   queued operations are applied, or none is).
+        if self.is_malformed {
+            error!("refusing to commit malformed store batch");
+
+            return Err(StoreKVError::Serialization);
+        }
+
+        if let Some(ref store) = self.action.store {
+            // Queue batch to pending writes? (if committed from a pending action)
+            if let (Some(pending), Some(writes)) = (self.action.pending, self.writes) {
+                debug!("store queue batch: {} operations", writes.len());
+
+                pending.queue(writes);
+
+                return Ok(());
+            }
+
+            debug!("store commit batch: {} operations", self.batch.len());
+
+            store.do_write(self.batch).map_err(StoreKVError::Database)
+        } else {
+            Err(StoreKVError::NotFound)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.batch.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.batch.is_empty()
+    }
+
+    pub fn switch<'c>(
+        mut self,
+        action: &'c StoreKVAction<'a>,
+    ) -> Result<StoreKVBatch<'a, 'c>, StoreKVError> {
+        // Continue the batch on another bucket of the same store (eg. to move an object from a \
+        //   bucket to another in a single batch)
+        // Notice: counts and drained objects are settled first, as they are bound to the bucket.
+        self.settle()?;
+
+        let is_same_store = match (&self.action.store, &action.store) {
+            (Some(store), Some(to_store)) => Arc::ptr_eq(store, to_store),
+            _ => false,
+        };
+
+        let mut batch = StoreKVBatch {
+            action,
+            batch: self.batch,
+            is_malformed: self.is_malformed,
+            terms_counted: (0, 0),
+            objects_counted: (0, 0),
+            objects_truncated: HashMap::new(),
+            writes: self.writes,
+        };
+
+        if !is_same_store {
+            batch.malformed("switch to another store");
+        }
+
+        Ok(batch)
+    }
+
+    pub fn count_terms(&mut self, count_added: u32, count_removed: u32) -> &mut Self {
+        self.terms_counted.0 += count_added;
+        self.terms_counted.1 += count_removed;
+
+        self
+    }
+
+    pub fn count_objects(&mut self, count_added: u32, count_removed: u32) -> &mut Self {
+        self.objects_counted.0 += count_added;
+        self.objects_counted.1 += count_removed;
+
+        self
+    }
+
+    pub fn truncate_objects(
+        &mut self,
+        term_hashed: StoreTermHashed,
+        iids: Vec<StoreObjectIID>,
+    ) -> &mut Self {
+        for iid in iids {
+            self.objects_truncated
+                .entry(iid)
+                .or_default()
+                .push(term_hashed);
+        }
+
+        self
+    }
+
+    pub fn set_meta_to_value(&mut self, meta: StoreMetaKey, value: StoreMetaValue) -> &mut Self {
+        let bucket = self.action.bucket.as_str();
+        let store_key = StoreKeyerBuilder::meta_to_value(bucket, &meta);
+
+        debug!("store set meta-to-value: {}", store_key);
+
+        let value_string = match value {
+            StoreMetaValue::IIDIncr(iid_incr) => iid_incr.to_string(),
+            StoreMetaValue::OIDCount(oid_count) => oid_count.to_string(),
+            StoreMetaValue::TermCount(term_count) => term_count.to_string(),
+            StoreMetaValue::Retention(retention) => {
+                StoreKVAction::encode_retention(&retention, bucket)
+            }
+            StoreMetaValue::IDFCount(idf_count) => {
+                StoreKVAction::encode_idf_count(idf_count, bucket)
+            }
+            StoreMetaValue::Immutable(immutable) => immutable.to_string(),
+            StoreMetaValue::BucketName(bucket_name) => bucket_name,
+        };
+
+        self.put(store_key.as_bytes(), value_string.as_bytes())
+    }
+
+    pub fn delete_meta_to_value(&mut self, meta: StoreMetaKey) -> &mut Self {
+        let store_key = StoreKeyerBuilder::meta_to_value(self.action.bucket.as_str(), &meta);
+
+        debug!("store delete meta-to-value: {}", store_key);
+
+        self.delete(store_key.as_bytes())
+    }
+
+    pub fn set_term_to_iids(
+        &mut self,
+        term_hashed: StoreTermHashed,
+        iids: &[StoreObjectIID],
+    ) -> &mut Self {
+        // Encode IID list into storage serialized format
+        let iids_encoded = StoreKVAction::encode_u32_list(iids);
+
+        debug!(
+            "store set term-to-iids: {} with encoded value: {:?}",
+            term_hashed, iids_encoded
+        );
+
+        self.put_term_to_iids(term_hashed, &iids_encoded, iids.len())
+    }
+
+    pub fn set_term_to_postings(
+        &mut self,
+        term_hashed: StoreTermHashed,
+        postings: &StoreKVTermPostings,
+    ) -> &mut Self {
+        debug!("store set term-to-postings: {}", term_hashed);
+
+        self.put_term_to_iids(term_hashed, &postings.encode(), postings.len())
+    }
+
+    pub fn delete_term_to_iids(&mut self, term_hashed: StoreTermHashed) -> &mut Self {
+        let bucket = self.action.bucket.as_str();
+        let store_key = StoreKeyerBuilder::term_to_iids(bucket, term_hashed);
+
+        debug!("store delete term-to-iids: {}", store_key);
+
+        // Notice: the term list size and cached IDF weight (if any) go along with the list.
+        self.delete(store_key.as_bytes());
+        self.delete(StoreKeyerBuilder::term_to_size(bucket, term_hashed).as_bytes());
+        self.delete_term_to_idf(term_hashed)
+    }
+
+    pub fn delete_term_to_idf(&mut self, term_hashed: StoreTermHashed) -> &mut Self {
+        let store_key = StoreKeyerBuilder::term_to_idf(self.action.bucket.as_str(), term_hashed);
+
+        debug!("store delete term-to-idf: {}", store_key);
+
+        self.delete(store_key.as_bytes())
+    }
+
+    pub fn set_oid_to_iid(&mut self, oid: StoreObjectOID, iid: StoreObjectIID) -> &mut Self {
+        match self.normalize_oid(oid) {
+            Some(oid) => {
+                let store_key = StoreKeyerBuilder::oid_to_iid(self.action.bucket.as_str(), &oid);
+
+                debug!("store set oid-to-iid: {} with iid: {}", store_key, iid);
+
+                self.put(store_key.as_bytes(), &StoreKVAction::encode_u32(iid))
+            }
+            None => self.malformed("set oid-to-iid"),
+        }
+    }
+
+    pub fn delete_oid_to_iid(&mut self, oid: StoreObjectOID) -> &mut Self {
+        match self.normalize_oid(oid) {
+            Some(oid) => {
+                let store_key = StoreKeyerBuilder::oid_to_iid(self.action.bucket.as_str(), &oid);
+
+                debug!("store delete oid-to-iid: {}", store_key);
+
+                self.delete(store_key.as_bytes())
+            }
+            None => self.malformed("delete oid-to-iid"),
+        }
+    }
+
+    pub fn set_iid_to_oid(&mut self, iid: StoreObjectIID, oid: StoreObjectOID) -> &mut Self {
+        match self.normalize_oid(oid) {
+            Some(oid) => {
+                let store_key = StoreKeyerBuilder::iid_to_oid(self.action.bucket.as_str(), iid);
+
+                debug!("store set iid-to-oid: {}", store_key);
+
+                self.put(store_key.as_bytes(), oid.as_bytes())
+            }
+            None => self.malformed("set iid-to-oid"),
+        }
+    }
+
+    pub fn delete_iid_to_oid(&mut self, iid: StoreObjectIID) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_oid(self.action.bucket.as_str(), iid);
+
+        debug!("store delete iid-to-oid: {}", store_key);
+
+        self.delete(store_key.as_bytes())
+    }
+
+    pub fn set_iid_to_terms(
+        &mut self,
+        iid: StoreObjectIID,
+        terms_hashed: &[StoreTermHashed],
+    ) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_terms(self.action.bucket.as_str(), iid);
+
+        // Encode term list into storage serialized format
+        let terms_hashed_encoded = StoreKVAction::encode_u32_list(terms_hashed);
+
+        debug!(
+            "store set iid-to-terms: {} with encoded value: {:?}",
+            store_key, terms_hashed_encoded
+        );
+
+        self.put(store_key.as_bytes(), &terms_hashed_encoded)
+    }
+
+    pub fn delete_iid_to_terms(&mut self, iid: StoreObjectIID) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_terms(self.action.bucket.as_str(), iid);
+
+        debug!("store delete iid-to-terms: {}", store_key);
+
+        self.delete(store_key.as_bytes())
+    }
+
+    pub fn set_iid_to_boost(&mut self, iid: StoreObjectIID, boost: StoreObjectBoost) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_boost(self.action.bucket.as_str(), iid);
+
+        debug!("store set iid-to-boost: {}", store_key);
+
+        // Default boost? Do not store it (this saves space for non-boosted objects)
+        if boost == STORE_OBJECT_BOOST_DEFAULT {
+            self.delete(store_key.as_bytes())
+        } else {
+            self.put(store_key.as_bytes(), &StoreKVAction::encode_f32(boost))
+        }
+    }
+
+    pub fn delete_iid_to_boost(&mut self, iid: StoreObjectIID) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_boost(self.action.bucket.as_str(), iid);
+
+        debug!("store delete iid-to-boost: {}", store_key);
+
+        self.delete(store_key.as_bytes())
+    }
+
+    pub fn set_iid_to_time(&mut self, iid: StoreObjectIID, time: StoreObjectTime) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_time(self.action.bucket.as_str(), iid);
+
+        debug!("store set iid-to-time: {}", store_key);
+
+        self.put(store_key.as_bytes(), &StoreKVAction::encode_u32(time))
+    }
+
+    pub fn delete_iid_to_time(&mut self, iid: StoreObjectIID) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_time(self.action.bucket.as_str(), iid);
+
+        debug!("store delete iid-to-time: {}", store_key);
+
+        self.delete(store_key.as_bytes())
+    }
+
+    pub fn set_iid_to_weights(
+        &mut self,
+        iid: StoreObjectIID,
+        weights: &[(StoreTermHashed, StoreTermWeight)],
+    ) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_weights(self.action.bucket.as_str(), iid);
+
+        debug!("store set iid-to-weights: {}", store_key);
+
+        // No weighted term? Do not store an empty list (terms weigh the default weight)
+        if weights.is_empty() {
+            self.delete(store_key.as_bytes())
+        } else {
+            self.put(
+                store_key.as_bytes(),
+                &StoreKVAction::encode_weights(weights),
+            )
+        }
+    }
+
+    pub fn delete_iid_to_weights(&mut self, iid: StoreObjectIID) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_weights(self.action.bucket.as_str(), iid);
+
+        debug!("store delete iid-to-weights: {}", store_key);
+
+        self.delete(store_key.as_bytes())
+    }
+
+    pub fn set_iid_to_snippet(&mut self, iid: StoreObjectIID, snippet: &str) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_snippet(self.action.bucket.as_str(), iid);
+
+        debug!("store set iid-to-snippet: {}", store_key);
+
+        // Empty snippet? Do not store it (this saves space for objects without a snippet)
+        if snippet.is_empty() {
+            self.delete(store_key.as_bytes())
+        } else {
+            self.put(store_key.as_bytes(), snippet.as_bytes())
+        }
+    }
+
+    pub fn delete_iid_to_snippet(&mut self, iid: StoreObjectIID) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_snippet(self.action.bucket.as_str(), iid);
+
+        debug!("store delete iid-to-snippet: {}", store_key);
+
+        self.delete(store_key.as_bytes())
+    }
+
+    pub fn set_iid_to_lang(&mut self, iid: StoreObjectIID, lang: Lang) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_lang(self.action.bucket.as_str(), iid);
+
+        debug!("store set iid-to-lang: {}", store_key);
+
+        self.put(store_key.as_bytes(), lang.code().as_bytes())
+    }
+
+    pub fn delete_iid_to_lang(&mut self, iid: StoreObjectIID) -> &mut Self {
+        let store_key = StoreKeyerBuilder::iid_to_lang(self.action.bucket.as_str(), iid);
+
+        debug!("store delete iid-to-lang: {}", store_key);
+
+        self.delete(store_key.as_bytes())
+    }
+
+    pub fn set_oid_ordered_to_iid(
+        &mut self,
+        oid: StoreObjectOID,
+        iid: StoreObjectIID,
+    ) -> &mut Self {
+        match self.normalize_oid(oid) {
+            Some(oid) => {
+                let store_key =
+                    StoreKeyerBuilder::oid_ordered_to_iid(self.action.bucket.as_str(), &oid);
+
+                debug!("store set oid-ordered-to-iid: {:?}", store_key);
+
+                self.put(&store_key, &StoreKVAction::encode_u32(iid))
+            }
+            None => self.malformed("set oid-ordered-to-iid"),
+        }
+    }
+
+    pub fn delete_oid_ordered_to_iid(&mut self, oid: StoreObjectOID) -> &mut Self {
+        match self.normalize_oid(oid) {
+            Some(oid) => {
+                let store_key =
+                    StoreKeyerBuilder::oid_ordered_to_iid(self.action.bucket.as_str(), &oid);
+
+                debug!("store delete oid-ordered-to-iid: {:?}", store_key);
+
+                self.delete(&store_key)
+            }
+            None => self.malformed("delete oid-ordered-to-iid"),
+        }
+    }
+
+    fn settle(&mut self) -> Result<(), StoreKVError> {
+        let action = self.action;
+
+        // Update objects drained from term lists (once per object)
+        if !self.objects_truncated.is_empty() {
+            let objects_truncated = mem::take(&mut self.objects_truncated);
+
+            if action
+                .batch_truncate_object(self, objects_truncated)
+                .is_err()
+            {
+                self.malformed("truncate objects");
+            }
+        }
+
+        // Update bucket counts (once per batch, as counts are read from the database, which \
+        //   does not see writes queued to the batch)
+        // Notice: counts may be missing (eg. on buckets created before they were introduced), \
+        //   hence the saturating increments and decrements.
+        if self.terms_counted != (0, 0) {
+            let ((count_added, count_removed), term_count) =
+                (mem::take(&mut self.terms_counted), action.get_term_count()?);
+
+            self.set_meta_to_value(
+                StoreMetaKey::TermCount,
+                StoreMetaValue::TermCount(
+                    term_count
+                        .saturating_add(count_added)
+                        .saturating_sub(count_removed),
+                ),
+            );
+        }
+
+        if self.objects_counted != (0, 0) {
+            let ((count_added, count_removed), oid_count) = (
+                mem::take(&mut self.objects_counted),
+                action.get_oid_count()?,
+            );
+
+            self.set_meta_to_value(
+                StoreMetaKey::OIDCount,
+                StoreMetaValue::OIDCount(
+                    oid_count
+                        .saturating_add(count_added)
+                        .saturating_sub(count_removed),
+                ),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn put_term_to_iids(
+        &mut self,
+        term_hashed: StoreTermHashed,
+        value: &[u8],
+        size: usize,
+    ) -> &mut Self {
+        let bucket = self.action.bucket.as_str();
+
+        // Notice: the list size is written along with the list, as to keep term statistics in \
+        //   sync with term lists without having to read them.
+        self.put(
+            StoreKeyerBuilder::term_to_iids(bucket, term_hashed).as_bytes(),
+            value,
+        );
+        self.put(
+            StoreKeyerBuilder::term_to_size(bucket, term_hashed).as_bytes(),
+            &StoreKVAction::encode_u32(size as u32),
+        )
+    }
+
+    fn normalize_oid(&self, oid: StoreObjectOID) -> Option<String> {
+        // Notice: OIDs are validated once normalized, as normalization may eg. empty them.
+        let oid = self.action.normalize_oid(oid);
+
+        if StoreItemPart::from_str(&oid).is_ok() {
+            Some(oid.into_owned())
+        } else {
+            None
+        }
+    }
+
+    fn malformed(&mut self, operation: &str) -> &mut Self {
+        error!("got malformed store batch operation: {}", operation);
+
+        self.is_malformed = true;
+
+        self
+    }
+
+    fn put<K: AsRef<[u8]>>(&mut self, key: K, value: &[u8]) -> &mut Self {
+        if let Some(ref mut writes) = self.writes {
+            writes.push((key.as_ref().to_vec(), Some(value.to_vec())));
+        }
+
+        self.batch.put(key, value);
+
+        self
+    }
+
+    fn delete<K: AsRef<[u8]>>(&mut self, key: K) -> &mut Self {
+        if let Some(ref mut writes) = self.writes {
+            writes.push((key.as_ref().to_vec(), None));
+        }
+
+        self.batch.delete(key);
+
+        self
+    }
+}
+
+impl StoreKVPending {
+    pub fn is_empty(&self) -> bool {
+        self.batch.borrow().is_empty()
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Option<Vec<u8>>> {
+        self.writes.borrow().get(key).cloned()
+    }
+
+    fn queue(&self, writes: Vec<StoreKVPendingWrite>) {
+        let (mut batch, mut pending_writes) = (self.batch.borrow_mut(), self.writes.borrow_mut());
+
+        for (key, value) in writes {
+            match value {
+                Some(ref value) => batch.put(&key, value),
+                None => batch.delete(&key),
+            }
+
+            pending_writes.insert(key, value);
+        }
+    }
+}
+
+impl<'a> StoreKVIIDList<'a> {
+    pub fn iter(&self) -> StoreKVIIDCursor<'_> {
+        // Skip the format byte? (IIDs and positions format)
+        let words = if self.positioned {
+            &self.value[1..]
+        } else {
+            &self.value[..]
+        };
+
+        StoreKVIIDCursor {
+            chunks: words.chunks_exact(4),
+            positioned: self.positioned,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        if self.positioned {
+            self.iter().count()
+        } else {
+            self.value.len() / 4
+        }
+    }
+}
+
+impl<'a> Iterator for StoreKVIIDCursor<'a> {
+    type Item = StoreObjectIID;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let iid = self.chunks.next().map(LittleEndian::read_u32)?;
+
+        // Skip over the positions of the IID? (IIDs and positions format)
+        if self.positioned {
+            let positions_count = self.chunks.next().map(LittleEndian::read_u32)?;
+
+            if positions_count > 0 {
+                self.chunks.nth(positions_count as usize - 1)?;
+            }
+        }
+
+        Some(iid)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.positioned {
+            (0, self.chunks.size_hint().1)
+        } else {
+            self.chunks.size_hint()
+        }
+    }
+}
+
+impl StoreKVTermPostings {
+    pub fn iids(&self) -> &[StoreObjectIID] {
+        &self.iids
+    }
+
+    pub fn into_iids(self) -> Vec<StoreObjectIID> {
+        self.iids
+    }
+
+    pub fn len(&self) -> usize {
+        self.iids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iids.is_empty()
+    }
+
+    pub fn has_positions(&self) -> bool {
+        self.positions.is_some()
+    }
+
+    pub fn positions_of(&self, iid: StoreObjectIID) -> Option<&[StoreTermPosition]> {
+        let index = self.iids.iter().position(|cur_iid| cur_iid == &iid)?;
+
+        self.positions
+            .as_ref()
+            .map(|positions| positions[index].as_slice())
+    }
+
+    pub fn iter_positions(
+        &self,
+    ) -> impl Iterator<Item = (StoreObjectIID, Option<&[StoreTermPosition]>)> {
+        self.iids.iter().enumerate().map(move |(index, iid)| {
+            (
+                *iid,
+                self.positions
+                    .as_ref()
+                    .map(|positions| positions[index].as_slice()),
+            )
+        })
+    }
+
+    pub fn remove(&mut self, iid: StoreObjectIID) -> bool {
+        if let Some(index) = self.iids.iter().position(|cur_iid| cur_iid == &iid) {
+            self.iids.remove(index);
+
+            if let Some(ref mut positions) = self.positions {
+                positions.remove(index);
+            }
+
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn insert_first(&mut self, iid: StoreObjectIID, positions: Option<Vec<StoreTermPosition>>) {
+        self.remove(iid);
+
+        // Upgrade to the IIDs and positions format? (IIDs already stored get no positions)
+        // Notice: values never get downgraded to the IIDs only format, as this would drop the \
+        //   positions of other IIDs.
+        if positions.is_some() && self.positions.is_none() {
+            self.positions = Some(vec![Vec::new(); self.iids.len()]);
+        }
+
+        self.iids.insert(0, iid);
+
+        if let Some(ref mut all_positions) = self.positions {
+            all_positions.insert(0, positions.unwrap_or_default());
+        }
+    }
+
+    pub fn truncate(&mut self, limit: usize) -> Vec<StoreObjectIID> {
+        if let Some(ref mut positions) = self.positions {
+            positions.truncate(limit);
+        }
+
+        if self.iids.len() > limit {
+            self.iids.drain(limit..).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        if let Some(ref positions) = self.positions {
+            // Encode as: [format] then for each IID: [iid][positions count][positions...]
+            let words_count: usize = positions
+                .iter()
+                .map(|iid_positions| iid_positions.len())
+                .sum();
+
+            let mut encoded = Vec::with_capacity(1 + (self.iids.len() * 2 + words_count) * 4);
+
+            encoded.push(TERM_FORMAT_POSITIONS);
+
+            for (iid, iid_positions) in self.iids.iter().zip(positions.iter()) {
+                encoded.extend(&StoreKVAction::encode_u32(*iid));
+                encoded.extend(&StoreKVAction::encode_u32(iid_positions.len() as u32));
+
+                for position in iid_positions {
+                    encoded.extend(&StoreKVAction::encode_u32(*position));
+                }
+            }
+
+            encoded
+        } else {
+            StoreKVAction::encode_u32_list(&self.iids)
+        }
+    }
+
+    fn decode(encoded: &[u8]) -> Result<StoreKVTermPostings, StoreKVError> {
+        // IIDs only format? (legacy format, with no format byte)
+        if encoded.len() % 4 == 0 {
+            return Ok(StoreKVTermPostings {
+                iids: StoreKVAction::decode_u32_list(encoded)?,
+                positions: None,
+            });
+        }
+
+        if encoded[0] != TERM_FORMAT_POSITIONS || encoded.len() % 4 != 1 {
+            error!("got term-to-iids value with unknown format");
+
+            return Err(StoreKVError::Serialization);
+        }
+
+        let words = StoreKVAction::decode_u32_list(&encoded[1..])?;
+
+        let (mut iids, mut positions) = (Vec::new(), Vec::new());
+        let mut words_iter = words.into_iter();
+
+        while let Some(iid) = words_iter.next() {
+            let positions_count = words_iter.next().ok_or(StoreKVError::Serialization)? as usize;
+            let iid_positions: Vec<StoreTermPosition> =
+                words_iter.by_ref().take(positions_count).collect();
+
+            if iid_positions.len() != positions_count {
+                return Err(StoreKVError::Serialization);
+            }
+
+            iids.push(iid);
+            positions.push(iid_positions);
+        }
+
+        Ok(StoreKVTermPostings {
+            iids,
+            positions: Some(positions),
+        })
+    }
+}
+
+impl fmt::Display for StoreKVError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreKVError::Database(err) => write!(f, "database error: {}", err),
+            StoreKVError::Io(kind) => write!(f, "io error: {:?}", kind),
+            StoreKVError::Serialization => write!(f, "serialization error"),
+            StoreKVError::NotFound => write!(f, "store not found"),
+            StoreKVError::Incompatible => write!(f, "incompatible store format"),
+            StoreKVError::Immutable => write!(f, "bucket is immutable"),
+            StoreKVError::Collision => write!(f, "bucket hash collision"),
+            StoreKVError::ReadOnly => write!(f, "read-only store action"),
+            StoreKVError::Invalid => write!(f, "invalid argument"),
+        }
+    }
+}
+
+impl Error for StoreKVError {}
+
+impl From<DBError> for StoreKVError {
+    fn from(err: DBError) -> StoreKVError {
+        StoreKVError::Database(err)
+    }
+}
+
+impl From<io::Error> for StoreKVError {
+    fn from(err: io::Error) -> StoreKVError {
+        StoreKVError::Io(err.kind())
+    }
+}
+
+#[cfg(test)]
+impl Drop for StoreKVTemporary {
+    fn drop(&mut self) {
+        // Notice: the database gets closed before its directory is removed (unless it is still \
+        //   referenced elsewhere).
+        self.store.take();
+
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+impl StoreKVOIDNormalization {
+    pub fn from_config(steps: &[ConfigStoreKVOIDNormalization]) -> StoreKVOIDNormalization {
+        StoreKVOIDNormalization {
+            trim: steps.contains(&ConfigStoreKVOIDNormalization::Trim),
+            lowercase: steps.contains(&ConfigStoreKVOIDNormalization::Lowercase),
+            nfc: steps.contains(&ConfigStoreKVOIDNormalization::Nfc),
+        }
+    }
+
+    pub fn normalize<'o>(&self, oid: &'o str) -> Cow<'o, str> {
+        // Notice: steps are applied in a fixed order, and each step is idempotent (normalizing \
+        //   an already-normalized OID leaves it unchanged).
+        let mut normalized = Cow::Borrowed(if self.trim { oid.trim() } else { oid });
+
+        if self.lowercase {
+            let lowercased = normalized.to_lowercase();
+
+            if lowercased != normalized {
+                normalized = Cow::Owned(lowercased);
+            }
+        }
+
+        if self.nfc && !is_nfc(&normalized) {
+            normalized = Cow::Owned(normalized.nfc().collect());
+        }
+
+        normalized
+    }
+
+    fn encode(&self) -> String {
+        let mut steps = Vec::new();
+
+        for (step, is_enabled) in &[
+            ("trim", self.trim),
+            ("lowercase", self.lowercase),
+            ("nfc", self.nfc),
+        ] {
+            if *is_enabled {
+                steps.push(*step);
+            }
+        }
+
+        steps.join(",")
+    }
+
+    fn decode(value: &str) -> Option<StoreKVOIDNormalization> {
+        let mut oid_normalization = StoreKVOIDNormalization::default();
+
+        for step in value.split(',').filter(|step| !step.is_empty()) {
+            match step {
+                "trim" => oid_normalization.trim = true,
+                "lowercase" => oid_normalization.lowercase = true,
+                "nfc" => oid_normalization.nfc = true,
+                _ => return None,
+            }
+        }
+
+        Some(oid_normalization)
+    }
+}
+
+impl StoreKVBlockCache {
+    fn new(capacity: usize) -> StoreKVBlockCache {
+        StoreKVBlockCache {
+            cache: Cache::new_lru_cache(capacity),
+            capacity,
+        }
+    }
+}
+
+impl StoreKVKey {
+    pub fn from_atom(collection_hash: StoreKVAtom) -> StoreKVKey {
+        StoreKVKey { collection_hash }
+    }
+
+    pub fn from_str(collection_str: &str) -> StoreKVKey {
+        StoreKVKey {
+            collection_hash: StoreKeyerHasher::to_compact(collection_str),
+        }
+    }
+}
+
+impl fmt::Display for StoreKVKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{:x?}>", self.collection_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    const MEMTABLE_ENTRIES_PROPERTY: &str = "rocksdb.num-entries-active-mem-table";
+
+    fn flush_object(
+        action: &StoreKVAction,
+        iid: StoreObjectIID,
+        oid: StoreObjectOID,
+        iid_terms_hashed: &[StoreTermHashed],
+    ) -> Result<u32, StoreKVError> {
+        let mut batch = action.batch();
+
+        let count = action.batch_flush_bucket(&mut batch, iid, oid, iid_terms_hashed)?;
+
+        batch.commit().and(Ok(count))
+    }
+
+    #[test]
+    fn it_acquires_database() {
+        assert!(StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:1").is_ok());
+    }
+
+    #[test]
+    fn it_reuses_pooled_databases() {
+        let acquire = || {
+            StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:46")
+                .unwrap()
+                .unwrap()
+        };
+
+        let store = acquire();
+
+        // Concurrent acquires share the same database handle (rather than opening it again)
+        let handles: Vec<_> = (0..4).map(|_| thread::spawn(acquire)).collect();
+
+        for handle in handles {
+            assert!(Arc::ptr_eq(&store, &handle.join().unwrap()));
+        }
+
+        assert!(Arc::ptr_eq(&store, &acquire()));
+    }
+
+    #[test]
+    fn it_maps_collections_to_directories() {
+        let path =
+            |collection| StoreKVBuilder::path(StoreKVKey::from_str(collection).collection_hash);
+
+        // Each collection gets its own directory, right under the store path (collection \
+        //   names are hashed, thus they never get to form a path)
+        for collection in &["c:test:1", "../c:test:1", "c:test/../../1"] {
+            assert_eq!(
+                path(collection).parent(),
+                Some(APP_CONF.store.kv.path.as_path())
+            );
+        }
+
+        assert_ne!(path("c:test:1"), path("c:test:2"));
+        assert_eq!(path("c:test:1"), path("c:test:1"));
+    }
+
+    #[test]
+    fn it_janitors_database() {
+        StoreKVPool::janitor();
+    }
+
+    #[test]
+    fn it_trims_database() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:14")
+            .unwrap()
+            .unwrap();
+
+        assert!(store.put(&[0], &[1; 65536]).is_ok());
+
+        let memtable_size_before = store.memtable_size().unwrap_or(0);
+
+        assert!(memtable_size_before >= 65536);
+
+        StoreKVPool::trim(true);
+
+        assert!(store.memtable_size().unwrap_or(0) < memtable_size_before);
+    }
+
+    #[test]
+    fn it_compacts_idle_databases_once() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:62")
+            .unwrap()
+            .unwrap();
+
+        // Simulate a database that was used after it was last compacted, a while ago
+        *store.last_compacted.write().unwrap() = UNIX_EPOCH;
+        *store.last_used.write().unwrap() = UNIX_EPOCH + Duration::from_secs(1);
+
+        StoreKVPool::compact(false);
+
+        let last_compacted = *store.last_compacted.read().unwrap();
+
+        assert!(last_compacted > UNIX_EPOCH + Duration::from_secs(1));
+
+        // Not used since last compacted (not compacted again)
+        StoreKVPool::compact(false);
+
+        assert_eq!(*store.last_compacted.read().unwrap(), last_compacted);
+    }
+
+    #[test]
+    fn it_syncs_databases() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:21")
+            .unwrap()
+            .unwrap();
+
+        // Write without the WAL (as when the WAL is disabled), which only lives in memtables
+        let mut write_options = WriteOptions::default();
+
+        write_options.disable_wal(true);
+
+        assert!(store
+            .database
+            .put_opt(&[0], &[2, 1], &write_options)
+            .is_ok());
+        assert!(store.int_property(MEMTABLE_ENTRIES_PROPERTY).unwrap_or(0) > 0);
+
+        assert!(StoreKVPool::sync_store(&store, false).is_ok());
+        assert_eq!(store.int_property(MEMTABLE_ENTRIES_PROPERTY), Some(0));
+        assert_eq!(store.get(&[0], None), Ok(Some(vec![2, 1])));
+
+        assert!(StoreKVPool::sync().is_ok());
+    }
+
+    #[test]
+    fn it_reports_database_stats() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:56")
+            .unwrap()
+            .unwrap();
+
+        assert!(store.put(&[0], &[5, 6]).is_ok());
+
+        let stats = store.stats();
+
+        assert!(stats.estimated_keys.is_some());
+        assert!(stats.memtable_size.unwrap_or(0) > 0);
+
+        // Unknown properties are reported as missing
+        assert_eq!(store.int_property("rocksdb.unknown-property"), None);
+
+        assert!(StoreKVPool::stats()
+            .iter()
+            .any(|(key, _)| *key == StoreKVKey::from_str("c:test:56")));
+    }
+
+    #[test]
+    fn it_flushes_all_databases() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:55")
+            .unwrap()
+            .unwrap();
+
+        assert!(store.put(&[0], &[5, 5]).is_ok());
+        assert!(store.int_property(MEMTABLE_ENTRIES_PROPERTY).unwrap_or(0) > 0);
+
+        assert!(StoreKVPool::flush_all().is_ok());
+
+        // Memtables got written to disk (ie. the write now lives in a table file)
+        assert_eq!(store.int_property(MEMTABLE_ENTRIES_PROPERTY), Some(0));
+        assert_eq!(store.get(&[0], None), Ok(Some(vec![5, 5])));
+    }
+
+    #[test]
+    fn it_closes_idle_databases_not_in_use() {
+        let (pool, access_lock) = (
+            Arc::new(RwLock::new(HashMap::new())),
+            Arc::new(RwLock::new(false)),
+        );
+
+        let keys: Vec<StoreKVKey> = (0..2)
+            .map(|index| StoreKVKey::from_str(&format!("c:test:54:{}", index)))
+            .collect();
+
+        for key in &keys {
+            let store = StoreKVBuilder::build(*key).unwrap();
+
+            *store.last_used.write().unwrap() = UNIX_EPOCH;
+
+            pool.write().unwrap().insert(*key, Arc::new(store));
+        }
+
+        // Idle databases still in use are kept open
+        let store_in_use = pool.read().unwrap().get(&keys[1]).unwrap().clone();
+
+        StoreKVPool::proceed_janitor("kv", &pool, 60, &access_lock);
+
+        assert!(!pool.read().unwrap().contains_key(&keys[0]));
+        assert!(pool.read().unwrap().contains_key(&keys[1]));
+
+        drop(store_in_use);
+
+        StoreKVPool::proceed_janitor("kv", &pool, 60, &access_lock);
+
+        assert!(pool.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_evicts_least_recently_used_databases() {
+        let mut pool: HashMap<StoreKVKey, StoreKVBox> = HashMap::new();
+
+        let keys: Vec<StoreKVKey> = (0..4)
+            .map(|index| StoreKVKey::from_str(&format!("c:test:30:{}", index)))
+            .collect();
+
+        // Open more databases than allowed, the first one being the coldest
+        for (index, key) in keys.iter().enumerate() {
+            StoreKVPool::close_evicted(StoreKVPool::evict(&mut pool, 2));
+
+            let store = StoreKVBuilder::build(*key).unwrap();
+
+            *store.last_used.write().unwrap() = UNIX_EPOCH + Duration::from_secs(index as u64);
+
+            pool.insert(*key, Arc::new(store));
+
+            assert!(pool.len() <= 2);
+        }
+
+        assert!(pool.contains_key(&keys[2]));
+        assert!(pool.contains_key(&keys[3]));
+
+        // Databases in use are never closed (even if they are the coldest)
+        let store_in_use = pool.get(&keys[2]).unwrap().clone();
+
+        assert_eq!(StoreKVPool::evict(&mut pool, 2).len(), 1);
+        assert!(pool.contains_key(&keys[2]));
+        assert!(!pool.contains_key(&keys[3]));
+
+        assert!(StoreKVPool::evict(&mut pool, 1).is_empty());
+        assert_eq!(pool.len(), 1);
+
+        drop(store_in_use);
+
+        let evicted = StoreKVPool::evict(&mut pool, 1);
+
+        assert!(pool.is_empty());
+        assert_eq!(evicted.len(), 1);
+        assert!(evicted[0].0 == keys[2]);
+
+        // Evicted databases are closed by their caller once the pool is released
+        StoreKVPool::close_evicted(evicted);
+    }
+
+    #[test]
+    fn it_recompresses_databases() {
+        let path = StoreKVBuilder::path(StoreKVKey::from_str("c:test:26").collection_hash);
+
+        // Write compressible data without compression
+        {
+            let database = StoreKVBuilder::open_path(&path, DBCompressionType::None).unwrap();
+
+            for index in 0..1000_u32 {
+                assert!(database.put(index.to_le_bytes(), [7; 256]).is_ok());
+            }
+
+            assert!(StoreKVPool::recompress_database(&database, &path).is_ok());
+        }
+
+        // Switch codec, and rewrite all data with it
+        let database = StoreKVBuilder::open_path(&path, DBCompressionType::Zstd).unwrap();
+
+        let (size_before, size_after) = StoreKVPool::recompress_database(&database, &path).unwrap();
+
+        assert!(size_before > 0);
+        assert!(size_after < size_before);
+        assert_eq!(database.get(999_u32.to_le_bytes()), Ok(Some(vec![7; 256])));
+
+        // Recompressing a collection that does not exist fails
+        assert!(StoreKVPool::recompress("c:test:26:none").is_err());
+    }
+
+    #[test]
+    fn it_erases_a_single_bucket() {
+        let acquire = || StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:48").unwrap();
+        let (action, other_action) = (
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:48").unwrap(), acquire()),
+            StoreKVActionBuilder::access(
+                StoreItemPart::from_str("b:test:48:other").unwrap(),
+                acquire(),
+            ),
+        );
+
+        for action in &[&action, &other_action] {
+            assert!(action.batch_erase_bucket().is_ok());
+
+            assert_eq!(action.get_or_assign_iid("o:1", None), Ok(0));
+            assert!(action.set_term_to_iids(1, &[0]).is_ok());
+            assert!(action.set_iid_to_terms(0, &[1]).is_ok());
+
+            // Notice: the last possible key of a range must be erased as well.
+            assert!(action.set_iid_to_time(u32::MAX, 1000).is_ok());
+        }
+
+        assert_eq!(action.batch_erase_bucket(), Ok(1));
+
+        assert_eq!(action.get_oid_to_iid("o:1"), Ok(None));
+        assert_eq!(action.get_term_to_iids(1), Ok(None));
+        assert_eq!(action.get_iid_to_terms(0), Ok(None));
+        assert_eq!(action.get_iid_to_time(u32::MAX), Ok(None));
+        assert_eq!(action.get_oid_count(), Ok(0));
+
+        // Keys from other buckets of the collection are kept as-is
+        assert_eq!(other_action.get_oid_to_iid("o:1"), Ok(Some(0)));
+        assert_eq!(other_action.get_term_to_iids(1), Ok(Some(vec![0])));
+        assert_eq!(other_action.get_iid_to_terms(0), Ok(Some(vec![1])));
+        assert_eq!(other_action.get_iid_to_time(u32::MAX), Ok(Some(1000)));
+        assert_eq!(other_action.get_oid_count(), Ok(1));
+    }
+
+    #[test]
+    fn it_round_trips_term_to_iids() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:44").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:44").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        // Store a few hundred IIDs, which must be read back in the same order
+        let iids: Vec<StoreObjectIID> = (0..400).map(|index| 400 - index * 7 % 397).collect();
+
+        assert_eq!(action.get_term_to_iids(1), Ok(None));
+        assert!(action.set_term_to_iids(1, &iids).is_ok());
+        assert_eq!(action.get_term_to_iids(1), Ok(Some(iids.clone())));
+        assert_eq!(action.get_terms_size(&[1]), vec![400]);
+
+        // An empty list is stored as such (it is not mistaken for a missing term)
+        assert!(action.set_term_to_iids(2, &[]).is_ok());
+        assert_eq!(action.get_term_to_iids(2), Ok(Some(Vec::new())));
+
+        assert!(action.delete_term_to_iids(1).is_ok());
+        assert_eq!(action.get_term_to_iids(1), Ok(None));
+    }
+
+    #[test]
+    fn it_commits_batches_atomically() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:45").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:45").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        // Index an object in a single batch (batched and single writes use the same keys)
+        let mut batch = action.batch();
+
+        batch
+            .set_oid_to_iid("o:1", 1)
+            .set_iid_to_oid(1, "o:1")
+            .set_iid_to_terms(1, &[7, 8])
+            .set_term_to_iids(7, &[1])
+            .set_term_to_iids(8, &[1]);
+
+        assert_eq!(batch.len(), 7);
+        assert!(batch.commit().is_ok());
+
+        assert!(action.set_term_to_iids(7, &[2, 1]).is_ok());
+
+        assert_eq!(action.get_oid_to_iid("o:1"), Ok(Some(1)));
+        assert_eq!(action.get_iid_to_oid(1), Ok(Some("o:1".to_string())));
+        assert_eq!(action.get_iid_to_terms(1), Ok(Some(vec![7, 8])));
+        assert_eq!(action.get_term_to_iids(7), Ok(Some(vec![2, 1])));
+        assert_eq!(action.get_terms_size(&[7, 8]), vec![2, 1]);
+
+        // A malformed operation fails the whole batch (nothing gets written)
+        let mut batch = action.batch();
+
+        batch
+            .set_term_to_iids(9, &[3])
+            .set_iid_to_oid(3, "")
+            .delete_oid_to_iid("o:1");
+
+        assert_eq!(batch.commit(), Err(StoreKVError::Serialization));
+
+        assert_eq!(action.get_term_to_iids(9), Ok(None));
+        assert_eq!(action.get_iid_to_oid(3), Ok(None));
+        assert_eq!(action.get_oid_to_iid("o:1"), Ok(Some(1)));
+
+        // Batches are bound to a store
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:45").unwrap(), None);
+
+        assert!(action.batch().is_empty());
+        assert_eq!(action.batch().commit(), Err(StoreKVError::NotFound));
+
+        // Batches can only be continued on buckets from the same store
+        let (action, other_action) = (
+            StoreKVActionBuilder::access(
+                StoreItemPart::from_str("b:test:45").unwrap(),
+                StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:45").unwrap(),
+            ),
+            StoreKVActionBuilder::access(
+                StoreItemPart::from_str("b:test:45").unwrap(),
+                StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:69").unwrap(),
+            ),
+        );
+
+        let mut batch = action.batch();
+
+        batch.set_term_to_iids(10, &[4]);
+
+        assert_eq!(
+            batch.switch(&other_action).unwrap().commit(),
+            Err(StoreKVError::Serialization)
+        );
+        assert_eq!(action.get_term_to_iids(10), Ok(None));
+    }
+
+    #[test]
+    fn it_reads_from_snapshots() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:70").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:70").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+        assert!(action.set_term_to_iids(1, &[2, 1]).is_ok());
+        assert!(action.set_iid_to_oid(1, "o:1").is_ok());
+
+        let snapshot = action.snapshot();
+        let snapshot_action = action.with_snapshot(snapshot.as_ref());
+
+        // Writes committed after the snapshot is taken are not seen from the snapshot
+        let mut batch = action.batch();
+
+        batch
+            .set_term_to_iids(1, &[3, 2, 1])
+            .set_term_to_iids(4, &[3])
+            .set_iid_to_oid(3, "o:3");
+
+        assert!(batch.commit().is_ok());
+
+        assert_eq!(action.get_term_to_iids(1), Ok(Some(vec![3, 2, 1])));
+        assert_eq!(snapshot_action.get_term_to_iids(1), Ok(Some(vec![2, 1])));
+        assert_eq!(
+            snapshot_action
+                .get_term_to_iids_pinned(1)
+                .map(|iids| iids.map(|iids| iids.count())),
+            Ok(Some(2))
+        );
+        assert_eq!(
+            snapshot_action.get_terms_to_iids(&[1, 4]),
+            Ok(vec![Some(vec![2, 1]), None])
+        );
+        assert_eq!(snapshot_action.get_iid_to_oid(3), Ok(None));
+        assert_eq!(action.get_iid_to_oid(3), Ok(Some("o:3".to_string())));
+
+        // Writes from the snapshot are refused
+        assert_eq!(
+            snapshot_action.set_iid_to_oid(4, "o:4"),
+            Err(StoreKVError::ReadOnly)
+        );
+        assert_eq!(
+            snapshot_action.batch_erase_bucket(),
+            Err(StoreKVError::ReadOnly)
+        );
+        assert_eq!(action.get_iid_to_oid(4), Ok(None));
+
+        // Actions without a snapshot read the latest state
+        assert_eq!(
+            action.with_snapshot(None).get_term_to_iids(1),
+            Ok(Some(vec![3, 2, 1]))
+        );
+    }
+
+    #[test]
+    fn it_reads_from_pending_writes() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:72").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:72").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+        assert!(action.set_term_to_iids(1, &[1]).is_ok());
+        assert!(action.set_iid_to_oid(1, "o:1").is_ok());
+
+        let pending = StoreKVPending::default();
+        let pending_action = action.with_pending(&pending);
+
+        // Writes committed from a pending action get queued, and are seen from it only
+        let mut batch = pending_action.batch();
+
+        batch
+            .set_term_to_iids(1, &[2, 1])
+            .set_iid_to_oid(2, "o:2")
+            .delete_iid_to_oid(1);
+
+        assert!(batch.commit().is_ok());
+
+        assert!(!pending.is_empty());
+        assert_eq!(pending_action.get_term_to_iids(1), Ok(Some(vec![2, 1])));
+        assert_eq!(
+            pending_action.get_iid_to_oid(2),
+            Ok(Some("o:2".to_string()))
+        );
+        assert_eq!(pending_action.get_iid_to_oid(1), Ok(None));
+        assert_eq!(action.get_term_to_iids(1), Ok(Some(vec![1])));
+        assert_eq!(action.get_iid_to_oid(2), Ok(None));
+
+        // Queued writes get written at once
+        assert!(StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:72")
+            .unwrap()
+            .unwrap()
+            .commit_pending(pending)
+            .is_ok());
+
+        assert_eq!(action.get_term_to_iids(1), Ok(Some(vec![2, 1])));
+        assert_eq!(action.get_iid_to_oid(2), Ok(Some("o:2".to_string())));
+        assert_eq!(action.get_iid_to_oid(1), Ok(None));
+    }
+
+    #[test]
+    fn it_flushes_objects_in_one_batch() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:67").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:67").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        let (iid_1, iid_2) = (
+            action.get_or_assign_iid("o:1", None).unwrap(),
+            action.get_or_assign_iid("o:2", None).unwrap(),
+        );
+
+        for (iid, terms_hashed) in &[(iid_1, vec![1, 2]), (iid_2, vec![2])] {
+            for term_hashed in terms_hashed {
+                assert!(action.batch_link_term(*term_hashed, *iid, None).is_ok());
+            }
+
+            assert!(action.set_iid_to_terms(*iid, terms_hashed).is_ok());
+        }
+
+        assert_eq!(action.get_oid_count(), Ok(2));
+        assert_eq!(action.get_term_count(), Ok(2));
+
+        // Nothing gets written until the batch is committed
+        let mut batch = action.batch();
+
+        assert_eq!(
+            action.batch_flush_bucket(&mut batch, iid_1, "o:1", &[1, 2]),
+            Ok(2)
+        );
+
+        assert_eq!(action.get_oid_to_iid("o:1"), Ok(Some(iid_1)));
+        assert_eq!(action.get_term_to_iids(1), Ok(Some(vec![iid_1])));
+
+        assert!(batch.commit().is_ok());
+
+        assert_eq!(action.get_oid_to_iid("o:1"), Ok(None));
+        assert_eq!(action.get_iid_to_terms(iid_1), Ok(None));
+        assert_eq!(action.get_term_to_iids(1), Ok(None));
+        assert_eq!(action.get_term_to_iids(2), Ok(Some(vec![iid_2])));
+        assert_eq!(action.get_oid_count(), Ok(1));
+        assert_eq!(action.get_term_count(), Ok(1));
+
+        // Objects drained from several term lists in a batch get updated once
+        let iid_3 = action.get_or_assign_iid("o:3", None).unwrap();
+
+        assert!(action.set_iid_to_terms(iid_2, &[2, 3]).is_ok());
+        assert!(action.set_iid_to_terms(iid_3, &[2, 3, 4]).is_ok());
+
+        let mut batch = action.batch();
+
+        batch
+            .truncate_objects(2, vec![iid_2, iid_3])
+            .truncate_objects(3, vec![iid_2, iid_3]);
+
+        assert!(batch.commit().is_ok());
+
+        assert_eq!(action.get_oid_to_iid("o:2"), Ok(None));
+        assert_eq!(action.get_iid_to_terms(iid_3), Ok(Some(vec![4])));
+        assert_eq!(action.get_oid_count(), Ok(1));
+    }
+
+    #[test]
+    fn it_reports_store_errors() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:47").unwrap();
+        let action = StoreKVActionBuilder::access(
+            StoreItemPart::from_str("b:test:47").unwrap(),
+            store.clone(),
+        );
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        // Missing keys are not errors, while undecodable values are
+        assert_eq!(action.get_iid_to_time(1), Ok(None));
+
+        let store_key = StoreKeyerBuilder::iid_to_time("b:test:47", 1);
+
+        assert!(store
+            .unwrap()
+            .put(&store_key.as_bytes(), &[0x01, 0x02])
+            .is_ok());
+
+        assert_eq!(action.get_iid_to_time(1), Err(StoreKVError::Serialization));
+
+        // Writes to a store that does not exist fail
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:47").unwrap(), None);
+
+        assert_eq!(action.set_iid_to_time(1, 1000), Err(StoreKVError::NotFound));
+        assert_eq!(
+            StoreKVError::NotFound.to_string(),
+            "store not found".to_string()
+        );
+    }
+
+    #[test]
+    fn it_scans_iid_indexes() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:53").unwrap();
+        let (action, other_action) = (
+            StoreKVActionBuilder::access(
+                StoreItemPart::from_str("b:test:53").unwrap(),
+                store.clone(),
+            ),
+            StoreKVActionBuilder::access(
+                StoreItemPart::from_str("b:test:53:other").unwrap(),
+                store,
+            ),
+        );
+
+        assert!(action.batch_erase_bucket().is_ok());
+        assert!(other_action.batch_erase_bucket().is_ok());
+
+        assert!(action.set_iid_to_terms(2, &[20, 21]).is_ok());
+        assert!(action.set_iid_to_terms(1, &[10]).is_ok());
+        assert!(action.set_iid_to_oid(1, "o:1").is_ok());
+        assert!(action.set_iid_to_boost(1, 2.0).is_ok());
+        assert!(other_action.set_iid_to_terms(3, &[30]).is_ok());
+
+        // Scans only yield entries from the requested index, for the accessed bucket
+        assert_eq!(
+            action.scan_iid_to_terms().collect::<Vec<_>>(),
+            vec![(1, vec![10]), (2, vec![20, 21])]
+        );
+        assert_eq!(
+            action.scan_iid_to_oid().collect::<Vec<_>>(),
+            vec![(1, "o:1".to_string())]
+        );
+        assert_eq!(
+            other_action.scan_iid_to_terms().collect::<Vec<_>>(),
+            vec![(3, vec![30])]
+        );
+        assert_eq!(other_action.scan_iid_to_oid().count(), 0);
+    }
+
+    #[test]
+    fn it_scans_terms() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:60").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:60").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        assert!(action.set_term_to_iids(9, &[2, 1]).is_ok());
+        assert!(action.set_term_to_iids(4, &[1]).is_ok());
+        assert!(action.set_iid_to_terms(1, &[4, 9]).is_ok());
+
+        let terms: Vec<(StoreTermHashed, Vec<StoreObjectIID>)> = action
+            .scan_terms()
+            .map(|(term_hashed, postings)| (term_hashed, postings.into_iids()))
+            .collect();
+
+        assert_eq!(terms, vec![(4, vec![1]), (9, vec![2, 1])]);
+    }
+
+    #[test]
+    fn it_pops_next_iids_concurrently() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:52").unwrap();
+        let action = StoreKVActionBuilder::access(
+            StoreItemPart::from_str("b:test:52").unwrap(),
+            store.clone(),
+        );
+
+        assert!(action.batch_erase_bucket().is_ok());
+        assert_eq!(action.pop_next_iid(), Ok(0));
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let store = store.clone();
+
+                thread::spawn(move || {
+                    let action = StoreKVActionBuilder::access(
+                        StoreItemPart::from_str("b:test:52").unwrap(),
+                        store,
+                    );
+
+                    (0..25)
+                        .map(|_| action.pop_next_iid().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut iids: Vec<StoreObjectIID> = workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect();
+
+        iids.sort_unstable();
+        iids.dedup();
+
+        // No IID got handed out twice, and the increment accounts for all of them
+        assert_eq!(iids, (1..101).collect::<Vec<_>>());
+        assert_eq!(action.pop_next_iid(), Ok(101));
+        assert_eq!(action.get_or_assign_iid("o:1", None), Ok(102));
+    }
+
+    #[test]
+    fn it_recovers_missing_iid_increments() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:63").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:63").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        for (index, oid) in ["o:1", "o:2", "o:3"].iter().enumerate() {
+            assert_eq!(
+                action.get_or_assign_iid(oid, None),
+                Ok(index as StoreObjectIID)
+            );
+        }
+
+        // Lose the increment, while objects remain in the bucket
+        assert!(action.delete_meta_to_value(StoreMetaKey::IIDIncr).is_ok());
+
+        assert_eq!(action.get_or_assign_iid("o:4", None), Ok(3));
+        assert_eq!(action.pop_next_iid(), Ok(4));
+
+        // Empty buckets start over from the first IID
+        assert!(action.batch_erase_bucket().is_ok());
+        assert_eq!(action.pop_next_iid(), Ok(0));
+    }
+
+    #[test]
+    fn it_lists_collections_and_buckets() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:64").unwrap();
+        let action = |bucket| {
+            StoreKVActionBuilder::access(StoreItemPart::from_str(bucket).unwrap(), store.clone())
+        };
+
+        for bucket in &["b:test:64:1", "b:test:64:2", "b:test:64:3"] {
+            assert!(action(bucket).batch_erase_bucket().is_ok());
+        }
+
+        assert_eq!(
+            store.as_ref().unwrap().name(),
+            Some("c:test:64".to_string())
+        );
+        assert!(StoreKVPool::list_collections()
+            .unwrap()
+            .contains(&"c:test:64".to_string()));
+        assert_eq!(StoreKVPool::list_buckets("c:test:64"), Ok(vec![]));
+
+        // Buckets are named once an object is pushed to them
+        assert!(action("b:test:64:2").get_or_assign_iid("o:1", None).is_ok());
+        assert!(action("b:test:64:1").get_or_assign_iid("o:1", None).is_ok());
+
+        assert_eq!(
+            StoreKVPool::list_buckets("c:test:64"),
+            Ok(vec!["b:test:64:1".to_string(), "b:test:64:2".to_string()])
+        );
+
+        // Names are not swapped along with bucket contents
+        assert_eq!(
+            action("b:test:64:1").batch_swap_bucket(&action("b:test:64:3")),
+            Ok(1)
+        );
+        assert_eq!(
+            StoreKVPool::list_buckets("c:test:64"),
+            Ok(vec!["b:test:64:2".to_string(), "b:test:64:3".to_string()])
+        );
+
+        assert!(action("b:test:64:2").batch_erase_bucket().is_ok());
+        assert_eq!(
+            StoreKVPool::list_buckets("c:test:64"),
+            Ok(vec!["b:test:64:3".to_string()])
+        );
+        assert_eq!(StoreKVPool::list_buckets("c:test:64:missing"), Ok(vec![]));
+    }
+
+    #[test]
+    fn it_registers_collections() {
+        let registry = || {
+            let _registry = STORE_REGISTRY.lock().unwrap();
+
+            StoreKVPool::read_registry().unwrap().unwrap_or_default()
+        };
+
+        assert!(StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:73")
+            .unwrap()
+            .is_some());
+
+        // Collections opened by name are listed from the registry
+        assert!(StoreKVPool::list_collections()
+            .unwrap()
+            .contains(&"c:test:73".to_string()));
+        assert!(registry().contains(&"c:test:73".to_string()));
+
+        // The registry gets rebuilt from collections if removed
+        assert!(StoreKVPool::unregister_all().is_ok());
+
+        assert!(StoreKVPool::list_collections()
+            .unwrap()
+            .contains(&"c:test:73".to_string()));
+        assert!(registry().contains(&"c:test:73".to_string()));
+
+        // Erased collections are removed from the registry
+        assert_eq!(StoreKVActionBuilder::erase("c:test:73", None), Ok(1));
+
+        assert!(!StoreKVPool::list_collections()
+            .unwrap()
+            .contains(&"c:test:73".to_string()));
+        assert!(!registry().contains(&"c:test:73".to_string()));
+    }
+
+    #[test]
+    fn it_detects_bucket_collisions() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:66").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:66").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        // Buckets with no recorded name, or with their own name recorded, pass
+        assert_eq!(action.check_bucket_name(), Ok(()));
+        assert!(action.get_or_assign_iid("o:1", None).is_ok());
+        assert_eq!(action.check_bucket_name(), Ok(()));
+
+        // Another bucket recorded under the same bucket hash collides (if guarded)
+        assert!(action
+            .set_meta_to_value(
+                StoreMetaKey::BucketName,
+                StoreMetaValue::BucketName("b:test:66:other".to_string())
+            )
+            .is_ok());
+
+        assert_eq!(action.check_bucket_name(), Err(StoreKVError::Collision));
+        assert_eq!(action.check_bucket(), Ok(()));
+    }
+
+    #[test]
+    fn it_backs_up_to_checkpoints() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:51").unwrap();
+        let action = StoreKVActionBuilder::access(
+            StoreItemPart::from_str("b:test:51").unwrap(),
+            store.clone(),
+        );
+
+        let destination = env::temp_dir().join("sonic-test-checkpoint-51");
+
+        let _ = fs::remove_dir_all(&destination);
+
+        assert!(action.batch_erase_bucket().is_ok());
+        assert_eq!(action.get_or_assign_iid("o:1", None), Ok(0));
+
+        // Writes made after a checkpoint are not part of it
+        let store = store.unwrap();
+
+        assert!(store.backup(&destination).is_ok());
+        assert_eq!(action.get_or_assign_iid("o:2", None), Ok(1));
+
+        let checkpoint = Arc::new(StoreKVBuilder::open_at(&destination).unwrap());
+        let checkpoint_action = StoreKVActionBuilder::access(
+            StoreItemPart::from_str("b:test:51").unwrap(),
+            Some(checkpoint),
+        );
+
+        assert_eq!(checkpoint_action.get_oid_to_iid("o:1"), Ok(Some(0)));
+        assert_eq!(checkpoint_action.get_oid_to_iid("o:2"), Ok(None));
+        assert_eq!(checkpoint_action.get_oid_count(), Ok(1));
+        assert_eq!(action.get_oid_count(), Ok(2));
+
+        // A checkpoint cannot overwrite an existing path
+        assert!(matches!(
+            store.backup(&destination),
+            Err(StoreKVError::Database(_))
+        ));
+    }
+
+    #[test]
+    fn it_opens_databases_with_compression_level() {
+        let path = env::temp_dir().join("sonic-test-compression-level");
+
+        let _ = fs::remove_dir_all(&path);
+
+        {
+            let database = DB::open(
+                &StoreKVBuilder::configure(DBCompressionType::Zstd, Some(19)),
+                &path,
+            )
+            .unwrap();
+
+            assert!(database.put(&[0], &[1; 64]).is_ok());
+            assert!(database.flush().is_ok());
+            assert_eq!(database.get(&[0]), Ok(Some(vec![1; 64])));
+        }
+
+        let _ = fs::remove_dir_all(&path);
+    }
+
+    #[test]
+    fn it_opens_temporary_databases() {
+        let path = {
+            let temporary = StoreKVBuilder::open_temporary("temporary");
+            let action = StoreKVActionBuilder::access(
+                StoreItemPart::from_str("b:test:58").unwrap(),
+                temporary.store.clone(),
+            );
+
+            assert_eq!(action.get_iid_to_oid(0), Ok(None));
+            assert!(action.set_iid_to_oid(0, "o:1").is_ok());
+            assert_eq!(action.get_iid_to_oid(0), Ok(Some("o:1".to_string())));
+            assert!(action.delete_iid_to_oid(0).is_ok());
+            assert_eq!(action.get_iid_to_oid(0), Ok(None));
+
+            temporary.path.clone()
+        };
+
+        // Temporary databases are removed once dropped
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn it_swaps_buckets_in_chunks() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:75").unwrap();
+
+        let (action, with_action) = (
+            StoreKVActionBuilder::access(
+                StoreItemPart::from_str("b:test:75").unwrap(),
+                store.clone(),
+            ),
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:75:with").unwrap(), store),
+        );
+
+        assert!(action.batch_erase_bucket().is_ok());
+        assert!(with_action.batch_erase_bucket().is_ok());
+
+        // Store more terms than a chunk holds, partly stored at the same keys in both buckets
+        let count_terms = SWAP_CHUNK_KEYS as StoreTermHashed * 2;
+
+        for term_hashed in 0..count_terms {
+            assert!(action.set_term_to_iids(term_hashed, &[1]).is_ok());
+        }
+
+        for term_hashed in (count_terms - 10)..(count_terms + 10) {
+            assert!(with_action.set_term_to_iids(term_hashed, &[2]).is_ok());
+        }
+
+        assert!(action.batch_swap_bucket(&with_action).is_ok());
+
+        for term_hashed in 0..(count_terms + 10) {
+            let (iids, with_iids) = (
+                action.get_term_to_iids(term_hashed).unwrap(),
+                with_action.get_term_to_iids(term_hashed).unwrap(),
+            );
+
+            assert_eq!(
+                iids,
+                if term_hashed >= count_terms - 10 {
+                    Some(vec![2])
+                } else {
+                    None
+                }
+            );
+            assert_eq!(
+                with_iids,
+                if term_hashed < count_terms {
+                    Some(vec![1])
+                } else {
+                    None
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn it_migrates_key_formats() {
+        let path = env::temp_dir().join("sonic-test-migrate-57");
+
+        let _ = fs::remove_dir_all(&path);
+
+        // Create a database with narrow keys, with no recorded key format
+        {
+            let database = StoreKVBuilder::open_path(&path, StoreKVBuilder::compression()).unwrap();
+
+            let mut key = vec![2, 0, 0, 0, 0];
+
+            StoreKeyerLayout::Narrow.encode_bucket("b:test:57", &mut key[1..]);
+            key.extend_from_slice(&StoreKeyerHasher::to_compact("o:1").to_le_bytes());
+
+            assert!(database.put(&key, [4, 0, 0, 0]).is_ok());
+
+            // Keys from buckets with a recorded name are migrated as well
+            let mut key_named = vec![0, 0, 0, 0, 0];
+
+            StoreKeyerLayout::Narrow.encode_bucket("b:test:57:named", &mut key_named[1..]);
+            key_named.extend_from_slice(&StoreMetaKey::BucketName.as_u32().to_le_bytes());
+
+            assert!(database.put(&key_named, b"b:test:57:named").is_ok());
+
+            // Enough keys to be migrated in multiple chunks
+            for iid in 0..(MIGRATE_CHUNK_KEYS as u32 * 2) {
+                let mut key_chunked = vec![3, 0, 0, 0, 0];
+
+                StoreKeyerLayout::Narrow.encode_bucket("b:test:57", &mut key_chunked[1..]);
+                key_chunked.extend_from_slice(&iid.to_le_bytes());
+
+                assert!(database.put(&key_chunked, b"o:2").is_ok());
+            }
+        }
+
+        let count_keys = MIGRATE_CHUNK_KEYS * 2 + 2;
+
+        // Keys from unknown buckets cannot be migrated (the database is left untouched)
+        assert_eq!(
+            StoreKVBuilder::migrate(&path, &[], 1, 2),
+            Err(StoreKVError::Incompatible)
+        );
+        assert!(!StoreKVBuilder::path_with_suffix(&path, "migrate").exists());
+
+        assert_eq!(
+            StoreKVBuilder::migrate(&path, &["b:test:57"], 1, 2),
+            Ok(count_keys)
+        );
+        assert_eq!(
+            StoreKVBuilder::migrate(&path, &["b:test:57"], 1, 2),
+            Err(StoreKVError::Incompatible)
+        );
+
+        // Migrated keys are readable with wide keys
+        {
+            let database = StoreKVBuilder::open_path(&path, StoreKVBuilder::compression()).unwrap();
+
+            assert_eq!(
+                StoreKV::open_key_format(
+                    &database,
+                    StoreKeyerLayout::Wide,
+                    APP_CONF.store.kv.bucket_hash_seed,
+                    false
+                ),
+                Ok(())
+            );
+
+            let mut key = vec![2, 0, 0, 0, 0, 0, 0, 0, 0];
+
+            StoreKeyerLayout::Wide.encode_bucket("b:test:57", &mut key[1..]);
+            key.extend_from_slice(&StoreKeyerHasher::to_compact("o:1").to_le_bytes());
+
+            assert_eq!(database.get(&key), Ok(Some(vec![4, 0, 0, 0])));
+
+            let mut key_chunked = vec![3, 0, 0, 0, 0, 0, 0, 0, 0];
+
+            StoreKeyerLayout::Wide.encode_bucket("b:test:57", &mut key_chunked[1..]);
+            key_chunked.extend_from_slice(&(MIGRATE_CHUNK_KEYS as u32 + 1).to_le_bytes());
+
+            assert_eq!(database.get(&key_chunked), Ok(Some(b"o:2".to_vec())));
+        }
+
+        // Migrated keys are not readable with narrow keys, until migrated back
+        assert!(matches!(
+            StoreKVBuilder::open_at(&path),
+            Err(StoreKVError::Incompatible)
+        ));
+        assert_eq!(
+            StoreKVBuilder::migrate(&path, &["b:test:57"], 2, 1),
+            Ok(count_keys)
+        );
+
+        let store = Arc::new(StoreKVBuilder::open_at(&path).unwrap());
+
+        let action = StoreKVActionBuilder::access(
+            StoreItemPart::from_str("b:test:57").unwrap(),
+            Some(store.clone()),
+        );
+        let action_named = StoreKVActionBuilder::access(
+            StoreItemPart::from_str("b:test:57:named").unwrap(),
+            Some(store),
+        );
+
+        assert_eq!(action.get_oid_to_iid("o:1"), Ok(Some(4)));
+        assert!(matches!(
+            action_named.get_meta_to_value(StoreMetaKey::BucketName),
+            Ok(Some(StoreMetaValue::BucketName(name))) if name == "b:test:57:named"
+        ));
+    }
+
+    #[test]
+    fn it_records_key_formats() {
+        let path = StoreKVBuilder::path(StoreKVKey::from_str("c:test:50").collection_hash);
+
+        let _ = fs::remove_dir_all(&path);
+
+        let database = StoreKVBuilder::open_path(&path, DBCompressionType::None).unwrap();
+        let format_key = StoreKeyerBuilder::collection_to_key_format();
+
+        assert!(database.delete(format_key).is_ok());
+
+        // The key format is recorded on creation, and cannot be changed afterwards
+        assert_eq!(
+            StoreKV::open_key_format(&database, StoreKeyerLayout::Wide, 0, true),
+            Ok(())
+        );
+        assert_eq!(database.get(format_key), Ok(Some(vec![2])));
+
+        assert_eq!(
+            StoreKV::open_key_format(&database, StoreKeyerLayout::Narrow, 0, false),
+            Err(StoreKVError::Incompatible)
+        );
+        assert_eq!(
+            StoreKV::open_key_format(&database, StoreKeyerLayout::Wide, 0, false),
+            Ok(())
+        );
+
+        // Existing databases with no recorded key format use narrow keys
+        assert!(database.delete(format_key).is_ok());
+
+        assert_eq!(
+            StoreKV::open_key_format(&database, StoreKeyerLayout::Wide, 0, false),
+            Err(StoreKVError::Incompatible)
+        );
+        assert_eq!(
+            StoreKV::open_key_format(&database, StoreKeyerLayout::Narrow, 0, false),
+            Ok(())
+        );
+        assert_eq!(database.get(format_key), Ok(Some(vec![1])));
+
+        // Databases created with another seed cannot be opened either
+        assert_eq!(
+            StoreKV::open_key_format(&database, StoreKeyerLayout::Narrow, 7, false),
+            Err(StoreKVError::Incompatible)
+        );
+
+        assert!(database
+            .put(
+                format_key,
+                StoreKV::encode_key_format(StoreKeyerLayout::Wide, 7)
+            )
+            .is_ok());
+
+        assert_eq!(database.get(format_key), Ok(Some(vec![2, 7, 0, 0, 0])));
+        assert_eq!(
+            StoreKV::open_key_format(&database, StoreKeyerLayout::Wide, 7, false),
+            Ok(())
+        );
+        assert_eq!(
+            StoreKV::open_key_format(&database, StoreKeyerLayout::Wide, 0, false),
+            Err(StoreKVError::Incompatible)
+        );
+    }
+
+    #[test]
+    fn it_normalizes_oids() {
+        let path = StoreKVBuilder::path(StoreKVKey::from_str("c:test:43").collection_hash);
+
+        let open = |steps: &[ConfigStoreKVOIDNormalization]| {
+            let database = StoreKVBuilder::open_path(&path, DBCompressionType::None).unwrap();
+
+            Arc::new(
+                StoreKVBuilder::build_store(database, StoreKVOIDNormalization::from_config(steps))
+                    .unwrap(),
+            )
+        };
+
+        let action = |store: &StoreKVBox| {
+            StoreKVActionBuilder::access(
+                StoreItemPart::from_str("b:test:43").unwrap(),
+                Some(store.clone()),
+            )
+        };
+
+        let _ = fs::remove_dir_all(&path);
+
+        // Normalization is recorded on creation, and applied to all OIDs
+        let store = open(&[
+            ConfigStoreKVOIDNormalization::Lowercase,
+            ConfigStoreKVOIDNormalization::Trim,
+        ]);
+
+        let iid = action(&store).get_or_assign_iid("  User@X ", None).unwrap();
+
+        assert_eq!(action(&store).get_or_assign_iid("user@x", None), Ok(iid));
+        assert_eq!(action(&store).get_oid_to_iid("USER@x"), Ok(Some(iid)));
+        assert_eq!(
+            action(&store).get_iid_to_oid(iid),
+            Ok(Some("user@x".to_string()))
+        );
+        assert_eq!(action(&store).get_oid_count(), Ok(1));
+
+        drop(store);
+
+        // Changing normalization of an existing store is refused (while the recorded one still \
+        //   opens, whatever the order its steps are listed in)
+        assert_eq!(
+            StoreKVBuilder::build_store(
+                StoreKVBuilder::open_path(&path, DBCompressionType::None).unwrap(),
+                StoreKVOIDNormalization::from_config(&[ConfigStoreKVOIDNormalization::Nfc])
+            )
+            .err(),
+            Some(StoreKVError::Incompatible)
+        );
+
+        let store = open(&[
+            ConfigStoreKVOIDNormalization::Trim,
+            ConfigStoreKVOIDNormalization::Lowercase,
+        ]);
+
+        assert_eq!(action(&store).get_oid_to_iid(" user@X"), Ok(Some(iid)));
+    }
+
+    #[test]
+    fn it_applies_oid_normalization_steps() {
+        let normalize = |steps: &[ConfigStoreKVOIDNormalization], oid: &str| {
+            StoreKVOIDNormalization::from_config(steps)
+                .normalize(oid)
+                .into_owned()
+        };
+
+        assert_eq!(normalize(&[], " User@X "), " User@X ");
+        assert_eq!(
+            normalize(&[ConfigStoreKVOIDNormalization::Trim], " User@X "),
+            "User@X"
+        );
+        assert_eq!(
+            normalize(&[ConfigStoreKVOIDNormalization::Nfc], "Cafe\u{301}"),
+            "Caf\u{e9}"
+        );
+        assert_eq!(
+            normalize(
+                &[
+                    ConfigStoreKVOIDNormalization::Nfc,
+                    ConfigStoreKVOIDNormalization::Lowercase
+                ],
+                "CAFE\u{301}"
+            ),
+            "caf\u{e9}"
+        );
+
+        let oid_normalization = StoreKVOIDNormalization::from_config(&[
+            ConfigStoreKVOIDNormalization::Trim,
+            ConfigStoreKVOIDNormalization::Nfc,
+        ]);
+
+        assert_eq!(
+            StoreKVOIDNormalization::decode(&oid_normalization.encode()),
+            Some(oid_normalization)
+        );
+        assert_eq!(
+            StoreKVOIDNormalization::decode(""),
+            Some(StoreKVOIDNormalization::default())
+        );
+        assert_eq!(StoreKVOIDNormalization::decode("upper"), None);
+    }
+
+    #[test]
+    fn it_proceeds_primitives() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:2")
+            .unwrap()
+            .unwrap();
+
+        assert!(store.get(&[0], None).is_ok());
+        assert!(store.put(&[0], &[1, 0, 0, 0]).is_ok());
+        assert!(store.delete(&[0]).is_ok());
+    }
+
+    #[test]
+    fn it_proceeds_actions() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:3").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:3").unwrap(), store);
+
+        assert!(action.get_meta_to_value(StoreMetaKey::IIDIncr).is_ok());
+        assert!(action
+            .set_meta_to_value(StoreMetaKey::IIDIncr, StoreMetaValue::IIDIncr(1))
+            .is_ok());
+
+        assert!(action.get_term_to_iids(1).is_ok());
+        assert!(action.set_term_to_iids(1, &[0, 1, 2]).is_ok());
+        assert!(action.delete_term_to_iids(1).is_ok());
+
+        assert!(action.get_oid_to_iid(&"s".to_string()).is_ok());
+        assert!(action.set_oid_to_iid(&"s".to_string(), 4).is_ok());
+        assert!(action.delete_oid_to_iid(&"s".to_string()).is_ok());
+
+        assert!(action.get_iid_to_oid(4).is_ok());
+        assert!(action.set_iid_to_oid(4, &"s".to_string()).is_ok());
+        assert!(action.delete_iid_to_oid(4).is_ok());
+
+        assert!(action.get_iid_to_terms(4).is_ok());
+        assert!(action.set_iid_to_terms(4, &[45402]).is_ok());
+        assert!(action.delete_iid_to_terms(4).is_ok());
+
+        assert!(action.set_iid_to_boost(5, 2.5).is_ok());
+        assert_eq!(action.get_iid_to_boost(5), Ok(2.5));
+        assert!(action.delete_iid_to_boost(5).is_ok());
+        assert_eq!(action.get_iid_to_boost(5), Ok(STORE_OBJECT_BOOST_DEFAULT));
+
+        assert!(action
+            .set_iid_to_weights(6, &[(45402, 3.0), (1, 0.5)])
+            .is_ok());
+        assert_eq!(
+            action.get_iid_to_weights(6),
+            Ok(Some(vec![(45402, 3.0), (1, 0.5)]))
+        );
+        assert!(action.set_iid_to_weights(6, &[]).is_ok());
+        assert_eq!(action.get_iid_to_weights(6), Ok(None));
+
+        assert!(action.set_iid_to_boost(8, 2.0).is_ok());
+        assert!(action.set_iid_to_weights(8, &[(45402, 3.0)]).is_ok());
+        assert!(action.set_iid_to_terms(8, &[45402, 1]).is_ok());
+
+        let rankings = action.get_iids_to_ranking(&[8, 9], true).unwrap();
+
+        assert_eq!(rankings.len(), 2);
+        assert_eq!(rankings[0].boost, 2.0);
+        assert_eq!(rankings[0].weights, vec![(45402, 3.0)]);
+        assert_eq!(rankings[0].terms, Some(vec![45402, 1]));
+        assert_eq!(rankings[1].boost, STORE_OBJECT_BOOST_DEFAULT);
+        assert!(rankings[1].weights.is_empty());
+        assert_eq!(rankings[1].terms, None);
+
+        assert_eq!(
+            action.get_iids_to_ranking(&[8], false).unwrap()[0].terms,
+            None
+        );
+
+        assert!(action.set_iid_to_snippet(7, "A short preview").is_ok());
+        assert_eq!(
+            action.get_iid_to_snippet(7),
+            Ok(Some("A short preview".to_string()))
+        );
+        assert!(action.set_iid_to_snippet(7, "").is_ok());
+        assert_eq!(action.get_iid_to_snippet(7), Ok(None));
+    }
+
+    #[test]
+    fn it_assigns_iids_within_objects_limit() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:4").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:4").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        assert_eq!(action.get_or_assign_iid("o:1", Some(2)), Ok(0));
+        assert_eq!(action.get_or_assign_iid("o:2", Some(2)), Ok(1));
+        assert_eq!(
+            action.get_or_assign_iid("o:3", Some(2)),
+            Err(StoreKVAssignError::BucketFull)
+        );
+
+        // Existing objects can still be updated
+        assert_eq!(action.get_or_assign_iid("o:1", Some(2)), Ok(0));
+        assert_eq!(action.get_oid_count(), Ok(2));
+
+        // Removing an object frees a slot
+        assert!(flush_object(&action, 1, "o:2", &[]).is_ok());
+        assert_eq!(action.get_oid_count(), Ok(1));
+        assert_eq!(action.get_or_assign_iid("o:3", Some(2)), Ok(2));
+    }
+
+    #[test]
+    fn it_moves_objects_between_buckets() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:7").unwrap();
+        let (from_action, to_action) = (
+            StoreKVActionBuilder::access(
+                StoreItemPart::from_str("b:test:7:1").unwrap(),
+                store.clone(),
+            ),
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:7:2").unwrap(), store),
+        );
+
+        assert!(from_action.batch_erase_bucket().is_ok());
+        assert!(to_action.batch_erase_bucket().is_ok());
+
+        // Index object in source bucket
+        let terms_hashed = [StoreTermHash::from("moved"), StoreTermHash::from("object")];
+
+        let iid = from_action.get_or_assign_iid("o:1", None).unwrap();
+
+        for term_hashed in &terms_hashed {
+            assert!(from_action.batch_link_term(*term_hashed, iid, None).is_ok());
+        }
+
+        assert!(from_action.set_iid_to_terms(iid, &terms_hashed).is_ok());
+
+        // Move object, and check that it can only be found in destination bucket
+        assert_eq!(
+            from_action.batch_move_object(&to_action, "o:1", None),
+            Ok(1)
+        );
+
+        let to_iid = to_action.get_oid_to_iid("o:1").unwrap().unwrap();
+
+        for term_hashed in &terms_hashed {
+            assert_eq!(from_action.get_term_to_iids(*term_hashed), Ok(None));
+            assert_eq!(
+                to_action.get_term_to_iids(*term_hashed),
+                Ok(Some(vec![to_iid]))
+            );
+        }
+
+        assert_eq!(from_action.get_oid_to_iid("o:1"), Ok(None));
+        assert_eq!(
+            to_action.get_iid_to_oid(to_iid),
+            Ok(Some("o:1".to_string()))
+        );
+        assert_eq!(
+            (from_action.get_oid_count(), to_action.get_oid_count()),
+            (Ok(0), Ok(1))
+        );
+
+        // Moving a non-existing object does nothing, moving over an existing object fails
+        assert_eq!(
+            from_action.batch_move_object(&to_action, "o:1", None),
+            Ok(0)
+        );
+
+        assert!(from_action.get_or_assign_iid("o:1", None).is_ok());
+
+        assert_eq!(
+            from_action.batch_move_object(&to_action, "o:1", None),
+            Err(StoreKVAssignError::Failed)
+        );
+    }
+
+    #[test]
+    fn it_keeps_buckets_on_failed_moves() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:68").unwrap();
+        let (from_action, to_action) = (
+            StoreKVActionBuilder::access(
+                StoreItemPart::from_str("b:test:68:1").unwrap(),
+                store.clone(),
+            ),
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:68:2").unwrap(), store),
+        );
+
+        assert!(from_action.batch_erase_bucket().is_ok());
+        assert!(to_action.batch_erase_bucket().is_ok());
+
+        // Index object in source bucket, and another object in destination bucket
+        let terms_hashed = [StoreTermHash::from("moved"), StoreTermHash::from("object")];
+
+        let (iid, to_iid_other) = (
+            from_action.get_or_assign_iid("o:1", None).unwrap(),
+            to_action.get_or_assign_iid("o:2", None).unwrap(),
+        );
+
+        for term_hashed in &terms_hashed {
+            assert!(from_action.batch_link_term(*term_hashed, iid, None).is_ok());
+        }
+
+        assert!(from_action.set_iid_to_terms(iid, &terms_hashed).is_ok());
+        assert!(to_action
+            .batch_link_term(terms_hashed[0], to_iid_other, None)
+            .is_ok());
+
+        // Corrupt the last term list of the object (the move fails once the destination \
+        //   bucket has been written to)
+        assert!(from_action
+            .store
+            .as_ref()
+            .unwrap()
+            .put(
+                &StoreKeyerBuilder::term_to_iids("b:test:68:1", terms_hashed[1]).as_bytes(),
+                &[0x02, 0, 0, 0, 0],
+            )
+            .is_ok());
+
+        assert_eq!(
+            from_action.batch_move_object(&to_action, "o:1", None),
+            Err(StoreKVAssignError::Failed)
+        );
+
+        // Both buckets are left untouched
+        assert_eq!(from_action.get_oid_to_iid("o:1"), Ok(Some(iid)));
+        assert_eq!(
+            from_action.get_term_to_iids(terms_hashed[0]),
+            Ok(Some(vec![iid]))
+        );
+        assert_eq!(to_action.get_oid_to_iid("o:1"), Ok(None));
+        assert_eq!(
+            to_action.get_term_to_iids(terms_hashed[0]),
+            Ok(Some(vec![to_iid_other]))
+        );
+        assert_eq!(to_action.get_term_to_iids(terms_hashed[1]), Ok(None));
+        assert_eq!(
+            (from_action.get_oid_count(), to_action.get_oid_count()),
+            (Ok(1), Ok(1))
+        );
+        assert_eq!(
+            (from_action.get_term_count(), to_action.get_term_count()),
+            (Ok(2), Ok(1))
+        );
+    }
+
+    #[test]
+    fn it_counts_bucket_terms() {
+        use crate::executor::pop::ExecutorPop;
+        use crate::executor::push::ExecutorPush;
+        use crate::query::actions::Query;
+        use crate::query::builder::QueryBuilder;
+        use crate::query::types::QueryPushSuggest;
+
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:5").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:5").unwrap(), store);
+
+        assert!(action.batch_erase_bucket().is_ok());
+
+        // Push and pop terms through executors, with repetitions
+        for index in 0..200 {
+            let (object, text) = (
+                format!("o:{}", index % 40),
+                format!("falcon{} harbor{}", index % 60, index % 25),
+            );
+
+            if let Ok(Query::Push(store, lexer, suggest, boost, weights, snippet, verbose)) =
+                QueryBuilder::push(
+                    "c:test:5",
+                    "b:test:5",
+                    &object,
+                    &text,
+                    None,
+                    QueryPushSuggest::Incremental,
+                    None,
+                    &[],
+                    None,
+                    false,
+                )
+            {
+                assert!(ExecutorPush::execute(
+                    store, lexer, suggest, boost, weights, snippet, verbose
+                )
+                .is_ok());
+            } else {
+                panic!("push query could not be built");
+            }
+        }
+
+        let term_count_pushed = action.get_term_count().unwrap();
+
+        assert_eq!(term_count_pushed, action.scan_terms().count() as u32);
+
+        // Notice: terms 'falcon0' to 'falcon19' are linked to objects 'o:N' and 'o:N+20' \
+        //   only, thus they get removed from the bucket once popped from both.
+        for index in 0..40 {
+            let (object, text) = (format!("o:{}", index), format!("falcon{}", index % 20));
+
+            if let Ok(Query::Pop(store, lexer)) =
+                QueryBuilder::pop("c:test:5", "b:test:5", &object, &text)
+            {
+                assert!(ExecutorPop::execute(store, lexer).is_ok());
+            } else {
+                panic!("pop query could not be built");
+            }
+        }
+
+        let term_count = action.get_term_count().unwrap();
 
-            debug!("store delete iid-to-terms: {}", store_key);
+        assert_eq!(term_count, action.scan_terms().count() as u32);
+        assert!(term_count < term_count_pushed);
 
-            store.delete(&store_key.as_bytes()).or(Err(()))
-        } else {
-            Err(())
-        }
+        // Deleting a term that is not there leaves the count as-is
+        assert!(action
+            .delete_term_to_iids(StoreTermHash::from("falcon:missing"))
+            .is_ok());
+        assert_eq!(action.get_term_count(), Ok(term_count));
     }
 
-    pub fn batch_flush_bucket(
-        &self,
-        iid: StoreObjectIID,
-        oid: StoreObjectOID<'a>,
-        iid_terms_hashed: &[StoreTermHashed],
-    ) -> Result<u32, ()> {
-        let mut count = 0;
-
-        debug!(
-            "store batch flush bucket: {} with hashed terms: {:?}",
-            iid, iid_terms_hashed
+    #[test]
+    fn it_handles_write_stalls() {
+        assert_eq!(StoreKV::write_stall_from_properties(Some(0), Some(0)), None);
+        assert_eq!(StoreKV::write_stall_from_properties(None, None), None);
+        assert_eq!(
+            StoreKV::write_stall_from_properties(Some(0), Some(16777216)),
+            Some(StoreKVWriteStall::Delayed)
+        );
+        assert_eq!(
+            StoreKV::write_stall_from_properties(Some(1), Some(0)),
+            Some(StoreKVWriteStall::Stopped)
         );
 
-        // Delete OID <> IID association
-        match (
-            self.delete_oid_to_iid(oid),
-            self.delete_iid_to_oid(iid),
-            self.delete_iid_to_terms(iid),
-        ) {
-            (Ok(_), Ok(_), Ok(_)) => {
-                // Delete IID from each associated term
-                for iid_term in iid_terms_hashed {
-                    if let Ok(Some(mut iid_term_iids)) = self.get_term_to_iids(*iid_term) {
-                        if iid_term_iids.contains(&iid) {
-                            count += 1;
-
-                            // Remove IID from list of IIDs
-                            iid_term_iids.retain(|cur_iid| cur_iid != &iid);
-                        }
-
-                        let is_ok = if iid_term_iids.is_empty() {
-                            self.delete_term_to_iids(*iid_term).is_ok()
-                        } else {
-                            self.set_term_to_iids(*iid_term, &iid_term_iids).is_ok()
-                        };
+        let write_stalls = StoreKVPool::count_write_stalls();
 
-                        if !is_ok {
-                            return Err(());
-                        }
-                    }
-                }
+        assert_eq!(
+            StoreKVAction::handle_write_stall(
+                StoreKVWriteStall::Delayed,
+                &ConfigStoreKVDatabaseWriteStall::Block
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            StoreKVAction::handle_write_stall(
+                StoreKVWriteStall::Stopped,
+                &ConfigStoreKVDatabaseWriteStall::Reject
+            ),
+            Err(StoreKVWriteStall::Stopped)
+        );
 
-                Ok(count)
-            }
-            _ => Err(()),
-        }
+        assert!(StoreKVPool::count_write_stalls() >= write_stalls + 2);
     }
 
-    pub fn batch_truncate_object(
-        &self,
-        term_hashed: StoreTermHashed,
-        term_iids_drain: Drain<StoreObjectIID>,
-    ) -> Result<u32, ()> {
-        let mut count = 0;
+    #[test]
+    fn it_prefetches_terms() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:8").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:8").unwrap(), store);
 
-        for term_iid_drain in term_iids_drain {
-            debug!("store batch truncate object iid: {}", term_iid_drain);
+        assert!(action.batch_erase_bucket().is_ok());
 
-            // Nuke term in IID to Terms list
-            if let Ok(Some(mut term_iid_drain_terms)) = self.get_iid_to_terms(term_iid_drain) {
-                count += 1;
+        assert!(action.set_term_to_iids(1, &[0, 1, 2]).is_ok());
+        assert!(action.set_term_to_iids(3, &[2]).is_ok());
 
-                term_iid_drain_terms.retain(|cur_term| cur_term != &term_hashed);
+        // Prefetched terms must match terms read one after the other (including missing ones)
+        let terms_hashed = [1, 2, 3, 1];
 
-                // IID to Terms list is empty? Flush whole object.
-                if term_iid_drain_terms.is_empty() {
-                    // Acquire OID for this drained IID
-                    if let Ok(Some(term_iid_drain_oid)) = self.get_iid_to_oid(term_iid_drain) {
-                        if self
-                            .batch_flush_bucket(term_iid_drain, &term_iid_drain_oid, &Vec::new())
-                            .is_err()
-                        {
-                            error!(
-                                "failed executing store batch truncate object batch-flush-bucket"
-                            );
-                        }
-                    } else {
-                        error!("failed getting store batch truncate object iid-to-oid");
-                    }
-                } else {
-                    // Update IID to Terms list
-                    if self
-                        .set_iid_to_terms(term_iid_drain, &term_iid_drain_terms)
-                        .is_err()
-                    {
-                        error!("failed setting store batch truncate object iid-to-terms");
-                    }
-                }
-            }
-        }
+        let sequential: Result<Vec<Option<Vec<StoreObjectIID>>>, StoreKVError> = terms_hashed
+            .iter()
+            .map(|term_hashed| action.get_term_to_iids(*term_hashed))
+            .collect();
 
-        Ok(count)
+        assert_eq!(action.get_terms_to_iids(&terms_hashed), sequential);
+        assert_eq!(
+            action.get_terms_to_iids(&terms_hashed),
+            Ok(vec![
+                Some(vec![0, 1, 2]),
+                None,
+                Some(vec![2]),
+                Some(vec![0, 1, 2])
+            ])
+        );
+        assert_eq!(action.get_terms_to_iids(&[]), Ok(vec![]));
     }
 
-    pub fn batch_erase_bucket(&self) -> Result<u32, ()> {
-        if let Some(ref store) = self.store {
-            // Generate all key prefix values (with dummy post-prefix values; we dont care)
-            let (k_meta_to_value, k_term_to_iids, k_oid_to_iid, k_iid_to_oid, k_iid_to_terms) = (
-                StoreKeyerBuilder::meta_to_value(self.bucket.as_str(), &StoreMetaKey::IIDIncr),
-                StoreKeyerBuilder::term_to_iids(self.bucket.as_str(), 0),
-                StoreKeyerBuilder::oid_to_iid(self.bucket.as_str(), &String::new()),
-                StoreKeyerBuilder::iid_to_oid(self.bucket.as_str(), 0),
-                StoreKeyerBuilder::iid_to_terms(self.bucket.as_str(), 0),
-            );
+    #[test]
+    fn it_retains_bucket_objects() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:10").unwrap();
+        let action = StoreKVActionBuilder::access(
+            StoreItemPart::from_str("b:test:10").unwrap(),
+            store.clone(),
+        );
 
-            let key_prefixes: [StoreKeyerPrefix; 5] = [
-                k_meta_to_value.as_prefix(),
-                k_term_to_iids.as_prefix(),
-                k_oid_to_iid.as_prefix(),
-                k_iid_to_oid.as_prefix(),
-                k_iid_to_terms.as_prefix(),
-            ];
+        assert!(action.batch_erase_bucket().is_ok());
 
-            // Scan all keys per-prefix and nuke them right away
-            for key_prefix in &key_prefixes {
-                debug!(
-                    "store batch erase bucket: {} for prefix: {:?}",
-                    self.bucket.as_str(),
-                    key_prefix
-                );
+        // Push objects at different times (one hour apart, the oldest one first)
+        let now = 1_000_000;
+        let objects = ["o:test:10:1", "o:test:10:2", "o:test:10:3", "o:test:10:4"];
 
-                // Generate start and end prefix for batch delete (in other words, the minimum \
-                //   key value possible, and the highest key value possible)
-                let key_prefix_start: StoreKeyerKey = [
-                    key_prefix[0],
-                    key_prefix[1],
-                    key_prefix[2],
-                    key_prefix[3],
-                    key_prefix[4],
-                    0,
-                    0,
-                    0,
-                    0,
-                ];
-                let key_prefix_end: StoreKeyerKey = [
-                    key_prefix[0],
-                    key_prefix[1],
-                    key_prefix[2],
-                    key_prefix[3],
-                    key_prefix[4],
-                    255,
-                    255,
-                    255,
-                    255,
-                ];
+        for (index, oid) in objects.iter().enumerate() {
+            let iid = action.get_or_assign_iid(oid, None).unwrap();
 
-                // Batch-delete keys matching range
-                let mut batch = WriteBatch::default();
+            assert!(action.batch_link_term(index as u32, iid, None).is_ok());
+            assert!(action.set_iid_to_terms(iid, &[index as u32]).is_ok());
+            assert!(action
+                .set_iid_to_time(iid, now - (objects.len() - index) as u32 * 3600)
+                .is_ok());
+        }
 
-                batch.delete_range(&key_prefix_start, &key_prefix_end);
+        assert_eq!(action.get_retention(), Ok(None));
 
-                // Commit operation to database
-                if let Err(err) = store.do_write(batch) {
-                    error!(
-                        "failed in store batch erase bucket: {} with error: {}",
-                        self.bucket.as_str(),
-                        err
-                    );
-                } else {
-                    // Ensure last key is deleted (as RocksDB end key is exclusive; while \
-                    //   start key is inclusive, we need to ensure the end-of-range key is \
-                    //   deleted)
-                    store.delete(&key_prefix_end).ok();
+        // No policy set? Nothing purged
+        assert_eq!(StoreKVPool::retain_store(store.as_ref().unwrap(), now), 0);
+        assert_eq!(action.get_oid_count(), Ok(4));
 
-                    debug!(
-                        "succeeded in store batch erase bucket: {}",
-                        self.bucket.as_str()
-                    );
-                }
-            }
+        // Objects older than 3 hours get purged on next retention
+        let retention = StoreRetention {
+            max_age: Some(3 * 3600 - 1),
+            max_objects: None,
+        };
 
-            info!(
-                "done processing store batch erase bucket: {}",
-                self.bucket.as_str()
-            );
+        assert!(action.set_retention(Some(retention)).is_ok());
+        assert_eq!(action.get_retention(), Ok(Some(retention)));
 
-            Ok(1)
-        } else {
-            Err(())
-        }
-    }
+        assert_eq!(StoreKVPool::retain_store(store.as_ref().unwrap(), now), 2);
+        assert_eq!(action.get_oid_count(), Ok(2));
+        assert_eq!(action.get_oid_to_iid("o:test:10:2"), Ok(None));
+        assert_eq!(action.get_term_to_iids(1), Ok(None));
+        assert!(action.get_oid_to_iid("o:test:10:3").unwrap().is_some());
 
-    fn encode_u32(decoded: u32) -> [u8; 4] {
-        let mut encoded = [0; 4];
+        // Objects in excess get purged on next retention (oldest first)
+        assert!(action
+            .set_retention(Some(StoreRetention {
+                max_age: None,
+                max_objects: Some(1),
+            }))
+            .is_ok());
 
-        LittleEndian::write_u32(&mut encoded, decoded);
+        assert_eq!(StoreKVPool::retain_store(store.as_ref().unwrap(), now), 1);
+        assert_eq!(action.get_oid_to_iid("o:test:10:3"), Ok(None));
+        assert!(action.get_oid_to_iid("o:test:10:4").unwrap().is_some());
 
-        encoded
+        // Empty policy removes policy
+        assert!(action
+            .set_retention(Some(StoreRetention {
+                max_age: None,
+                max_objects: None,
+            }))
+            .is_ok());
+        assert_eq!(action.get_retention(), Ok(None));
     }
 
-    fn decode_u32(encoded: &[u8]) -> Result<u32, ()> {
-        Cursor::new(encoded).read_u32::<LittleEndian>().or(Err(()))
-    }
+    #[test]
+    fn it_iterates_pinned_terms() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:8").unwrap();
+        let action = StoreKVActionBuilder::access(
+            StoreItemPart::from_str("b:test:8:pinned").unwrap(),
+            store,
+        );
 
-    fn encode_u32_list(decoded: &[u32]) -> Vec<u8> {
-        // Pre-reserve required capacity as to avoid heap resizes (50% performance gain relative \
-        //   to initializing this with a zero-capacity)
-        let mut encoded = Vec::with_capacity(decoded.len() * 4);
+        assert!(action.batch_erase_bucket().is_ok());
 
-        for decoded_item in decoded {
-            encoded.extend(&Self::encode_u32(*decoded_item))
+        assert!(action
+            .set_term_to_iids(1, &[0, 1, 2, 45402, 4294967295])
+            .is_ok());
+        assert!(action.set_term_to_iids(2, &[]).is_ok());
+
+        // Pinned terms must yield the same IIDs as allocated terms (including missing ones)
+        for term_hashed in 1..4 {
+            assert_eq!(
+                action
+                    .get_term_to_iids_pinned(term_hashed)
+                    .unwrap()
+                    .map(|term_iids| term_iids.iter().collect::<Vec<StoreObjectIID>>()),
+                action.get_term_to_iids(term_hashed).unwrap()
+            );
         }
 
-        encoded
+        assert_eq!(
+            action
+                .get_term_to_iids_pinned(1)
+                .unwrap()
+                .unwrap()
+                .iter()
+                .size_hint(),
+            (5, Some(5))
+        );
     }
 
-    fn decode_u32_list(encoded: &[u8]) -> Result<Vec<u32>, ()> {
-        // Pre-reserve required capacity as to avoid heap resizes (50% performance gain relative \
-        //   to initializing this with a zero-capacity)
-        let mut decoded = Vec::with_capacity(encoded.len() / 4);
+    #[test]
+    fn it_reads_mixed_term_formats() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:34").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:34").unwrap(), store);
 
-        for encoded_chunk in encoded.chunks(4) {
-            if let Ok(decoded_chunk) = Self::decode_u32(encoded_chunk) {
-                decoded.push(decoded_chunk);
-            } else {
-                return Err(());
-            }
-        }
+        assert!(action.batch_erase_bucket().is_ok());
 
-        Ok(decoded)
-    }
-}
+        // Term 1 holds IIDs only, term 2 holds IIDs and positions, term 3 gets upgraded
+        assert!(action.set_term_to_iids(1, &[2, 1]).is_ok());
+        assert!(action.batch_link_term(2, 1, Some(vec![0, 4])).is_ok());
+        assert!(action.batch_link_term(2, 2, Some(vec![])).is_ok());
+        assert!(action.set_term_to_iids(3, &[1]).is_ok());
+        assert!(action.batch_link_term(3, 2, Some(vec![7])).is_ok());
 
-impl StoreKVKey {
-    pub fn from_atom(collection_hash: StoreKVAtom) -> StoreKVKey {
-        StoreKVKey { collection_hash }
-    }
+        assert_eq!(action.get_term_to_iids(1), Ok(Some(vec![2, 1])));
+        assert_eq!(action.get_term_to_iids(2), Ok(Some(vec![2, 1])));
+        assert_eq!(action.get_term_to_iids(3), Ok(Some(vec![2, 1])));
+        assert_eq!(
+            action.get_terms_to_iids(&[1, 2, 3, 4]),
+            Ok(vec![
+                Some(vec![2, 1]),
+                Some(vec![2, 1]),
+                Some(vec![2, 1]),
+                None
+            ])
+        );
 
-    pub fn from_str(collection_str: &str) -> StoreKVKey {
-        StoreKVKey {
-            collection_hash: StoreKeyerHasher::to_compact(collection_str),
+        for term_hashed in 1..4 {
+            let term_iids = action
+                .get_term_to_iids_pinned(term_hashed)
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(
+                term_iids.iter().collect::<Vec<StoreObjectIID>>(),
+                vec![2, 1]
+            );
+            assert_eq!(term_iids.count(), 2);
         }
-    }
-}
 
-impl fmt::Display for StoreKVKey {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "<{:x?}>", self.collection_hash)
-    }
-}
+        let (legacy, positioned, upgraded) = (
+            action.get_term_to_postings(1).unwrap().unwrap(),
+            action.get_term_to_postings(2).unwrap().unwrap(),
+            action.get_term_to_postings(3).unwrap().unwrap(),
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        assert!(!legacy.has_positions());
+        assert_eq!(legacy.positions_of(1), None);
+        assert_eq!(positioned.positions_of(1), Some(&[0, 4][..]));
+        assert_eq!(positioned.positions_of(2), Some(&[][..]));
+        assert_eq!(upgraded.positions_of(2), Some(&[7][..]));
+        assert_eq!(upgraded.positions_of(1), Some(&[][..]));
 
-    #[test]
-    fn it_acquires_database() {
-        assert!(StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:1").is_ok());
-    }
+        // Removing an IID keeps the positions of other IIDs
+        assert_eq!(flush_object(&action, 2, "o:test:34", &[2, 3]), Ok(2));
+        assert_eq!(
+            action
+                .get_term_to_postings(2)
+                .unwrap()
+                .unwrap()
+                .positions_of(1),
+            Some(&[0, 4][..])
+        );
 
-    #[test]
-    fn it_janitors_database() {
-        StoreKVPool::janitor();
+        // Values with an unknown format are rejected
+        assert!(StoreKVTermPostings::decode(&[0x02, 0, 0, 0, 0]).is_err());
+        assert!(
+            StoreKVTermPostings::decode(&[TERM_FORMAT_POSITIONS, 1, 0, 0, 0, 2, 0, 0, 0]).is_err()
+        );
     }
 
     #[test]
-    fn it_proceeds_primitives() {
-        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:2")
-            .unwrap()
-            .unwrap();
+    fn it_keeps_term_sizes() {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:39").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:39").unwrap(), store);
 
-        assert!(store.get(&[0]).is_ok());
-        assert!(store.put(&[0], &[1, 0, 0, 0]).is_ok());
-        assert!(store.delete(&[0]).is_ok());
-    }
+        assert!(action.batch_erase_bucket().is_ok());
 
-    #[test]
-    fn it_proceeds_actions() {
-        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:test:3").unwrap();
-        let action =
-            StoreKVActionBuilder::access(StoreItemPart::from_str("b:test:3").unwrap(), store);
+        // Sizes are written along with term lists
+        assert!(action.set_term_to_iids(1, &[2, 1, 0]).is_ok());
+        assert!(action.batch_link_term(2, 1, Some(vec![0])).is_ok());
+        assert!(action.batch_link_term(2, 2, Some(vec![3])).is_ok());
 
-        assert!(action.get_meta_to_value(StoreMetaKey::IIDIncr).is_ok());
-        assert!(action
-            .set_meta_to_value(StoreMetaKey::IIDIncr, StoreMetaValue::IIDIncr(1))
-            .is_ok());
+        assert_eq!(action.get_term_to_size(1), Ok(Some(3)));
+        assert_eq!(action.get_term_to_size(2), Ok(Some(2)));
 
-        assert!(action.get_term_to_iids(1).is_ok());
-        assert!(action.set_term_to_iids(1, &[0, 1, 2]).is_ok());
-        assert!(action.delete_term_to_iids(1).is_ok());
+        assert_eq!(flush_object(&action, 2, "o:test:39", &[1, 2]), Ok(2));
 
-        assert!(action.get_oid_to_iid(&"s".to_string()).is_ok());
-        assert!(action.set_oid_to_iid(&"s".to_string(), 4).is_ok());
-        assert!(action.delete_oid_to_iid(&"s".to_string()).is_ok());
+        assert_eq!(action.get_term_to_size(1), Ok(Some(2)));
+        assert_eq!(action.get_term_to_size(2), Ok(Some(1)));
 
-        assert!(action.get_iid_to_oid(4).is_ok());
-        assert!(action.set_iid_to_oid(4, &"s".to_string()).is_ok());
-        assert!(action.delete_iid_to_oid(4).is_ok());
+        assert_eq!(flush_object(&action, 1, "o:test:39", &[2]), Ok(1));
 
-        assert!(action.get_iid_to_terms(4).is_ok());
-        assert!(action.set_iid_to_terms(4, &[45402]).is_ok());
-        assert!(action.delete_iid_to_terms(4).is_ok());
+        assert_eq!(action.get_term_to_size(2), Ok(None));
+
+        // Terms with no stored size have their size counted from their list
+        let term_to_iids = StoreKeyerBuilder::term_to_iids("b:test:39", 3);
+
+        assert!(action
+            .store
+            .as_ref()
+            .unwrap()
+            .put(
+                &term_to_iids.as_bytes(),
+                &StoreKVAction::encode_u32_list(&[4, 3])
+            )
+            .is_ok());
+
+        assert_eq!(action.get_term_to_size(3), Ok(None));
+        assert_eq!(action.get_terms_size(&[1, 2, 3]), vec![2, 0, 2]);
     }
 
     #[test]
@@ -1306,4 +7371,98 @@ mod benches {
 
         b.iter(|| StoreKVAction::decode_u32_list(&encoded_atom_list));
     }
+
+    #[bench]
+    fn bench_encode_atom_list_large(b: &mut Bencher) {
+        let atom_list: Vec<StoreObjectIID> = (0..1000).collect();
+
+        b.iter(|| StoreKVAction::encode_u32_list(&atom_list));
+    }
+
+    #[bench]
+    fn bench_decode_atom_list_large(b: &mut Bencher) {
+        let atom_list: Vec<StoreObjectIID> = (0..1000).collect();
+        let encoded_atom_list = StoreKVAction::encode_u32_list(&atom_list);
+
+        b.iter(|| StoreKVAction::decode_u32_list(&encoded_atom_list));
+    }
+
+    #[bench]
+    fn bench_get_term_to_iids_allocated(b: &mut Bencher) {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:bench:1").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:bench:1").unwrap(), store);
+
+        let term_iids: Vec<StoreObjectIID> = (0..1000).collect();
+
+        action.set_term_to_iids(100, &term_iids).ok();
+
+        // Notice: this allocates a list of decoded IIDs on each read
+        b.iter(|| {
+            action
+                .get_term_to_iids(100)
+                .unwrap()
+                .unwrap()
+                .into_iter()
+                .fold(0, |sum, iid| sum ^ iid)
+        });
+    }
+
+    #[bench]
+    fn bench_get_term_to_iids_pinned(b: &mut Bencher) {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:bench:1").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:bench:1").unwrap(), store);
+
+        let term_iids: Vec<StoreObjectIID> = (0..1000).collect();
+
+        action.set_term_to_iids(100, &term_iids).ok();
+
+        // Notice: this decodes IIDs straight from the database value, without allocating
+        b.iter(|| {
+            action
+                .get_term_to_iids_pinned(100)
+                .unwrap()
+                .unwrap()
+                .iter()
+                .fold(0, |sum, iid| sum ^ iid)
+        });
+    }
+
+    #[bench]
+    fn bench_get_terms_to_iids_sequential(b: &mut Bencher) {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:bench:1").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:bench:1").unwrap(), store);
+
+        let terms_hashed: Vec<StoreTermHashed> = (0..8).collect();
+
+        for term_hashed in &terms_hashed {
+            action.set_term_to_iids(*term_hashed, &[0, 1, 2]).ok();
+        }
+
+        // Notice: this issues 1 database read per term
+        b.iter(|| {
+            terms_hashed
+                .iter()
+                .map(|term_hashed| action.get_term_to_iids(*term_hashed))
+                .collect::<Vec<_>>()
+        });
+    }
+
+    #[bench]
+    fn bench_get_terms_to_iids_prefetch(b: &mut Bencher) {
+        let store = StoreKVPool::acquire(StoreKVAcquireMode::Any, "c:bench:1").unwrap();
+        let action =
+            StoreKVActionBuilder::access(StoreItemPart::from_str("b:bench:1").unwrap(), store);
+
+        let terms_hashed: Vec<StoreTermHashed> = (0..8).collect();
+
+        for term_hashed in &terms_hashed {
+            action.set_term_to_iids(*term_hashed, &[0, 1, 2]).ok();
+        }
+
+        // Notice: this issues a single batched database read for all terms
+        b.iter(|| action.get_terms_to_iids(&terms_hashed));
+    }
 }
@@ -15,3 +15,7 @@ pub mod identifiers;
 pub mod item;
 pub mod kv;
 pub mod operation;
+pub mod snapshot;
+
+// Revision of the on-disk store format (to be bumped on any breaking change in keys or values)
+pub const STORE_FORMAT_REVISION: u8 = 1;
@@ -4,43 +4,131 @@
 // Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
 // License: Mozilla Public License v2.0 (MPL v2.0)
 
+use rocksdb::ErrorKind as DBErrorKind;
+
+use crate::executor::cooccur::ExecutorCoOccur;
 use crate::executor::count::ExecutorCount;
 use crate::executor::flushb::ExecutorFlushB;
 use crate::executor::flushc::ExecutorFlushC;
 use crate::executor::flusho::ExecutorFlushO;
+use crate::executor::freeze::ExecutorFreeze;
+use crate::executor::idf::ExecutorIDF;
 use crate::executor::list::ExecutorList;
+use crate::executor::listo::ExecutorListO;
+use crate::executor::moveo::ExecutorMoveO;
 use crate::executor::pop::ExecutorPop;
-use crate::executor::push::ExecutorPush;
+use crate::executor::push::{ExecutorPush, ExecutorPushRequest, ExecutorPushStats};
+use crate::executor::retain::ExecutorRetain;
+use crate::executor::retention::ExecutorRetention;
 use crate::executor::search::ExecutorSearch;
+use crate::executor::snapshot::ExecutorSnapshot;
 use crate::executor::suggest::ExecutorSuggest;
+use crate::executor::swap::ExecutorSwap;
+use crate::executor::terms::ExecutorTerms;
 use crate::query::actions::Query;
+use crate::store::fst::StoreFSTError;
+use crate::store::item::STORE_ITEM_ATTRIBUTE_PREFIX;
+use crate::store::kv::StoreKVError;
+use crate::APP_CONF;
 
 pub struct StoreOperationDispatch;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StoreOperationError {
+    Failed,
+    BucketFull,
+    BucketImmutable,
+    BucketCollision,
+    StoreBusy,
+    StoreCorrupted,
+    Timeout,
+}
+
+const RESULTS_TRUNCATED_MARKER: &str = "[truncated]";
+
 impl StoreOperationDispatch {
-    pub fn dispatch(query: Query) -> Result<Option<String>, ()> {
+    pub fn dispatch(query: Query) -> Result<Option<String>, StoreOperationError> {
         // Dispatch de-constructed query to its target executor
         match query {
-            Query::Search(store, query_id, lexer, limit, offset) => {
-                ExecutorSearch::execute(store, query_id, lexer, limit, offset)
-                    .map(|results| results.map(|results| results.join(" ")))
+            Query::Search(
+                store,
+                query_id,
+                lexer,
+                limit,
+                offset,
+                timeout,
+                typos,
+                completion,
+                snippets,
+                with_terms,
+            ) => ExecutorSearch::execute(
+                store, query_id, lexer, limit, offset, timeout, typos, completion, snippets,
+                with_terms,
+            )
+            .map(|results| results.map(|results| Self::join_results(&results))),
+            Query::SearchBoolean(
+                store,
+                query_id,
+                expression,
+                limit,
+                offset,
+                snippets,
+                with_terms,
+            ) => ExecutorSearch::execute_boolean(
+                store, query_id, expression, limit, offset, snippets, with_terms,
+            )
+            .map(|results| results.map(|results| Self::join_results(&results))),
+            Query::SearchPhrase(store, query_id, lexer, limit, offset, snippets, with_terms) => {
+                ExecutorSearch::execute_phrase(
+                    store, query_id, lexer, limit, offset, snippets, with_terms,
+                )
+                .map(|results| results.map(|results| Self::join_results(&results)))
+            }
+            Query::SearchFederated(queries, limit, offset) => {
+                ExecutorSearch::execute_federated(queries, limit, offset)
+                    .map(|results| results.map(|results| Self::join_results(&results)))
             }
             Query::Suggest(store, query_id, lexer, limit) => {
                 ExecutorSuggest::execute(store, query_id, lexer, limit)
-                    .map(|results| results.map(|results| results.join(" ")))
+                    .map(|results| results.map(|results| Self::join_results(&results)))
+            }
+            Query::CoOccur(store, query_id, lexer, limit) => {
+                ExecutorCoOccur::execute(store, query_id, lexer, limit)
+                    .map(|results| results.map(|results| Self::join_results(&results)))
             }
             Query::List(store, query_id, limit, offset) => {
                 ExecutorList::execute(store, query_id, limit, offset)
-                    .map(|results| results.join(" "))
-                    .map(|results| Some(results))
+                    .map(|results| Some(Self::join_results(&results)))
+            }
+            Query::Push(store, lexer, suggest, boost, weights, snippet, verbose) => {
+                ExecutorPush::execute(store, lexer, suggest, boost, weights, snippet, verbose)
+                    .map(|stats| stats.map(Self::format_push_stats))
             }
-            Query::Push(store, lexer) => ExecutorPush::execute(store, lexer).map(|_| None),
             Query::Pop(store, lexer) => {
                 ExecutorPop::execute(store, lexer).map(|count| Some(count.to_string()))
             }
+            Query::ListO(store, prefix, limit, offset) => {
+                ExecutorListO::execute(store, prefix, limit, offset).map(|oids| {
+                    // Notice: an empty result is not allowed, thus say there is no object
+                    if oids.is_empty() {
+                        Some("none".to_string())
+                    } else {
+                        Some(Self::join_results(&oids))
+                    }
+                })
+            }
             Query::Count(store) => {
                 ExecutorCount::execute(store).map(|count| Some(count.to_string()))
             }
+            Query::CountObjects(store) => {
+                ExecutorCount::execute_objects(store).map(|count| Some(count.to_string()))
+            }
+            Query::Terms(store) => {
+                ExecutorTerms::execute(store).map(|count| Some(count.to_string()))
+            }
+            Query::RefreshIDF(store) => {
+                ExecutorIDF::execute(store).map(|count| Some(count.to_string()))
+            }
             Query::FlushC(store) => {
                 ExecutorFlushC::execute(store).map(|count| Some(count.to_string()))
             }
@@ -50,6 +138,230 @@ impl StoreOperationDispatch {
             Query::FlushO(store) => {
                 ExecutorFlushO::execute(store).map(|count| Some(count.to_string()))
             }
+            Query::MoveO(store, to_bucket) => {
+                ExecutorMoveO::execute(store, to_bucket).map(|count| Some(count.to_string()))
+            }
+            Query::Swap(store, with_bucket) => {
+                ExecutorSwap::execute(store, with_bucket).map(|count| Some(count.to_string()))
+            }
+            Query::Retain(store, max_age, max_objects) => {
+                ExecutorRetain::execute(store, max_age, max_objects).map(|_| None)
+            }
+            Query::Freeze(store, immutable) => {
+                ExecutorFreeze::execute(store, immutable).map(|_| None)
+            }
+            Query::Retention(store) => ExecutorRetention::execute(store).map(|retention| {
+                Some(format!(
+                    "age({}) objects({})",
+                    retention.max_age.unwrap_or(0),
+                    retention.max_objects.unwrap_or(0)
+                ))
+            }),
+            Query::Snapshot(store, name) => {
+                ExecutorSnapshot::execute(store, name).map(|count| Some(count.to_string()))
+            }
+            Query::Diff(store, from_name, to_name) => {
+                ExecutorSnapshot::execute_diff(store, from_name, to_name).map(|diff| {
+                    let mut results: Vec<String> = Vec::new();
+
+                    results.extend(diff.added.iter().map(|oid| format!("added({})", oid)));
+                    results.extend(diff.removed.iter().map(|oid| format!("removed({})", oid)));
+                    results.extend(diff.changed.iter().map(|(oid, added, removed)| {
+                        format!("changed({},{},{})", oid, added, removed)
+                    }));
+
+                    // Notice: an empty result is not allowed, thus say there is no change
+                    if results.is_empty() {
+                        Some("none".to_string())
+                    } else {
+                        Some(results.join(" "))
+                    }
+                })
+            }
         }
     }
+
+    pub fn dispatch_pushes(
+        queries: Vec<Query>,
+    ) -> Result<Vec<Result<Option<String>, StoreOperationError>>, StoreOperationError> {
+        // Dispatch de-constructed push queries to the push executor, all at once (this is used \
+        //   for pipelined pushes, which must all target the same collection)
+        let pushes = queries
+            .into_iter()
+            .map(|query| match query {
+                Query::Push(store, lexer, suggest, boost, weights, snippet, verbose) => {
+                    Ok((store, lexer, suggest, boost, weights, snippet, verbose))
+                }
+                _ => Err(StoreOperationError::Failed),
+            })
+            .collect::<Result<Vec<ExecutorPushRequest>, StoreOperationError>>()?;
+
+        ExecutorPush::execute_pipelined(pushes).map(|results| {
+            results
+                .into_iter()
+                .map(|result| result.map(|stats| stats.map(Self::format_push_stats)))
+                .collect()
+        })
+    }
+
+    fn format_push_stats(stats: ExecutorPushStats) -> String {
+        format!(
+            "tokens({}) terms_new({}) terms_existing({}) object({}) took({})",
+            stats.tokens,
+            stats.terms_new,
+            stats.terms_existing,
+            if stats.object_new { "new" } else { "existing" },
+            stats.took.as_micros()
+        )
+    }
+
+    fn join_results(results: &[String]) -> String {
+        Self::join_results_within(results, APP_CONF.channel.response_size_maximum)
+    }
+
+    fn join_results_within(results: &[String], size_maximum: usize) -> String {
+        let mut joined = String::new();
+
+        for (index, result) in results.iter().enumerate() {
+            let separator_size = if joined.is_empty() { 0 } else { 1 };
+
+            // Truncate results once they would grow above the maximum size (whole results are \
+            //   dropped, and the attribute tells the client to paginate to get the others)
+            if size_maximum > 0 && joined.len() + separator_size + result.len() > size_maximum {
+                info!(
+                    "truncated {} results out of {}, as they are above the maximum size",
+                    results.len() - index,
+                    results.len()
+                );
+
+                if !joined.is_empty() {
+                    joined.push(' ');
+                }
+
+                joined.push_str(RESULTS_TRUNCATED_MARKER);
+
+                // Keep trailing attributes of dropped results (eg. partial results)
+                for attribute in results[index..]
+                    .iter()
+                    .filter(|result| result.starts_with(STORE_ITEM_ATTRIBUTE_PREFIX))
+                {
+                    joined.push(' ');
+                    joined.push_str(attribute);
+                }
+
+                break;
+            }
+
+            if !joined.is_empty() {
+                joined.push(' ');
+            }
+
+            joined.push_str(result);
+        }
+
+        joined
+    }
+}
+
+impl From<StoreKVError> for StoreOperationError {
+    fn from(err: StoreKVError) -> StoreOperationError {
+        // Tell stored data that cannot be read back apart from other failures (those are \
+        //   not expected to be solved by retrying the operation)
+        match err {
+            StoreKVError::Database(ref err) if err.kind() == DBErrorKind::Corruption => {
+                StoreOperationError::StoreCorrupted
+            }
+            StoreKVError::Serialization | StoreKVError::Incompatible => {
+                StoreOperationError::StoreCorrupted
+            }
+            StoreKVError::Immutable => StoreOperationError::BucketImmutable,
+            StoreKVError::Collision => StoreOperationError::BucketCollision,
+            _ => StoreOperationError::Failed,
+        }
+    }
+}
+
+impl From<StoreFSTError> for StoreOperationError {
+    fn from(err: StoreFSTError) -> StoreOperationError {
+        match err {
+            StoreFSTError::Graph => StoreOperationError::StoreCorrupted,
+            _ => StoreOperationError::Failed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_truncates_results_above_maximum_size() {
+        let results: Vec<String> = vec!["conversation:1", "conversation:2", "conversation:3"]
+            .into_iter()
+            .map(|result| result.to_string())
+            .collect();
+
+        assert_eq!(
+            StoreOperationDispatch::join_results_within(&results, 0),
+            "conversation:1 conversation:2 conversation:3"
+        );
+        assert_eq!(
+            StoreOperationDispatch::join_results_within(&results, 44),
+            "conversation:1 conversation:2 conversation:3"
+        );
+        assert_eq!(
+            StoreOperationDispatch::join_results_within(&results, 32),
+            "conversation:1 conversation:2 [truncated]"
+        );
+        assert_eq!(
+            StoreOperationDispatch::join_results_within(&results, 8),
+            "[truncated]"
+        );
+
+        let results_partial: Vec<String> = vec!["conversation:1", "conversation:2", "[partial]"]
+            .into_iter()
+            .map(|result| result.to_string())
+            .collect();
+
+        assert_eq!(
+            StoreOperationDispatch::join_results_within(&results_partial, 20),
+            "conversation:1 [truncated] [partial]"
+        );
+    }
+
+    #[test]
+    fn it_maps_store_errors() {
+        assert_eq!(
+            StoreOperationError::from(StoreKVError::Serialization),
+            StoreOperationError::StoreCorrupted
+        );
+        assert_eq!(
+            StoreOperationError::from(StoreKVError::Incompatible),
+            StoreOperationError::StoreCorrupted
+        );
+        assert_eq!(
+            StoreOperationError::from(StoreKVError::NotFound),
+            StoreOperationError::Failed
+        );
+        assert_eq!(
+            StoreOperationError::from(StoreKVError::Invalid),
+            StoreOperationError::Failed
+        );
+        assert_eq!(
+            StoreOperationError::from(StoreKVError::Immutable),
+            StoreOperationError::BucketImmutable
+        );
+        assert_eq!(
+            StoreOperationError::from(StoreKVError::Collision),
+            StoreOperationError::BucketCollision
+        );
+        assert_eq!(
+            StoreOperationError::from(StoreFSTError::Graph),
+            StoreOperationError::StoreCorrupted
+        );
+        assert_eq!(
+            StoreOperationError::from(StoreFSTError::Lookup),
+            StoreOperationError::Failed
+        );
+    }
 }
@@ -0,0 +1,272 @@
+// Sonic
+//
+// Fast, lightweight and schema-less search backend
+// Copyright: 2019, Valerian Saliou <valerian@valeriansaliou.name>
+// License: Mozilla Public License v2.0 (MPL v2.0)
+
+use hashbrown::HashMap;
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use super::identifiers::StoreTermHashed;
+use crate::APP_CONF;
+
+pub struct StoreSnapshotPool;
+
+pub struct StoreSnapshot {
+    objects: HashMap<String, Vec<StoreTermHashed>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StoreSnapshotDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<(String, usize, usize)>,
+}
+
+type StoreSnapshotKey = (String, String, String);
+type StoreSnapshotEntry = (StoreSnapshotKey, Instant, Arc<StoreSnapshot>);
+
+lazy_static! {
+    static ref SNAPSHOTS: RwLock<VecDeque<StoreSnapshotEntry>> = RwLock::new(VecDeque::new());
+}
+
+impl StoreSnapshotPool {
+    pub fn insert(collection: &str, bucket: &str, name: &str, snapshot: StoreSnapshot) {
+        let maximum = APP_CONF.store.kv.max_snapshots;
+
+        // Snapshots are disabled? (do not hold anything)
+        if maximum == 0 {
+            return;
+        }
+
+        let snapshot_key = Self::make_key(collection, bucket, name);
+        let mut snapshots = SNAPSHOTS.write().unwrap();
+
+        Self::evict_expired(&mut snapshots);
+
+        // Replace any previous snapshot with the same name
+        snapshots.retain(|(existing_key, _, _)| existing_key != &snapshot_key);
+
+        // Evict oldest snapshots first, if the pool is full
+        while snapshots.len() >= maximum {
+            snapshots.pop_front();
+        }
+
+        snapshots.push_back((snapshot_key, Instant::now(), Arc::new(snapshot)));
+    }
+
+    pub fn get(collection: &str, bucket: &str, name: &str) -> Option<Arc<StoreSnapshot>> {
+        let snapshot_key = Self::make_key(collection, bucket, name);
+
+        SNAPSHOTS
+            .read()
+            .unwrap()
+            .iter()
+            .find(|(existing_key, taken_at, _)| {
+                existing_key == &snapshot_key && !Self::is_expired(*taken_at)
+            })
+            .map(|(_, _, snapshot)| snapshot.clone())
+    }
+
+    pub fn janitor() {
+        debug!("scanning for expired snapshots");
+
+        let count_evicted = Self::evict_expired(&mut SNAPSHOTS.write().unwrap());
+
+        info!(
+            "done scanning for expired snapshots, evicted: {}",
+            count_evicted
+        );
+    }
+
+    fn evict_expired(snapshots: &mut VecDeque<StoreSnapshotEntry>) -> usize {
+        let count_before = snapshots.len();
+
+        snapshots.retain(|(_, taken_at, _)| !Self::is_expired(*taken_at));
+
+        count_before - snapshots.len()
+    }
+
+    fn is_expired(taken_at: Instant) -> bool {
+        let expire_after = APP_CONF.store.kv.snapshot_expire_after;
+
+        expire_after > 0 && taken_at.elapsed() >= Duration::from_secs(expire_after)
+    }
+
+    fn make_key(collection: &str, bucket: &str, name: &str) -> StoreSnapshotKey {
+        (collection.to_string(), bucket.to_string(), name.to_string())
+    }
+}
+
+impl StoreSnapshot {
+    pub fn from_objects(objects: Vec<(String, Vec<StoreTermHashed>)>) -> StoreSnapshot {
+        StoreSnapshot {
+            objects: objects
+                .into_iter()
+                .map(|(oid, mut terms_hashed)| {
+                    // Sort terms, as to compare them in linear time when diffing
+                    terms_hashed.sort_unstable();
+                    terms_hashed.dedup();
+
+                    (oid, terms_hashed)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn diff(&self, to: &StoreSnapshot) -> StoreSnapshotDiff {
+        let (mut added, mut removed, mut changed) = (Vec::new(), Vec::new(), Vec::new());
+
+        for (oid, to_terms_hashed) in to.objects.iter() {
+            if let Some(from_terms_hashed) = self.objects.get(oid) {
+                let (terms_added, terms_removed) =
+                    Self::diff_terms(from_terms_hashed, to_terms_hashed);
+
+                if terms_added > 0 || terms_removed > 0 {
+                    changed.push((oid.to_owned(), terms_added, terms_removed));
+                }
+            } else {
+                added.push(oid.to_owned());
+            }
+        }
+
+        for oid in self.objects.keys() {
+            if !to.objects.contains_key(oid) {
+                removed.push(oid.to_owned());
+            }
+        }
+
+        // Sort diffed objects by OID (snapshots do not keep the push order)
+        added.sort_unstable();
+        removed.sort_unstable();
+        changed.sort_unstable();
+
+        StoreSnapshotDiff {
+            added,
+            removed,
+            changed,
+        }
+    }
+
+    fn diff_terms(from: &[StoreTermHashed], to: &[StoreTermHashed]) -> (usize, usize) {
+        let (mut from_index, mut to_index) = (0, 0);
+        let (mut terms_added, mut terms_removed) = (0, 0);
+
+        // Walk both sorted term lists at once
+        while from_index < from.len() || to_index < to.len() {
+            match (from.get(from_index), to.get(to_index)) {
+                (Some(from_term), Some(to_term)) if from_term == to_term => {
+                    from_index += 1;
+                    to_index += 1;
+                }
+                (Some(from_term), Some(to_term)) if from_term < to_term => {
+                    terms_removed += 1;
+                    from_index += 1;
+                }
+                (Some(_), None) => {
+                    terms_removed += 1;
+                    from_index += 1;
+                }
+                _ => {
+                    terms_added += 1;
+                    to_index += 1;
+                }
+            }
+        }
+
+        (terms_added, terms_removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_diffs_snapshots() {
+        let from = StoreSnapshot::from_objects(vec![
+            ("o:1".to_string(), vec![3, 1, 2]),
+            ("o:2".to_string(), vec![4]),
+            ("o:3".to_string(), vec![5, 6]),
+        ]);
+        let to = StoreSnapshot::from_objects(vec![
+            ("o:1".to_string(), vec![1, 2, 3]),
+            ("o:3".to_string(), vec![6, 7, 8]),
+            ("o:4".to_string(), vec![9]),
+        ]);
+
+        assert_eq!(
+            from.diff(&to),
+            StoreSnapshotDiff {
+                added: vec!["o:4".to_string()],
+                removed: vec!["o:2".to_string()],
+                changed: vec![("o:3".to_string(), 2, 1)],
+            }
+        );
+    }
+
+    #[test]
+    fn it_replaces_named_snapshots() {
+        StoreSnapshotPool::insert(
+            "c:test:18",
+            "b:test:18",
+            "s:1",
+            StoreSnapshot::from_objects(vec![("o:1".to_string(), vec![1])]),
+        );
+        StoreSnapshotPool::insert(
+            "c:test:18",
+            "b:test:18",
+            "s:1",
+            StoreSnapshot::from_objects(vec![]),
+        );
+
+        assert_eq!(
+            StoreSnapshotPool::get("c:test:18", "b:test:18", "s:1")
+                .map(|snapshot| snapshot.count()),
+            Some(0)
+        );
+        assert!(StoreSnapshotPool::get("c:test:18", "b:other:18", "s:1").is_none());
+    }
+
+    #[test]
+    fn it_expires_snapshots() {
+        let expire_after = Duration::from_secs(APP_CONF.store.kv.snapshot_expire_after);
+
+        StoreSnapshotPool::insert(
+            "c:test:76",
+            "b:test:76",
+            "s:1",
+            StoreSnapshot::from_objects(vec![]),
+        );
+
+        assert!(StoreSnapshotPool::get("c:test:76", "b:test:76", "s:1").is_some());
+
+        // Age the snapshot past its expiry, then check that it is not served anymore
+        {
+            let mut snapshots = SNAPSHOTS.write().unwrap();
+
+            let (_, taken_at, _) = snapshots
+                .iter_mut()
+                .find(|(key, _, _)| key.0 == "c:test:76")
+                .unwrap();
+
+            *taken_at = Instant::now().checked_sub(expire_after).unwrap();
+        }
+
+        assert!(StoreSnapshotPool::get("c:test:76", "b:test:76", "s:1").is_none());
+
+        StoreSnapshotPool::janitor();
+
+        assert!(!SNAPSHOTS
+            .read()
+            .unwrap()
+            .iter()
+            .any(|(key, _, _)| key.0 == "c:test:76"));
+    }
+}
@@ -9,6 +9,7 @@ use std::time::{Duration, Instant};
 
 use crate::store::fst::StoreFSTPool;
 use crate::store::kv::StoreKVPool;
+use crate::store::snapshot::StoreSnapshotPool;
 
 pub struct TaskerBuilder;
 pub struct Tasker;
@@ -51,9 +52,14 @@ impl Tasker {
         // #1: Janitors
         StoreKVPool::janitor();
         StoreFSTPool::janitor();
+        StoreSnapshotPool::janitor();
+        StoreKVPool::trim(false);
+        StoreKVPool::compact(false);
 
         // #2: Others
+        StoreKVPool::retain(false);
         StoreKVPool::flush(false);
         StoreFSTPool::consolidate(false);
+        StoreKVPool::consolidate(false);
     }
 }